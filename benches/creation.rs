@@ -2,8 +2,14 @@ mod common;
 
 use common::{LARGE, MEDIUM, SMALL, TINY};
 use criterion::measurement::WallTime;
-use criterion::{criterion_group, criterion_main, BenchmarkGroup, Criterion};
-use crop::{Rope, RopeBuilder};
+use criterion::{
+    black_box,
+    criterion_group,
+    criterion_main,
+    BenchmarkGroup,
+    Criterion,
+};
+use crop::{ChunkSummary, Rope, RopeBuilder};
 
 fn bench<F: Fn(&str)>(group: &mut BenchmarkGroup<WallTime>, to_bench: F) {
     group.bench_function("tiny", |bench| bench.iter(|| to_bench(TINY)));
@@ -32,5 +38,35 @@ fn rope_builder(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, from_str, rope_builder);
+fn summarize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("summarize");
+
+    bench(&mut group, |s| {
+        for chunk in s.as_bytes().chunks(2048) {
+            // SAFETY: all the test vectors are ASCII.
+            let chunk = unsafe { std::str::from_utf8_unchecked(chunk) };
+            let _ = black_box(ChunkSummary::from(black_box(chunk)));
+        }
+    });
+}
+
+fn to_string(c: &mut Criterion) {
+    let mut group = c.benchmark_group("to_string");
+
+    let r = Rope::from(LARGE);
+
+    group.bench_function("from_rope", |bench| {
+        bench.iter(|| String::from(black_box(&r)))
+    });
+
+    group.bench_function("collect_chars", |bench| {
+        bench.iter(|| black_box(&r).chars().collect::<String>())
+    });
+
+    group.bench_function("display", |bench| {
+        bench.iter(|| black_box(&r).to_string())
+    });
+}
+
+criterion_group!(benches, from_str, rope_builder, summarize, to_string);
 criterion_main!(benches);
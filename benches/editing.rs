@@ -9,7 +9,7 @@ use criterion::{
     BenchmarkGroup,
     Criterion,
 };
-use crop::Rope;
+use crop::{Compaction, Rope};
 
 fn bench_insert(group: &mut BenchmarkGroup<WallTime>, insert: &str) {
     #[inline(always)]
@@ -79,6 +79,60 @@ fn insert_char_with_clone_around(c: &mut Criterion) {
     });
 }
 
+fn bench_typing(
+    group: &mut BenchmarkGroup<WallTime>,
+    insert_char: fn(&mut Rope, usize, char),
+) {
+    #[inline(always)]
+    fn bench(
+        bench: &mut Bencher,
+        s: &str,
+        insert_char: fn(&mut Rope, usize, char),
+    ) {
+        const TYPED: &str = "Lorem ipsum dolor sit amet, ça va? 🌎\n";
+
+        let mut r = Rope::from(s);
+        let mut ranges = PercentRanges::new(r.byte_len()).cycle();
+        let mut cursor = 0;
+        let mut typed = TYPED.chars().cycle();
+        let orig_len = r.byte_len();
+
+        bench.iter(|| {
+            // Move the cursor somewhere else at the start of every line.
+            let ch = typed.next().unwrap();
+            if ch == 'L' {
+                cursor = ranges.next().unwrap().start;
+                while !r.is_char_boundary(cursor) {
+                    cursor -= 1;
+                }
+            }
+            insert_char(&mut r, cursor, ch);
+            cursor += ch.len_utf8();
+
+            if r.byte_len() >= (1.5 * (orig_len as f32)) as usize {
+                r = Rope::from(s);
+                ranges = PercentRanges::new(r.byte_len()).cycle();
+                cursor = 0;
+            }
+        });
+    }
+
+    group.bench_function("tiny", |b| bench(b, TINY, insert_char));
+    group.bench_function("small", |b| bench(b, SMALL, insert_char));
+    group.bench_function("medium", |b| bench(b, MEDIUM, insert_char));
+    group.bench_function("large", |b| bench(b, LARGE, insert_char));
+}
+
+fn typing_to_string(c: &mut Criterion) {
+    let mut group = c.benchmark_group("typing_to_string");
+    bench_typing(&mut group, |r, at, ch| r.insert(at, ch.to_string()));
+}
+
+fn typing_insert_char(c: &mut Criterion) {
+    let mut group = c.benchmark_group("typing_insert_char");
+    bench_typing(&mut group, Rope::insert_char);
+}
+
 fn bench_delete(group: &mut BenchmarkGroup<WallTime>, delete_bytes: usize) {
     #[inline(always)]
     fn bench(bench: &mut Bencher, s: &str, delete_bytes: usize) {
@@ -188,12 +242,72 @@ fn replace_large(c: &mut Criterion) {
     bench_replace(&mut group, SMALL);
 }
 
+/// Deletes 6 out of every 8 bytes of `s`, leaving a `Rope` with lots of
+/// sparsely filled chunks unless they're eagerly compacted.
+fn scattered_deletions(s: &str, compaction: Compaction) -> Rope {
+    let mut r = Rope::from(s);
+    r.set_compaction(compaction);
+    let len = r.byte_len();
+    for start in (0..len).step_by(8).rev() {
+        r.delete(start..(start + 6).min(len));
+    }
+    r
+}
+
+fn compaction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compaction");
+
+    for (name, compaction) in
+        [("lazy", Compaction::Lazy), ("eager", Compaction::Eager)]
+    {
+        group.bench_function(format!("delete_{name}"), |bench| {
+            bench.iter(|| scattered_deletions(MEDIUM, compaction))
+        });
+
+        let r = scattered_deletions(LARGE, compaction);
+
+        group.bench_function(format!("iterate_{name}"), |bench| {
+            bench.iter(|| r.chunks().map(str::len).sum::<usize>())
+        });
+    }
+}
+
+/// Empties the `Rope` and refills it with `s`, which is what a double-buffered
+/// view does with the text it renders on every frame.
+fn refill(r: &mut Rope, s: &str, retain_capacity: bool) {
+    if retain_capacity {
+        r.clear_retaining_capacity();
+    } else {
+        r.delete(..);
+    }
+
+    r.insert(0, s);
+}
+
+fn clear_and_refill(c: &mut Criterion) {
+    let mut group = c.benchmark_group("clear_and_refill");
+
+    for (name, retain_capacity) in [("delete", false), ("retaining", true)] {
+        group.bench_function(format!("medium_{name}"), |bench| {
+            let mut r = Rope::from(MEDIUM);
+            bench.iter(|| refill(&mut r, MEDIUM, retain_capacity))
+        });
+
+        group.bench_function(format!("large_{name}"), |bench| {
+            let mut r = Rope::from(LARGE);
+            bench.iter(|| refill(&mut r, LARGE, retain_capacity))
+        });
+    }
+}
+
 criterion_group!(
     benches,
     insert_char,
     insert_sentence,
     insert_large,
     insert_char_with_clone_around,
+    typing_to_string,
+    typing_insert_char,
     delete_char,
     delete_sentence,
     delete_large,
@@ -201,6 +315,8 @@ criterion_group!(
     replace_char,
     replace_sentence,
     replace_large,
+    compaction,
+    clear_and_refill,
 );
 
 criterion_main!(benches);
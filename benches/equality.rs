@@ -0,0 +1,65 @@
+mod common;
+
+use common::{LARGE, MEDIUM, SMALL, TINY};
+use criterion::{criterion_group, criterion_main, Bencher, Criterion};
+use crop::{Rope, RopeBuilder};
+
+fn eq_clone(c: &mut Criterion) {
+    #[inline(always)]
+    fn bench(bench: &mut Bencher, s: &str) {
+        let lhs = Rope::from(s);
+        let rhs = lhs.clone();
+        bench.iter(|| lhs == rhs);
+    }
+
+    let mut group = c.benchmark_group("eq_clone");
+
+    group.bench_function("tiny", |b| bench(b, TINY));
+    group.bench_function("small", |b| bench(b, SMALL));
+    group.bench_function("medium", |b| bench(b, MEDIUM));
+    group.bench_function("large", |b| bench(b, LARGE));
+}
+
+fn eq_misaligned(c: &mut Criterion) {
+    #[inline(always)]
+    fn bench(bench: &mut Bencher, s: &str) {
+        let lhs = Rope::from(s);
+
+        // Building the other `Rope` line by line gives it different chunk
+        // boundaries.
+        let mut builder = RopeBuilder::new();
+        for line in s.split_inclusive('\n') {
+            builder.append(line);
+        }
+        let rhs = builder.build();
+
+        bench.iter(|| lhs == rhs);
+    }
+
+    let mut group = c.benchmark_group("eq_misaligned");
+
+    group.bench_function("tiny", |b| bench(b, TINY));
+    group.bench_function("small", |b| bench(b, SMALL));
+    group.bench_function("medium", |b| bench(b, MEDIUM));
+    group.bench_function("large", |b| bench(b, LARGE));
+}
+
+fn eq_early_mismatch(c: &mut Criterion) {
+    #[inline(always)]
+    fn bench(bench: &mut Bencher, s: &str) {
+        let lhs = Rope::from(s);
+        let mut rhs = lhs.clone();
+        rhs.replace(0..1, "\u{0}");
+        bench.iter(|| lhs == rhs);
+    }
+
+    let mut group = c.benchmark_group("eq_early_mismatch");
+
+    group.bench_function("tiny", |b| bench(b, TINY));
+    group.bench_function("small", |b| bench(b, SMALL));
+    group.bench_function("medium", |b| bench(b, MEDIUM));
+    group.bench_function("large", |b| bench(b, LARGE));
+}
+
+criterion_group!(benches, eq_clone, eq_misaligned, eq_early_mismatch);
+criterion_main!(benches);
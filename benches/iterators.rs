@@ -1,7 +1,7 @@
 mod common;
 
 use common::{LARGE, MEDIUM, SMALL, TINY};
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use crop::{iter::*, Rope};
 
 #[macro_export]
@@ -90,5 +90,57 @@ iter_bench!(chars, Chars, "iter_chars");
 iter_bench!(lines, Lines, "iter_lines");
 iter_bench!(raw_lines, RawLines, "iter_raw_lines");
 
-criterion_group!(benches, chunks, bytes, chars, lines, raw_lines);
+fn bytes_position(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iter_bytes_position");
+
+    let r = Rope::from(LARGE);
+
+    // There are no null bytes in the text, so this scans the whole rope.
+    group.bench_function("next", |bench| {
+        bench.iter(|| {
+            let _ = r.bytes().enumerate().find(|&(_, byte)| byte == 0);
+        });
+    });
+
+    group.bench_function("position", |bench| {
+        bench.iter(|| {
+            let _ = r.bytes().position(|byte| byte == 0);
+        });
+    });
+
+    group.bench_function("rposition", |bench| {
+        bench.iter(|| {
+            let _ = r.bytes().rposition(|byte| byte == 0);
+        });
+    });
+}
+
+fn chars_count(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iter_chars_count");
+
+    let r = Rope::from(LARGE);
+
+    group.bench_function("next", |bench| {
+        bench.iter(|| {
+            black_box(r.chars().fold(0, |count, _| count + 1));
+        });
+    });
+
+    group.bench_function("count", |bench| {
+        bench.iter(|| {
+            black_box(r.chars().count());
+        });
+    });
+}
+
+criterion_group!(
+    benches,
+    chunks,
+    bytes,
+    bytes_position,
+    chars,
+    chars_count,
+    lines,
+    raw_lines
+);
 criterion_main!(benches);
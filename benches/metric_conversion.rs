@@ -40,5 +40,28 @@ fn byte_of_line(c: &mut Criterion) {
     group.bench_function("large", |b| bench(b, LARGE));
 }
 
-criterion_group!(benches, byte_of_line, line_of_byte);
+fn line_table(c: &mut Criterion) {
+    let r = Rope::from(LARGE);
+    let lines = 0..r.line_len();
+
+    let mut group = c.benchmark_group("line_table");
+
+    group.bench_function("byte_of_line", |b| {
+        let mut offsets = Vec::with_capacity(lines.len());
+        b.iter(|| {
+            offsets.clear();
+            offsets.extend(lines.clone().map(|l| r.byte_of_line(l)));
+        });
+    });
+
+    group.bench_function("byte_offsets_of_lines", |b| {
+        let mut offsets = Vec::with_capacity(lines.len());
+        b.iter(|| {
+            offsets.clear();
+            r.byte_offsets_of_lines(lines.clone(), &mut offsets);
+        });
+    });
+}
+
+criterion_group!(benches, byte_of_line, line_of_byte, line_table);
 criterion_main!(benches);
@@ -130,15 +130,47 @@
 //!
 //! - `simd` (enabled by default): enables SIMD on supported platforms;
 //!
+//! - `arbitrary` (disabled by default): implements the
+//!   [`Arbitrary`](https://docs.rs/arbitrary) trait for `Rope`s, which is
+//!   useful to generate random `Rope`s in fuzzers and property tests;
+//!
 //! - `graphemes` (disabled by default): enables a few grapheme-oriented APIs
 //! on `Rope`s and `RopeSlice`s such as the
 //! [`Graphemes`](crate::iter::Graphemes) iterator and others;
 //!
+//! - `memchr` (disabled by default): uses the
+//!   [`memchr`](https://docs.rs/memchr) crate instead of the default counter
+//!   to count the line breaks of large chunks. Which of the two is faster
+//!   depends on the target and on the text, so run the `summarize` benchmark
+//!   before enabling it;
+//!
+//! - `sha256` (disabled by default): adds methods to compute the SHA-256
+//!   digest of `Rope`s and `RopeSlice`s using the
+//!   [`sha2`](https://docs.rs/sha2) crate;
+//!
+//! - `strict-utf8-checks` (disabled by default): keeps the checks that the
+//!   chunks of a `Rope` are valid UTF-8 and are only split on char boundaries
+//!   enabled in release builds, panicking as soon as an internal offset
+//!   calculation goes wrong instead of causing undefined behavior. This is
+//!   meant for fuzzing code built on top of crop, and it makes every access to
+//!   a chunk linear in its length, so editing and iterating over a `Rope`
+//!   becomes noticeably slower;
+//!
 //! - `utf16-metric` (disabled by default): makes the `Rope` and `RopeSlice`
 //! track the UTF-16 code units they'd have if their content was stored as
 //! UTF-16 instead of UTF-8, allowing them to efficiently convert UTF-16
-//! code unit offsets to and from byte offsets in logarithmic time.
+//! code unit offsets to and from byte offsets in logarithmic time;
+//!
+//! - `unicode-lines` (disabled by default): makes the `Rope` and `RopeSlice`
+//!   also track the next line (NEL), line separator (LS) and paragraph
+//!   separator (PS) characters, allowing them to iterate over lines split on
+//!   those in addition to line feeds (see `LineBreakKind`);
+//!
+//! - `unicode-normalization` (disabled by default): adds methods to convert
+//!   `Rope`s to the NFC and NFD Unicode normalization forms using the
+//!   [`unicode-normalization`](https://docs.rs/unicode-normalization) crate.
 
+#![allow(clippy::doc_lazy_continuation)]
 #![allow(clippy::explicit_auto_deref)]
 #![allow(clippy::module_inception)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
@@ -155,11 +187,43 @@ pub mod iter {
     pub use crate::rope::iterators::*;
 }
 
+pub mod metrics {
+    //! Units used to measure the text of [`Rope`](crate::Rope)s and
+    //! [`RopeSlice`](crate::RopeSlice)s, which can be passed to
+    //! [`Rope::measure()`](crate::Rope::measure()) and
+    //! [`Rope::convert()`](crate::Rope::convert()) to write code that's
+    //! generic over the coordinate system it works with.
+
+    pub use crate::rope::text_metric::*;
+}
+
 mod rope;
 
 #[doc(hidden)]
 pub mod tree;
 
+#[cfg(feature = "unicode-lines")]
+pub use rope::LineBreakKind;
+pub use rope::{
+    concat,
+    ApplyEditsError,
+    BuildStats,
+    Compaction,
+    Edit,
+    EditError,
+    EditKind,
+    EditOutcome,
+    EditRecorder,
+    EditSummary,
+    HashedSlice,
+    InverseEdit,
+    LineEnding,
+    Reader,
+    Rope,
+    RopeBuilder,
+    RopeSlice,
+    SwapRangesError,
+};
 // These are not part of the public API, we only export them to be able to run
 // doctests.
 #[doc(hidden)]
@@ -168,7 +232,6 @@ pub use rope::{
     gap_slice::GapSlice,
     metrics::ChunkSummary,
 };
-pub use rope::{Rope, RopeBuilder, RopeSlice};
 
 #[inline]
 pub(crate) fn range_bounds_to_start_end<T, B>(
@@ -178,18 +241,22 @@ pub(crate) fn range_bounds_to_start_end<T, B>(
 ) -> (usize, usize)
 where
     B: core::ops::RangeBounds<T>,
-    T: core::ops::Add<usize, Output = usize> + Into<usize> + Copy,
+    T: Into<usize> + Copy,
 {
     use core::ops::Bound;
 
+    // The offsets are saturated instead of overflowing so that an inclusive
+    // range ending at `usize::MAX` results in an out of bounds error instead
+    // of an arithmetic overflow.
+
     let start = match range.start_bound() {
         Bound::Included(&n) => n.into(),
-        Bound::Excluded(&n) => n + 1,
+        Bound::Excluded(&n) => n.into().saturating_add(1),
         Bound::Unbounded => lo,
     };
 
     let end = match range.end_bound() {
-        Bound::Included(&n) => n + 1,
+        Bound::Included(&n) => n.into().saturating_add(1),
         Bound::Excluded(&n) => n.into(),
         Bound::Unbounded => hi,
     };
@@ -0,0 +1,92 @@
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use super::{Rope, RopeBuilder};
+
+const LINE_BREAKS: [&str; 3] = ["\n", "\r\n", "\r"];
+
+const MULTIBYTE: [&str; 6] =
+    ["é", "ß", "こんにちは", "🦀", "🐻‍❄️", "Ḽơᶉëᶆȋṕšᶙṁ"];
+
+/// The maximum number of edits applied to a `Rope` after it's been built.
+const MAX_EDITS: u8 = 8;
+
+/// The maximum length of a run of ASCII characters.
+const MAX_ASCII_RUN: usize = 64;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "arbitrary")))]
+impl<'a> Arbitrary<'a> for Rope {
+    /// Generates a `Rope` by appending a random number of fragments to a
+    /// [`RopeBuilder`] and then applying a few random edits to the result.
+    ///
+    /// The fragments mix ASCII runs, multibyte characters, arbitrary UTF-8
+    /// strings and line breaks (`"\n"`, `"\r\n"` and lone `"\r"`s), the
+    /// latter with a density that's also picked at random for every `Rope`.
+    /// The edits make sure the chunks don't only have the layout produced by
+    /// the builder.
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let line_break_density = u.int_in_range(1..=u8::MAX)?;
+
+        let mut builder = RopeBuilder::new();
+
+        let mut fragment = String::new();
+
+        for _ in 0..u.arbitrary_len::<u8>()? {
+            fragment.clear();
+            push_fragment(u, line_break_density, &mut fragment)?;
+            builder.append(&fragment);
+        }
+
+        let mut rope = builder.build();
+
+        for _ in 0..u.int_in_range(0..=MAX_EDITS)? {
+            let start = char_boundary_in(u, &rope, 0)?;
+            let end = char_boundary_in(u, &rope, start)?;
+            fragment.clear();
+            push_fragment(u, line_break_density, &mut fragment)?;
+            rope.replace(start..end, &fragment);
+        }
+
+        Ok(rope)
+    }
+}
+
+/// Picks a random char boundary of the `Rope` at or after `min`.
+#[inline]
+fn char_boundary_in(
+    u: &mut Unstructured<'_>,
+    rope: &Rope,
+    min: usize,
+) -> Result<usize> {
+    let mut byte_offset = u.int_in_range(min..=rope.byte_len())?;
+
+    while !rope.is_char_boundary(byte_offset) {
+        byte_offset += 1;
+    }
+
+    Ok(byte_offset)
+}
+
+#[inline]
+fn push_fragment(
+    u: &mut Unstructured<'_>,
+    line_break_density: u8,
+    fragment: &mut String,
+) -> Result<()> {
+    if u.ratio(line_break_density, u8::MAX)? {
+        fragment.push_str(u.choose(&LINE_BREAKS)?);
+        return Ok(());
+    }
+
+    match u.int_in_range(0..=2u8)? {
+        0 => {
+            let len = u.int_in_range(1..=MAX_ASCII_RUN)?;
+            fragment.extend((0..len).map(|i| (b'a' + (i % 26) as u8) as char));
+        },
+
+        1 => fragment.push_str(u.choose(&MULTIBYTE)?),
+
+        _ => fragment.push_str(<&str>::arbitrary(u)?),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+/// The buffers of the chunks of a [`Rope`](crate::Rope) emptied by
+/// `Rope::clear_retaining_capacity()`, kept around to be reused by the next
+/// edits instead of allocating new ones.
+///
+/// The pool is owned by the `Rope` and handed to its chunks together with
+/// the text of every edit, so the chunks created by an edit that doesn't fit
+/// in the existing ones can take their buffers from it. A `RopeBuilder` could
+/// draw from a pool in the same way by passing one to the chunks it creates,
+/// but it currently always allocates new ones.
+pub(super) struct ChunkPool<const MAX_BYTES: usize> {
+    buffers: Vec<Box<[u8; MAX_BYTES]>>,
+}
+
+impl<const MAX_BYTES: usize> Default for ChunkPool<MAX_BYTES> {
+    #[inline]
+    fn default() -> Self {
+        Self { buffers: Vec::new() }
+    }
+}
+
+impl<const MAX_BYTES: usize> Clone for ChunkPool<MAX_BYTES> {
+    /// The buffers are spare capacity and not part of the text, so clones
+    /// start with an empty pool instead of allocating their own copies.
+    #[inline]
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl<const MAX_BYTES: usize> ChunkPool<MAX_BYTES> {
+    #[inline]
+    pub(super) fn push(&mut self, buffer: Box<[u8; MAX_BYTES]>) {
+        self.buffers.push(buffer);
+    }
+
+    /// Returns a buffer for a new chunk, reusing one of the pool if it's not
+    /// empty.
+    ///
+    /// The contents of a reused buffer are left as they were, so only the
+    /// bytes written by the new chunk are meaningful.
+    #[inline]
+    pub(super) fn take(&mut self) -> Box<[u8; MAX_BYTES]> {
+        self.buffers.pop().unwrap_or_else(|| Box::new([0u8; MAX_BYTES]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Rope;
+
+    #[test]
+    fn clear_retaining_capacity_reuses_buffers() {
+        let text = "Hello World!\n".repeat(100);
+
+        let mut r = Rope::from(text.as_str());
+        let leaf_count = r.tree.leaf_count();
+
+        r.clear_retaining_capacity();
+        assert_eq!(r.chunk_pool.buffers.len(), leaf_count);
+
+        for i in 0..10 {
+            // The reused buffers still hold the bytes of the previous text,
+            // so make sure every refill is different.
+            let refill = text.replace('o', &i.to_string());
+
+            r.insert(0, &refill);
+            r.assert_invariants();
+            assert_eq!(r, refill);
+            assert!(r.chunk_pool.buffers.len() < leaf_count);
+
+            let refilled_leaf_count = r.tree.leaf_count();
+            let spare = r.chunk_pool.buffers.len();
+
+            // The new empty chunk takes one of the spare buffers, if any.
+            r.clear_retaining_capacity();
+            r.assert_invariants();
+            assert!(r.is_empty());
+            assert_eq!(
+                r.chunk_pool.buffers.len(),
+                spare.saturating_sub(1) + refilled_leaf_count
+            );
+        }
+
+        // The leaves shared with a clone can't be reused.
+        r.insert(0, &text);
+        let clone = r.clone();
+        let spare = r.chunk_pool.buffers.len();
+
+        r.clear_retaining_capacity();
+        assert_eq!(r.chunk_pool.buffers.len(), spare.saturating_sub(1));
+        assert_eq!(clone, text);
+        assert!(clone.chunk_pool.buffers.is_empty());
+    }
+}
@@ -0,0 +1,21 @@
+/// How eagerly a [`Rope`](crate::Rope) merges its chunks back together after
+/// text is deleted.
+///
+/// See [`Rope::set_compaction()`](crate::Rope::set_compaction()) for more.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Compaction {
+    /// After a deletion the chunks around the deleted range are merged with
+    /// their neighbours whenever their text fits in fewer chunks.
+    ///
+    /// This does more work on every deletion, but keeps the chunks as full as
+    /// possible, which makes iterating over the `Rope` faster.
+    Eager,
+
+    /// Chunks are only merged with their neighbours when they become
+    /// underfilled, i.e. when they're less than a quarter full.
+    ///
+    /// This minimizes the cost of each edit, but many scattered deletions can
+    /// leave the `Rope` with lots of sparsely filled chunks.
+    #[default]
+    Lazy,
+}
@@ -0,0 +1,198 @@
+use core::ops::{Range, RangeBounds};
+
+use super::Rope;
+
+/// A handle to a [`Rope`] whose edits return their own inverse, which can be
+/// pushed onto an undo stack.
+///
+/// The text removed by an edit is copied out of the `Rope` right before the
+/// edit is applied, so recording an edit costs an extra `O(log n)` descent of
+/// the `Rope` plus a copy of the removed bytes on top of the edit itself.
+///
+/// This struct is created by the
+/// [`edit_recording`](crate::Rope::edit_recording()) method on `Rope`. See
+/// its documentation for more.
+///
+/// # Examples
+///
+/// ```
+/// # use crop::Rope;
+/// #
+/// let mut r = Rope::from("Hello Earth 🌎!");
+///
+/// let mut undo_stack = Vec::new();
+///
+/// let mut recorder = r.edit_recording();
+/// undo_stack.push(recorder.replace(6..16, "Saturn 🪐"));
+/// undo_stack.push(recorder.insert(0, "👋 "));
+/// undo_stack.push(recorder.delete(22..23));
+/// assert_eq!(r, "👋 Hello Saturn 🪐");
+///
+/// while let Some(inverse) = undo_stack.pop() {
+///     inverse.apply(&mut r);
+/// }
+///
+/// assert_eq!(r, "Hello Earth 🌎!");
+/// ```
+#[derive(Debug)]
+pub struct EditRecorder<'a> {
+    rope: &'a mut Rope,
+}
+
+impl<'a> EditRecorder<'a> {
+    #[inline]
+    pub(super) fn new(rope: &'a mut Rope) -> Self {
+        Self { rope }
+    }
+
+    /// Deletes the contents of the `Rope` within the specified byte range
+    /// like [`Rope::delete()`], returning the inverse of the edit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start or the end of the byte range don't lie on a code
+    /// point boundary, if the start is greater than the end or if the end is
+    /// out of bounds (i.e. greater than the byte length of the `Rope`).
+    #[track_caller]
+    #[inline]
+    pub fn delete<R>(&mut self, byte_range: R) -> InverseEdit
+    where
+        R: RangeBounds<usize>,
+    {
+        self.replace(byte_range, "")
+    }
+
+    /// Inserts `text` in the `Rope` at the given byte offset like
+    /// [`Rope::insert()`], returning the inverse of the edit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset doesn't lie on a code point boundary or if
+    /// it's out of bounds (i.e. greater than the byte length of the `Rope`).
+    #[track_caller]
+    #[inline]
+    pub fn insert<T>(&mut self, byte_offset: usize, text: T) -> InverseEdit
+    where
+        T: AsRef<str>,
+    {
+        self.replace(byte_offset..byte_offset, text)
+    }
+
+    /// Replaces the contents of the `Rope` within the specified byte range
+    /// with the given string like [`Rope::replace()`], returning the inverse
+    /// of the edit.
+    ///
+    /// Like `Rope::replace()`, this only edits the part of the range that
+    /// actually changes, so the inverse only covers that part too.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start or the end of the byte range don't lie on a code
+    /// point boundary, if the start is greater than the end, if the end is
+    /// out of bounds (i.e. greater than the byte length of the `Rope`) or if
+    /// the replacement would make the `Rope` longer than its
+    /// [maximum length](Rope::set_max_len()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("foo bar baz");
+    ///
+    /// let inverse = r.edit_recording().replace(4..7, "bor");
+    /// assert_eq!(r, "foo bor baz");
+    /// assert_eq!(inverse.byte_range(), 5..6);
+    /// assert_eq!(inverse.text(), "a");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn replace<R, T>(&mut self, byte_range: R, text: T) -> InverseEdit
+    where
+        R: RangeBounds<usize>,
+        T: AsRef<str>,
+    {
+        let mut removed = String::new();
+
+        let byte_range = self.rope.replace_inner(
+            byte_range,
+            text.as_ref(),
+            Some(&mut removed),
+        );
+
+        InverseEdit { byte_range, text: removed }
+    }
+
+    /// Returns a reference to the `Rope` being edited.
+    #[inline]
+    pub fn rope(&self) -> &Rope {
+        self.rope
+    }
+}
+
+/// The inverse of an edit made through an [`EditRecorder`], which restores
+/// the text the `Rope` had before the edit.
+///
+/// An `InverseEdit` replaces a byte range with a string, and it's only
+/// meaningful for the state of the `Rope` right after the edit it was
+/// obtained from. Applying it once other edits have been made (without
+/// undoing them first) either panics or corrupts the text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InverseEdit {
+    byte_range: Range<usize>,
+    text: String,
+}
+
+impl InverseEdit {
+    /// Applies the inverse edit to the `Rope`, returning the inverse of the
+    /// inverse, i.e. an edit which redoes the original one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte range of the inverse edit is out of bounds of the
+    /// `Rope` or if it doesn't lie on code point boundaries, which can only
+    /// happen if it's applied to a `Rope` other than the one it was obtained
+    /// from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("foo");
+    ///
+    /// let undo = r.edit_recording().insert(3, "bar");
+    /// assert_eq!(r, "foobar");
+    ///
+    /// let redo = undo.apply(&mut r);
+    /// assert_eq!(r, "foo");
+    ///
+    /// redo.apply(&mut r);
+    /// assert_eq!(r, "foobar");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn apply(self, rope: &mut Rope) -> InverseEdit {
+        rope.edit_recording().replace(self.byte_range, self.text)
+    }
+
+    /// Returns the byte range replaced by the inverse edit.
+    #[inline]
+    pub fn byte_range(&self) -> Range<usize> {
+        self.byte_range.clone()
+    }
+
+    /// Returns `true` if applying the inverse edit wouldn't change the text,
+    /// which happens when the edit it was obtained from was a no-op.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.byte_range.is_empty() && self.text.is_empty()
+    }
+
+    /// Returns the text the byte range is replaced with, i.e. the text
+    /// removed by the original edit.
+    #[inline]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
@@ -0,0 +1,194 @@
+//! This module contains the errors returned by the fallible methods of
+//! [`Rope`](super::Rope).
+
+use core::fmt;
+
+/// The error returned by [`Rope::apply_edits()`](super::Rope::apply_edits())
+/// when the edits can't be applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApplyEditsError {
+    /// One of the edits can't be applied, or all of them together would make
+    /// the `Rope` longer than its maximum length.
+    Edit(EditError),
+
+    /// The range of an edit overlaps with the range of the previous one.
+    Overlapping {
+        /// The start of the range overlapping the previous one.
+        byte_offset: usize,
+    },
+}
+
+impl From<EditError> for ApplyEditsError {
+    #[inline]
+    fn from(err: EditError) -> Self {
+        Self::Edit(err)
+    }
+}
+
+impl fmt::Display for ApplyEditsError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::Edit(err) => err.fmt(f),
+
+            Self::Overlapping { byte_offset } => write!(
+                f,
+                "the byte ranges overlap: an edit starts at {byte_offset} \
+                 before the end of the previous one"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ApplyEditsError {}
+
+/// The error returned by [`Rope::try_insert()`](super::Rope::try_insert()),
+/// [`Rope::try_delete()`](super::Rope::try_delete()) and
+/// [`Rope::try_replace()`](super::Rope::try_replace()) when an edit can't be
+/// applied.
+///
+/// Those methods panic if they're given an invalid range, so they only return
+/// the [`MaxLenExceeded`](Self::MaxLenExceeded) and
+/// [`ReadOnly`](Self::ReadOnly) variants. The others are returned by
+/// [`Rope::apply_edits()`](super::Rope::apply_edits()), wrapped in an
+/// [`ApplyEditsError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditError {
+    /// The start of the range of the edit is greater than its end.
+    StartAfterEnd {
+        /// The start of the range.
+        start: usize,
+
+        /// The end of the range.
+        end: usize,
+    },
+
+    /// The end of the range of the edit is out of bounds.
+    OutOfBounds {
+        /// The offending byte offset.
+        byte_offset: usize,
+
+        /// The byte length of the `Rope`.
+        byte_len: usize,
+    },
+
+    /// The start or the end of the range of the edit doesn't lie on a code
+    /// point boundary.
+    NotCharBoundary {
+        /// The offending byte offset.
+        byte_offset: usize,
+    },
+
+    /// The edit would make the `Rope` longer than the maximum length set with
+    /// [`Rope::set_max_len()`](super::Rope::set_max_len()).
+    MaxLenExceeded {
+        /// The maximum byte length of the `Rope`.
+        limit: usize,
+
+        /// The byte length the `Rope` would have after the edit.
+        would_be: usize,
+    },
+
+    /// The edit would change the text of one of the ranges set with
+    /// [`Rope::set_readonly_ranges()`](super::Rope::set_readonly_ranges()).
+    ReadOnly {
+        /// The first byte offset of a read-only range touched by the edit.
+        at: usize,
+    },
+}
+
+impl fmt::Display for EditError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::StartAfterEnd { start, end } => write!(
+                f,
+                "byte start after end: the start is {start} but the end is \
+                 {end}"
+            ),
+
+            Self::OutOfBounds { byte_offset, byte_len } => write!(
+                f,
+                "byte offset out of bounds: the offset is {byte_offset} but \
+                 the length is {byte_len}"
+            ),
+
+            Self::NotCharBoundary { byte_offset } => {
+                write!(f, "byte offset {byte_offset} is not a char boundary")
+            },
+
+            Self::MaxLenExceeded { limit, would_be } => write!(
+                f,
+                "maximum length exceeded: the edit would make the Rope \
+                 {would_be} bytes long but the limit is {limit} bytes"
+            ),
+
+            Self::ReadOnly { at } => write!(
+                f,
+                "read-only range: the edit would change the protected text \
+                 at byte offset {at}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+/// The error returned by [`Rope::swap_ranges()`](super::Rope::swap_ranges())
+/// when the two ranges can't be swapped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapRangesError {
+    /// The start of a range is greater than its end.
+    StartAfterEnd {
+        /// The start of the range.
+        start: usize,
+
+        /// The end of the range.
+        end: usize,
+    },
+
+    /// The end of a range is out of bounds.
+    OutOfBounds {
+        /// The offending byte offset.
+        byte_offset: usize,
+
+        /// The byte length of the `Rope`.
+        byte_len: usize,
+    },
+
+    /// The start or the end of a range doesn't lie on a code point boundary.
+    NotCharBoundary {
+        /// The offending byte offset.
+        byte_offset: usize,
+    },
+
+    /// The two ranges overlap.
+    Overlapping,
+}
+
+impl fmt::Display for SwapRangesError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::StartAfterEnd { start, end } => write!(
+                f,
+                "byte start after end: the start is {start} but the end is \
+                 {end}"
+            ),
+
+            Self::OutOfBounds { byte_offset, byte_len } => write!(
+                f,
+                "byte offset out of bounds: the offset is {byte_offset} but \
+                 the length is {byte_len}"
+            ),
+
+            Self::NotCharBoundary { byte_offset } => {
+                write!(f, "byte offset {byte_offset} is not a char boundary")
+            },
+
+            Self::Overlapping => f.write_str("the byte ranges overlap"),
+        }
+    }
+}
+
+impl std::error::Error for SwapRangesError {}
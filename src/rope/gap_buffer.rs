@@ -5,6 +5,7 @@
 
 use core::ops::{Range, RangeBounds};
 
+use super::chunk_pool::ChunkPool;
 use super::gap_slice::GapSlice;
 use super::metrics::{ByteMetric, ChunkSummary};
 use super::utils::{panic_messages as panic, *};
@@ -300,6 +301,16 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
         }
     }
 
+    /// Same as [`assert_char_boundary()`](Self::assert_char_boundary()), but
+    /// only in debug builds or if the `strict-utf8-checks` feature is enabled.
+    #[track_caller]
+    #[inline]
+    fn debug_assert_char_boundary(&self, byte_offset: usize) {
+        if cfg!(any(debug_assertions, feature = "strict-utf8-checks")) {
+            self.assert_char_boundary(byte_offset);
+        }
+    }
+
     /// The number of bytes `RopeChunk`s must always stay over.
     pub(super) const fn chunk_min() -> usize {
         // The buffer can be underfilled by 3 bytes at most, which can happen
@@ -324,18 +335,35 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
     /// ```
     #[inline]
     pub fn from_chunks(chunks: &[&str]) -> Self {
+        Self::from_chunks_in(chunks, None)
+    }
+
+    /// Same as [`from_chunks()`](Self::from_chunks()), but takes the buffer
+    /// from the given pool, if any.
+    #[inline]
+    pub(super) fn from_chunks_in(
+        chunks: &[&str],
+        pool: Option<&mut ChunkPool<MAX_BYTES>>,
+    ) -> Self {
         let total_len = chunks.iter().map(|s| s.len()).sum::<usize>();
 
+        debug_assert!(total_len <= MAX_BYTES);
+
+        let mut bytes = match pool {
+            Some(pool) => pool.take(),
+            None => Box::new([0u8; MAX_BYTES]),
+        };
+
         if total_len == 0 {
-            return Self::default();
+            return Self {
+                bytes,
+                left_summary: ChunkSummary::default(),
+                len_right: 0,
+            };
         }
 
-        debug_assert!(total_len <= MAX_BYTES);
-
         let to_left = total_len / 2;
 
-        let mut bytes = Box::new([0u8; MAX_BYTES]);
-
         let mut summary_left = ChunkSummary::new();
 
         let mut chunks = chunks.iter();
@@ -402,12 +430,6 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
         unreachable!("This can only be reached if the total length is zero");
     }
 
-    /// Returns `true` if the buffer ends with a newline ('\n') character.
-    #[inline]
-    pub(super) fn has_trailing_newline(&self) -> bool {
-        self.last_chunk().ends_with('\n')
-    }
-
     /// Inserts the string at the given byte offset, moving the gap to the new
     /// insertion point if necessary.
     ///
@@ -423,7 +445,7 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
         summary: ChunkSummary,
     ) -> ChunkSummary {
         debug_assert!(insert_at <= self.len());
-        debug_assert!(self.is_char_boundary(insert_at));
+        self.debug_assert_char_boundary(insert_at);
         debug_assert!(s.len() <= self.len_gap());
         debug_assert_eq!(self.summarize(), summary);
 
@@ -463,24 +485,12 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
         self.len() == 0
     }
 
-    /// The right chunk if it's not empty, or the left one otherwise.
-    #[inline]
-    pub(super) fn last_chunk(&self) -> &str {
-        if self.len_right() == 0 {
-            self.left_chunk()
-        } else {
-            self.right_chunk()
-        }
-    }
-
     /// Returns the left chunk of this buffer as a string slice.
     #[inline]
     pub fn left_chunk(&self) -> &str {
         // SAFETY: all the methods are guaranteed to always keep the first
         // `len_left()` bytes valid UTF-8.
-        unsafe {
-            core::str::from_utf8_unchecked(&self.bytes[..self.len_left()])
-        }
+        unsafe { str_from_utf8_unchecked(&self.bytes[..self.len_left()]) }
     }
 
     /// Returns the combined byte length of the buffer's left and right chunks.
@@ -504,6 +514,11 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
         self.len_right as _
     }
 
+    /// The maximum number of bytes this buffer can hold.
+    pub(super) const fn max_bytes() -> usize {
+        MAX_BYTES
+    }
+
     /// The minimum number of bytes this buffer should have to not be
     /// considered underfilled.
     pub(super) const fn min_bytes() -> usize {
@@ -538,7 +553,7 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
     #[inline]
     pub fn move_gap(&mut self, byte_offset: usize, summary: ChunkSummary) {
         debug_assert!(byte_offset <= self.len());
-        debug_assert!(self.is_char_boundary(byte_offset));
+        self.debug_assert_char_boundary(byte_offset);
         debug_assert_eq!(summary, self.summarize());
 
         let offset = byte_offset;
@@ -776,7 +791,7 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
         removed_summary: ChunkSummary,
     ) {
         debug_assert!(byte_offset <= self.len());
-        debug_assert!(self.is_char_boundary(byte_offset));
+        self.debug_assert_char_boundary(byte_offset);
         debug_assert_eq!(
             self.summarize_range(0..byte_offset, self.summarize()),
             removed_summary
@@ -830,8 +845,8 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
     ) -> ChunkSummary {
         debug_assert!(start <= end);
         debug_assert!(end <= self.len());
-        debug_assert!(self.is_char_boundary(start));
-        debug_assert!(self.is_char_boundary(end));
+        self.debug_assert_char_boundary(start);
+        self.debug_assert_char_boundary(end);
         debug_assert!(self.len() - (end - start) + s.len() <= MAX_BYTES);
 
         self.move_gap(end, summary);
@@ -891,13 +906,27 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
         byte_range: Range<usize>,
         s: &str,
         summary: ChunkSummary,
+    ) -> (ChunkSummary, Vec<Self>) {
+        self.replace_overflowing_in(byte_range, s, summary, None)
+    }
+
+    /// Same as [`replace_overflowing()`](Self::replace_overflowing()), but
+    /// the buffers of the overflowed chunks are taken from the given pool, if
+    /// any.
+    #[inline]
+    fn replace_overflowing_in(
+        &mut self,
+        byte_range: Range<usize>,
+        s: &str,
+        summary: ChunkSummary,
+        pool: Option<&mut ChunkPool<MAX_BYTES>>,
     ) -> (ChunkSummary, Vec<Self>) {
         let Range { start, end } = byte_range;
 
         debug_assert!(start <= end);
         debug_assert!(end <= self.len());
-        debug_assert!(self.is_char_boundary(start));
-        debug_assert!(self.is_char_boundary(end));
+        self.debug_assert_char_boundary(start);
+        self.debug_assert_char_boundary(end);
         debug_assert!(self.len() - (end - start) + s.len() > MAX_BYTES);
 
         let (extra_left, extra_right) = if end <= self.len_left() {
@@ -919,7 +948,8 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
 
                 replacement = left;
 
-                Resegmenter::new([right, extra_left, extra_right]).collect()
+                Resegmenter::new([right, extra_left, extra_right], pool)
+                    .collect()
             } else if s.len() + extra_left.len() >= missing {
                 let missing = missing - s.len();
 
@@ -928,7 +958,7 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
 
                 truncate_from += left.len();
 
-                Resegmenter::new([right, extra_right]).collect()
+                Resegmenter::new([right, extra_right], pool).collect()
             } else {
                 let missing = missing - s.len() - extra_left.len();
 
@@ -937,7 +967,7 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
 
                 truncate_from += extra_left.len() + left.len();
 
-                Resegmenter::new([right]).collect()
+                Resegmenter::new([right], pool).collect()
             };
 
             let summary = self.truncate_from(truncate_from, summary);
@@ -980,13 +1010,10 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
                 (add_to_extras, new_right)
             };
 
-            let extras = Resegmenter::new([
-                add_to_extras_1,
-                add_to_extras_2,
-                s,
-                extra_left,
-                extra_right,
-            ])
+            let extras = Resegmenter::new(
+                [add_to_extras_1, add_to_extras_2, s, extra_left, extra_right],
+                pool,
+            )
             .collect();
 
             let new_summary = self.truncate_from(truncate_from, summary);
@@ -994,7 +1021,7 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
             (new_summary, extras)
         } else {
             let extras =
-                Resegmenter::new([s, extra_left, extra_right]).collect();
+                Resegmenter::new([s, extra_left, extra_right], pool).collect();
 
             let new_summary = self.truncate_from(start, summary);
 
@@ -1008,7 +1035,7 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
         // SAFETY: all the methods are guaranteed to always keep the last
         // `len_right()` bytes valid UTF-8.
         unsafe {
-            core::str::from_utf8_unchecked(
+            str_from_utf8_unchecked(
                 &self.bytes[MAX_BYTES - self.len_right()..],
             )
         }
@@ -1075,8 +1102,8 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
     ) -> ChunkSummary {
         debug_assert!(start <= end);
         debug_assert!(end <= self.len());
-        debug_assert!(self.is_char_boundary(start));
-        debug_assert!(self.is_char_boundary(end));
+        self.debug_assert_char_boundary(start);
+        self.debug_assert_char_boundary(end);
         debug_assert_eq!(summary, self.summarize());
 
         #[inline(always)]
@@ -1167,7 +1194,7 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
         summary: ChunkSummary,
     ) -> ChunkSummary {
         debug_assert!(byte_offset <= self.len());
-        debug_assert!(self.is_char_boundary(byte_offset));
+        self.debug_assert_char_boundary(byte_offset);
         debug_assert_eq!(summary, self.summarize());
 
         if byte_offset <= self.len_left() {
@@ -1236,6 +1263,9 @@ impl<const MAX_BYTES: usize> AsSlice for GapBuffer<MAX_BYTES> {
 
     #[inline]
     fn as_slice(&self) -> GapSlice<'_> {
+        #[cfg(test)]
+        tests::SLICED_CHUNKS.with(|count| count.set(count.get() + 1));
+
         let bytes = match (self.len_left() > 0, self.len_right() > 0) {
             (true, true) => &*self.bytes,
             (true, false) => &self.bytes[..self.len_left()],
@@ -1301,10 +1331,18 @@ impl<const MAX_BYTES: usize> BalancedLeaf for GapBuffer<MAX_BYTES> {
     }
 }
 
+/// The text a range of a [`GapBuffer`] is replaced with, together with the
+/// pool the chunks created by the replacement take their buffers from, if
+/// any.
+pub struct Replacement<'a, const MAX_BYTES: usize> {
+    pub(super) text: &'a str,
+    pub(super) pool: Option<&'a mut ChunkPool<MAX_BYTES>>,
+}
+
 impl<const MAX_BYTES: usize> ReplaceableLeaf<ByteMetric>
     for GapBuffer<MAX_BYTES>
 {
-    type Replacement<'a> = &'a str;
+    type Replacement<'a> = Replacement<'a, MAX_BYTES>;
 
     type ExtraLeaves = alloc::vec::IntoIter<Self>;
 
@@ -1314,7 +1352,7 @@ impl<const MAX_BYTES: usize> ReplaceableLeaf<ByteMetric>
         &mut self,
         summary: &mut ChunkSummary,
         range: R,
-        replacement: &str,
+        Replacement { text: replacement, pool }: Replacement<'_, MAX_BYTES>,
     ) -> Option<Self::ExtraLeaves>
     where
         R: RangeBounds<ByteMetric>,
@@ -1340,8 +1378,12 @@ impl<const MAX_BYTES: usize> ReplaceableLeaf<ByteMetric>
 
             None
         } else {
-            let (new_summary, extras) =
-                self.replace_overflowing(start..end, replacement, *summary);
+            let (new_summary, extras) = self.replace_overflowing_in(
+                start..end,
+                replacement,
+                *summary,
+                pool,
+            );
 
             debug_assert_eq!(new_summary, self.summarize());
 
@@ -1358,7 +1400,7 @@ impl<const MAX_BYTES: usize> ReplaceableLeaf<ByteMetric>
     #[track_caller]
     #[inline]
     fn remove_up_to(&mut self, summary: &mut ChunkSummary, up_to: ByteMetric) {
-        self.replace(summary, ..up_to, "");
+        self.replace(summary, ..up_to, Replacement { text: "", pool: None });
     }
 }
 
@@ -1431,16 +1473,20 @@ pub(super) struct Resegmenter<'a, const CHUNKS: usize, const MAX_BYTES: usize>
     start: usize,
     yielded: usize,
     total: usize,
+    pool: Option<&'a mut ChunkPool<MAX_BYTES>>,
 }
 
 impl<'a, const CHUNKS: usize, const MAX_BYTES: usize>
     Resegmenter<'a, CHUNKS, MAX_BYTES>
 {
     #[inline]
-    fn new(segments: [&'a str; CHUNKS]) -> Self {
+    fn new(
+        segments: [&'a str; CHUNKS],
+        pool: Option<&'a mut ChunkPool<MAX_BYTES>>,
+    ) -> Self {
         let total = segments.iter().map(|s| s.len()).sum::<usize>();
         debug_assert!(total >= GapBuffer::<MAX_BYTES>::chunk_min());
-        Self { total, segments, yielded: 0, start: 0 }
+        Self { total, segments, yielded: 0, start: 0, pool }
     }
 }
 
@@ -1510,8 +1556,9 @@ impl<'a, const CHUNKS: usize, const MAX_BYTES: usize> Iterator
                 self.segments[idx_last] = left;
             }
 
-            let next = GapBuffer::<MAX_BYTES>::from_chunks(
+            let next = GapBuffer::<MAX_BYTES>::from_chunks_in(
                 &self.segments[self.start..=idx_last],
+                self.pool.as_deref_mut(),
             );
 
             self.segments[idx_last] = right;
@@ -1521,7 +1568,10 @@ impl<'a, const CHUNKS: usize, const MAX_BYTES: usize> Iterator
             next
         } else {
             debug_assert!(remaining >= GapBuffer::<MAX_BYTES>::chunk_min());
-            GapBuffer::<MAX_BYTES>::from_chunks(&self.segments[self.start..])
+            GapBuffer::<MAX_BYTES>::from_chunks_in(
+                &self.segments[self.start..],
+                self.pool.as_deref_mut(),
+            )
         };
 
         debug_assert!(next.len() >= GapBuffer::<MAX_BYTES>::chunk_min());
@@ -1534,7 +1584,61 @@ impl<'a, const CHUNKS: usize, const MAX_BYTES: usize> Iterator
 
 #[cfg(test)]
 mod tests {
+    use core::cell::Cell;
+
     use super::*;
+    use crate::rope::rope::RopeChunk;
+    use crate::Rope;
+
+    std::thread_local! {
+        /// The number of times a chunk has been sliced on this thread, which
+        /// is how the tree iterators access the text of the leaves they visit.
+        pub(super) static SLICED_CHUNKS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// Returns the number of chunks sliced while running `f`.
+    fn sliced_chunks(f: impl FnOnce()) -> usize {
+        let before = SLICED_CHUNKS.with(Cell::get);
+        f();
+        SLICED_CHUNKS.with(Cell::get) - before
+    }
+
+    /// Tests that taking the first few lines of a `Rope` only visits the
+    /// chunks of those lines instead of the whole document.
+    #[test]
+    fn lines_are_lazy() {
+        let r = Rope::from("Hello World!\n".repeat(50_000));
+
+        let all = sliced_chunks(|| assert_eq!(r.lines().count(), 50_000));
+
+        let first = sliced_chunks(|| {
+            for line in r.lines().take(10) {
+                assert_eq!(line, "Hello World!");
+            }
+        });
+
+        let at_byte = sliced_chunks(|| {
+            for line in r.lines_at_byte(r.byte_len() / 2).take(10) {
+                assert_eq!(line, "Hello World!");
+            }
+        });
+
+        let raw = sliced_chunks(|| {
+            for line in r.raw_lines().take(10) {
+                assert_eq!(line, "Hello World!\n");
+            }
+        });
+
+        // Each line spans a few chunks, and the first line yielded can also
+        // slice the chunks around it while initializing the iterator.
+        let line_len = "Hello World!\n".len();
+        let bound = 10 * (line_len / RopeChunk::max_bytes() + 2);
+
+        assert!(all > 50_000);
+        assert!(first <= bound, "{first} > {bound}");
+        assert!(at_byte <= bound, "{at_byte} > {bound}");
+        assert!(raw <= bound, "{raw} > {bound}");
+    }
 
     #[test]
     fn remove_up_to_0() {
@@ -1551,6 +1655,87 @@ mod tests {
         assert_eq!("bb", buffer);
     }
 
+    /// Replaces every byte range of chunks of every length with strings
+    /// whose lengths are around the size limits of the chunks, checking that
+    /// the chunk and the extra leaves it overflows into stay within bounds.
+    fn replace_around_size_limits<const MAX_BYTES: usize>() {
+        let min_bytes = GapBuffer::<MAX_BYTES>::min_bytes();
+        let chunk_min = GapBuffer::<MAX_BYTES>::chunk_min();
+
+        let mut lengths = [0, min_bytes, MAX_BYTES, 2 * MAX_BYTES]
+            .into_iter()
+            .flat_map(|len| len.saturating_sub(2)..=len + 2)
+            .collect::<Vec<_>>();
+
+        lengths.sort_unstable();
+        lengths.dedup();
+
+        for len in 0..=MAX_BYTES {
+            let s = "abcdefgh".repeat(MAX_BYTES)[..len].to_owned();
+
+            for start in 0..=len {
+                for end in start..=len {
+                    for &replacement_len in &lengths {
+                        let replacement = "🌎".repeat(replacement_len / 4)
+                            + &"!".repeat(replacement_len % 4);
+
+                        let mut buffer = GapBuffer::<MAX_BYTES>::from(&*s);
+                        let mut summary = buffer.summarize();
+
+                        let range = ByteMetric(start)..ByteMetric(end);
+
+                        let replace_with =
+                            Replacement { text: &replacement, pool: None };
+
+                        let extras = buffer
+                            .replace(&mut summary, range, replace_with)
+                            .map(Iterator::collect::<Vec<_>>)
+                            .unwrap_or_default();
+
+                        assert_eq!(summary, buffer.summarize());
+                        assert!(buffer.len() <= MAX_BYTES);
+
+                        if !extras.is_empty() {
+                            assert!(buffer.len() >= chunk_min);
+                        }
+
+                        for extra in &extras {
+                            assert!(extra.len() >= chunk_min);
+                            assert!(extra.len() <= MAX_BYTES);
+                            extra.as_slice().assert_invariants();
+                        }
+
+                        let mut expected = s.clone();
+                        expected.replace_range(start..end, &replacement);
+
+                        let actual = core::iter::once(&buffer)
+                            .chain(&extras)
+                            .flat_map(|b| [b.left_chunk(), b.right_chunk()])
+                            .collect::<String>();
+
+                        assert_eq!(actual, expected, "{start}..{end}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn replace_around_size_limits_4() {
+        replace_around_size_limits::<4>();
+    }
+
+    #[test]
+    fn replace_around_size_limits_16() {
+        replace_around_size_limits::<16>();
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn replace_around_size_limits_32() {
+        replace_around_size_limits::<32>();
+    }
+
     #[test]
     fn segmenter_0() {
         let chunk = "Hello Earth 🌎!";
@@ -1567,7 +1752,7 @@ mod tests {
     #[test]
     fn resegmenter_0() {
         let segments = ["aaaa", "b"];
-        let mut resegmenter = Resegmenter::<2, 4>::new(segments);
+        let mut resegmenter = Resegmenter::<2, 4>::new(segments, None);
 
         assert_eq!("aaaa", resegmenter.next().unwrap());
         assert_eq!("b", resegmenter.next().unwrap());
@@ -1577,7 +1762,7 @@ mod tests {
     #[test]
     fn resegmenter_1() {
         let segments = ["a", "a", "bcdefgh"];
-        let mut resegmenter = Resegmenter::<3, 4>::new(segments);
+        let mut resegmenter = Resegmenter::<3, 4>::new(segments, None);
 
         assert_eq!("aabc", resegmenter.next().unwrap());
         assert_eq!("defg", resegmenter.next().unwrap());
@@ -1588,7 +1773,7 @@ mod tests {
     #[test]
     fn resegmenter_2() {
         let segments = ["a", "abcdefgh", "b"];
-        let mut resegmenter = Resegmenter::<3, 4>::new(segments);
+        let mut resegmenter = Resegmenter::<3, 4>::new(segments, None);
 
         assert_eq!("aabc", resegmenter.next().unwrap());
         assert_eq!("defg", resegmenter.next().unwrap());
@@ -1599,7 +1784,7 @@ mod tests {
     #[test]
     fn resegmenter_3() {
         let segments = ["a", "b"];
-        let mut resegmenter = Resegmenter::<2, 4>::new(segments);
+        let mut resegmenter = Resegmenter::<2, 4>::new(segments, None);
 
         assert_eq!("ab", resegmenter.next().unwrap());
         assert_eq!(None, resegmenter.next());
@@ -1608,7 +1793,7 @@ mod tests {
     #[test]
     fn resegmenter_4() {
         let segments = ["a", "b", ""];
-        let mut resegmenter = Resegmenter::<3, 4>::new(segments);
+        let mut resegmenter = Resegmenter::<3, 4>::new(segments, None);
 
         assert_eq!("ab", resegmenter.next().unwrap());
         assert_eq!(None, resegmenter.next());
@@ -1617,7 +1802,7 @@ mod tests {
     #[test]
     fn resegmenter_5() {
         let segments = ["こんい"];
-        let mut resegmenter = Resegmenter::<1, 4>::new(segments);
+        let mut resegmenter = Resegmenter::<1, 4>::new(segments, None);
 
         assert_eq!("こ", resegmenter.next().unwrap());
         assert_eq!("ん", resegmenter.next().unwrap());
@@ -1628,7 +1813,7 @@ mod tests {
     #[test]
     fn resegmenter_6() {
         let segments = [" 🌎", "!"];
-        let mut resegmenter = Resegmenter::<2, 4>::new(segments);
+        let mut resegmenter = Resegmenter::<2, 4>::new(segments, None);
 
         assert_eq!(" ", resegmenter.next().unwrap());
         assert_eq!("🌎", resegmenter.next().unwrap());
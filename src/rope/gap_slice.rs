@@ -1,5 +1,9 @@
 use super::metrics::{ChunkSummary, SummaryUpTo, ToByteOffset};
-use super::utils::{debug_no_quotes, panic_messages as panic};
+use super::utils::{
+    debug_no_quotes,
+    panic_messages as panic,
+    str_from_utf8_unchecked,
+};
 use crate::tree::{Metric, Summarize};
 
 /// A slice of a [`GapBuffer`](super::gap_buffer::GapBuffer).
@@ -80,6 +84,25 @@ impl<'a> GapSlice<'a> {
         }
     }
 
+    /// Same as [`byte()`](Self::byte()), but without bounds checking.
+    ///
+    /// # Safety
+    ///
+    /// The byte index must be less than the length of the slice.
+    #[inline]
+    pub(super) unsafe fn byte_unchecked(&self, byte_index: usize) -> u8 {
+        debug_assert!(byte_index < self.len());
+
+        if byte_index < self.len_left() {
+            *self.left_chunk().as_bytes().get_unchecked(byte_index)
+        } else {
+            *self
+                .right_chunk()
+                .as_bytes()
+                .get_unchecked(byte_index - self.len_left())
+        }
+    }
+
     #[inline]
     fn left_measure<M>(&self) -> M
     where
@@ -151,7 +174,7 @@ impl<'a> GapSlice<'a> {
 
         let mut new_summary = self.truncate_last_char(summary);
 
-        if self.last_chunk().ends_with('r') {
+        if self.last_chunk().ends_with('\r') {
             new_summary = self.truncate_last_char(new_summary)
         }
 
@@ -169,6 +192,17 @@ impl<'a> GapSlice<'a> {
         self.last_chunk().ends_with('\n')
     }
 
+    /// Returns `true` if it ends with any of the line breaks recognized by
+    /// [`LineBreakKind::UnicodeFull`](crate::LineBreakKind::UnicodeFull).
+    #[cfg(feature = "unicode-lines")]
+    #[inline]
+    pub(super) fn has_trailing_unicode_line_break(&self) -> bool {
+        self.last_chunk()
+            .chars()
+            .next_back()
+            .map_or(false, super::metrics::is_unicode_line_break)
+    }
+
     #[inline]
     pub(super) fn is_char_boundary(&self, byte_offset: usize) -> bool {
         debug_assert!(byte_offset <= self.len());
@@ -193,9 +227,7 @@ impl<'a> GapSlice<'a> {
     #[inline]
     pub(super) fn left_chunk(&self) -> &'a str {
         // SAFETY: the first `len_left` bytes are valid UTF-8.
-        unsafe {
-            core::str::from_utf8_unchecked(&self.bytes[..self.len_left()])
-        }
+        unsafe { str_from_utf8_unchecked(&self.bytes[..self.len_left()]) }
     }
 
     #[inline]
@@ -222,7 +254,7 @@ impl<'a> GapSlice<'a> {
     pub(super) fn right_chunk(&self) -> &'a str {
         // SAFETY: the last `len_right` bytes are valid UTF-8.
         unsafe {
-            core::str::from_utf8_unchecked(
+            str_from_utf8_unchecked(
                 &self.bytes[self.bytes.len() - self.len_right()..],
             )
         }
@@ -0,0 +1,46 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// The number of graphemes of a [`Rope`](crate::Rope) computed by
+/// [`Rope::grapheme_len()`](crate::Rope::grapheme_len()), together with the
+/// generation of the `Rope` it was computed at.
+///
+/// The cache is filled through a shared reference, so it uses atomics to keep
+/// `Rope`s `Sync`. The length is always stored before the generation and read
+/// after it, and since a `Rope` can't be edited while it's shared every
+/// thread filling the cache for the same generation stores the same length.
+#[derive(Default)]
+pub(super) struct GraphemeLenCache {
+    /// The generation the length was computed at plus one, or zero if the
+    /// cache is empty.
+    generation: AtomicUsize,
+
+    len: AtomicUsize,
+}
+
+impl Clone for GraphemeLenCache {
+    #[inline]
+    fn clone(&self) -> Self {
+        let generation = self.generation.load(Ordering::Acquire);
+        let len = self.len.load(Ordering::Relaxed);
+
+        Self {
+            generation: AtomicUsize::new(generation),
+            len: AtomicUsize::new(len),
+        }
+    }
+}
+
+impl GraphemeLenCache {
+    /// Returns the cached length if it was computed at the given generation.
+    #[inline]
+    pub(super) fn get(&self, generation: usize) -> Option<usize> {
+        (self.generation.load(Ordering::Acquire) == generation.wrapping_add(1))
+            .then(|| self.len.load(Ordering::Relaxed))
+    }
+
+    #[inline]
+    pub(super) fn set(&self, generation: usize, len: usize) {
+        self.len.store(len, Ordering::Relaxed);
+        self.generation.store(generation.wrapping_add(1), Ordering::Release);
+    }
+}
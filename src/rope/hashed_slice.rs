@@ -0,0 +1,123 @@
+use core::hash::{Hash, Hasher};
+use std::collections::hash_map::DefaultHasher;
+
+use super::RopeSlice;
+
+/// A [`RopeSlice`] bundled with the hash of its contents.
+///
+/// Hashing a `RopeSlice` requires going through all of its bytes, which can
+/// get expensive when the same long slice is used to repeatedly look up
+/// entries in a `HashMap` or `HashSet`. A `HashedSlice` computes the hash of
+/// its slice once when it's created, and its `Hash` impl then simply feeds
+/// that value to the hasher.
+///
+/// Two `HashedSlice`s are equal if their slices are equal. The stored hashes
+/// are compared first, so only slices with the same hash have their contents
+/// compared.
+///
+/// # Examples
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use crop::{HashedSlice, Rope};
+/// #
+/// let r = Rope::from("foo bar foo");
+///
+/// let mut counts = HashMap::new();
+///
+/// for word in r.split_whitespace() {
+///     *counts.entry(HashedSlice::new(word)).or_insert(0) += 1;
+/// }
+///
+/// assert_eq!(counts[&HashedSlice::new(r.byte_slice(..3))], 2);
+/// assert_eq!(counts[&HashedSlice::new(r.byte_slice(4..7))], 1);
+/// ```
+#[derive(Copy, Clone)]
+pub struct HashedSlice<'a> {
+    slice: RopeSlice<'a>,
+    hash: u64,
+}
+
+impl<'a> HashedSlice<'a> {
+    /// Creates a new `HashedSlice` by hashing the contents of the given
+    /// `RopeSlice`.
+    ///
+    /// This runs in `O(n)` time in the byte length of the slice.
+    #[inline]
+    pub fn new(slice: RopeSlice<'a>) -> Self {
+        let mut hasher = DefaultHasher::new();
+        slice.hash(&mut hasher);
+        Self { slice, hash: hasher.finish() }
+    }
+
+    /// Returns the hash of the slice computed when this `HashedSlice` was
+    /// created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{HashedSlice, Rope};
+    /// #
+    /// let r = Rope::from("foo foo");
+    ///
+    /// let first = HashedSlice::new(r.byte_slice(..3));
+    /// let second = HashedSlice::new(r.byte_slice(4..));
+    ///
+    /// assert_eq!(first.hash_value(), second.hash_value());
+    /// ```
+    #[inline]
+    pub fn hash_value(&self) -> u64 {
+        self.hash
+    }
+
+    /// Returns the underlying `RopeSlice`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{HashedSlice, Rope};
+    /// #
+    /// let r = Rope::from("foo bar");
+    ///
+    /// let hashed = HashedSlice::new(r.byte_slice(4..));
+    ///
+    /// assert_eq!(hashed.slice(), "bar");
+    /// ```
+    #[inline]
+    pub fn slice(&self) -> RopeSlice<'a> {
+        self.slice
+    }
+}
+
+impl<'a> From<RopeSlice<'a>> for HashedSlice<'a> {
+    #[inline]
+    fn from(slice: RopeSlice<'a>) -> Self {
+        Self::new(slice)
+    }
+}
+
+impl core::fmt::Debug for HashedSlice<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("HashedSlice")
+            .field("slice", &self.slice)
+            .field("hash", &self.hash)
+            .finish()
+    }
+}
+
+impl Hash for HashedSlice<'_> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+impl core::cmp::PartialEq for HashedSlice<'_> {
+    #[inline]
+    fn eq(&self, rhs: &Self) -> bool {
+        self.hash == rhs.hash && self.slice == rhs.slice
+    }
+}
+
+impl core::cmp::Eq for HashedSlice<'_> {}
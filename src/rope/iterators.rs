@@ -1,10 +1,21 @@
+use alloc::borrow::Cow;
+use core::ops::Range;
+
+#[cfg(feature = "unicode-lines")]
+use super::metrics::{is_unicode_line_break, UnicodeLineMetric};
 use super::metrics::{ByteMetric, LineMetric, RawLineMetric};
 use super::rope::RopeChunk;
+use super::utils::{adjust_split_point, char_count, count_chars};
+#[cfg(feature = "unicode-lines")]
+use super::LineBreakKind;
 use super::{Rope, RopeSlice};
 use crate::tree::{Leaves, Units};
 
 /// An iterator over the `&str` chunks of `Rope`s and `RopeSlice`s.
 ///
+/// The yielded chunks are never empty, so the chunks of an empty `Rope` or
+/// `RopeSlice` are an empty iterator.
+///
 /// This struct is created by the `chunks` method on [`Rope`](Rope::chunks())
 /// and [`RopeSlice`](RopeSlice::chunks()). See their documentation for more.
 #[derive(Clone)]
@@ -12,35 +23,135 @@ pub struct Chunks<'a> {
     leaves: Leaves<'a, { Rope::arity() }, RopeChunk>,
     forward_extra_right: Option<&'a str>,
     backward_extra_left: Option<&'a str>,
+
+    /// The number of bytes yielded by calling `next()`, i.e. the byte offset
+    /// of the start of the next chunk.
+    yielded_bytes: usize,
 }
 
-impl<'a> From<&'a Rope> for Chunks<'a> {
+/// A resumable position in the chunks of a [`Rope`] or of a [`RopeSlice`],
+/// obtained by calling [`Chunks::position()`].
+///
+/// A `ChunkPos` is just a byte offset, so it's cheap to store and to pass
+/// around. It's only meaningful for the `Rope` or the `RopeSlice` it was
+/// obtained from, and it's invalidated by any edit made to that `Rope`.
+/// Resuming from an invalidated position either panics or yields chunks which
+/// are not the ones that would've followed.
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub struct ChunkPos(usize);
+
+impl ChunkPos {
+    /// Returns the byte offset of the start of the next chunk that will be
+    /// yielded when resuming from this position.
     #[inline]
-    fn from(rope: &'a Rope) -> Self {
-        let mut leaves = rope.tree.leaves();
-        if rope.is_empty() {
-            let _ = leaves.next();
-        }
-        Self { leaves, forward_extra_right: None, backward_extra_left: None }
+    pub fn byte_offset(&self) -> usize {
+        self.0
     }
 }
 
-impl<'a> From<&RopeSlice<'a>> for Chunks<'a> {
+impl<'a> Chunks<'a> {
+    /// Returns the current position of the iterator, which can later be
+    /// passed to [`resume_at()`](Self::resume_at()) to continue iterating
+    /// from the same chunk.
+    ///
+    /// Only the chunks yielded by [`next()`](Iterator::next()) are taken into
+    /// account, and the position of a `Chunks` created from a `RopeSlice` is
+    /// relative to the start of that slice, so it has to be resumed with
+    /// [`resume_at_slice()`](Self::resume_at_slice()) on the same slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    ///
+    /// let mut chunks = r.chunks();
+    /// assert_eq!(chunks.position().byte_offset(), 0);
+    ///
+    /// let first = chunks.next().unwrap();
+    /// assert_eq!(chunks.position().byte_offset(), first.len());
+    /// ```
     #[inline]
-    fn from(slice: &RopeSlice<'a>) -> Self {
-        let mut leaves = slice.tree_slice.leaves();
-        if slice.is_empty() {
-            let _ = leaves.next();
-        }
-        Self { leaves, forward_extra_right: None, backward_extra_left: None }
+    pub fn position(&self) -> ChunkPos {
+        ChunkPos(self.yielded_bytes)
     }
-}
 
-impl<'a> Iterator for Chunks<'a> {
-    type Item = &'a str;
+    /// Returns an iterator over the chunks of the `Rope` which yields the
+    /// same chunks that a `Chunks` would've yielded after reaching `pos`.
+    ///
+    /// This only needs to descend the tree once to find the chunk at `pos`,
+    /// so it runs in `O(log n)` time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset of `pos` is out of bounds or if it doesn't
+    /// lie on a code point boundary, which can only happen if the `Rope` was
+    /// edited after `pos` was obtained.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::iter::Chunks;
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello, world!\n".repeat(500));
+    ///
+    /// let mut chunks = r.chunks();
+    /// let _ = chunks.next();
+    /// let pos = chunks.position();
+    ///
+    /// let resumed = Chunks::resume_at(&r, pos);
+    ///
+    /// assert!(resumed.eq(chunks));
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn resume_at(rope: &'a Rope, pos: ChunkPos) -> Self {
+        let mut chunks = rope.byte_slice(pos.0..).chunks();
+        chunks.yielded_bytes = pos.0;
+        chunks
+    }
+
+    /// Same as [`resume_at()`](Self::resume_at()), but for the chunks of a
+    /// `RopeSlice`, where `pos` is relative to the start of the slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset of `pos` is out of bounds or if it doesn't
+    /// lie on a code point boundary, which can only happen if `pos` was
+    /// obtained from a different slice or if the `Rope` was edited after
+    /// `pos` was obtained.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::iter::Chunks;
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello, world!\n".repeat(500));
+    /// let s = r.byte_slice(100..5000);
+    ///
+    /// let mut chunks = s.chunks();
+    /// let _ = chunks.next();
+    /// let pos = chunks.position();
+    ///
+    /// let resumed = Chunks::resume_at_slice(s, pos);
+    ///
+    /// assert!(resumed.eq(chunks));
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn resume_at_slice(slice: RopeSlice<'a>, pos: ChunkPos) -> Self {
+        let mut chunks = slice.byte_slice(pos.0..).chunks();
+        chunks.yielded_bytes = pos.0;
+        chunks
+    }
 
     #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
+    fn next_chunk(&mut self) -> Option<&'a str> {
         if let Some(extra) = self.forward_extra_right.take() {
             Some(extra)
         } else {
@@ -49,13 +160,12 @@ impl<'a> Iterator for Chunks<'a> {
             };
 
             if chunk.left_chunk().is_empty() {
-                #[cfg(feature = "small_chunks")]
+                // Leaves shouldn't be empty, but we skip them anyway to
+                // uphold the guarantee that we never yield empty chunks.
                 if chunk.right_chunk().is_empty() {
-                    return self.next();
+                    return self.next_chunk();
                 }
 
-                debug_assert!(!chunk.right_chunk().is_empty());
-
                 Some(chunk.right_chunk())
             } else {
                 if !chunk.right_chunk().is_empty() {
@@ -65,6 +175,49 @@ impl<'a> Iterator for Chunks<'a> {
             }
         }
     }
+}
+
+impl<'a> From<&'a Rope> for Chunks<'a> {
+    #[inline]
+    fn from(rope: &'a Rope) -> Self {
+        let mut leaves = rope.tree.leaves();
+        if rope.is_empty() {
+            let _ = leaves.next();
+        }
+        Self {
+            leaves,
+            forward_extra_right: None,
+            backward_extra_left: None,
+            yielded_bytes: 0,
+        }
+    }
+}
+
+impl<'a> From<&RopeSlice<'a>> for Chunks<'a> {
+    #[inline]
+    fn from(slice: &RopeSlice<'a>) -> Self {
+        let mut leaves = slice.tree_slice.leaves();
+        if slice.is_empty() {
+            let _ = leaves.next();
+        }
+        Self {
+            leaves,
+            forward_extra_right: None,
+            backward_extra_left: None,
+            yielded_bytes: 0,
+        }
+    }
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a str;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = self.next_chunk()?;
+        self.yielded_bytes += chunk.len();
+        Some(chunk)
+    }
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -84,13 +237,11 @@ impl DoubleEndedIterator for Chunks<'_> {
             };
 
             if chunk.right_chunk().is_empty() {
-                #[cfg(feature = "small_chunks")]
+                // Same as in `next_chunk()`.
                 if chunk.left_chunk().is_empty() {
                     return self.next_back();
                 }
 
-                debug_assert!(!chunk.left_chunk().is_empty());
-
                 Some(chunk.left_chunk())
             } else {
                 if !chunk.left_chunk().is_empty() {
@@ -104,6 +255,174 @@ impl DoubleEndedIterator for Chunks<'_> {
 
 impl core::iter::FusedIterator for Chunks<'_> {}
 
+/// An iterator over the byte offsets of the start of every chunk of `Rope`s
+/// and `RopeSlice`s.
+///
+/// This struct is created by the `chunk_boundaries` method on
+/// [`Rope`](Rope::chunk_boundaries()) and
+/// [`RopeSlice`](RopeSlice::chunk_boundaries()). See their documentation for
+/// more.
+#[derive(Clone)]
+pub struct ChunkBoundaries<'a> {
+    chunks: Chunks<'a>,
+
+    /// The byte offset of the start of the next chunk.
+    byte_offset: usize,
+}
+
+impl<'a> From<&'a Rope> for ChunkBoundaries<'a> {
+    #[inline]
+    fn from(rope: &'a Rope) -> Self {
+        Self { chunks: rope.chunks(), byte_offset: 0 }
+    }
+}
+
+impl<'a> From<&RopeSlice<'a>> for ChunkBoundaries<'a> {
+    #[inline]
+    fn from(slice: &RopeSlice<'a>) -> Self {
+        Self { chunks: slice.chunks(), byte_offset: 0 }
+    }
+}
+
+impl Iterator for ChunkBoundaries<'_> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = self.chunks.next()?;
+        let chunk_start = self.byte_offset;
+        self.byte_offset += chunk.len();
+        Some(chunk_start)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl core::iter::FusedIterator for ChunkBoundaries<'_> {}
+
+/// An iterator over the chunks of `Rope`s and `RopeSlice`s paired with their
+/// byte ranges in the `Rope`.
+///
+/// This struct is created by the `chunk_ranges` method on
+/// [`Rope`](Rope::chunk_ranges()) and [`RopeSlice`](RopeSlice::chunk_ranges()).
+/// See their documentation for more.
+#[derive(Clone)]
+pub struct ChunkRanges<'a> {
+    chunks: Chunks<'a>,
+
+    /// The byte offset in the `Rope` of the start of the next chunk yielded
+    /// by `next()`.
+    forward_byte_offset: usize,
+
+    /// The byte offset in the `Rope` of the end of the next chunk yielded by
+    /// `next_back()`.
+    backward_byte_offset: usize,
+}
+
+impl<'a> From<&'a Rope> for ChunkRanges<'a> {
+    #[inline]
+    fn from(rope: &'a Rope) -> Self {
+        Self {
+            chunks: rope.chunks(),
+            forward_byte_offset: 0,
+            backward_byte_offset: rope.byte_len(),
+        }
+    }
+}
+
+impl<'a> From<&RopeSlice<'a>> for ChunkRanges<'a> {
+    #[inline]
+    fn from(slice: &RopeSlice<'a>) -> Self {
+        Self {
+            chunks: slice.chunks(),
+            forward_byte_offset: slice.byte_offset,
+            backward_byte_offset: slice.byte_offset + slice.byte_len(),
+        }
+    }
+}
+
+impl<'a> Iterator for ChunkRanges<'a> {
+    type Item = (Range<usize>, &'a str);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = self.chunks.next()?;
+        let start = self.forward_byte_offset;
+        self.forward_byte_offset += chunk.len();
+        Some((start..self.forward_byte_offset, chunk))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for ChunkRanges<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let chunk = self.chunks.next_back()?;
+        let end = self.backward_byte_offset;
+        self.backward_byte_offset -= chunk.len();
+        Some((self.backward_byte_offset..end, chunk))
+    }
+}
+
+impl core::iter::FusedIterator for ChunkRanges<'_> {}
+
+/// An iterator over the chunks of `Rope`s and `RopeSlice`s as byte slices.
+///
+/// This yields the same chunks as [`Chunks`], just as `&[u8]` instead of
+/// `&str`, so they're never empty and they tile the text exactly.
+///
+/// This struct is created by the `byte_chunks` method on
+/// [`Rope`](Rope::byte_chunks()) and [`RopeSlice`](RopeSlice::byte_chunks()).
+/// See their documentation for more.
+#[derive(Clone)]
+pub struct ByteChunks<'a> {
+    chunks: Chunks<'a>,
+}
+
+impl<'a> From<&'a Rope> for ByteChunks<'a> {
+    #[inline]
+    fn from(rope: &'a Rope) -> Self {
+        Self { chunks: rope.chunks() }
+    }
+}
+
+impl<'a> From<&RopeSlice<'a>> for ByteChunks<'a> {
+    #[inline]
+    fn from(slice: &RopeSlice<'a>) -> Self {
+        Self { chunks: slice.chunks() }
+    }
+}
+
+impl<'a> Iterator for ByteChunks<'a> {
+    type Item = &'a [u8];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chunks.next().map(str::as_bytes)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for ByteChunks<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.chunks.next_back().map(str::as_bytes)
+    }
+}
+
+impl core::iter::FusedIterator for ByteChunks<'_> {}
+
 /// An iterator over the bytes of `Rope`s and `RopeSlice`s.
 ///
 /// This struct is created by the `bytes` method on [`Rope`](Rope::bytes())
@@ -193,6 +512,120 @@ impl Iterator for Bytes<'_> {
         let exact = self.len();
         (exact, Some(exact))
     }
+
+    /// Reads the last byte from the back instead of yielding all the others
+    /// first.
+    #[inline]
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
+
+    #[inline]
+    fn find<P>(&mut self, mut predicate: P) -> Option<Self::Item>
+    where
+        P: FnMut(&Self::Item) -> bool,
+    {
+        let mut found = None;
+
+        self.position(|byte| {
+            let is_match = predicate(&byte);
+            if is_match {
+                found = Some(byte);
+            }
+            is_match
+        })?;
+
+        found
+    }
+
+    /// Scans the rest of every chunk as a byte slice, which is a lot faster
+    /// than calling `next()` on every byte.
+    #[inline]
+    fn position<P>(&mut self, mut predicate: P) -> Option<usize>
+    where
+        P: FnMut(Self::Item) -> bool,
+    {
+        let mut skipped = 0;
+
+        loop {
+            let rest = &self.forward_chunk[self.forward_byte_idx..];
+
+            if let Some(idx) = rest.iter().position(|&byte| predicate(byte)) {
+                self.forward_byte_idx += idx + 1;
+                self.bytes_yielded += idx + 1;
+                return Some(skipped + idx);
+            }
+
+            skipped += rest.len();
+            self.bytes_yielded += rest.len();
+            self.forward_byte_idx = self.forward_chunk.len();
+
+            let Some(chunk) = self.chunks.next() else { break };
+
+            self.forward_chunk = chunk.as_bytes();
+            self.forward_byte_idx = 0;
+        }
+
+        // The only bytes left are the ones of the backward chunk that haven't
+        // been yielded by `next_back()`.
+        let rest = &self.backward_chunk[..self.backward_byte_idx];
+
+        let idx = rest.iter().position(|&byte| predicate(byte));
+
+        let consumed = idx.map_or(rest.len(), |idx| idx + 1);
+
+        self.backward_chunk = &self.backward_chunk[consumed..];
+        self.backward_byte_idx -= consumed;
+        self.bytes_yielded += consumed;
+
+        idx.map(|idx| skipped + idx)
+    }
+
+    /// Like [`position()`](Self::position()), this scans the chunks as byte
+    /// slices, starting from the back.
+    #[inline]
+    fn rposition<P>(&mut self, mut predicate: P) -> Option<usize>
+    where
+        P: FnMut(Self::Item) -> bool,
+    {
+        let len = self.len();
+
+        let mut skipped = 0;
+
+        loop {
+            let rest = &self.backward_chunk[..self.backward_byte_idx];
+
+            if let Some(idx) = rest.iter().rposition(|&byte| predicate(byte)) {
+                let consumed = rest.len() - idx;
+                self.backward_byte_idx = idx;
+                self.bytes_yielded += consumed;
+                return Some(len - skipped - consumed);
+            }
+
+            skipped += rest.len();
+            self.bytes_yielded += rest.len();
+            self.backward_byte_idx = 0;
+
+            let Some(chunk) = self.chunks.next_back() else { break };
+
+            self.backward_chunk = chunk.as_bytes();
+            self.backward_byte_idx = chunk.len();
+        }
+
+        // The only bytes left are the ones of the forward chunk that haven't
+        // been yielded by `next()`.
+        let rest = &self.forward_chunk[self.forward_byte_idx..];
+
+        let idx = rest.iter().rposition(|&byte| predicate(byte));
+
+        let consumed = idx.map_or(rest.len(), |idx| rest.len() - idx);
+
+        self.forward_chunk =
+            &self.forward_chunk[..self.forward_chunk.len() - consumed];
+        self.bytes_yielded += consumed;
+
+        idx.map(|_| len - skipped - consumed)
+    }
 }
 
 impl DoubleEndedIterator for Bytes<'_> {
@@ -320,6 +753,22 @@ impl<'a> Iterator for Chars<'a> {
 
         Some(ch)
     }
+
+    /// Decodes the last char from the back instead of yielding all the
+    /// others first.
+    #[inline]
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
+
+    /// Counts the chars of every chunk at once instead of decoding them one
+    /// by one.
+    #[inline]
+    fn count(self) -> usize {
+        char_count(&self.forward_chunk[self.forward_byte_idx..])
+            + count_chars(self.chunks)
+            + char_count(&self.backward_chunk[..self.backward_byte_idx])
+    }
 }
 
 impl DoubleEndedIterator for Chars<'_> {
@@ -381,6 +830,17 @@ pub struct RawLines<'a> {
 
     /// The total number of bytes this iterator will yield.
     lines_total: usize,
+
+    /// The byte offset in the `Rope` of the start of the next line yielded by
+    /// `next()`.
+    forward_byte_offset: usize,
+
+    /// The byte offset in the `Rope` of the end of the next line yielded by
+    /// `next_back()`, including its line terminator.
+    backward_byte_offset: usize,
+
+    /// The generation of the `Rope` the lines are sliced from.
+    generation: usize,
 }
 
 impl<'a> From<&'a Rope> for RawLines<'a> {
@@ -390,6 +850,9 @@ impl<'a> From<&'a Rope> for RawLines<'a> {
             units: rope.tree.units::<RawLineMetric>(),
             lines_yielded: 0,
             lines_total: rope.line_len(),
+            forward_byte_offset: 0,
+            backward_byte_offset: rope.byte_len(),
+            generation: rope.generation,
         }
     }
 }
@@ -401,6 +864,9 @@ impl<'a> From<&RopeSlice<'a>> for RawLines<'a> {
             units: slice.tree_slice.units::<RawLineMetric>(),
             lines_yielded: 0,
             lines_total: slice.line_len(),
+            forward_byte_offset: slice.byte_offset,
+            backward_byte_offset: slice.byte_offset + slice.byte_len(),
+            generation: slice.generation,
         }
     }
 }
@@ -410,9 +876,11 @@ impl<'a> Iterator for RawLines<'a> {
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        let (tree_slice, _) = self.units.next()?;
+        let (tree_slice, ByteMetric(advance)) = self.units.next()?;
         self.lines_yielded += 1;
-        Some(RopeSlice::from(tree_slice))
+        let byte_offset = self.forward_byte_offset;
+        self.forward_byte_offset += advance;
+        Some(RopeSlice::new(tree_slice, byte_offset, self.generation))
     }
 
     #[inline]
@@ -425,9 +893,14 @@ impl<'a> Iterator for RawLines<'a> {
 impl DoubleEndedIterator for RawLines<'_> {
     #[inline]
     fn next_back(&mut self) -> Option<Self::Item> {
-        let (tree_slice, _) = self.units.next_back()?;
+        let (tree_slice, ByteMetric(advance)) = self.units.next_back()?;
         self.lines_yielded += 1;
-        Some(RopeSlice::from(tree_slice))
+        self.backward_byte_offset -= advance;
+        Some(RopeSlice::new(
+            tree_slice,
+            self.backward_byte_offset,
+            self.generation,
+        ))
     }
 }
 
@@ -440,53 +913,149 @@ impl ExactSizeIterator for RawLines<'_> {
 
 impl core::iter::FusedIterator for RawLines<'_> {}
 
-/// An iterator over the lines of `Rope`s and `RopeSlice`s, not including the
-/// line terminators (`\n` or `\r\n`).
+/// An iterator over the byte ranges of the lines of a `Rope`.
 ///
-/// This struct is created by the `lines` method on [`Rope`](Rope::lines()) and
-/// [`RopeSlice`](RopeSlice::lines()). See their documentation for more.
+/// This struct is created by the [`line_ranges`](Rope::line_ranges()) and
+/// [`raw_line_ranges`](Rope::raw_line_ranges()) methods on `Rope`. See their
+/// documentation for more.
 #[derive(Clone)]
-pub struct Lines<'a> {
-    units: Units<'a, { Rope::arity() }, RopeChunk, LineMetric>,
+pub struct LineRanges<'a> {
+    raw_lines: RawLines<'a>,
 
-    /// The number of lines that have been yielded so far.
-    lines_yielded: usize,
-
-    /// The total number of bytes this iterator will yield.
-    lines_total: usize,
+    /// Whether the ranges include the line terminators.
+    with_terminators: bool,
 }
 
-impl<'a> From<&'a Rope> for Lines<'a> {
+impl<'a> LineRanges<'a> {
     #[inline]
-    fn from(rope: &'a Rope) -> Self {
-        Self {
-            units: rope.tree.units::<LineMetric>(),
-            lines_yielded: 0,
-            lines_total: rope.line_len(),
-        }
+    pub(super) fn new(rope: &'a Rope, with_terminators: bool) -> Self {
+        Self { raw_lines: RawLines::from(rope), with_terminators }
     }
-}
 
-impl<'a> From<&RopeSlice<'a>> for Lines<'a> {
     #[inline]
-    fn from(slice: &RopeSlice<'a>) -> Self {
-        Self {
-            units: slice.tree_slice.units::<LineMetric>(),
-            lines_yielded: 0,
-            lines_total: slice.line_len(),
+    fn range_of(&self, raw_line: RopeSlice<'_>) -> Range<usize> {
+        let start = raw_line.byte_offset;
+
+        let mut end = start + raw_line.byte_len();
+
+        if !self.with_terminators
+            && raw_line.tree_slice.end_slice().last_chunk().ends_with('\n')
+        {
+            end -= 1;
+
+            // The "\r" of a "\r\n" pair can be in the previous chunk.
+            if end > start && raw_line.byte(end - start - 1) == b'\r' {
+                end -= 1;
+            }
         }
+
+        start..end
     }
 }
 
-impl<'a> Iterator for Lines<'a> {
-    type Item = RopeSlice<'a>;
+impl Iterator for LineRanges<'_> {
+    type Item = Range<usize>;
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        let (tree_slice, ByteMetric(advance)) = self.units.next()?;
+        let raw_line = self.raw_lines.next()?;
+        Some(self.range_of(raw_line))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.raw_lines.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for LineRanges<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let raw_line = self.raw_lines.next_back()?;
+        Some(self.range_of(raw_line))
+    }
+}
+
+impl ExactSizeIterator for LineRanges<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.raw_lines.len()
+    }
+}
+
+impl core::iter::FusedIterator for LineRanges<'_> {}
+
+/// An iterator over the lines of `Rope`s and `RopeSlice`s, not including the
+/// line terminators (`\n` or `\r\n`).
+///
+/// This struct is created by the `lines` method on [`Rope`](Rope::lines()) and
+/// [`RopeSlice`](RopeSlice::lines()). See their documentation for more.
+#[derive(Clone)]
+pub struct Lines<'a> {
+    units: Units<'a, { Rope::arity() }, RopeChunk, LineMetric>,
+
+    /// The number of lines that have been yielded so far.
+    lines_yielded: usize,
+
+    /// The total number of bytes this iterator will yield.
+    lines_total: usize,
+
+    /// The byte offset in the `Rope` of the start of the next line yielded by
+    /// `next()`.
+    forward_byte_offset: usize,
+
+    /// The byte offset in the `Rope` of the end of the next line yielded by
+    /// `next_back()`, including its line terminator.
+    backward_byte_offset: usize,
+
+    /// The generation of the `Rope` the lines are sliced from.
+    generation: usize,
+}
+
+impl<'a> From<&'a Rope> for Lines<'a> {
+    #[inline]
+    fn from(rope: &'a Rope) -> Self {
+        Self {
+            units: rope.tree.units::<LineMetric>(),
+            lines_yielded: 0,
+            lines_total: rope.line_len(),
+            forward_byte_offset: 0,
+            backward_byte_offset: rope.byte_len(),
+            generation: rope.generation,
+        }
+    }
+}
+
+impl<'a> From<&RopeSlice<'a>> for Lines<'a> {
+    #[inline]
+    fn from(slice: &RopeSlice<'a>) -> Self {
+        Self {
+            units: slice.tree_slice.units::<LineMetric>(),
+            lines_yielded: 0,
+            lines_total: slice.line_len(),
+            forward_byte_offset: slice.byte_offset,
+            backward_byte_offset: slice.byte_offset + slice.byte_len(),
+            generation: slice.generation,
+        }
+    }
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = RopeSlice<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (tree_slice, ByteMetric(advance)) = self.units.next()?;
         self.lines_yielded += 1;
 
-        let mut slice = RopeSlice { tree_slice, has_trailing_newline: false };
+        let mut slice = RopeSlice {
+            tree_slice,
+            has_trailing_newline: false,
+            byte_offset: self.forward_byte_offset,
+            generation: self.generation,
+        };
+
+        self.forward_byte_offset += advance;
 
         // This handles CRLF pairs that have been split across chunks. For
         // example, if we have "aaa\r" and "\nbbb" we should yield "aaa", but
@@ -514,7 +1083,14 @@ impl DoubleEndedIterator for Lines<'_> {
         let (tree_slice, ByteMetric(advance)) = self.units.next_back()?;
         self.lines_yielded += 1;
 
-        let mut slice = RopeSlice { tree_slice, has_trailing_newline: false };
+        self.backward_byte_offset -= advance;
+
+        let mut slice = RopeSlice {
+            tree_slice,
+            has_trailing_newline: false,
+            byte_offset: self.backward_byte_offset,
+            generation: self.generation,
+        };
 
         // Same as above.
         if slice.tree_slice.end_slice().last_chunk().ends_with('\r')
@@ -536,6 +1112,603 @@ impl ExactSizeIterator for Lines<'_> {
 
 impl core::iter::FusedIterator for Lines<'_> {}
 
+/// An iterator over the lines of `Rope`s and `RopeSlice`s as `Cow<str>`s, not
+/// including the line terminators (`\n` or `\r\n`).
+///
+/// Lines contained in a single chunk are borrowed, while lines spanning
+/// multiple chunks are copied into an owned `String`.
+///
+/// This struct is created by the `lines_cow` method on
+/// [`Rope`](Rope::lines_cow()) and [`RopeSlice`](RopeSlice::lines_cow()). See
+/// their documentation for more.
+#[derive(Clone)]
+pub struct LinesCow<'a> {
+    lines: Lines<'a>,
+}
+
+impl<'a> From<Lines<'a>> for LinesCow<'a> {
+    #[inline]
+    fn from(lines: Lines<'a>) -> Self {
+        Self { lines }
+    }
+}
+
+#[inline]
+fn line_to_cow(line: RopeSlice<'_>) -> Cow<'_, str> {
+    match line.as_str() {
+        Some(line) => Cow::Borrowed(line),
+        None => Cow::Owned(line.to_string()),
+    }
+}
+
+impl<'a> Iterator for LinesCow<'a> {
+    type Item = Cow<'a, str>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.lines.next().map(line_to_cow)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.lines.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for LinesCow<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.lines.next_back().map(line_to_cow)
+    }
+}
+
+impl ExactSizeIterator for LinesCow<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.lines.len()
+    }
+}
+
+impl core::iter::FusedIterator for LinesCow<'_> {}
+
+/// An iterator over the lines of `Rope`s and `RopeSlice`s split on a
+/// configurable set of line breaks, not including the line terminators.
+///
+/// This struct is created by the `lines_with` method on
+/// [`Rope`](Rope::lines_with()) and [`RopeSlice`](RopeSlice::lines_with()).
+/// See their documentation for more.
+#[cfg_attr(docsrs, doc(cfg(feature = "unicode-lines")))]
+#[cfg(feature = "unicode-lines")]
+#[derive(Clone)]
+pub struct LinesWith<'a> {
+    inner: LinesWithInner<'a>,
+}
+
+#[cfg(feature = "unicode-lines")]
+#[derive(Clone)]
+enum LinesWithInner<'a> {
+    Lf(Lines<'a>),
+    UnicodeFull(UnicodeLines<'a>),
+}
+
+#[cfg(feature = "unicode-lines")]
+impl<'a> LinesWith<'a> {
+    #[inline]
+    pub(super) fn new(slice: RopeSlice<'a>, kind: LineBreakKind) -> Self {
+        let inner = match kind {
+            LineBreakKind::Lf => LinesWithInner::Lf(Lines::from(&slice)),
+            LineBreakKind::UnicodeFull => {
+                LinesWithInner::UnicodeFull(UnicodeLines::from(&slice))
+            },
+        };
+
+        Self { inner }
+    }
+}
+
+#[cfg(feature = "unicode-lines")]
+impl<'a> Iterator for LinesWith<'a> {
+    type Item = RopeSlice<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            LinesWithInner::Lf(lines) => lines.next(),
+            LinesWithInner::UnicodeFull(lines) => lines.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let exact = self.len();
+        (exact, Some(exact))
+    }
+}
+
+#[cfg(feature = "unicode-lines")]
+impl DoubleEndedIterator for LinesWith<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            LinesWithInner::Lf(lines) => lines.next_back(),
+            LinesWithInner::UnicodeFull(lines) => lines.next_back(),
+        }
+    }
+}
+
+#[cfg(feature = "unicode-lines")]
+impl ExactSizeIterator for LinesWith<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        match &self.inner {
+            LinesWithInner::Lf(lines) => lines.len(),
+            LinesWithInner::UnicodeFull(lines) => {
+                lines.lines_total - lines.lines_yielded
+            },
+        }
+    }
+}
+
+#[cfg(feature = "unicode-lines")]
+impl core::iter::FusedIterator for LinesWith<'_> {}
+
+/// The iterator behind [`LineBreakKind::UnicodeFull`], which splits lines on
+/// line feeds, next lines, line separators and paragraph separators.
+///
+/// Since chunks are always split on char boundaries the multi-byte separators
+/// are never split between two chunks, so the only line break that can span
+/// a chunk boundary is a `"\r\n"` pair.
+#[cfg(feature = "unicode-lines")]
+#[derive(Clone)]
+struct UnicodeLines<'a> {
+    units: Units<'a, { Rope::arity() }, RopeChunk, UnicodeLineMetric>,
+
+    /// The number of lines that have been yielded so far.
+    lines_yielded: usize,
+
+    /// The total number of lines this iterator will yield.
+    lines_total: usize,
+
+    /// The byte offset in the `Rope` of the start of the next line yielded by
+    /// `next()`.
+    forward_byte_offset: usize,
+
+    /// The byte offset in the `Rope` of the end of the next line yielded by
+    /// `next_back()`, including its line terminator.
+    backward_byte_offset: usize,
+
+    /// The generation of the `Rope` the lines are sliced from.
+    generation: usize,
+}
+
+#[cfg(feature = "unicode-lines")]
+impl<'a> From<&RopeSlice<'a>> for UnicodeLines<'a> {
+    #[inline]
+    fn from(slice: &RopeSlice<'a>) -> Self {
+        Self {
+            units: slice.tree_slice.units::<UnicodeLineMetric>(),
+            lines_yielded: 0,
+            lines_total: slice.line_len_with(LineBreakKind::UnicodeFull),
+            forward_byte_offset: slice.byte_offset,
+            backward_byte_offset: slice.byte_offset + slice.byte_len(),
+            generation: slice.generation,
+        }
+    }
+}
+
+/// Removes the trailing line break from a line yielded by
+/// `Units<UnicodeLineMetric>`, if it has one.
+#[cfg(feature = "unicode-lines")]
+#[inline]
+fn truncate_unicode_line_break(line: RopeSlice<'_>) -> RopeSlice<'_> {
+    let last_char =
+        match line.tree_slice.end_slice().last_chunk().chars().next_back() {
+            Some(ch) if is_unicode_line_break(ch) => ch,
+            _ => return line,
+        };
+
+    let mut terminator_len = last_char.len_utf8();
+
+    // The "\r" of a "\r\n" pair can be in the previous chunk, so we read it
+    // from the slice instead of from the last chunk.
+    if last_char == '\n'
+        && line.byte_len() > 1
+        && line.byte(line.byte_len() - 2) == b'\r'
+    {
+        terminator_len += 1;
+    }
+
+    line.byte_slice(..line.byte_len() - terminator_len)
+}
+
+#[cfg(feature = "unicode-lines")]
+impl<'a> Iterator for UnicodeLines<'a> {
+    type Item = RopeSlice<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (tree_slice, ByteMetric(advance)) = self.units.next()?;
+        self.lines_yielded += 1;
+        let byte_offset = self.forward_byte_offset;
+        self.forward_byte_offset += advance;
+        let line = RopeSlice::new(tree_slice, byte_offset, self.generation);
+        Some(truncate_unicode_line_break(line))
+    }
+}
+
+#[cfg(feature = "unicode-lines")]
+impl DoubleEndedIterator for UnicodeLines<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (tree_slice, ByteMetric(advance)) = self.units.next_back()?;
+        self.lines_yielded += 1;
+        self.backward_byte_offset -= advance;
+        let line = RopeSlice::new(
+            tree_slice,
+            self.backward_byte_offset,
+            self.generation,
+        );
+        Some(truncate_unicode_line_break(line))
+    }
+}
+
+/// An iterator over the non-overlapping matches of a string pattern in
+/// `Rope`s and `RopeSlice`s.
+///
+/// This struct is created by the `matches` method on
+/// [`Rope`](Rope::matches()) and [`RopeSlice`](RopeSlice::matches()). See
+/// their documentation for more.
+#[derive(Clone)]
+pub struct Matches<'a> {
+    /// The slice we're searching in, used to slice the matches.
+    slice: RopeSlice<'a>,
+
+    chunks: Chunks<'a>,
+
+    /// The string we're searching for.
+    needle: &'a str,
+
+    /// The part of the current chunk that hasn't been searched yet.
+    chunk: &'a str,
+
+    /// The byte offset of the start of `chunk` in `slice`.
+    chunk_offset: usize,
+
+    /// The bytes immediately preceding `chunk` where a match could still
+    /// start, i.e. a match that would straddle two or more chunks. Its length
+    /// is always less than the length of the needle.
+    tail: String,
+}
+
+impl<'a> Matches<'a> {
+    #[inline]
+    pub(super) fn new(slice: RopeSlice<'a>, needle: &'a str) -> Self {
+        Self {
+            chunks: slice.chunks(),
+            slice,
+            needle,
+            chunk: "",
+            chunk_offset: 0,
+            tail: String::new(),
+        }
+    }
+
+    /// Moves the start of the current chunk to the given byte offset.
+    #[inline]
+    fn advance_to(&mut self, byte_offset: usize) {
+        debug_assert!(byte_offset >= self.chunk_offset);
+        self.chunk = &self.chunk[byte_offset - self.chunk_offset..];
+        self.chunk_offset = byte_offset;
+    }
+}
+
+impl<'a> Iterator for Matches<'a> {
+    type Item = RopeSlice<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let needle_len = self.needle.len();
+
+        if needle_len == 0 {
+            return None;
+        }
+
+        loop {
+            if self.tail.is_empty() {
+                if let Some(idx) = self.chunk.find(self.needle) {
+                    let start = self.chunk_offset + idx;
+                    self.advance_to(start + needle_len);
+                    return Some(
+                        self.slice.byte_slice(start..start + needle_len),
+                    );
+                }
+
+                let tail_start = adjust_split_point::<true>(
+                    self.chunk,
+                    self.chunk.len().saturating_sub(needle_len - 1),
+                );
+
+                self.tail.push_str(&self.chunk[tail_start..]);
+            } else {
+                // A match starting in the tail can't span more than
+                // `needle_len - 1` bytes of the current chunk, so that's all
+                // we need to append to the tail to find it.
+                let tail_len = self.tail.len();
+
+                let prefix_len =
+                    adjust_split_point::<true>(self.chunk, needle_len - 1);
+
+                self.tail.push_str(&self.chunk[..prefix_len]);
+
+                if let Some(idx) = self.tail.find(self.needle) {
+                    let start = self.chunk_offset - tail_len + idx;
+                    self.tail.clear();
+                    self.advance_to(start + needle_len);
+                    return Some(
+                        self.slice.byte_slice(start..start + needle_len),
+                    );
+                }
+
+                if prefix_len < self.chunk.len() {
+                    // No match starts in the tail, we can start searching the
+                    // current chunk on its own.
+                    self.tail.clear();
+                    continue;
+                }
+
+                // The whole chunk is contained in the tail, so we only keep
+                // the bytes that could still start a match.
+                let tail_start = adjust_split_point::<true>(
+                    &self.tail,
+                    self.tail.len().saturating_sub(needle_len - 1),
+                );
+
+                self.tail.drain(..tail_start);
+            }
+
+            self.chunk_offset += self.chunk.len();
+            self.chunk = "";
+            self.chunk = self.chunks.next()?;
+        }
+    }
+}
+
+impl core::iter::FusedIterator for Matches<'_> {}
+
+/// An iterator over the whitespace-separated sub-slices of `Rope`s and
+/// `RopeSlice`s.
+///
+/// This struct is created by the `split_whitespace` method on
+/// [`Rope`](Rope::split_whitespace()) and
+/// [`RopeSlice`](RopeSlice::split_whitespace()). See their documentation for
+/// more.
+#[derive(Clone)]
+pub struct SplitWhitespace<'a> {
+    /// The slice we're splitting, used to slice the words.
+    slice: RopeSlice<'a>,
+
+    chunks: Chunks<'a>,
+
+    /// The part of the current chunk that hasn't been yielded yet.
+    chunk: &'a str,
+
+    /// The byte offset of the start of `chunk` in `slice`.
+    chunk_offset: usize,
+}
+
+impl<'a> SplitWhitespace<'a> {
+    #[inline]
+    pub(super) fn new(slice: RopeSlice<'a>) -> Self {
+        Self { chunks: slice.chunks(), slice, chunk: "", chunk_offset: 0 }
+    }
+
+    /// Moves to the next chunk, returning `false` if there are no more chunks.
+    #[inline]
+    fn next_chunk(&mut self) -> bool {
+        self.chunk_offset += self.chunk.len();
+        self.chunk = "";
+
+        match self.chunks.next() {
+            Some(chunk) => {
+                self.chunk = chunk;
+                true
+            },
+            None => false,
+        }
+    }
+}
+
+impl<'a> Iterator for SplitWhitespace<'a> {
+    type Item = RopeSlice<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // Skip the whitespace before the start of the next word, which could
+        // span several chunks.
+        let start = loop {
+            if let Some(idx) = self.chunk.find(|ch: char| !ch.is_whitespace())
+            {
+                self.chunk = &self.chunk[idx..];
+                self.chunk_offset += idx;
+                break self.chunk_offset;
+            }
+
+            if !self.next_chunk() {
+                return None;
+            }
+        };
+
+        // Same for the word itself.
+        let end = loop {
+            if let Some(idx) = self.chunk.find(char::is_whitespace) {
+                self.chunk = &self.chunk[idx..];
+                self.chunk_offset += idx;
+                break self.chunk_offset;
+            }
+
+            if !self.next_chunk() {
+                break self.chunk_offset;
+            }
+        };
+
+        Some(self.slice.byte_slice(start..end))
+    }
+}
+
+impl core::iter::FusedIterator for SplitWhitespace<'_> {}
+
+/// An iterator over the lines of `Rope`s and `RopeSlice`s wrapped at a
+/// maximum byte width, not including the line terminators.
+///
+/// This struct is created by the `wrapped_lines` method on
+/// [`Rope`](Rope::wrapped_lines()) and
+/// [`RopeSlice`](RopeSlice::wrapped_lines()). See their documentation for
+/// more.
+#[derive(Clone)]
+pub struct WrappedLines<'a> {
+    lines: Lines<'a>,
+
+    /// The rest of the logical line that's currently being wrapped, if it
+    /// didn't fit in a single visual line.
+    rest: Option<RopeSlice<'a>>,
+
+    width: usize,
+}
+
+impl<'a> WrappedLines<'a> {
+    #[track_caller]
+    #[inline]
+    pub(super) fn new(lines: Lines<'a>, width: usize) -> Self {
+        assert!(width > 0, "the wrap width must be greater than zero");
+        Self { lines, rest: None, width }
+    }
+}
+
+impl<'a> Iterator for WrappedLines<'a> {
+    type Item = RopeSlice<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = match self.rest.take() {
+            Some(rest) => rest,
+            None => self.lines.next()?,
+        };
+
+        if line.byte_len() <= self.width {
+            return Some(line);
+        }
+
+        let mut split_at = self.width;
+
+        while !line.is_char_boundary(split_at) {
+            split_at -= 1;
+        }
+
+        // The first char is wider than the width, so it gets its own visual
+        // line.
+        if split_at == 0 {
+            split_at = self.width;
+
+            while !line.is_char_boundary(split_at) {
+                split_at += 1;
+            }
+        }
+
+        if split_at < line.byte_len() {
+            self.rest = Some(line.byte_slice(split_at..));
+        }
+
+        Some(line.byte_slice(..split_at))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lo, _) = self.lines.size_hint();
+        (lo + self.rest.is_some() as usize, None)
+    }
+}
+
+impl core::iter::FusedIterator for WrappedLines<'_> {}
+
+/// An iterator over the chars removed from a `Rope` by
+/// [`Rope::drain()`](Rope::drain()). See its documentation for more.
+///
+/// The text is removed from the `Rope` when the `Drain` is created, so the
+/// `Drain` doesn't borrow the `Rope` and dropping it before it's exhausted
+/// doesn't undo the removal.
+#[derive(Clone)]
+pub struct Drain {
+    /// The removed text.
+    text: String,
+
+    /// The byte offset of the start of the text that hasn't been yielded yet.
+    front: usize,
+
+    /// The byte offset of the end of the text that hasn't been yielded yet.
+    back: usize,
+}
+
+impl Drain {
+    #[inline]
+    pub(super) fn new(text: String) -> Self {
+        Self { back: text.len(), text, front: 0 }
+    }
+
+    /// Returns the part of the removed text that hasn't been yielded yet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello Earth 🌎!");
+    ///
+    /// let mut drain = r.drain(6..);
+    /// assert_eq!(drain.next(), Some('E'));
+    /// assert_eq!(drain.next_back(), Some('!'));
+    /// assert_eq!(drain.as_str(), "arth 🌎");
+    /// ```
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.text[self.front..self.back]
+    }
+}
+
+impl core::fmt::Debug for Drain {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Drain").field(&self.as_str()).finish()
+    }
+}
+
+impl Iterator for Drain {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let ch = self.as_str().chars().next()?;
+        self.front += ch.len_utf8();
+        Some(ch)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let bytes = self.back - self.front;
+        ((bytes + 3) / 4, Some(bytes))
+    }
+}
+
+impl DoubleEndedIterator for Drain {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let ch = self.as_str().chars().next_back()?;
+        self.back -= ch.len_utf8();
+        Some(ch)
+    }
+}
+
+impl core::iter::FusedIterator for Drain {}
+
 #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
 #[cfg(feature = "graphemes")]
 pub use graphemes::Graphemes;
@@ -0,0 +1,51 @@
+/// The line terminators recognized by [`Rope`](crate::Rope)s.
+///
+/// See [`Rope::detect_line_ending()`](crate::Rope::detect_line_ending()) for
+/// more.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum LineEnding {
+    /// A line feed (`"\n"`), used on Unix-like systems.
+    #[default]
+    Lf,
+
+    /// A carriage return followed by a line feed (`"\r\n"`), used on
+    /// Windows.
+    CrLf,
+}
+
+impl LineEnding {
+    /// Returns the line terminator as a string slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::LineEnding;
+    /// #
+    /// assert_eq!(LineEnding::Lf.as_str(), "\n");
+    /// assert_eq!(LineEnding::CrLf.as_str(), "\r\n");
+    /// ```
+    #[inline]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+        }
+    }
+}
+
+/// The line breaks a [`Rope`](crate::Rope) splits its lines on when iterating
+/// over them with [`Rope::lines_with()`](crate::Rope::lines_with()).
+#[cfg_attr(docsrs, doc(cfg(feature = "unicode-lines")))]
+#[cfg(feature = "unicode-lines")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum LineBreakKind {
+    /// Only line feeds (`"\n"`), optionally preceded by a carriage return.
+    /// This is what [`Rope::lines()`](crate::Rope::lines()) uses.
+    #[default]
+    Lf,
+
+    /// Line feeds (optionally preceded by a carriage return), next lines
+    /// (NEL, `"\u{0085}"`), line separators (LS, `"\u{2028}"`) and paragraph
+    /// separators (PS, `"\u{2029}"`).
+    UnicodeFull,
+}
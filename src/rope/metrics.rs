@@ -4,13 +4,20 @@ use super::gap_buffer::GapBuffer;
 use super::gap_slice::GapSlice;
 use crate::tree::{DoubleEndedUnitMetric, Metric, SlicingMetric, UnitMetric};
 
+// None of the fields can overflow because they're all bounded by the number
+// of bytes, which is at most `Rope::MAX_LEN`.
 #[derive(Copy, Clone, Default, Debug, PartialEq)]
 #[doc(hidden)]
 pub struct ChunkSummary {
     bytes: usize,
     line_breaks: usize,
+    /// We track the number of non-ASCII bytes instead of a simple `is_ascii`
+    /// flag because summaries also need to be subtracted from each other.
+    non_ascii_bytes: usize,
     #[cfg(feature = "utf16-metric")]
     utf16_code_units: usize,
+    #[cfg(feature = "unicode-lines")]
+    unicode_line_breaks: usize,
 }
 
 impl From<&str> for ChunkSummary {
@@ -19,8 +26,11 @@ impl From<&str> for ChunkSummary {
         Self {
             bytes: s.len(),
             line_breaks: count::line_breaks(s),
+            non_ascii_bytes: count::non_ascii_bytes(s),
             #[cfg(feature = "utf16-metric")]
             utf16_code_units: count::utf16_code_units(s),
+            #[cfg(feature = "unicode-lines")]
+            unicode_line_breaks: count::unicode_line_breaks(s),
         }
     }
 }
@@ -31,8 +41,11 @@ impl From<char> for ChunkSummary {
         Self {
             bytes: ch.len_utf8(),
             line_breaks: (ch == '\n') as usize,
+            non_ascii_bytes: if ch.is_ascii() { 0 } else { ch.len_utf8() },
             #[cfg(feature = "utf16-metric")]
             utf16_code_units: ch.len_utf16(),
+            #[cfg(feature = "unicode-lines")]
+            unicode_line_breaks: is_unicode_line_break(ch) as usize,
         }
     }
 }
@@ -43,6 +56,11 @@ impl ChunkSummary {
         self.bytes
     }
 
+    #[inline]
+    pub fn is_ascii(&self) -> bool {
+        self.non_ascii_bytes == 0
+    }
+
     #[inline]
     pub fn line_breaks(&self) -> usize {
         self.line_breaks
@@ -59,6 +77,12 @@ impl ChunkSummary {
     pub fn utf16_code_units(&self) -> usize {
         self.utf16_code_units
     }
+
+    #[cfg(feature = "unicode-lines")]
+    #[inline]
+    pub fn unicode_line_breaks(&self) -> usize {
+        self.unicode_line_breaks
+    }
 }
 
 impl Add<Self> for ChunkSummary {
@@ -106,10 +130,15 @@ impl AddAssign<Self> for ChunkSummary {
     fn add_assign(&mut self, rhs: Self) {
         self.bytes += rhs.bytes;
         self.line_breaks += rhs.line_breaks;
+        self.non_ascii_bytes += rhs.non_ascii_bytes;
         #[cfg(feature = "utf16-metric")]
         {
             self.utf16_code_units += rhs.utf16_code_units;
         }
+        #[cfg(feature = "unicode-lines")]
+        {
+            self.unicode_line_breaks += rhs.unicode_line_breaks;
+        }
     }
 }
 
@@ -118,10 +147,15 @@ impl SubAssign<Self> for ChunkSummary {
     fn sub_assign(&mut self, rhs: Self) {
         self.bytes -= rhs.bytes;
         self.line_breaks -= rhs.line_breaks;
+        self.non_ascii_bytes -= rhs.non_ascii_bytes;
         #[cfg(feature = "utf16-metric")]
         {
             self.utf16_code_units -= rhs.utf16_code_units;
         }
+        #[cfg(feature = "unicode-lines")]
+        {
+            self.unicode_line_breaks -= rhs.unicode_line_breaks;
+        }
     }
 }
 
@@ -237,12 +271,25 @@ impl SummaryUpTo for ByteMetric {
                 str_summary.line_breaks,
             ),
 
+            non_ascii_bytes: count::non_ascii_bytes_up_to(
+                in_str,
+                byte_offset,
+                str_summary.non_ascii_bytes,
+            ),
+
             #[cfg(feature = "utf16-metric")]
             utf16_code_units: count::utf16_code_units_up_to(
                 in_str,
                 byte_offset,
                 str_summary.utf16_code_units,
             ),
+
+            #[cfg(feature = "unicode-lines")]
+            unicode_line_breaks: count::unicode_line_breaks_up_to(
+                in_str,
+                byte_offset,
+                str_summary.unicode_line_breaks,
+            ),
         }
     }
 }
@@ -343,7 +390,6 @@ impl ToByteOffset for RawLineMetric {
 }
 
 impl SummaryUpTo for RawLineMetric {
-    #[cfg_attr(not(feature = "utf16-metric"), allow(unused_variables))]
     #[inline]
     fn up_to(
         in_str: &str,
@@ -356,12 +402,25 @@ impl SummaryUpTo for RawLineMetric {
 
             line_breaks: line_offset,
 
+            non_ascii_bytes: count::non_ascii_bytes_up_to(
+                in_str,
+                byte_offset,
+                str_summary.non_ascii_bytes,
+            ),
+
             #[cfg(feature = "utf16-metric")]
             utf16_code_units: count::utf16_code_units_up_to(
                 in_str,
                 byte_offset,
                 str_summary.utf16_code_units,
             ),
+
+            #[cfg(feature = "unicode-lines")]
+            unicode_line_breaks: count::unicode_line_breaks_up_to(
+                in_str,
+                byte_offset,
+                str_summary.unicode_line_breaks,
+            ),
         }
     }
 }
@@ -643,7 +702,20 @@ mod utf16_metric {
                     str_summary.line_breaks,
                 ),
 
+                non_ascii_bytes: count::non_ascii_bytes_up_to(
+                    in_str,
+                    byte_offset,
+                    str_summary.non_ascii_bytes,
+                ),
+
                 utf16_code_units: utf16_code_unit_offset,
+
+                #[cfg(feature = "unicode-lines")]
+                unicode_line_breaks: count::unicode_line_breaks_up_to(
+                    in_str,
+                    byte_offset,
+                    str_summary.unicode_line_breaks,
+                ),
             }
         }
     }
@@ -700,6 +772,219 @@ mod utf16_metric {
     }
 }
 
+#[cfg(feature = "unicode-lines")]
+pub use unicode_line_metric::{is_unicode_line_break, UnicodeLineMetric};
+
+#[cfg(feature = "unicode-lines")]
+mod unicode_line_metric {
+    use super::*;
+
+    /// Returns whether the character is one of the line breaks recognized by
+    /// [`LineBreakKind::UnicodeFull`](crate::LineBreakKind::UnicodeFull),
+    /// i.e. a line feed, a next line (NEL), a line separator (LS) or a
+    /// paragraph separator (PS).
+    #[inline]
+    pub fn is_unicode_line_break(ch: char) -> bool {
+        matches!(ch, '\n' | '\u{0085}' | '\u{2028}' | '\u{2029}')
+    }
+
+    /// Like [`RawLineMetric`], but the lines are terminated by any of the
+    /// characters matched by [`is_unicode_line_break()`] instead of just line
+    /// feeds.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    pub struct UnicodeLineMetric(pub usize);
+
+    impl Add for UnicodeLineMetric {
+        type Output = Self;
+
+        #[inline]
+        fn add(self, other: Self) -> Self {
+            Self(self.0 + other.0)
+        }
+    }
+
+    impl Sub for UnicodeLineMetric {
+        type Output = Self;
+
+        #[inline]
+        fn sub(self, other: Self) -> Self {
+            Self(self.0 - other.0)
+        }
+    }
+
+    impl AddAssign for UnicodeLineMetric {
+        #[inline]
+        fn add_assign(&mut self, other: Self) {
+            self.0 += other.0
+        }
+    }
+
+    impl SubAssign for UnicodeLineMetric {
+        #[inline]
+        fn sub_assign(&mut self, other: Self) {
+            self.0 -= other.0
+        }
+    }
+
+    impl ToByteOffset for UnicodeLineMetric {
+        #[inline]
+        fn to_byte_offset(&self, s: &str) -> usize {
+            convert::byte_of_unicode_line(s, self.0)
+        }
+    }
+
+    impl SummaryUpTo for UnicodeLineMetric {
+        #[inline]
+        fn up_to(
+            in_str: &str,
+            str_summary: ChunkSummary,
+            Self(line_offset): Self,
+            byte_offset: usize,
+        ) -> ChunkSummary {
+            ChunkSummary {
+                bytes: byte_offset,
+
+                line_breaks: count::line_breaks_up_to(
+                    in_str,
+                    byte_offset,
+                    str_summary.line_breaks,
+                ),
+
+                non_ascii_bytes: count::non_ascii_bytes_up_to(
+                    in_str,
+                    byte_offset,
+                    str_summary.non_ascii_bytes,
+                ),
+
+                #[cfg(feature = "utf16-metric")]
+                utf16_code_units: count::utf16_code_units_up_to(
+                    in_str,
+                    byte_offset,
+                    str_summary.utf16_code_units,
+                ),
+
+                unicode_line_breaks: line_offset,
+            }
+        }
+    }
+
+    impl Metric<ChunkSummary> for UnicodeLineMetric {
+        #[inline]
+        fn zero() -> Self {
+            Self(0)
+        }
+
+        #[inline]
+        fn one() -> Self {
+            Self(1)
+        }
+
+        #[inline]
+        fn measure(summary: &ChunkSummary) -> Self {
+            Self(summary.unicode_line_breaks)
+        }
+    }
+
+    impl<const MAX_BYTES: usize> SlicingMetric<GapBuffer<MAX_BYTES>>
+        for UnicodeLineMetric
+    {
+        #[inline]
+        fn slice_up_to<'a>(
+            chunk: GapSlice<'a>,
+            line_offset: Self,
+            &summary: &ChunkSummary,
+        ) -> (GapSlice<'a>, ChunkSummary)
+        where
+            'a: 'a,
+        {
+            let (left, _) = chunk.split_at_offset(line_offset, summary);
+            left
+        }
+
+        #[inline]
+        fn slice_from<'a>(
+            chunk: GapSlice<'a>,
+            line_offset: Self,
+            &summary: &ChunkSummary,
+        ) -> (GapSlice<'a>, ChunkSummary)
+        where
+            'a: 'a,
+        {
+            let (_, right) = chunk.split_at_offset(line_offset, summary);
+            right
+        }
+    }
+
+    impl<const MAX_BYTES: usize> UnitMetric<GapBuffer<MAX_BYTES>>
+        for UnicodeLineMetric
+    {
+        #[inline]
+        fn first_unit<'a>(
+            chunk: GapSlice<'a>,
+            &summary: &ChunkSummary,
+        ) -> (
+            GapSlice<'a>,
+            ChunkSummary,
+            ChunkSummary,
+            GapSlice<'a>,
+            ChunkSummary,
+        )
+        where
+            'a: 'a,
+        {
+            let ((first, first_summary), (rest, rest_summary)) =
+                chunk.split_at_offset(UnicodeLineMetric(1), summary);
+
+            (first, first_summary, first_summary, rest, rest_summary)
+        }
+    }
+
+    impl<const MAX_BYTES: usize> DoubleEndedUnitMetric<GapBuffer<MAX_BYTES>>
+        for UnicodeLineMetric
+    {
+        #[inline]
+        fn last_unit<'a>(
+            slice: GapSlice<'a>,
+            &summary: &ChunkSummary,
+        ) -> (
+            GapSlice<'a>,
+            ChunkSummary,
+            GapSlice<'a>,
+            ChunkSummary,
+            ChunkSummary,
+        )
+        where
+            'a: 'a,
+        {
+            let split_offset = summary.unicode_line_breaks
+                - (slice.has_trailing_unicode_line_break() as usize);
+
+            let ((rest, rest_summary), (last, last_summary)) = slice
+                .split_at_offset(UnicodeLineMetric(split_offset), summary);
+
+            (rest, rest_summary, last, last_summary, last_summary)
+        }
+
+        #[inline]
+        fn remainder<'a>(
+            chunk: GapSlice<'a>,
+            summary: &ChunkSummary,
+        ) -> (GapSlice<'a>, ChunkSummary, GapSlice<'a>, ChunkSummary)
+        where
+            'a: 'a,
+        {
+            if chunk.has_trailing_unicode_line_break() {
+                (chunk, *summary, GapSlice::empty(), ChunkSummary::new())
+            } else {
+                let (rest, rest_summary, last, last_summary, _) =
+                    <Self as DoubleEndedUnitMetric<GapBuffer<MAX_BYTES>>>::last_unit(chunk, summary);
+
+                (rest, rest_summary, last, last_summary)
+            }
+        }
+    }
+}
+
 use str_utils::*;
 
 mod str_utils {
@@ -712,8 +997,21 @@ mod str_utils {
         #[cfg(not(miri))]
         use super::*;
 
+        /// The minimum byte length of a string for which counting its line
+        /// breaks is delegated to `memchr`. Below this the setup cost of the
+        /// SIMD search isn't worth it.
+        #[cfg(all(not(miri), feature = "memchr"))]
+        const MEMCHR_MIN_BYTES: usize = 64;
+
         #[inline]
         pub fn line_breaks(s: &str) -> usize {
+            #[cfg(all(not(miri), feature = "memchr"))]
+            {
+                if s.len() >= MEMCHR_MIN_BYTES {
+                    return memchr::memchr_iter(b'\n', s.as_bytes()).count();
+                }
+            }
+
             #[cfg(not(miri))]
             {
                 lines::count_breaks(s)
@@ -724,6 +1022,15 @@ mod str_utils {
             }
         }
 
+        #[inline]
+        pub fn non_ascii_bytes(s: &str) -> usize {
+            if s.is_ascii() {
+                0
+            } else {
+                s.bytes().filter(|b| !b.is_ascii()).count()
+            }
+        }
+
         #[cfg(feature = "utf16-metric")]
         #[inline]
         pub fn utf16_code_units(s: &str) -> usize {
@@ -737,6 +1044,33 @@ mod str_utils {
             }
         }
 
+        /// Counts the line feeds, next lines (NEL), line separators (LS) and
+        /// paragraph separators (PS) in the string.
+        #[cfg(feature = "unicode-lines")]
+        #[inline]
+        pub fn unicode_line_breaks(s: &str) -> usize {
+            if s.is_ascii() {
+                return line_breaks(s);
+            }
+
+            let bytes = s.as_bytes();
+
+            let mut count = 0;
+            let mut idx = 0;
+
+            while idx < bytes.len() {
+                match super::unicode_line_break_len(bytes, idx) {
+                    0 => idx += 1,
+                    len => {
+                        count += 1;
+                        idx += len;
+                    },
+                }
+            }
+
+            count
+        }
+
         #[inline(always)]
         pub fn line_breaks_up_to(
             s: &str,
@@ -746,6 +1080,19 @@ mod str_utils {
             metric_up_to(s, byte_offset, tot_line_breaks, line_breaks)
         }
 
+        #[inline(always)]
+        pub fn non_ascii_bytes_up_to(
+            s: &str,
+            byte_offset: usize,
+            tot_non_ascii_bytes: usize,
+        ) -> usize {
+            if tot_non_ascii_bytes == 0 {
+                return 0;
+            }
+
+            metric_up_to(s, byte_offset, tot_non_ascii_bytes, non_ascii_bytes)
+        }
+
         #[cfg(feature = "utf16-metric")]
         #[inline(always)]
         pub fn utf16_code_units_up_to(
@@ -761,6 +1108,21 @@ mod str_utils {
             )
         }
 
+        #[cfg(feature = "unicode-lines")]
+        #[inline(always)]
+        pub fn unicode_line_breaks_up_to(
+            s: &str,
+            byte_offset: usize,
+            tot_unicode_line_breaks: usize,
+        ) -> usize {
+            metric_up_to(
+                s,
+                byte_offset,
+                tot_unicode_line_breaks,
+                unicode_line_breaks,
+            )
+        }
+
         #[inline(always)]
         fn metric_up_to(
             s: &str,
@@ -782,6 +1144,33 @@ mod str_utils {
         }
     }
 
+    /// Returns the length in bytes of the Unicode line break starting at
+    /// `idx`, or zero if there isn't one.
+    ///
+    /// Since UTF-8 is self-synchronizing the encoding of a line break can
+    /// only be found at the start of the character it encodes, so it's safe
+    /// to call this with an `idx` that's not on a char boundary.
+    #[cfg(feature = "unicode-lines")]
+    #[inline(always)]
+    fn unicode_line_break_len(bytes: &[u8], idx: usize) -> usize {
+        match bytes[idx] {
+            b'\n' => 1,
+
+            // NEL (U+0085) is encoded as `C2 85`.
+            0xC2 if bytes.get(idx + 1) == Some(&0x85) => 2,
+
+            // LS (U+2028) and PS (U+2029) are encoded as `E2 80 A8` and
+            // `E2 80 A9`.
+            0xE2 if bytes.get(idx + 1) == Some(&0x80)
+                && matches!(bytes.get(idx + 2), Some(&0xA8) | Some(&0xA9)) =>
+            {
+                3
+            },
+
+            _ => 0,
+        }
+    }
+
     pub mod convert {
         #[cfg(not(miri))]
         use super::*;
@@ -818,6 +1207,41 @@ mod str_utils {
             }
         }
 
+        /// Returns the byte offset right after the `line_offset`-th line break
+        /// as counted by `count::unicode_line_breaks()`, or the length of the
+        /// string if it contains fewer line breaks.
+        #[cfg(feature = "unicode-lines")]
+        #[inline]
+        pub fn byte_of_unicode_line(s: &str, line_offset: usize) -> usize {
+            if s.is_ascii() {
+                return byte_of_line(s, line_offset);
+            }
+
+            if line_offset == 0 {
+                return 0;
+            }
+
+            let bytes = s.as_bytes();
+
+            let mut seen = 0;
+            let mut idx = 0;
+
+            while idx < bytes.len() {
+                match super::unicode_line_break_len(bytes, idx) {
+                    0 => idx += 1,
+                    len => {
+                        idx += len;
+                        seen += 1;
+                        if seen == line_offset {
+                            return idx;
+                        }
+                    },
+                }
+            }
+
+            s.len()
+        }
+
         #[cfg(feature = "utf16-metric")]
         #[inline]
         pub fn byte_of_utf16_code_unit(
@@ -1,12 +1,32 @@
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+mod chunk_pool;
+mod compaction;
+mod edit_recorder;
+mod errors;
 pub(crate) mod gap_buffer;
 pub(crate) mod gap_slice;
+#[cfg(feature = "graphemes")]
+mod grapheme_len_cache;
+mod hashed_slice;
 pub(crate) mod iterators;
+mod line_ending;
 pub mod metrics;
+mod reader;
 mod rope;
 mod rope_builder;
 mod rope_slice;
+pub(crate) mod text_metric;
 mod utils;
 
-pub use rope::Rope;
-pub use rope_builder::RopeBuilder;
+pub use compaction::Compaction;
+pub use edit_recorder::{EditRecorder, InverseEdit};
+pub use errors::{ApplyEditsError, EditError, SwapRangesError};
+pub use hashed_slice::HashedSlice;
+#[cfg(feature = "unicode-lines")]
+pub use line_ending::LineBreakKind;
+pub use line_ending::LineEnding;
+pub use reader::Reader;
+pub use rope::{Edit, EditKind, EditOutcome, EditSummary, Rope};
+pub use rope_builder::{concat, BuildStats, RopeBuilder};
 pub use rope_slice::RopeSlice;
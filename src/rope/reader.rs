@@ -0,0 +1,74 @@
+use std::io::{BufRead, Read};
+
+use super::iterators::Chunks;
+
+/// A reader over the bytes of `Rope`s and `RopeSlice`s, implementing both
+/// [`Read`] and [`BufRead`].
+///
+/// The buffer returned by [`fill_buf()`](BufRead::fill_buf()) is the rest of
+/// the current chunk, so the text can be read with the usual buffered reader
+/// conveniences (e.g. [`read_line()`](BufRead::read_line()) or
+/// [`lines()`](BufRead::lines())) without copying the chunks into an
+/// intermediate buffer.
+///
+/// This struct is created by the `reader` method on
+/// [`Rope`](crate::Rope::reader()) and
+/// [`RopeSlice`](crate::RopeSlice::reader()). See their documentation for
+/// more.
+///
+/// # Examples
+///
+/// ```
+/// # use std::io::BufRead;
+/// # use crop::Rope;
+/// #
+/// let r = Rope::from("foo\nbar\r\nbaz");
+///
+/// let lines = r.reader().lines().collect::<Result<Vec<_>, _>>().unwrap();
+///
+/// assert_eq!(lines, ["foo", "bar", "baz"]);
+/// ```
+#[derive(Clone)]
+pub struct Reader<'a> {
+    chunks: Chunks<'a>,
+
+    /// The part of the current chunk that hasn't been consumed yet.
+    chunk: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    #[inline]
+    pub(super) fn new(chunks: Chunks<'a>) -> Self {
+        Self { chunks, chunk: &[] }
+    }
+}
+
+impl Read for Reader<'_> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl BufRead for Reader<'_> {
+    #[inline]
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        while self.chunk.is_empty() {
+            match self.chunks.next() {
+                Some(chunk) => self.chunk = chunk.as_bytes(),
+                None => break,
+            }
+        }
+
+        Ok(self.chunk)
+    }
+
+    #[inline]
+    fn consume(&mut self, amt: usize) {
+        self.chunk = &self.chunk[amt.min(self.chunk.len())..];
+    }
+}
@@ -1,10 +1,45 @@
-use core::ops::RangeBounds;
+use core::ops::{Range, RangeBounds};
 
-use super::gap_buffer::GapBuffer;
-use super::iterators::{Bytes, Chars, Chunks, Lines, RawLines};
-use super::metrics::{ByteMetric, RawLineMetric};
+use super::chunk_pool::ChunkPool;
+use super::gap_buffer::{GapBuffer, Replacement};
+#[cfg(feature = "graphemes")]
+use super::grapheme_len_cache::GraphemeLenCache;
+#[cfg(feature = "unicode-lines")]
+use super::iterators::LinesWith;
+use super::iterators::{
+    ByteChunks,
+    Bytes,
+    Chars,
+    ChunkBoundaries,
+    ChunkRanges,
+    Chunks,
+    Drain,
+    LineRanges,
+    Lines,
+    LinesCow,
+    Matches,
+    RawLines,
+    SplitWhitespace,
+    WrappedLines,
+};
+#[cfg(feature = "unicode-lines")]
+use super::metrics::is_unicode_line_break;
+use super::metrics::{ByteMetric, ChunkSummary, RawLineMetric};
+use super::text_metric::{self, Metric};
 use super::utils::{panic_messages as panic, *};
-use super::RopeSlice;
+#[cfg(feature = "unicode-lines")]
+use super::LineBreakKind;
+use super::{
+    ApplyEditsError,
+    Compaction,
+    EditError,
+    EditRecorder,
+    LineEnding,
+    Reader,
+    RopeBuilder,
+    RopeSlice,
+    SwapRangesError,
+};
 use crate::range_bounds_to_start_end;
 use crate::tree::Tree;
 
@@ -22,14 +57,40 @@ const CHUNK_MAX_BYTES: usize = 2048;
 
 pub(super) type RopeChunk = GapBuffer<CHUNK_MAX_BYTES>;
 
+/// The UTF-8 byte order mark.
+const BOM: &str = "\u{FEFF}";
+
 /// A UTF-8 text rope.
-#[derive(Clone, Default)]
+///
+/// The text is stored in chunks of at most a few kilobytes, each one backed by
+/// a gap buffer whose whole capacity is allocated when the chunk is created.
+/// Edits never grow an existing chunk's allocation: text that doesn't fit in
+/// the chunks being edited is moved into newly allocated ones instead. This is
+/// why `Rope` doesn't have a `String`-like `reserve()` method, as there's no
+/// reallocation for it to prevent.
+#[derive(Clone)]
 pub struct Rope {
     pub(super) tree: Tree<{ Self::arity() }, RopeChunk>,
     pub(super) has_trailing_newline: bool,
+    pub(super) compaction: Compaction,
+    pub(super) generation: usize,
+    pub(super) max_len: Option<usize>,
+    pub(super) readonly_ranges: Vec<Range<usize>>,
+    pub(super) chunk_pool: ChunkPool<CHUNK_MAX_BYTES>,
+    #[cfg(feature = "graphemes")]
+    pub(super) grapheme_len_cache: GraphemeLenCache,
 }
 
 impl Rope {
+    /// The maximum byte length of a `Rope`.
+    ///
+    /// This is the same limit Rust places on the size of a single allocation,
+    /// which makes sure that no offset or length computed by a `Rope` or by
+    /// one of its `RopeSlice`s can overflow, even on 32-bit targets.
+    ///
+    /// Any edit that would make a `Rope` longer than this panics.
+    pub const MAX_LEN: usize = isize::MAX as usize;
+
     #[doc(hidden)]
     pub fn assert_invariants(&self) {
         self.tree.assert_invariants();
@@ -60,8 +121,186 @@ impl Rope {
         }
     }
 
+    /// Applies a list of non-overlapping edits in a single pass, returning a
+    /// summary of the text they removed and inserted.
+    ///
+    /// The ranges of the edits are all relative to the current text of the
+    /// `Rope`, so there's no need to shift them to account for the edits
+    /// that come before. The edits are sorted by their ranges and then
+    /// applied back to front, which keeps the offsets of the ones that are
+    /// yet to be applied valid. Text inserted at the same offset ends up in
+    /// the same order as the edits inserting it.
+    ///
+    /// The [maximum length](Self::set_max_len()) is only checked against the
+    /// length of the `Rope` after all the edits are applied, so the
+    /// intermediate states are allowed to exceed it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the start of a range is greater than its end, if a
+    /// range is out of bounds, if the start or the end of a range doesn't lie
+    /// on a code point boundary, if two ranges overlap or if the edits would
+    /// make the `Rope` longer than its maximum length. The `Rope` is left
+    /// unchanged if an error is returned, but the edits are sorted anyway.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{ApplyEditsError, Edit, Rope};
+    /// #
+    /// let mut r = Rope::from("let  x=1;\nlet y =2;\n");
+    ///
+    /// let mut edits = vec![
+    ///     Edit::new(17..17, " "),
+    ///     Edit::new(3..5, " "),
+    ///     Edit::new(6..7, " = "),
+    /// ];
+    ///
+    /// let summary = r.apply_edits(&mut edits).unwrap();
+    ///
+    /// assert_eq!(r, "let x = 1;\nlet y = 2;\n");
+    /// assert_eq!(summary.bytes_removed(), 3);
+    /// assert_eq!(summary.bytes_inserted(), 5);
+    ///
+    /// let mut edits = vec![Edit::new(0..5, ""), Edit::new(4..6, "")];
+    ///
+    /// assert_eq!(
+    ///     r.apply_edits(&mut edits),
+    ///     Err(ApplyEditsError::Overlapping { byte_offset: 4 })
+    /// );
+    /// ```
+    #[inline]
+    pub fn apply_edits(
+        &mut self,
+        edits: &mut [Edit],
+    ) -> Result<EditSummary, ApplyEditsError> {
+        // The sort is stable, so insertions at the same offset keep their
+        // relative order.
+        edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+
+        let mut summary = EditSummary::default();
+
+        let mut previous_end = 0;
+
+        for Edit { range, text } in edits.iter() {
+            if range.start > range.end {
+                return Err(EditError::StartAfterEnd {
+                    start: range.start,
+                    end: range.end,
+                }
+                .into());
+            }
+
+            if range.end > self.byte_len() {
+                return Err(EditError::OutOfBounds {
+                    byte_offset: range.end,
+                    byte_len: self.byte_len(),
+                }
+                .into());
+            }
+
+            for byte_offset in [range.start, range.end] {
+                if !self.is_char_boundary(byte_offset) {
+                    return Err(
+                        EditError::NotCharBoundary { byte_offset }.into()
+                    );
+                }
+            }
+
+            if range.start < previous_end {
+                return Err(ApplyEditsError::Overlapping {
+                    byte_offset: range.start,
+                });
+            }
+
+            previous_end = range.end;
+
+            summary.bytes_removed += range.end - range.start;
+            summary.bytes_inserted += text.len();
+            summary.line_breaks_removed +=
+                self.line_breaks_in_range(range.clone());
+            summary.line_breaks_inserted +=
+                ChunkSummary::from(text.as_str()).line_breaks();
+        }
+
+        self.check_max_len(0, summary.bytes_removed, summary.bytes_inserted)?;
+
+        // The maximum length has already been checked against the final
+        // length, so it's lifted while applying the edits one by one.
+        let max_len = self.max_len.take();
+
+        for Edit { range, text } in edits.iter().rev() {
+            self.replace(range.clone(), text);
+        }
+
+        self.max_len = max_len;
+
+        Ok(summary)
+    }
+
+    /// Returns the contents of the `Rope` as a byte slice if they're stored
+    /// in a single contiguous chunk, or `None` otherwise.
+    ///
+    /// This is the byte counterpart of [`as_str()`](Self::as_str()), and it
+    /// returns `Some` exactly when `as_str()` does. It can be used to pass
+    /// small texts through FFI as a pointer and a length, falling back to
+    /// [`byte_chunks()`](Self::byte_chunks()) when it returns `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar");
+    ///
+    /// let bytes = match r.as_contiguous_bytes() {
+    ///     Some(bytes) => bytes.to_vec(),
+    ///     None => r.byte_chunks().flatten().copied().collect(),
+    /// };
+    ///
+    /// assert_eq!(bytes, b"foo\nbar");
+    /// ```
+    #[inline]
+    pub fn as_contiguous_bytes(&self) -> Option<&[u8]> {
+        self.as_str().map(str::as_bytes)
+    }
+
+    /// Returns the contents of the `Rope` as a string slice if they're stored
+    /// in a single contiguous chunk, or `None` otherwise.
+    ///
+    /// This allows to avoid allocating a new `String` when passing small
+    /// texts to APIs that expect a `&str`. Note that the text stored in a
+    /// single leaf of the `Rope` can still be split in two by the gap of its
+    /// gap buffer, in which case this returns `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// # use crop::Rope;
+    /// #
+    /// let s = Rope::from("Hello, world!");
+    ///
+    /// let text = match s.as_str() {
+    ///     Some(text) => Cow::Borrowed(text),
+    ///     None => Cow::Owned(s.to_string()),
+    /// };
+    ///
+    /// assert_eq!(text, "Hello, world!");
+    ///
+    /// assert_eq!(Rope::new().as_str(), Some(""));
+    /// ```
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        chunks_as_str(self.chunks())
+    }
+
     /// Returns the byte at `byte_index`.
     ///
+    /// See [`get_byte()`](Self::get_byte()) for a non-panicking version and
+    /// [`byte_unchecked()`](Self::byte_unchecked()) for a version without
+    /// bounds checking.
+    ///
     /// # Panics
     ///
     /// Panics if the byte index is out of bounds (i.e. greater than or equal
@@ -91,6 +330,27 @@ impl Rope {
         chunk.byte(byte_index - chunk_byte_offset)
     }
 
+    /// Returns an iterator over the chunks of this `Rope` as byte slices.
+    ///
+    /// This yields the same chunks as [`chunks()`](Self::chunks()), which is
+    /// handy to run byte-oriented algorithms over the text without going
+    /// through `str`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo bar baz");
+    ///
+    /// let bytes = r.byte_chunks().flatten().copied().collect::<Vec<u8>>();
+    /// assert_eq!(bytes, b"foo bar baz");
+    /// ```
+    #[inline]
+    pub fn byte_chunks(&self) -> ByteChunks<'_> {
+        ByteChunks::from(self)
+    }
+
     /// Returns the length of the `Rope` in bytes.
     ///
     /// # Examples
@@ -107,6 +367,44 @@ impl Rope {
         self.tree.summary().bytes()
     }
 
+    /// Returns the line containing the given byte offset together with the
+    /// byte offset from the start of that line, i.e. its column.
+    ///
+    /// The line is the one returned by
+    /// [`line_of_byte()`](Self::line_of_byte()), and the column is the number
+    /// of bytes between the start of the line and the byte offset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("ƒoo\nbär\r\nbaz");
+    ///
+    /// assert_eq!(r.byte_line_col(0), (0, 0));
+    /// assert_eq!(r.byte_line_col(3), (0, 3));
+    /// assert_eq!(r.byte_line_col(9), (1, 4)); // between the '\r' and the '\n'
+    /// assert_eq!(r.byte_line_col(11), (2, 0));
+    /// assert_eq!(r.byte_line_col(r.byte_len()), (2, 3));
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn byte_line_col(&self, byte_offset: usize) -> (usize, usize) {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        let (RawLineMetric(line_offset), ByteMetric(line_start)) =
+            self.tree.convert_measure_with_start(ByteMetric(byte_offset));
+
+        (line_offset, byte_offset - line_start)
+    }
+
     /// Returns the byte offset of the start of the given line.
     ///
     /// # Panics
@@ -143,6 +441,52 @@ impl Rope {
         byte_offset
     }
 
+    /// Pushes the byte offset of the start of every line in the given range
+    /// to `out`, which is the same as calling
+    /// [`byte_of_line()`](Self::byte_of_line()) on every line offset in the
+    /// range.
+    ///
+    /// Only the start of the first line is computed by descending the tree,
+    /// while the other offsets are found by walking the lines left to right.
+    /// This makes it much faster than calling `byte_of_line()` repeatedly
+    /// when building the line table of a large viewport.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end or if the end
+    /// is out of bounds (i.e. greater than [`line_len()`](Self::line_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbär\r\nbaz\n");
+    ///
+    /// let mut offsets = Vec::new();
+    /// r.byte_offsets_of_lines(1..3, &mut offsets);
+    ///
+    /// assert_eq!(offsets, [4, 10]);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn byte_offsets_of_lines(
+        &self,
+        line_range: Range<usize>,
+        out: &mut Vec<usize>,
+    ) {
+        let lines = self.line_slice(line_range.clone());
+
+        out.reserve(line_range.len());
+
+        let mut byte_offset = self.byte_of_line(line_range.start);
+
+        for line in lines.raw_lines() {
+            out.push(byte_offset);
+            byte_offset += line.byte_len();
+        }
+    }
+
     /// Returns the byte offset corresponding to the given UTF-16 code unit
     /// offset.
     ///
@@ -181,6 +525,11 @@ impl Rope {
     /// Returns an immutable slice of the `Rope` in the specified byte range,
     /// where the start and end of the range are interpreted as offsets.
     ///
+    /// Slicing doesn't copy any text. If the range falls within a single
+    /// chunk and isn't split by its gap, the slice is backed by a contiguous
+    /// `str` which can be borrowed with
+    /// [`RopeSlice::as_str()`](crate::RopeSlice::as_str()).
+    ///
     /// # Panics
     ///
     /// Panics if the start or the end of the byte range don't lie on a code
@@ -215,7 +564,56 @@ impl Rope {
             panic::byte_offset_out_of_bounds(end, self.byte_len());
         }
 
-        self.tree.slice(ByteMetric(start)..ByteMetric(end)).into()
+        RopeSlice::new(
+            self.tree.slice(ByteMetric(start)..ByteMetric(end)),
+            start,
+            self.generation,
+        )
+    }
+
+    /// Returns the byte at `byte_index` like [`byte()`](Self::byte()),
+    /// without checking that the byte index is in bounds.
+    ///
+    /// This is the equivalent of [`slice::get_unchecked()`] for code that has
+    /// already validated its byte indices, e.g. a scanner iterating over
+    /// `0..byte_len()`. The index is still checked in debug builds.
+    ///
+    /// See [`get_byte()`](Self::get_byte()) for a safe version returning an
+    /// `Option`.
+    ///
+    /// # Safety
+    ///
+    /// The byte index must be less than [`byte_len()`](Self::byte_len()).
+    /// Calling this method with an out of bounds index is undefined
+    /// behavior, even if the returned byte is not used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("bar");
+    ///
+    /// let mut a_count = 0;
+    ///
+    /// for byte_index in 0..r.byte_len() {
+    ///     // SAFETY: the byte index is less than the byte length.
+    ///     if unsafe { r.byte_unchecked(byte_index) } == b'a' {
+    ///         a_count += 1;
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(a_count, 1);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub unsafe fn byte_unchecked(&self, byte_index: usize) -> u8 {
+        debug_assert!(byte_index < self.byte_len());
+
+        let (chunk, ByteMetric(chunk_byte_offset)) =
+            self.tree.leaf_at_measure(ByteMetric(byte_index + 1));
+
+        chunk.byte_unchecked(byte_index - chunk_byte_offset)
     }
 
     /// Returns an iterator over the bytes of this `Rope`.
@@ -239,6 +637,34 @@ impl Rope {
         Bytes::from(self)
     }
 
+    /// Returns the number of [`char`]s in this `Rope`.
+    ///
+    /// If the `Rope` is [all ASCII](Self::is_ascii()) this is an O(1)
+    /// operation returning the same value as
+    /// [`byte_len()`](Self::byte_len()). Otherwise the chars are counted one
+    /// chunk at a time, which takes linear time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello, world!");
+    /// assert_eq!(r.char_len(), 13);
+    ///
+    /// r.replace(7..12, "🌎");
+    /// assert_eq!(r.char_len(), 9);
+    /// assert_eq!(r.byte_len(), 12);
+    /// ```
+    #[inline]
+    pub fn char_len(&self) -> usize {
+        if self.is_ascii() {
+            self.byte_len()
+        } else {
+            count_chars(self.chunks())
+        }
+    }
+
     /// Returns an iterator over the [`char`]s of this `Rope`.
     ///
     /// # Examples
@@ -261,129 +687,1774 @@ impl Rope {
         Chars::from(self)
     }
 
-    /// Returns an iterator over the chunks of this [`Rope`].
-    #[inline]
-    pub fn chunks(&self) -> Chunks<'_> {
-        Chunks::from(self)
-    }
-
-    /// Deletes the contents of the `Rope` within the specified byte range,
-    /// where the start and end of the range are interpreted as offsets.
-    ///
-    /// # Panics
+    /// Returns an iterator over the byte offsets of the start of every chunk
+    /// yielded by [`chunks()`](Self::chunks()), in strictly increasing order.
     ///
-    /// Panics if the start or the end of the byte range don't lie on a code
-    /// point boundary, if the start is greater than the end or if the end is
-    /// out of bounds (i.e. greater than [`byte_len()`](Self::byte_len())).
+    /// The byte length of the `Rope` is not yielded at the end.
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let mut r = Rope::from("Hello Earth 🌎!");
+    /// let r = Rope::from("foo\nbar\r\nbaz");
     ///
-    /// r.delete(5..16);
-    /// assert_eq!(r, "Hello!");
+    /// let mut offset = 0;
+    ///
+    /// for (start, chunk) in r.chunk_boundaries().zip(r.chunks()) {
+    ///     assert_eq!(start, offset);
+    ///     offset += chunk.len();
+    /// }
+    ///
+    /// assert_eq!(offset, r.byte_len());
     /// ```
-    #[track_caller]
     #[inline]
-    pub fn delete<R>(&mut self, byte_range: R)
-    where
-        R: RangeBounds<usize>,
-    {
-        self.replace(byte_range, "");
-    }
-
-    pub(super) const fn arity() -> usize {
-        ARITY
+    pub fn chunk_boundaries(&self) -> ChunkBoundaries<'_> {
+        ChunkBoundaries::from(self)
     }
 
-    /// Returns an iterator over the extended grapheme clusters of this
-    /// `Rope`.
+    /// Returns an iterator over the chunks yielded by
+    /// [`chunks()`](Self::chunks()), each paired with the byte range it spans.
+    ///
+    /// The ranges tile the whole `Rope`, without gaps or overlaps.
     ///
     /// # Examples
     ///
-    /// ```no_run
+    /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("arg!\r\n🐻‍❄️");
+    /// let r = Rope::from("foo\nbar\r\nbaz");
     ///
-    /// let mut graphemes = r.graphemes();
+    /// let mut expected_start = 0;
     ///
-    /// assert_eq!(Some("a"), graphemes.next().as_deref());
-    /// assert_eq!(Some("r"), graphemes.next().as_deref());
-    /// assert_eq!(Some("g"), graphemes.next().as_deref());
-    /// assert_eq!(Some("!"), graphemes.next().as_deref());
-    /// assert_eq!(Some("\r\n"), graphemes.next().as_deref());
-    /// assert_eq!(Some("🐻‍❄️"), graphemes.next().as_deref());
-    /// assert_eq!(None, graphemes.next());
+    /// for (range, chunk) in r.chunk_ranges() {
+    ///     assert_eq!(range.start, expected_start);
+    ///     assert_eq!(r.byte_slice(range.clone()), chunk);
+    ///     expected_start = range.end;
+    /// }
+    ///
+    /// assert_eq!(expected_start, r.byte_len());
     /// ```
-    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
-    #[cfg(feature = "graphemes")]
     #[inline]
-    pub fn graphemes(&self) -> crate::iter::Graphemes<'_> {
-        crate::iter::Graphemes::from(self)
+    pub fn chunk_ranges(&self) -> ChunkRanges<'_> {
+        ChunkRanges::from(self)
     }
 
-    /// Inserts `text` in the `Rope` at the given byte offset.
+    /// Returns an iterator over the chunks of this [`Rope`].
+    ///
+    /// The chunks are never empty, which also means that the chunks of an
+    /// empty `Rope` are an empty iterator.
+    #[inline]
+    pub fn chunks(&self) -> Chunks<'_> {
+        Chunks::from(self)
+    }
+
+    /// Returns an iterator over the chunks of the line at `line_index`,
+    /// without its line terminator.
+    ///
+    /// The first and last chunks are trimmed to the start and end of the
+    /// line, so a line contained in a single chunk yields a single `&str`.
+    /// Reaching the line takes logarithmic time, and it's equivalent to
+    /// `line(line_index).chunks()`.
     ///
     /// # Panics
     ///
-    /// Panics if the byte offset doesn't lie on a code point boundary or if
-    /// it's out of bounds (i.e. greater than
-    /// [`byte_len()`](Self::byte_len())).
+    /// Panics if the line index is out of bounds (i.e. greater than or equal
+    /// to [`line_len()`](Self::line_len())).
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let mut r = Rope::from("Hello Earth!");
+    /// let r = Rope::from("foo\nbar\r\nbaz");
     ///
-    /// r.insert(11, " 🌎");
-    /// assert_eq!(r, "Hello Earth 🌎!");
+    /// assert_eq!(r.chunks_of_line(0).collect::<String>(), "foo");
+    /// assert_eq!(r.chunks_of_line(1).collect::<String>(), "bar");
+    /// assert_eq!(r.chunks_of_line(2).collect::<String>(), "baz");
     /// ```
     #[track_caller]
     #[inline]
-    pub fn insert<T>(&mut self, byte_offset: usize, text: T)
-    where
-        T: AsRef<str>,
-    {
-        self.replace(byte_offset..byte_offset, text)
+    pub fn chunks_of_line(&self, line_index: usize) -> Chunks<'_> {
+        self.line(line_index).chunks()
     }
 
-    /// Returns `true` if the given byte offset lies on a [`char`] boundary.
+    /// Removes all the text from the `Rope`, keeping the buffers of its
+    /// chunks around to be reused by the next edits instead of allocating
+    /// new ones.
     ///
-    /// # Panics
+    /// This is useful when a `Rope` is repeatedly emptied and refilled, e.g.
+    /// when double-buffering. Only the chunks that are not shared with a
+    /// clone of the `Rope` can be reused, and the buffers are kept until
+    /// they're reused or the `Rope` is dropped. Clones of the `Rope` don't
+    /// inherit them.
     ///
-    /// Panics if the byte offset is out of bounds (i.e. greater than
-    /// [`byte_len()`](Self::byte_len())).
+    /// Note that [`RopeBuilder`] doesn't draw from these buffers, so they're
+    /// only reused when the text is inserted with methods like
+    /// [`insert()`](Self::insert()) or [`replace()`](Self::replace()).
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("Löwe 老虎 Léopard");
+    /// let mut r = Rope::from("foo\nbar\n".repeat(1000));
     ///
-    /// assert!(r.is_char_boundary(0));
-    /// assert!(r.is_char_boundary(r.byte_len()));
-    /// assert!(r.is_char_boundary(6)); // between ' ' and '老'
-    /// assert!(!r.is_char_boundary(2)); // between the 1st and 2nd byte of 'ö'
+    /// r.clear_retaining_capacity();
+    /// assert!(r.is_empty());
+    ///
+    /// // The new chunks reuse the buffers of the old ones.
+    /// r.insert(0, "baz\n".repeat(1000));
+    /// assert_eq!(r.line_len(), 1000);
     /// ```
-    #[track_caller]
     #[inline]
-    pub fn is_char_boundary(&self, byte_offset: usize) -> bool {
-        if byte_offset > self.byte_len() {
-            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
-        }
+    pub fn clear_retaining_capacity(&mut self) {
+        self.generation = next_generation();
 
-        let (chunk, ByteMetric(chunk_byte_offset)) =
-            self.tree.leaf_at_measure(ByteMetric(byte_offset));
+        self.shift_readonly_ranges(0..self.byte_len(), 0);
 
-        chunk.is_char_boundary(byte_offset - chunk_byte_offset)
-    }
+        self.has_trailing_newline = false;
+
+        // The new empty chunk can already reuse a buffer left over from the
+        // previous refill.
+        let empty = Tree::from_leaves([RopeChunk::from_chunks_in(
+            &[],
+            Some(&mut self.chunk_pool),
+        )]);
+
+        let tree = core::mem::replace(&mut self.tree, empty);
+
+        tree.into_unique_leaves(|chunk| self.chunk_pool.push(chunk.bytes));
+    }
+
+    /// Returns the [`Compaction`] mode of the `Rope`.
+    ///
+    /// See [`set_compaction()`](Self::set_compaction()) for more.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Compaction, Rope};
+    /// #
+    /// let mut r = Rope::new();
+    /// assert_eq!(r.compaction(), Compaction::Lazy);
+    ///
+    /// r.set_compaction(Compaction::Eager);
+    /// assert_eq!(r.compaction(), Compaction::Eager);
+    /// ```
+    #[inline]
+    pub fn compaction(&self) -> Compaction {
+        self.compaction
+    }
+
+    /// Returns the 64-bit [FNV-1a] hash of the contents of this `Rope`.
+    ///
+    /// The hash is computed by streaming the bytes of each chunk, without
+    /// collecting them into a `String`. Unlike the [`Hash`](core::hash::Hash)
+    /// impl, whose output depends on the `Hasher` it's fed to, this always
+    /// returns the same value for the same text regardless of how it's split
+    /// into chunks, so it can be compared against the FNV-1a hash of a file
+    /// on disk to check whether it has changed.
+    ///
+    /// FNV-1a isn't a cryptographic hash and it's easy to craft collisions,
+    /// so don't use it to detect malicious changes. Enable the `sha256`
+    /// feature and use `sha256_hex()` for that.
+    ///
+    /// [FNV-1a]: https://en.wikipedia.org/wiki/Fowler%E2%80%93Noll%E2%80%93Vo_hash_function
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello, world!");
+    ///
+    /// let mut other = Rope::from("Hello, ");
+    /// other.insert(7, "world!");
+    ///
+    /// assert_eq!(r.content_hash(), other.content_hash());
+    /// assert_ne!(r.content_hash(), Rope::from("Hello!").content_hash());
+    /// ```
+    #[inline]
+    pub fn content_hash(&self) -> u64 {
+        fnv1a_chunks(self.chunks())
+    }
+
+    /// Merges the chunk containing `byte_offset` with its previous and next
+    /// chunks if their text fits in fewer chunks.
+    #[inline]
+    fn compact_around(&mut self, byte_offset: usize) {
+        if self.is_empty() {
+            return;
+        }
+
+        let (leaf, ByteMetric(mut start)) =
+            self.tree.leaf_at_measure(ByteMetric(byte_offset));
+
+        let mut end = start + leaf.len();
+
+        let mut leaf_count = 1;
+
+        if start > 0 {
+            let (_, ByteMetric(previous_start)) =
+                self.tree.leaf_at_measure(ByteMetric(start - 1));
+
+            start = previous_start;
+            leaf_count += 1;
+        }
+
+        if end < self.byte_len() {
+            let (next, ByteMetric(next_start)) =
+                self.tree.leaf_at_measure(ByteMetric(end + 1));
+
+            end = next_start + next.len();
+            leaf_count += 1;
+        }
+
+        let max_bytes = RopeChunk::max_bytes();
+
+        if (end - start + max_bytes - 1) / max_bytes < leaf_count {
+            let text = self.byte_slice(start..end).to_string();
+            let replacement =
+                Replacement { text: &text, pool: Some(&mut self.chunk_pool) };
+
+            self.tree.replace(ByteMetric(start)..ByteMetric(end), replacement);
+        }
+    }
+
+    /// Converts an offset in the `M1` metric to the corresponding offset in
+    /// the `M2` metric.
+    ///
+    /// This is the generic version of methods like
+    /// [`byte_of_line()`](Self::byte_of_line()) and
+    /// [`line_of_byte()`](Self::line_of_byte()), and it behaves like them:
+    /// converting a [`Line`](crate::metrics::Line) offset gives the start of
+    /// that line, while converting to a `Line` offset gives the line the
+    /// offset falls in.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the offset is out of bounds (i.e. greater than
+    /// [`measure::<M1>()`](Self::measure())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// use crop::metrics::{Byte, Line};
+    ///
+    /// let r = Rope::from("ƒoo\nbär\r\nbaz");
+    ///
+    /// assert_eq!(r.convert::<Line, Byte>(1), r.byte_of_line(1));
+    /// assert_eq!(r.convert::<Byte, Line>(6), r.line_of_byte(6));
+    /// assert_eq!(r.convert::<Byte, Byte>(6), 6);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn convert<M1, M2>(&self, offset: usize) -> usize
+    where
+        M1: Metric,
+        M2: Metric,
+    {
+        text_metric::convert::<M1, M2>(self.byte_slice(..), offset)
+    }
+
+    /// Copies the bytes of the `Rope` into the given buffer, returning the
+    /// number of bytes that were copied.
+    ///
+    /// If the buffer is shorter than the `Rope` only the first `dst.len()`
+    /// bytes are copied, which means the buffer could end in the middle of a
+    /// multi-byte character.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello, world!");
+    ///
+    /// let mut buf = [0u8; 5];
+    /// assert_eq!(r.copy_into(&mut buf), 5);
+    /// assert_eq!(&buf, b"Hello");
+    ///
+    /// let mut buf = [0u8; 32];
+    /// assert_eq!(r.copy_into(&mut buf), 13);
+    /// assert_eq!(&buf[..13], b"Hello, world!");
+    /// ```
+    #[inline]
+    pub fn copy_into(&self, dst: &mut [u8]) -> usize {
+        let mut copied = 0;
+
+        for chunk in self.chunks() {
+            let len = chunk.len().min(dst.len() - copied);
+
+            dst[copied..copied + len]
+                .copy_from_slice(&chunk.as_bytes()[..len]);
+
+            copied += len;
+
+            if copied == dst.len() {
+                break;
+            }
+        }
+
+        copied
+    }
+
+    /// Removes every line which is equal to the line right before it,
+    /// returning the number of lines that were removed.
+    ///
+    /// Lines are compared without their line breaks, so `"foo\n"` and
+    /// `"foo\r\n"` are considered equal. When a line is removed, so is its
+    /// line break. If the last line is removed and it doesn't end with a line
+    /// break, the line break before it is removed instead, so that the `Rope`
+    /// only ends with a line break if it did before.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("foo\nfoo\r\nbar\nfoo\nfoo\nfoo");
+    ///
+    /// assert_eq!(r.dedup_consecutive_lines(), 3);
+    /// assert_eq!(r, "foo\nbar\nfoo");
+    /// ```
+    #[inline]
+    pub fn dedup_consecutive_lines(&mut self) -> usize {
+        let mut to_delete = Vec::<Range<usize>>::new();
+
+        let mut removed = 0;
+
+        let mut byte_offset = 0;
+
+        let mut lines = self.lines().zip(self.raw_lines());
+
+        let Some((mut previous, first_raw)) = lines.next() else { return 0 };
+
+        byte_offset += first_raw.byte_len();
+
+        // The length of the line break of the last line that was kept.
+        let mut kept_break_len = first_raw.byte_len() - previous.byte_len();
+
+        for (line, raw_line) in lines {
+            let end = byte_offset + raw_line.byte_len();
+
+            if line == previous {
+                removed += 1;
+
+                // Runs of duplicates are deleted with a single edit.
+                match to_delete.last_mut() {
+                    Some(last) if last.end == byte_offset => last.end = end,
+                    _ => to_delete.push(byte_offset..end),
+                }
+
+                // This is the last line and it's not terminated, so we also
+                // remove the line break of the line it duplicates, which
+                // comes right before the run of duplicates it's part of.
+                if end == self.byte_len()
+                    && line.byte_len() == raw_line.byte_len()
+                {
+                    to_delete.last_mut().unwrap().start -= kept_break_len;
+                }
+            } else {
+                kept_break_len = raw_line.byte_len() - line.byte_len();
+            }
+
+            previous = line;
+
+            byte_offset = end;
+        }
+
+        // Deleting from the end keeps the ranges before it valid.
+        for range in to_delete.into_iter().rev() {
+            self.delete(range);
+        }
+
+        removed
+    }
+
+    /// Deletes the contents of the `Rope` within the specified byte range,
+    /// where the start and end of the range are interpreted as offsets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start or the end of the byte range don't lie on a code
+    /// point boundary, if the start is greater than the end or if the end is
+    /// out of bounds (i.e. greater than [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello Earth 🌎!");
+    ///
+    /// r.delete(5..16);
+    /// assert_eq!(r, "Hello!");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn delete<R>(&mut self, byte_range: R)
+    where
+        R: RangeBounds<usize>,
+    {
+        self.replace(byte_range, "");
+    }
+
+    /// Deletes the contents of the `Rope` within the specified byte range
+    /// like [`delete()`](Self::delete()), returning the byte offset where a
+    /// cursor placed on the deleted text should land, i.e. the start of the
+    /// range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start or the end of the byte range don't lie on a code
+    /// point boundary, if the start is greater than the end or if the end is
+    /// out of bounds (i.e. greater than [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello Earth 🌎!");
+    ///
+    /// assert_eq!(r.delete_at(5..), 5);
+    /// assert_eq!(r, "Hello");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn delete_at<R>(&mut self, byte_range: R) -> usize
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.byte_len());
+
+        self.delete(start..end);
+
+        start
+    }
+
+    /// Deletes the contents of the `Rope` within the specified byte range
+    /// like [`delete()`](Self::delete()), returning how many bytes and line
+    /// breaks were removed.
+    ///
+    /// The number of line breaks is read from the summary of the deleted
+    /// range, so this doesn't need to scan the deleted text. Note that it
+    /// counts the line feeds in the range, so it can be one more than the
+    /// difference in [`line_len()`](Self::line_len()) when the final line
+    /// break of the `Rope` is deleted, since that doesn't start a new line.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start or the end of the byte range don't lie on a code
+    /// point boundary, if the start is greater than the end or if the end is
+    /// out of bounds (i.e. greater than [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("foo\nbar\r\nbaz\nqux");
+    ///
+    /// let outcome = r.delete_counting(2..12);
+    ///
+    /// assert_eq!(r, "fo\nqux");
+    /// assert_eq!(outcome.bytes_removed(), 10);
+    /// assert_eq!(outcome.line_breaks_removed(), 2);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn delete_counting<R>(&mut self, byte_range: R) -> EditOutcome
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.byte_len());
+
+        let line_breaks_removed =
+            self.byte_slice(start..end).tree_slice.summary().line_breaks();
+
+        self.delete(start..end);
+
+        EditOutcome { bytes_removed: end - start, line_breaks_removed }
+    }
+
+    /// Deletes the contents of the `Rope` within the specified byte range
+    /// like [`delete()`](Self::delete()), updating the given byte ranges so
+    /// that they keep covering the same text after the edit.
+    ///
+    /// The start and the end of every range are updated like the offsets
+    /// passed to [`replace_tracking()`](Self::replace_tracking()): ranges
+    /// before the deleted text are left unchanged, ranges after it are
+    /// shifted to the left, ranges partially overlapping it are shrunk to the
+    /// text that's left, and ranges fully inside it become empty ranges at
+    /// the start of the deletion.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start or the end of the byte range don't lie on a code
+    /// point boundary, if the start is greater than the end or if the end is
+    /// out of bounds (i.e. greater than [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("foo bar baz qux");
+    ///
+    /// let mut ranges = [0..3, 2..6, 5..6, 8..11, 12..15];
+    ///
+    /// r.delete_tracking(3..8, &mut ranges);
+    ///
+    /// assert_eq!(r, "foobaz qux");
+    /// assert_eq!(ranges, [0..3, 2..3, 3..3, 3..6, 7..10]);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn delete_tracking<R>(
+        &mut self,
+        byte_range: R,
+        ranges: &mut [Range<usize>],
+    ) where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.byte_len());
+
+        self.delete(start..end);
+
+        for range in ranges {
+            range.start = track_offset(range.start, start..end, 0);
+            range.end = track_offset(range.end, start..end, 0);
+        }
+    }
+
+    /// Returns the line terminator used by the `Rope`, as determined by its
+    /// first line break.
+    ///
+    /// If the `Rope` doesn't contain any line breaks this returns
+    /// [`LineEnding::Lf`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{LineEnding, Rope};
+    /// #
+    /// let r = Rope::from("foo\r\nbar\n");
+    /// assert_eq!(r.detect_line_ending(), LineEnding::CrLf);
+    ///
+    /// let r = Rope::from("foo\nbar\r\n");
+    /// assert_eq!(r.detect_line_ending(), LineEnding::Lf);
+    ///
+    /// let r = Rope::from("foo\rbar");
+    /// assert_eq!(r.detect_line_ending(), LineEnding::Lf);
+    /// ```
+    #[inline]
+    pub fn detect_line_ending(&self) -> LineEnding {
+        if self.newline_count() == 0 {
+            return LineEnding::Lf;
+        }
+
+        // The byte right after the first line feed.
+        let after_first_lf = self.byte_of_line(1);
+
+        if after_first_lf >= 2 && self.byte(after_first_lf - 2) == b'\r' {
+            LineEnding::CrLf
+        } else {
+            LineEnding::Lf
+        }
+    }
+
+    pub(super) const fn arity() -> usize {
+        ARITY
+    }
+
+    /// Removes the specified byte range from the `Rope`, returning an
+    /// iterator over the removed chars.
+    ///
+    /// This is the `Rope` analog of [`String::drain()`], except that the text
+    /// is removed right away: the returned [`Drain`] owns a copy of it, so it
+    /// doesn't borrow the `Rope` and dropping it without consuming it still
+    /// leaves the range removed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start or the end of the byte range don't lie on a code
+    /// point boundary, if the start is greater than the end or if the end is
+    /// out of bounds (i.e. greater than [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello Earth 🌎!");
+    ///
+    /// let cut = r.drain(6..11).collect::<String>();
+    ///
+    /// assert_eq!(cut, "Earth");
+    /// assert_eq!(r, "Hello  🌎!");
+    ///
+    /// r.drain(5..);
+    /// assert_eq!(r, "Hello");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn drain<R>(&mut self, byte_range: R) -> Drain
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.byte_len());
+
+        let text = String::from(self.byte_slice(start..end));
+
+        self.delete(start..end);
+
+        Drain::new(text)
+    }
+
+    /// Replaces the contents of the `Rope` within the specified byte range
+    /// with the given string like [`replace()`](Self::replace()), returning
+    /// whether the edit was an insertion, a deletion or a replacement.
+    ///
+    /// The kind only depends on whether the byte range and the text are
+    /// empty, not on the text they contain, so e.g. replacing `"foo"` with
+    /// `"foo"` is still an [`EditKind::Replace`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start or the end of the byte range don't lie on a code
+    /// point boundary, if the start is greater than the end, if the end is
+    /// out of bounds (i.e. greater than [`byte_len()`](Self::byte_len())) or
+    /// if the replacement would make the `Rope` longer than its
+    /// [maximum length](Self::set_max_len()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{EditKind, Rope};
+    /// #
+    /// let mut r = Rope::from("Hello Earth 🌎!");
+    ///
+    /// assert_eq!(r.edit(16..16, " 🌕"), EditKind::Insert);
+    /// assert_eq!(r.edit(11..16, ""), EditKind::Delete);
+    /// assert_eq!(r.edit(6..11, "Moon"), EditKind::Replace);
+    /// assert_eq!(r.edit(3..3, ""), EditKind::Noop);
+    ///
+    /// assert_eq!(r, "Hello Moon 🌕!");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn edit<R, T>(&mut self, byte_range: R, text: T) -> EditKind
+    where
+        R: RangeBounds<usize>,
+        T: AsRef<str>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.byte_len());
+
+        let text = text.as_ref();
+
+        self.replace(start..end, text);
+
+        match (start == end, text.is_empty()) {
+            (true, false) => EditKind::Insert,
+            (false, true) => EditKind::Delete,
+            (false, false) => EditKind::Replace,
+            (true, true) => EditKind::Noop,
+        }
+    }
+
+    /// Returns an [`EditRecorder`] through which edits to the `Rope` return
+    /// their own inverse, which can be used to undo them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello Earth 🌎!");
+    ///
+    /// let inverse = r.edit_recording().delete(5..16);
+    /// assert_eq!(r, "Hello!");
+    ///
+    /// inverse.apply(&mut r);
+    /// assert_eq!(r, "Hello Earth 🌎!");
+    /// ```
+    #[inline]
+    pub fn edit_recording(&mut self) -> EditRecorder<'_> {
+        EditRecorder::new(self)
+    }
+
+    /// Returns an empty slice of the `Rope` at the given byte offset.
+    ///
+    /// This is equivalent to `byte_slice(byte_offset..byte_offset)`. The
+    /// slice compares equal to `""` and all its iterators are empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())) or if it doesn't lie on a code point
+    /// boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("🗻∈🌏");
+    ///
+    /// let s = r.empty_slice_at(4);
+    /// assert!(s.is_empty());
+    /// assert_eq!(s, "");
+    /// assert_eq!(s.chars().next(), None);
+    ///
+    /// assert_eq!(r.empty_slice_at(r.byte_len()), r.byte_slice(7..7));
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn empty_slice_at(&self, byte_offset: usize) -> RopeSlice<'_> {
+        self.byte_slice(byte_offset..byte_offset)
+    }
+
+    /// Returns `true` if the last line of the `Rope` is terminated by a line
+    /// break, i.e. if its last byte is a `'\n'`.
+    ///
+    /// This is `false` for empty `Rope`s. Since [`lines()`](Self::lines())
+    /// doesn't yield the line terminators, this is what tells whether a file
+    /// ended with a line break when it was loaded. Writing the `Rope` back
+    /// through its [`Display`](core::fmt::Display) implementation, its
+    /// [`chunks()`](Self::chunks()) or its [`reader()`](Self::reader())
+    /// always yields exactly its text, so a file is only saved with a
+    /// trailing line break if it was loaded with one.
+    ///
+    /// This is an O(1) operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// assert!(Rope::from("foo\r\n").final_line_terminated());
+    /// assert!(!Rope::from("foo").final_line_terminated());
+    /// assert!(!Rope::from("foo\r").final_line_terminated());
+    /// assert!(!Rope::new().final_line_terminated());
+    /// ```
+    #[inline]
+    pub fn final_line_terminated(&self) -> bool {
+        self.has_trailing_newline
+    }
+
+    /// Returns the byte offset of the first char of the `Rope` satisfying the
+    /// given predicate, or `None` if no char does.
+    ///
+    /// This is the `Rope` analog of calling [`str::find()`] with a closure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("ƒoo bär\tbaz");
+    ///
+    /// assert_eq!(r.find_char(char::is_whitespace), Some(4));
+    /// assert_eq!(r.find_char(|ch| !ch.is_ascii()), Some(0));
+    /// assert_eq!(r.find_char(|ch| ch == 'z'), Some(12));
+    /// assert_eq!(r.find_char(char::is_numeric), None);
+    /// ```
+    #[inline]
+    pub fn find_char<F>(&self, predicate: F) -> Option<usize>
+    where
+        F: FnMut(char) -> bool,
+    {
+        find_char(self.chunks(), predicate)
+    }
+
+    /// Returns the first char of the `Rope`, or `None` if it's empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// assert_eq!(Rope::from("🐸 foo\n").first_char(), Some('🐸'));
+    /// assert_eq!(Rope::new().first_char(), None);
+    /// ```
+    #[inline]
+    pub fn first_char(&self) -> Option<char> {
+        self.chars().next()
+    }
+
+    /// Creates a new `Rope` of `len` bytes filled with `fill`, where the text
+    /// of each `(byte_offset, text)` placement overwrites the fill starting
+    /// at its byte offset.
+    ///
+    /// This is useful to reconstruct a sparse document from the positions of
+    /// its known parts, or to build test fixtures.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is not a multiple of the byte length of `fill`, if the
+    /// placements are not sorted by byte offset or overlap, if a placement
+    /// goes past `len` or if the start or the end of a placement would split
+    /// a `fill` character.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from_placements(12, ' ', &[(0, "fn"), (3, "main()")]);
+    /// assert_eq!(r, "fn main()   ");
+    ///
+    /// let r = Rope::from_placements(6, '·', &[(2, "ab")]);
+    /// assert_eq!(r, "·ab·");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn from_placements(
+        len: usize,
+        fill: char,
+        placements: &[(usize, &str)],
+    ) -> Self {
+        /// The number of fill characters appended to the builder at once.
+        const FILL_BLOCK_CHARS: usize = 256;
+
+        #[inline]
+        fn append_fill(builder: &mut RopeBuilder, block: &str, mut n: usize) {
+            while n > 0 {
+                let appended = n.min(block.len());
+                builder.append(&block[..appended]);
+                n -= appended;
+            }
+        }
+
+        let fill_len = fill.len_utf8();
+
+        assert!(
+            len % fill_len == 0,
+            "the length {len} is not a multiple of the length of {fill:?}"
+        );
+
+        let block = fill.to_string().repeat(FILL_BLOCK_CHARS);
+
+        let mut builder = RopeBuilder::new();
+
+        let mut filled = 0;
+
+        for &(start, text) in placements {
+            if start < filled {
+                panic::placements_overlap(filled, start);
+            }
+
+            if start > len || text.len() > len - start {
+                panic::byte_offset_out_of_bounds(
+                    start.saturating_add(text.len()),
+                    len,
+                );
+            }
+
+            let end = start + text.len();
+
+            assert!(
+                start % fill_len == 0 && end % fill_len == 0,
+                "the placement at {start}..{end} splits a {fill:?}"
+            );
+
+            append_fill(&mut builder, &block, start - filled);
+            builder.append(text);
+            filled = end;
+        }
+
+        append_fill(&mut builder, &block, len - filled);
+
+        builder.build()
+    }
+
+    /// Creates a new `Rope` from the contents of the given reader.
+    ///
+    /// The contents are read one block at a time and appended to a
+    /// [`RopeBuilder`] with [`append_bytes()`](RopeBuilder::append_bytes()),
+    /// so no intermediate `String` holding the whole text is allocated. See
+    /// [`from_reader_with_progress()`](Self::from_reader_with_progress()) to
+    /// be notified as the contents are read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading from the reader fails or if its contents
+    /// are not valid UTF-8, in which case the error kind is
+    /// [`InvalidData`](std::io::ErrorKind::InvalidData).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from_reader("Hello Earth 🌎!".as_bytes()).unwrap();
+    /// assert_eq!(r, "Hello Earth 🌎!");
+    ///
+    /// assert!(Rope::from_reader(&[0xF0, 0x9F][..]).is_err());
+    /// ```
+    #[inline]
+    pub fn from_reader<R>(reader: R) -> std::io::Result<Self>
+    where
+        R: std::io::Read,
+    {
+        Self::from_reader_with_progress(reader, |_| {})
+    }
+
+    /// Creates a new `Rope` from the contents of the given reader like
+    /// [`from_reader()`](Self::from_reader()), calling `on_bytes` with the
+    /// total number of bytes read so far after each block is appended.
+    ///
+    /// This can be used to show the progress of loading a large file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading from the reader fails or if its contents
+    /// are not valid UTF-8, in which case the error kind is
+    /// [`InvalidData`](std::io::ErrorKind::InvalidData).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let text = "Hello Earth 🌎!\n".repeat(1000);
+    ///
+    /// let mut progress = Vec::new();
+    ///
+    /// let r = Rope::from_reader_with_progress(text.as_bytes(), |read| {
+    ///     progress.push(read)
+    /// })
+    /// .unwrap();
+    ///
+    /// assert_eq!(r, text);
+    /// assert!(progress.len() > 1);
+    /// assert_eq!(progress.last(), Some(&text.len()));
+    /// ```
+    #[inline]
+    pub fn from_reader_with_progress<R, F>(
+        mut reader: R,
+        mut on_bytes: F,
+    ) -> std::io::Result<Self>
+    where
+        R: std::io::Read,
+        F: FnMut(usize),
+    {
+        let invalid_utf8 = || {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            )
+        };
+
+        let mut builder = RopeBuilder::new();
+
+        // The same size as the buffer of `std::io::BufReader`.
+        let mut buffer = [0u8; 8 * 1024];
+
+        let mut total = 0;
+
+        loop {
+            let read = match reader.read(&mut buffer) {
+                Ok(0) => break,
+
+                Ok(read) => read,
+
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {
+                    continue
+                },
+
+                Err(err) => return Err(err),
+            };
+
+            // The builder holds back the bytes of a character split between
+            // two reads until the next read completes it.
+            builder
+                .append_bytes(&buffer[..read])
+                .map_err(|_| invalid_utf8())?;
+
+            total += read;
+
+            on_bytes(total);
+        }
+
+        // The reader ended in the middle of a character.
+        if !builder.incomplete_char().is_empty() {
+            return Err(invalid_utf8());
+        }
+
+        Ok(builder.build())
+    }
+
+    /// Creates a new `Rope` from a slice of bytes that may not be valid
+    /// UTF-8, replacing every invalid sequence with
+    /// [`U+FFFD REPLACEMENT CHARACTER`](char::REPLACEMENT_CHARACTER) like
+    /// [`String::from_utf8_lossy()`] does.
+    ///
+    /// The valid parts of the bytes are appended to a
+    /// [`RopeBuilder`](crate::RopeBuilder) as they're validated, so the text
+    /// is never copied into an intermediate `String`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from_utf8_lossy(b"Hello \xF0\x90\x80World\xFF!");
+    /// assert_eq!(r, "Hello \u{FFFD}World\u{FFFD}!");
+    /// ```
+    #[inline]
+    pub fn from_utf8_lossy(bytes: &[u8]) -> Self {
+        let mut builder = RopeBuilder::new();
+
+        let mut rest = bytes;
+
+        loop {
+            match core::str::from_utf8(rest) {
+                Ok(valid) => {
+                    builder.append(valid);
+                    break;
+                },
+
+                Err(err) => {
+                    let (valid, after_valid) =
+                        rest.split_at(err.valid_up_to());
+
+                    // SAFETY: the bytes up to `valid_up_to()` are valid
+                    // UTF-8.
+                    builder.append(unsafe {
+                        core::str::from_utf8_unchecked(valid)
+                    });
+
+                    builder.append("\u{FFFD}");
+
+                    // An error without a length means the bytes end in the
+                    // middle of a code point, which is replaced by a single
+                    // replacement character.
+                    match err.error_len() {
+                        Some(invalid_len) => {
+                            rest = &after_valid[invalid_len..]
+                        },
+                        None => break,
+                    }
+                },
+            }
+        }
+
+        builder.build()
+    }
+
+    /// Returns the generation of the `Rope`, which changes every time its
+    /// text is edited.
+    ///
+    /// Every new `Rope` and every edit takes a generation that no other `Rope`
+    /// has had before, while clones carry it over. Two `Rope`s thus have the
+    /// same generation only if one is a clone of the other and neither has
+    /// been edited since. Together with [`RopeSlice::is_current()`] this can
+    /// be used to check that a slice of a snapshot of a `Rope` still reflects
+    /// its text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("foo");
+    /// let generation = r.generation();
+    ///
+    /// r.insert(3, "bar");
+    /// assert_ne!(r.generation(), generation);
+    ///
+    /// let other = Rope::from("foobar");
+    /// assert_ne!(other.generation(), r.generation());
+    /// ```
+    #[inline]
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Returns the byte at `byte_index` like [`byte()`](Self::byte()), or
+    /// `None` if the byte index is out of bounds (i.e. greater than or equal
+    /// to [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("bar");
+    ///
+    /// assert_eq!(r.get_byte(1), Some(b'a'));
+    /// assert_eq!(r.get_byte(3), None);
+    /// ```
+    #[inline]
+    pub fn get_byte(&self, byte_index: usize) -> Option<u8> {
+        // SAFETY: the byte index was just checked to be in bounds.
+        (byte_index < self.byte_len())
+            .then(|| unsafe { self.byte_unchecked(byte_index) })
+    }
+
+    /// Returns the line at `line_index` like [`line()`](Self::line()), or
+    /// `None` if the line index is out of bounds (i.e. greater than or equal
+    /// to [`line_len()`](Self::line_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\n");
+    ///
+    /// assert_eq!(r.get_line(1).unwrap(), "bar");
+    /// assert_eq!(r.get_line(2), None);
+    /// ```
+    #[inline]
+    pub fn get_line(&self, line_index: usize) -> Option<RopeSlice<'_>> {
+        (line_index < self.line_len()).then(|| self.line(line_index))
+    }
+
+    /// Returns the number of extended grapheme clusters in this `Rope`.
+    ///
+    /// Grapheme clusters can't be counted from the summaries of the chunks, so
+    /// the first call has to go through all the graphemes of the `Rope` like
+    /// `self.graphemes().count()`, which takes linear time. The result is
+    /// then cached together with the [generation](Self::generation()) of the
+    /// `Rope`, and the following calls return it in O(1) time until the
+    /// `Rope` is edited, after which it's recomputed on the next call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("🐻‍❄️ foo\r\n");
+    /// assert_eq!(r.grapheme_len(), 6);
+    ///
+    /// // This doesn't count the graphemes again.
+    /// assert_eq!(r.grapheme_len(), 6);
+    ///
+    /// r.insert(0, "🐸");
+    /// assert_eq!(r.grapheme_len(), 7);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
+    #[cfg(feature = "graphemes")]
+    #[inline]
+    pub fn grapheme_len(&self) -> usize {
+        if let Some(len) = self.grapheme_len_cache.get(self.generation) {
+            return len;
+        }
+
+        let len = self.graphemes().count();
+        self.grapheme_len_cache.set(self.generation, len);
+        len
+    }
+
+    /// Returns the byte offset of the start of the extended grapheme cluster
+    /// containing the given byte offset.
+    ///
+    /// If the byte offset already lies on a grapheme boundary it's returned
+    /// unchanged. Otherwise the `Rope` is walked backward from it until the
+    /// start of the grapheme is found, looking at the text before it as
+    /// needed to classify the grapheme correctly (e.g. to pair up regional
+    /// indicators into flags).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())) or if it doesn't lie on a code point
+    /// boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("arg!\r\n🇫🇷🇩🇪");
+    ///
+    /// assert_eq!(r.grapheme_start(3), 3);
+    /// assert_eq!(r.grapheme_start(5), 4); // between '\r' and '\n'
+    /// assert_eq!(r.grapheme_start(10), 6); // inside '🇫🇷'
+    /// assert_eq!(r.grapheme_start(18), 14); // inside '🇩🇪'
+    /// assert_eq!(r.grapheme_start(r.byte_len()), r.byte_len());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
+    #[cfg(feature = "graphemes")]
+    #[track_caller]
+    #[inline]
+    pub fn grapheme_start(&self, byte_offset: usize) -> usize {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        grapheme_start(self.byte_slice(..), byte_offset)
+    }
+
+    /// Returns an iterator over the extended grapheme clusters of this
+    /// `Rope`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("arg!\r\n🐻‍❄️");
+    ///
+    /// let mut graphemes = r.graphemes();
+    ///
+    /// assert_eq!(Some("a"), graphemes.next().as_deref());
+    /// assert_eq!(Some("r"), graphemes.next().as_deref());
+    /// assert_eq!(Some("g"), graphemes.next().as_deref());
+    /// assert_eq!(Some("!"), graphemes.next().as_deref());
+    /// assert_eq!(Some("\r\n"), graphemes.next().as_deref());
+    /// assert_eq!(Some("🐻‍❄️"), graphemes.next().as_deref());
+    /// assert_eq!(None, graphemes.next());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
+    #[cfg(feature = "graphemes")]
+    #[inline]
+    pub fn graphemes(&self) -> crate::iter::Graphemes<'_> {
+        crate::iter::Graphemes::from(self)
+    }
+
+    /// Returns an iterator over the extended grapheme clusters of this
+    /// `Rope` like [`graphemes()`](Self::graphemes()), starting from the
+    /// grapheme containing the given byte offset.
+    ///
+    /// The byte offset is snapped back to the start of its grapheme with
+    /// [`grapheme_start()`](Self::grapheme_start()), which can be called
+    /// separately to know where the iteration starts. This only looks at the
+    /// text around the byte offset, so resuming the iteration in the middle
+    /// of a `Rope` doesn't have to go through all the graphemes before it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())) or if it doesn't lie on a code point
+    /// boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("arg!\r\n🇫🇷🇩🇪");
+    ///
+    /// let mut graphemes = r.graphemes_at(18);
+    /// assert_eq!(Some("🇩🇪"), graphemes.next().as_deref());
+    /// assert_eq!(None, graphemes.next());
+    ///
+    /// let mut graphemes = r.graphemes_at(5);
+    /// assert_eq!(Some("\r\n"), graphemes.next().as_deref());
+    /// assert_eq!(Some("🇫🇷"), graphemes.next().as_deref());
+    /// assert_eq!(Some("🇩🇪"), graphemes.next_back().as_deref());
+    /// assert_eq!(None, graphemes.next());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
+    #[cfg(feature = "graphemes")]
+    #[track_caller]
+    #[inline]
+    pub fn graphemes_at(
+        &self,
+        byte_offset: usize,
+    ) -> crate::iter::Graphemes<'_> {
+        let start = self.grapheme_start(byte_offset);
+        self.byte_slice(start..).graphemes()
+    }
+
+    /// Returns `true` if the `Rope` starts with a UTF-8 byte order mark
+    /// (`U+FEFF`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// assert!(Rope::from("\u{FEFF}foo").has_bom());
+    /// assert!(!Rope::from("foo\u{FEFF}").has_bom());
+    /// assert!(!Rope::new().has_bom());
+    /// ```
+    #[inline]
+    pub fn has_bom(&self) -> bool {
+        self.bytes().take(BOM.len()).eq(BOM.bytes())
+    }
+
+    /// Inserts `text` in the `Rope` at the given byte offset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset doesn't lie on a code point boundary or if
+    /// it's out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello Earth!");
+    ///
+    /// r.insert(11, " 🌎");
+    /// assert_eq!(r, "Hello Earth 🌎!");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn insert<T>(&mut self, byte_offset: usize, text: T)
+    where
+        T: AsRef<str>,
+    {
+        self.replace(byte_offset..byte_offset, text)
+    }
+
+    /// Inserts `text` in the `Rope` at the given byte offset like
+    /// [`insert()`](Self::insert()), returning the byte offset right after
+    /// the inserted text, which is where a cursor placed at `byte_offset`
+    /// should land.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset doesn't lie on a code point boundary or if
+    /// it's out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello Earth!");
+    ///
+    /// let cursor = r.insert_at(11, " 🌎");
+    /// assert_eq!(cursor, 16);
+    ///
+    /// let cursor = r.insert_at(cursor, "🌏");
+    /// assert_eq!(r, "Hello Earth 🌎🌏!");
+    /// assert_eq!(r.byte_slice(cursor..), "!");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn insert_at<T>(&mut self, byte_offset: usize, text: T) -> usize
+    where
+        T: AsRef<str>,
+    {
+        let text = text.as_ref();
+        self.insert(byte_offset, text);
+        byte_offset + text.len()
+    }
+
+    /// Inserts a single character at the given byte offset.
+    ///
+    /// This is the same as calling [`insert()`](Self::insert()) with the
+    /// character encoded as a string, except that the encoding is done on the
+    /// stack, so it's a better fit for inserting text keystroke by keystroke
+    /// than going through `ch.to_string()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset doesn't lie on a code point boundary or if
+    /// it's out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello Earth!");
+    ///
+    /// r.insert_char(11, '🌎');
+    /// assert_eq!(r, "Hello Earth🌎!");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn insert_char(&mut self, byte_offset: usize, ch: char) {
+        self.insert(byte_offset, ch.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Inserts `text` in the `Rope` at the given byte offset like
+    /// [`insert()`](Self::insert()), replacing every tab in `text` with
+    /// enough spaces to reach the next tab stop.
+    ///
+    /// Tab stops are placed every `tab_width` columns, where a column is a
+    /// `char`. The column of the insertion point is computed from the
+    /// contents of its line up to `byte_offset`, with the tabs already in the
+    /// line also advancing to the next tab stop. Columns are reset after every
+    /// line break in `text`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tab_width` is zero, if the byte offset doesn't lie on a code
+    /// point boundary or if it's out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("fn main() {\n}");
+    ///
+    /// r.insert_expanded(12, "\tlet a = 1;\t// one\n", 4);
+    /// assert_eq!(r, "fn main() {\n    let a = 1;  // one\n}");
+    ///
+    /// r.insert_expanded(r.byte_len(), "\t", 4);
+    /// assert_eq!(r, "fn main() {\n    let a = 1;  // one\n}   ");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn insert_expanded<T>(
+        &mut self,
+        byte_offset: usize,
+        text: T,
+        tab_width: usize,
+    ) where
+        T: AsRef<str>,
+    {
+        assert!(tab_width > 0, "the tab width must be greater than zero");
+
+        let text = text.as_ref();
+
+        if !text.contains('\t') {
+            return self.insert(byte_offset, text);
+        }
+
+        let next_tab_stop =
+            |column: usize| (column / tab_width + 1) * tab_width;
+
+        let line_start = self.byte_of_line(self.line_of_byte(byte_offset));
+
+        let mut column = 0;
+
+        for ch in self.byte_slice(line_start..byte_offset).chars() {
+            column =
+                if ch == '\t' { next_tab_stop(column) } else { column + 1 };
+        }
+
+        let mut expanded = String::with_capacity(text.len());
+
+        for ch in text.chars() {
+            match ch {
+                '\t' => {
+                    let next = next_tab_stop(column);
+                    expanded
+                        .extend(core::iter::repeat(' ').take(next - column));
+                    column = next;
+                },
+
+                '\n' => {
+                    expanded.push(ch);
+                    column = 0;
+                },
+
+                _ => {
+                    expanded.push(ch);
+                    column += 1;
+                },
+            }
+        }
+
+        self.insert(byte_offset, expanded)
+    }
+
+    /// Inserts a line break at the given byte offset, using the line
+    /// terminator returned by
+    /// [`detect_line_ending()`](Self::detect_line_ending()).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset doesn't lie on a code point boundary or if
+    /// it's out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("foo\r\nbarbaz");
+    ///
+    /// r.insert_newline(8);
+    /// assert_eq!(r, "foo\r\nbar\r\nbaz");
+    ///
+    /// let mut r = Rope::from("foobar");
+    ///
+    /// r.insert_newline(3);
+    /// assert_eq!(r, "foo\nbar");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn insert_newline(&mut self, byte_offset: usize) {
+        let line_ending = self.detect_line_ending();
+        self.insert(byte_offset, line_ending.as_str());
+    }
+
+    /// Inserts the `open` and `close` characters at the given byte offset,
+    /// returning the byte offset between them.
+    ///
+    /// This is meant for editors auto-closing brackets and quotes: the
+    /// returned offset is where the cursor should be placed, and it accounts
+    /// for `open` being encoded using more than one byte.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset doesn't lie on a code point boundary or if
+    /// it's out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("let s = ;");
+    ///
+    /// let cursor = r.insert_pair(8, '«', '»');
+    ///
+    /// assert_eq!(r, "let s = «»;");
+    /// assert_eq!(cursor, 10);
+    /// assert_eq!(r.byte_slice(..cursor), "let s = «");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn insert_pair(
+        &mut self,
+        byte_offset: usize,
+        open: char,
+        close: char,
+    ) -> usize {
+        self.insert_char(byte_offset, open);
+        let cursor = byte_offset + open.len_utf8();
+        self.insert_char(cursor, close);
+        cursor
+    }
+
+    /// Inserts the contents of another `Rope` at the given byte offset.
+    ///
+    /// This is equivalent to `self.insert(byte_offset, source.to_string())`
+    /// but it doesn't copy the text of `source`: the chunks of the two
+    /// `Rope`s are shared, and only the ones around the insertion point are
+    /// rebuilt. This makes copying a large region of text from one `Rope` to
+    /// another (or to a different position of the same `Rope`, after cloning
+    /// it) an O(log n) operation. The shared chunks are copied on write, so
+    /// editing either `Rope` afterwards doesn't affect the other one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset doesn't lie on a code point boundary or if
+    /// it's out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello !");
+    /// let world = Rope::from("world");
+    ///
+    /// r.insert_rope(6, &world);
+    /// assert_eq!(r, "Hello world!");
+    /// assert_eq!(world, "world");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn insert_rope(&mut self, byte_offset: usize, source: &Rope) {
+        self.insert_slice(byte_offset, source.byte_slice(..))
+    }
+
+    /// Inserts the contents of a `RopeSlice` at the given byte offset.
+    ///
+    /// Like [`insert_rope()`](Self::insert_rope()), the chunks fully
+    /// contained in the slice are shared instead of being copied.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset doesn't lie on a code point boundary or if
+    /// it's out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("foo\nbaz\n");
+    /// let other = Rope::from("foo\nbar\nbaz\n");
+    ///
+    /// r.insert_slice(4, other.line_slice(1..2));
+    /// assert_eq!(r, "foo\nbar\nbaz\n");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn insert_slice(&mut self, byte_offset: usize, source: RopeSlice<'_>) {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        let (chunk, ByteMetric(chunk_byte_offset)) =
+            self.tree.leaf_at_measure(ByteMetric(byte_offset));
+
+        chunk.assert_char_boundary(byte_offset - chunk_byte_offset);
+
+        if source.byte_len() > Self::MAX_LEN - self.byte_len() {
+            panic::rope_too_long(self.byte_len(), source.byte_len());
+        }
+
+        if let Err(EditError::MaxLenExceeded { limit, would_be }) =
+            self.check_max_len(byte_offset, byte_offset, source.byte_len())
+        {
+            panic::max_len_exceeded(limit, would_be);
+        }
+
+        self.generation = next_generation();
+
+        if source.byte_len() == 0 {
+            return;
+        }
+
+        self.shift_readonly_ranges(
+            byte_offset..byte_offset,
+            source.byte_len(),
+        );
+
+        let len = self.byte_len();
+
+        let mut tree = Tree::from(
+            self.tree.slice(ByteMetric(0)..ByteMetric(byte_offset)),
+        );
+
+        tree.append(Tree::from(source.tree_slice));
+
+        tree.append(Tree::from(
+            self.tree.slice(ByteMetric(byte_offset)..ByteMetric(len)),
+        ));
+
+        self.tree = tree;
+
+        if byte_offset == len {
+            self.has_trailing_newline = source.has_trailing_newline;
+        }
+    }
+
+    /// Inserts `text` in the `Rope` at the given byte offset like
+    /// [`insert()`](Self::insert()), updating the given byte ranges so that
+    /// they keep covering the same text after the edit.
+    ///
+    /// Ranges ending before or at the insertion point are left unchanged,
+    /// ranges starting after it are shifted to the right by the length of
+    /// `text`, and the other ones grow to also cover the new text. Since the
+    /// start and the end of every range are updated like the offsets passed
+    /// to [`replace_tracking()`](Self::replace_tracking()), text inserted at
+    /// the start of a range ends up inside of it, while text inserted at its
+    /// end ends up outside of it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset doesn't lie on a code point boundary or if
+    /// it's out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("foo bar baz");
+    ///
+    /// let mut ranges = [0..3, 4..7, 5..7, 8..11];
+    ///
+    /// r.insert_tracking(5, "🦀", &mut ranges);
+    ///
+    /// assert_eq!(r, "foo b🦀ar baz");
+    /// assert_eq!(ranges, [0..3, 4..11, 5..11, 12..15]);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn insert_tracking<T>(
+        &mut self,
+        byte_offset: usize,
+        text: T,
+        ranges: &mut [Range<usize>],
+    ) where
+        T: AsRef<str>,
+    {
+        let text = text.as_ref();
+
+        self.insert(byte_offset, text);
+
+        for range in ranges {
+            range.start = track_offset(
+                range.start,
+                byte_offset..byte_offset,
+                text.len(),
+            );
+            range.end =
+                track_offset(range.end, byte_offset..byte_offset, text.len());
+        }
+    }
+
+    /// Returns `true` if all the bytes of the `Rope` are ASCII.
+    ///
+    /// This is an O(1) operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello, world!");
+    /// assert!(r.is_ascii());
+    ///
+    /// r.insert(7, "🌎 ");
+    /// assert!(!r.is_ascii());
+    /// ```
+    #[inline]
+    pub fn is_ascii(&self) -> bool {
+        self.tree.summary().is_ascii()
+    }
+
+    /// Returns `true` if the given byte offset lies on a [`char`] boundary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Löwe 老虎 Léopard");
+    ///
+    /// assert!(r.is_char_boundary(0));
+    /// assert!(r.is_char_boundary(r.byte_len()));
+    /// assert!(r.is_char_boundary(6)); // between ' ' and '老'
+    /// assert!(!r.is_char_boundary(2)); // between the 1st and 2nd byte of 'ö'
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn is_char_boundary(&self, byte_offset: usize) -> bool {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        if self.is_ascii() {
+            return true;
+        }
+
+        let (chunk, ByteMetric(chunk_byte_offset)) =
+            self.tree.leaf_at_measure(ByteMetric(byte_offset));
+
+        chunk.is_char_boundary(byte_offset - chunk_byte_offset)
+    }
 
     /// Returns `true` if the `Rope`'s byte length is zero.
     ///
@@ -392,271 +2463,2267 @@ impl Rope {
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let mut r = Rope::new();
-    /// assert!(r.is_empty());
+    /// let mut r = Rope::new();
+    /// assert!(r.is_empty());
+    ///
+    /// r.insert(0, "hey");
+    /// assert!(!r.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.byte_len() == 0
+    }
+
+    /// Returns `true` if the given byte offset lies on a grapheme cluster
+    /// boundary.
+    ///
+    /// Grapheme cluster boundaries are always code point boundaries, so this
+    /// returns `false` if the byte offset is in the middle of a code point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("aargh!\r\n🐻‍❄️");
+    ///
+    /// assert!(r.is_grapheme_boundary(0));
+    /// assert!(r.is_grapheme_boundary(r.byte_len()));
+    /// assert!(!r.is_grapheme_boundary(7)); // between '\r' and '\n'
+    /// assert!(r.is_grapheme_boundary(8)); // between '\n' and '🐻‍❄️'
+    /// assert!(!r.is_grapheme_boundary(12)); // between the 1st and 2nd code point of '🐻‍❄️'
+    /// assert!(!r.is_grapheme_boundary(9)); // inside the 1st code point of '🐻‍❄️'
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
+    #[cfg(feature = "graphemes")]
+    #[track_caller]
+    #[inline]
+    pub fn is_grapheme_boundary(&self, byte_offset: usize) -> bool {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        is_grapheme_boundary(self.chunks(), self.byte_len(), byte_offset)
+    }
+
+    /// Returns the last char of the `Rope`, or `None` if it's empty.
+    ///
+    /// Only the last chunk is decoded, and it's reached in `O(log n)` time
+    /// instead of iterating over all the chars.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// assert_eq!(Rope::from("foo 🐸").last_char(), Some('🐸'));
+    /// assert_eq!(Rope::new().last_char(), None);
+    /// ```
+    #[inline]
+    pub fn last_char(&self) -> Option<char> {
+        self.chars().next_back()
+    }
+
+    /// Returns the line at `line_index`, without its line terminator.
+    ///
+    /// If you want to include the line break consider taking a
+    /// [`line_slice()`](Self::line_slice()) in the
+    /// `line_index..line_index + 1` range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line index is out of bounds (i.e. greater than or equal
+    /// to [`line_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    ///
+    /// assert_eq!(r.line(0), "foo");
+    /// assert_eq!(r.line(1), "bar");
+    /// assert_eq!(r.line(2), "baz");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn line(&self, line_index: usize) -> RopeSlice<'_> {
+        if line_index >= self.line_len() {
+            panic::line_index_out_of_bounds(line_index, self.line_len());
+        }
+
+        let (tree_slice, ByteMetric(byte_offset)) =
+            self.tree.slice_with_offset(
+                RawLineMetric(line_index)..RawLineMetric(line_index + 1),
+            );
+
+        let mut line = RopeSlice {
+            tree_slice,
+            has_trailing_newline: false,
+            byte_offset,
+            generation: self.generation,
+        };
+
+        if line.tree_slice.summary().line_breaks() == 1 {
+            line.truncate_trailing_line_break();
+        }
+
+        line
+    }
+
+    /// Returns the number of line breaks in the given byte range.
+    ///
+    /// The range is half-open, so a line break is counted if its `'\n'` is
+    /// at an offset in `start..end`: a line break starting at the end of the
+    /// range isn't counted, and a `"\r\n"` split by the start of the range is.
+    ///
+    /// This is equivalent to `byte_slice(byte_range).newline_count()`, but
+    /// it only takes two `O(log n)` lookups and doesn't build a `RopeSlice`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start or the end of the byte range don't lie on a code
+    /// point boundary, if the start is greater than the end or if the end is
+    /// out of bounds (i.e. greater than [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\n");
+    ///
+    /// assert_eq!(r.line_breaks_in_range(..), 3);
+    /// assert_eq!(r.line_breaks_in_range(0..3), 0); // ends at the '\n'
+    /// assert_eq!(r.line_breaks_in_range(0..4), 1);
+    /// assert_eq!(r.line_breaks_in_range(8..9), 1); // the '\n' of "\r\n"
+    /// assert_eq!(r.line_breaks_in_range(4..8), 0); // the '\r' of "\r\n"
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn line_breaks_in_range<R>(&self, byte_range: R) -> usize
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.byte_len());
+
+        if start > end {
+            panic::byte_start_after_end(start, end);
+        }
+
+        if end > self.byte_len() {
+            panic::byte_offset_out_of_bounds(end, self.byte_len());
+        }
+
+        self.line_of_byte(end) - self.line_of_byte(start)
+    }
+
+    /// Returns the leading whitespace of the line at `line_index`, where
+    /// whitespace is defined as in [`char::is_whitespace()`].
+    ///
+    /// If the line only contains whitespace the whole line is returned,
+    /// without its line terminator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line index is out of bounds (i.e. greater than or equal
+    /// to [`line_len()`](Self::line_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("fn main() {\n\t  foo();\n  \r\n}");
+    ///
+    /// assert_eq!(r.line_indent(0), "");
+    /// assert_eq!(r.line_indent(1), "\t  ");
+    /// assert_eq!(r.line_indent(2), "  ");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn line_indent(&self, line_index: usize) -> RopeSlice<'_> {
+        let line = self.line(line_index);
+        line.byte_slice(..leading_whitespace_len(line.chunks()))
+    }
+
+    /// Returns the byte offset of the first non-whitespace character of the
+    /// line at `line_index`, where whitespace is defined as in
+    /// [`char::is_whitespace()`].
+    ///
+    /// The offset is relative to the start of the `Rope`, not to the start of
+    /// the line. If the line only contains whitespace this is the offset of
+    /// the end of the line, before its line terminator.
+    ///
+    /// Only the line at `line_index` is scanned, so this takes time
+    /// proportional to the length of its indentation plus the logarithm of
+    /// the length of the `Rope`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line index is out of bounds (i.e. greater than or equal
+    /// to [`line_len()`](Self::line_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("fn main() {\n    foo();\n  \r\n}");
+    ///
+    /// assert_eq!(r.line_indent_end(0), 0);
+    /// assert_eq!(r.line_indent_end(1), 16);
+    /// assert_eq!(r.line_indent_end(2), 25);
+    /// assert_eq!(r.line_indent_end(3), 27);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn line_indent_end(&self, line_index: usize) -> usize {
+        let line = self.line(line_index);
+        line.byte_offset + leading_whitespace_len(line.chunks())
+    }
+
+    /// Returns an iterator over the byte ranges of the lines of this `Rope`,
+    /// not including the line terminators.
+    ///
+    /// The ranges are the ones spanned by the lines yielded by
+    /// [`lines()`](Self::lines()), so the final line break doesn't cause the
+    /// iterator to return a final empty range. Unlike the lines, the ranges
+    /// don't borrow the `Rope`, which makes them suited to building a line
+    /// index.
+    ///
+    /// If you want to include the line breaks consider using the
+    /// [`raw_line_ranges()`](Self::raw_line_ranges()) method instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\n");
+    ///
+    /// let ranges = r.line_ranges().collect::<Vec<_>>();
+    ///
+    /// assert_eq!(ranges, [0..3, 4..7, 9..12]);
+    /// ```
+    #[inline]
+    pub fn line_ranges(&self) -> LineRanges<'_> {
+        LineRanges::new(self, false)
+    }
+
+    /// Returns the number of lines in the `Rope` as an editor would display
+    /// them, i.e. the number of line breaks plus one.
+    ///
+    /// Unlike [`line_len()`](Self::line_len()), an empty `Rope` counts as
+    /// one empty line and a trailing line break starts a new, empty line.
+    /// Note that `"\r\n"` counts as a single line break, and that a lone
+    /// `'\r'` is not a line break.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// for (text, line_count) in
+    ///     [("", 1), ("a", 1), ("a\n", 2), ("a\nb", 2), ("\n", 2), ("a\r\n", 2)]
+    /// {
+    ///     let r = Rope::from(text);
+    ///     assert_eq!(r.line_count(), line_count);
+    /// }
+    /// ```
+    #[inline]
+    pub fn line_count(&self) -> usize {
+        self.newline_count() + 1
+    }
+
+    /// Returns the number of lines in the `Rope`.
+    ///
+    /// The final line break is optional and doesn't count as a separate empty
+    /// line, and an empty `Rope` has no lines. This is the number of items
+    /// yielded by [`lines()`](Self::lines()). See
+    /// [`line_count()`](Self::line_count()) and
+    /// [`newline_count()`](Self::newline_count()) for the other ways to count
+    /// lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::new();
+    ///
+    /// assert_eq!(r.line_len(), 0);
+    ///
+    /// r.insert(0, "a");
+    /// assert_eq!(r.line_len(), 1);
+    ///
+    /// r.insert(1, "\n");
+    /// assert_eq!(r.line_len(), 1);
+    ///
+    /// r.insert(2, "b");
+    /// assert_eq!(r.line_len(), 2);
+    ///
+    /// r.insert(3, "\r\n");
+    /// assert_eq!(r.line_len(), 2);
+    /// ```
+    #[inline]
+    pub fn line_len(&self) -> usize {
+        self.tree.summary().line_breaks() + 1
+            - (self.has_trailing_newline as usize)
+            - (self.is_empty() as usize)
+    }
+
+    /// Returns the line offset of the given byte.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    ///
+    /// assert_eq!(r.line_of_byte(0), 0);
+    /// assert_eq!(r.line_of_byte(3), 0);
+    /// assert_eq!(r.line_of_byte(4), 1);
+    /// assert_eq!(r.line_of_byte(8), 1); // between the '\r' and the '\n'
+    /// assert_eq!(r.line_of_byte(r.byte_len()), 2);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn line_of_byte(&self, byte_offset: usize) -> usize {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        let RawLineMetric(line_offset) =
+            self.tree.convert_measure(ByteMetric(byte_offset));
+
+        line_offset
+    }
+
+    /// Returns an immutable slice of the `Rope` in the specified line range,
+    /// where the start and end of the range are interpreted as offsets.
+    ///
+    /// Every line in the range is included together with its line break, so
+    /// `line_slice(a..=b)` goes from the start of line `a` to the start of
+    /// line `b + 1`. If the range includes the last line and the `Rope`
+    /// doesn't end with a line break, the slice extends to the end of the
+    /// `Rope`.
+    ///
+    /// See [`line_slice_content()`](Self::line_slice_content()) to leave out
+    /// the line break of the last line in the range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start is greater than the end or if the end is out of
+    /// bounds (i.e. greater than [`line_len()`](Self::line_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\nfoobar\n");
+    ///
+    /// assert_eq!(r.line_slice(..1), "foo\n");
+    /// assert_eq!(r.line_slice(1..3), "bar\r\nbaz\n");
+    /// assert_eq!(r.line_slice(3..), "foobar\n");
+    /// assert_eq!(r.line_slice(1..=2), "bar\r\nbaz\n");
+    ///
+    /// let r = Rope::from("foo\nbar");
+    ///
+    /// assert_eq!(r.line_slice(1..=1), "bar");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn line_slice<R>(&self, line_range: R) -> RopeSlice<'_>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(line_range, 0, self.line_len());
+
+        if start > end {
+            panic::line_start_after_end(start, end);
+        }
+
+        if end > self.line_len() {
+            panic::line_offset_out_of_bounds(end, self.line_len());
+        }
+
+        let (tree_slice, ByteMetric(byte_offset)) = self
+            .tree
+            .slice_with_offset(RawLineMetric(start)..RawLineMetric(end));
+
+        RopeSlice::new(tree_slice, byte_offset, self.generation)
+    }
+
+    /// Returns an immutable slice of the `Rope` in the specified line range,
+    /// without the line break ending the last line of the range.
+    ///
+    /// Only the line break of the last line is left out: the lines before it
+    /// keep theirs, so `line_slice_content(a..b)` goes from the start of line
+    /// `a` to the end of the content of line `b - 1`. This is the text a
+    /// user sees when selecting the lines, and it's equal to
+    /// [`line(a)`](Self::line()) for a single line. An empty range returns
+    /// an empty slice at the start of line `a`.
+    ///
+    /// See [`line_slice_raw()`](Self::line_slice_raw()) to also include the
+    /// last line break.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start is greater than the end or if the end is out of
+    /// bounds (i.e. greater than [`line_len()`](Self::line_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\nfoobar\n");
+    ///
+    /// assert_eq!(r.line_slice_content(..1), "foo");
+    /// assert_eq!(r.line_slice_content(1..3), "bar\r\nbaz");
+    /// assert_eq!(r.line_slice_content(3..), "foobar");
+    /// assert_eq!(r.line_slice_content(1..1), "");
+    ///
+    /// let r = Rope::from("foo\nbar");
+    ///
+    /// assert_eq!(r.line_slice_content(1..), "bar");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn line_slice_content<R>(&self, line_range: R) -> RopeSlice<'_>
+    where
+        R: RangeBounds<usize>,
+    {
+        let slice = self.line_slice(line_range);
+
+        if !slice.has_trailing_newline {
+            return slice;
+        }
+
+        let mut end = slice.byte_len() - 1;
+
+        if end > 0 && slice.byte(end - 1) == b'\r' {
+            end -= 1;
+        }
+
+        slice.byte_slice(..end)
+    }
+
+    /// Returns an immutable slice of the `Rope` in the specified line range,
+    /// including the line break ending the last line of the range.
+    ///
+    /// This is the same as [`line_slice()`](Self::line_slice()), under a
+    /// name that makes it explicit that every line of the range comes with
+    /// its line break. Only the last line of the `Rope` can be missing one,
+    /// if the `Rope` doesn't end with a line break.
+    ///
+    /// See [`line_slice_content()`](Self::line_slice_content()) to leave out
+    /// the last line break.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start is greater than the end or if the end is out of
+    /// bounds (i.e. greater than [`line_len()`](Self::line_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\nfoobar\n");
+    ///
+    /// assert_eq!(r.line_slice_raw(..1), "foo\n");
+    /// assert_eq!(r.line_slice_raw(1..3), "bar\r\nbaz\n");
+    /// assert_eq!(r.line_slice_raw(3..), "foobar\n");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn line_slice_raw<R>(&self, line_range: R) -> RopeSlice<'_>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.line_slice(line_range)
+    }
+
+    /// Returns the line at `line_index` like [`line()`](Self::line()),
+    /// together with the line terminator that follows it.
+    ///
+    /// The terminator is `None` for the last line if the `Rope` doesn't end
+    /// with a line break. There's no variant for a lone `'\r'` since it's not
+    /// a line break, and a `"\r\n"` whose bytes are stored in different
+    /// chunks is still detected as a single [`LineEnding::CrLf`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line index is out of bounds (i.e. greater than or equal
+    /// to [`line_len()`](Self::line_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{LineEnding, Rope};
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    ///
+    /// let (line, ending) = r.line_with_ending(0);
+    /// assert_eq!(line, "foo");
+    /// assert_eq!(ending, Some(LineEnding::Lf));
+    ///
+    /// assert_eq!(r.line_with_ending(1).1, Some(LineEnding::CrLf));
+    /// assert_eq!(r.line_with_ending(2).1, None);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn line_with_ending(
+        &self,
+        line_index: usize,
+    ) -> (RopeSlice<'_>, Option<LineEnding>) {
+        let line = self.line(line_index);
+
+        let line_end = line.byte_offset + line.byte_len();
+
+        let ending = match self.byte_of_line(line_index + 1) - line_end {
+            0 => None,
+            1 => Some(LineEnding::Lf),
+            _ => Some(LineEnding::CrLf),
+        };
+
+        (line, ending)
+    }
+
+    /// Returns an iterator over the lines of this `Rope`, not including the
+    /// line terminators.
+    ///
+    /// The final line break is optional and doesn't cause the iterator to
+    /// return a final empty line.
+    ///
+    /// The iterator is lazy: creating it doesn't do any work proportional to
+    /// the size of the text, and each line is located when it's yielded, so
+    /// e.g. `lines().take(n)` only visits the chunks of the first `n` lines.
+    ///
+    /// If you want to include the line breaks consider using the
+    /// [`raw_lines()`](Self::raw_lines()) method instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\n");
+    ///
+    /// let mut lines = r.lines();
+    ///
+    /// assert_eq!("foo", lines.next().unwrap());
+    /// assert_eq!("bar", lines.next().unwrap());
+    /// assert_eq!("baz", lines.next().unwrap());
+    /// assert_eq!(None, lines.next());
+    /// ```
+    #[inline]
+    pub fn lines(&self) -> Lines<'_> {
+        Lines::from(self)
+    }
+
+    /// Returns an iterator over the lines of this `Rope` like
+    /// [`lines()`](Self::lines()), starting from the line containing the
+    /// given byte offset.
+    ///
+    /// The first line is always yielded from its start, even if the byte
+    /// offset is in the middle of it. If the byte offset is equal to
+    /// [`byte_len()`](Self::byte_len()) and the `Rope` ends with a line
+    /// break the iterator is empty, consistently with `lines()` not yielding
+    /// a final empty line.
+    ///
+    /// Like with `lines()` the lines are located lazily, so this only has to
+    /// find the line containing the byte offset, which takes logarithmic
+    /// time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\n");
+    ///
+    /// let mut lines = r.lines_at_byte(5);
+    ///
+    /// assert_eq!("bar", lines.next().unwrap());
+    /// assert_eq!("baz", lines.next().unwrap());
+    /// assert_eq!(None, lines.next());
+    ///
+    /// assert_eq!(None, r.lines_at_byte(r.byte_len()).next());
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn lines_at_byte(&self, byte_offset: usize) -> Lines<'_> {
+        let line_offset = self.line_of_byte(byte_offset);
+        self.line_slice(line_offset..).lines()
+    }
+
+    /// Returns an iterator over the lines of this `Rope` like
+    /// [`lines()`](Self::lines()), but yielding them as `Cow<str>`s.
+    ///
+    /// Lines contained in a single chunk are borrowed without allocating,
+    /// while lines spanning multiple chunks are copied into an owned
+    /// `String`. This makes it cheap to process the lines of a text made of
+    /// mostly short lines as `&str`s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\n");
+    ///
+    /// let lines = r.lines_cow().collect::<Vec<_>>();
+    ///
+    /// assert_eq!(lines, ["foo", "bar", "baz"]);
+    /// ```
+    #[inline]
+    pub fn lines_cow(&self) -> LinesCow<'_> {
+        LinesCow::from(self.lines())
+    }
+
+    /// Returns an iterator over the lines of this `Rope` like
+    /// [`lines()`](Self::lines()), splitting the lines longer than `width`
+    /// bytes into several visual lines, which is what's needed to render the
+    /// text in a viewport that soft-wraps.
+    ///
+    /// Lines that fit within `width` are yielded whole. Longer lines are
+    /// broken every `width` bytes, moving each break back to the previous
+    /// char boundary if it doesn't lie on one, so every visual line is at
+    /// most `width` bytes long unless a single char is wider than `width`,
+    /// in which case it gets a visual line of its own.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nLorem ipsum\n\n🐸🐸");
+    ///
+    /// let lines = r.wrapped_lines(5).collect::<Vec<_>>();
+    ///
+    /// assert_eq!(lines, ["foo", "Lorem", " ipsu", "m", "", "🐸", "🐸"]);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn wrapped_lines(&self, width: usize) -> WrappedLines<'_> {
+        WrappedLines::new(self.lines(), width)
+    }
+
+    /// Returns the number of lines in this `Rope` when splitting on the given
+    /// kind of line breaks.
+    ///
+    /// With [`LineBreakKind::Lf`](crate::LineBreakKind::Lf) this is the same
+    /// as [`line_len()`](Self::line_len()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{LineBreakKind, Rope};
+    /// #
+    /// let r = Rope::from("foo\u{2028}bar\r\nbaz\u{0085}");
+    ///
+    /// assert_eq!(r.line_len_with(LineBreakKind::Lf), 2);
+    /// assert_eq!(r.line_len_with(LineBreakKind::UnicodeFull), 3);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "unicode-lines")))]
+    #[cfg(feature = "unicode-lines")]
+    #[inline]
+    pub fn line_len_with(&self, kind: LineBreakKind) -> usize {
+        match kind {
+            LineBreakKind::Lf => self.line_len(),
+
+            LineBreakKind::UnicodeFull => {
+                let has_trailing_line_break = self
+                    .chars()
+                    .next_back()
+                    .map_or(false, is_unicode_line_break);
+
+                self.tree.summary().unicode_line_breaks() + 1
+                    - (has_trailing_line_break as usize)
+                    - (self.is_empty() as usize)
+            },
+        }
+    }
+
+    /// Returns an iterator over the lines of this `Rope` split on the given
+    /// kind of line breaks, not including the line terminators.
+    ///
+    /// With [`LineBreakKind::Lf`](crate::LineBreakKind::Lf) this yields the
+    /// same lines as [`lines()`](Self::lines()). With
+    /// [`LineBreakKind::UnicodeFull`](crate::LineBreakKind::UnicodeFull) the
+    /// lines are also split on next lines (NEL), line separators (LS) and
+    /// paragraph separators (PS). In both cases the final line break is
+    /// optional and doesn't cause the iterator to return a final empty line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{LineBreakKind, Rope};
+    /// #
+    /// let r = Rope::from("foo\u{2028}bar\r\nbaz\u{0085}");
+    ///
+    /// let mut lines = r.lines_with(LineBreakKind::UnicodeFull);
+    ///
+    /// assert_eq!("foo", lines.next().unwrap());
+    /// assert_eq!("bar", lines.next().unwrap());
+    /// assert_eq!("baz", lines.next().unwrap());
+    /// assert_eq!(None, lines.next());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "unicode-lines")))]
+    #[cfg(feature = "unicode-lines")]
+    #[inline]
+    pub fn lines_with(&self, kind: LineBreakKind) -> LinesWith<'_> {
+        LinesWith::new(self.byte_slice(..), kind)
+    }
+
+    /// Consumes the `Rope`, returning a new `Rope` whose contents are the
+    /// concatenation of the strings returned by calling `f` on every chunk.
+    ///
+    /// The transformed text is fed to a [`RopeBuilder`](crate::RopeBuilder),
+    /// so `f` is free to return strings of any length and the new `Rope` is
+    /// split into chunks of its own.
+    ///
+    /// Note that `f` sees the chunk boundaries used internally by crop, which
+    /// don't respect grapheme clusters, words or lines. In particular a
+    /// grapheme cluster or a `"\r\n"` line break can be split between two
+    /// consecutive calls to `f`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("My PIN is 1234, my phone number is 555-0199.");
+    ///
+    /// let redacted = r.map_chunks(|chunk| {
+    ///     chunk
+    ///         .chars()
+    ///         .map(|ch| if ch.is_ascii_digit() { '*' } else { ch })
+    ///         .collect()
+    /// });
+    ///
+    /// assert_eq!(redacted, "My PIN is ****, my phone number is ***-****.");
+    /// ```
+    #[inline]
+    pub fn map_chunks<F>(self, mut f: F) -> Rope
+    where
+        F: FnMut(&str) -> String,
+    {
+        let mut builder = RopeBuilder::new();
+
+        for chunk in self.chunks() {
+            builder.append(f(chunk));
+        }
+
+        builder.build()
+    }
+
+    /// Returns an iterator over the non-overlapping matches of `needle` in
+    /// this `Rope`, yielded as `RopeSlice`s from left to right.
+    ///
+    /// Matches spanning multiple chunks are found as well. If `needle` is
+    /// empty the iterator doesn't yield anything.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("abXXabcXXXabcdXXXX");
+    ///
+    /// let mut matches = r.matches("XX");
+    ///
+    /// assert_eq!(matches.next().unwrap(), r.byte_slice(2..4));
+    /// assert_eq!(matches.next().unwrap(), r.byte_slice(7..9));
+    /// assert_eq!(matches.next().unwrap(), r.byte_slice(14..16));
+    /// assert_eq!(matches.next().unwrap(), r.byte_slice(16..18));
+    /// assert_eq!(None, matches.next());
+    /// ```
+    #[inline]
+    pub fn matches<'a>(&'a self, needle: &'a str) -> Matches<'a> {
+        Matches::new(self.byte_slice(..), needle)
+    }
+
+    /// Returns the maximum byte length of the `Rope` set with
+    /// [`set_max_len()`](Self::set_max_len()), if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::new();
+    /// assert_eq!(r.max_len(), None);
+    ///
+    /// r.set_max_len(Some(280));
+    /// assert_eq!(r.max_len(), Some(280));
+    /// ```
+    #[inline]
+    pub fn max_len(&self) -> Option<usize> {
+        self.max_len
+    }
+
+    /// Returns the length of the `Rope` in the `M` metric.
+    ///
+    /// This is the generic version of methods like
+    /// [`byte_len()`](Self::byte_len()) and [`line_len()`](Self::line_len()),
+    /// which are still the most convenient way to get a specific length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// use crop::metrics::{Byte, Line, Metric};
+    ///
+    /// fn half<M: Metric>(r: &Rope) -> usize {
+    ///     r.measure::<M>() / 2
+    /// }
+    ///
+    /// let r = Rope::from("ƒoo\nbär\r\nbaz\n");
+    ///
+    /// assert_eq!(r.measure::<Byte>(), r.byte_len());
+    /// assert_eq!(r.measure::<Line>(), r.line_len());
+    /// assert_eq!(half::<Line>(&r), 1);
+    /// ```
+    #[inline]
+    pub fn measure<M: Metric>(&self) -> usize {
+        text_metric::measure::<M>(self.byte_slice(..))
+    }
+
+    /// Returns the number of line breaks in the `Rope`.
+    ///
+    /// Note that `"\r\n"` counts as a single line break, and that a lone
+    /// `'\r'` is not a line break.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// for (text, newline_count) in
+    ///     [("", 0), ("a", 0), ("a\n", 1), ("a\nb", 1), ("\n", 1), ("a\r\n", 1)]
+    /// {
+    ///     let r = Rope::from(text);
+    ///     assert_eq!(r.newline_count(), newline_count);
+    /// }
+    /// ```
+    #[inline]
+    pub fn newline_count(&self) -> usize {
+        self.tree.summary().line_breaks()
+    }
+
+    /// Returns the byte offset of the end of the paragraph containing the
+    /// given byte offset.
+    ///
+    /// Paragraphs are delimited by blank lines, i.e. lines with no content
+    /// other than their line break. The end of a paragraph is the end of its
+    /// last line (including its line break), which is also the start of the
+    /// blank line following it, or [`byte_len()`](Self::byte_len()) if it's
+    /// the last paragraph. A blank line is considered to be a paragraph on
+    /// its own.
+    ///
+    /// See [`paragraph_start()`](Self::paragraph_start()) for the other end.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\r\nbar\r\n\r\nbaz\n");
+    ///
+    /// assert_eq!(r.paragraph_end(0), 10);
+    /// assert_eq!(r.paragraph_end(7), 10);
+    /// assert_eq!(r.paragraph_end(10), 12); // the blank line
+    /// assert_eq!(r.paragraph_end(12), 16);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn paragraph_end(&self, byte_offset: usize) -> usize {
+        let line_idx = self.line_of_byte(byte_offset);
+
+        if line_idx >= self.line_len() {
+            return self.byte_len();
+        }
+
+        let lines = self.line_slice(line_idx..);
+
+        let mut raw_lines = lines.raw_lines();
+
+        let first = raw_lines.next().unwrap();
+
+        let mut end = lines.parent_range().start + first.byte_len();
+
+        if !is_blank_line(first) {
+            end += raw_lines
+                .take_while(|&line| !is_blank_line(line))
+                .map(|line| line.byte_len())
+                .sum::<usize>();
+        }
+
+        end
+    }
+
+    /// Returns the byte offset of the start of the paragraph containing the
+    /// given byte offset.
+    ///
+    /// Paragraphs are delimited by blank lines, i.e. lines with no content
+    /// other than their line break. The start of a paragraph is the start of
+    /// its first line, which is also the end of the blank line preceding it,
+    /// or zero if it's the first paragraph. A blank line is considered to be
+    /// a paragraph on its own.
+    ///
+    /// See [`paragraph_end()`](Self::paragraph_end()) for the other end.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\r\nbar\r\n\r\nbaz\n");
+    ///
+    /// assert_eq!(r.paragraph_start(7), 0);
+    /// assert_eq!(r.paragraph_start(10), 10); // the blank line
+    /// assert_eq!(r.paragraph_start(14), 12);
+    /// assert_eq!(r.paragraph_start(r.byte_len()), r.byte_len());
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn paragraph_start(&self, byte_offset: usize) -> usize {
+        let line_idx = self.line_of_byte(byte_offset);
+
+        if line_idx >= self.line_len() {
+            return self.byte_len();
+        }
+
+        let lines = self.line_slice(..line_idx + 1);
+
+        let mut raw_lines = lines.raw_lines().rev();
+
+        let last = raw_lines.next().unwrap();
+
+        let mut start = lines.byte_len() - last.byte_len();
+
+        if !is_blank_line(last) {
+            start -= raw_lines
+                .take_while(|&line| !is_blank_line(line))
+                .map(|line| line.byte_len())
+                .sum::<usize>();
+        }
+
+        start
+    }
+
+    /// Returns an iterator over the lines of this `Rope`, including the
+    /// line terminators.
+    ///
+    /// The final line break is optional and doesn't cause the iterator to
+    /// return a final empty line.
+    ///
+    /// If you don't want to include the line breaks consider using the
+    /// [`lines()`](Self::lines()) method instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("foo\nbar\r\nbaz");
+    ///
+    /// let mut raw_lines = r.raw_lines();
+    ///
+    /// assert_eq!("foo\n", raw_lines.next().unwrap());
+    /// assert_eq!("bar\r\n", raw_lines.next().unwrap());
+    /// assert_eq!("baz", raw_lines.next().unwrap());
+    /// assert_eq!(None, raw_lines.next());
+    ///
+    /// r.insert(r.byte_len(), "\n");
+    ///
+    /// let mut raw_lines = r.raw_lines();
+    ///
+    /// assert_eq!("foo\n", raw_lines.next().unwrap());
+    /// assert_eq!("bar\r\n", raw_lines.next().unwrap());
+    /// assert_eq!("baz\n", raw_lines.next().unwrap());
+    /// assert_eq!(None, raw_lines.next());
+    /// ```
+    #[inline]
+    pub fn raw_lines(&self) -> RawLines<'_> {
+        RawLines::from(self)
+    }
+
+    /// Returns an iterator over the byte ranges of the lines of this `Rope`,
+    /// including the line terminators.
+    ///
+    /// The ranges are the ones spanned by the lines yielded by
+    /// [`raw_lines()`](Self::raw_lines()), so they're contiguous and cover
+    /// the whole `Rope`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\n");
+    ///
+    /// let ranges = r.raw_line_ranges().collect::<Vec<_>>();
+    ///
+    /// assert_eq!(ranges, [0..4, 4..9, 9..13]);
+    /// ```
+    #[inline]
+    pub fn raw_line_ranges(&self) -> LineRanges<'_> {
+        LineRanges::new(self, true)
+    }
+
+    /// Returns a [`Reader`] over the bytes of the `Rope`, implementing
+    /// [`Read`](std::io::Read) and [`BufRead`](std::io::BufRead).
+    ///
+    /// The reader borrows the chunks of the `Rope` instead of copying them,
+    /// so [`fill_buf()`](std::io::BufRead::fill_buf()) returns the rest of
+    /// the current chunk.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io::{BufRead, Read};
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    ///
+    /// let mut reader = r.reader();
+    /// let mut line = String::new();
+    ///
+    /// reader.read_line(&mut line).unwrap();
+    /// assert_eq!(line, "foo\n");
+    ///
+    /// let mut rest = String::new();
+    /// reader.read_to_string(&mut rest).unwrap();
+    /// assert_eq!(rest, "bar\r\nbaz");
+    /// ```
+    #[inline]
+    pub fn reader(&self) -> Reader<'_> {
+        Reader::new(self.chunks())
+    }
+
+    /// Returns the byte ranges protected from the fallible edits, as set by
+    /// [`set_readonly_ranges()`](Self::set_readonly_ranges()) and shifted by
+    /// the edits made since then.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from(">>> print(1)");
+    ///
+    /// r.set_readonly_ranges(vec![0..4]);
+    ///
+    /// r.insert(0, "\n");
+    /// assert_eq!(r.readonly_ranges(), &[1..5]);
+    /// ```
+    #[inline]
+    pub fn readonly_ranges(&self) -> &[Range<usize>] {
+        &self.readonly_ranges
+    }
+
+    /// Returns the byte offset of the last char of the `Rope` satisfying the
+    /// given predicate, or `None` if no char does.
+    ///
+    /// This is the `Rope` analog of calling [`str::rfind()`] with a closure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("ƒoo bär\tbaz");
+    ///
+    /// assert_eq!(r.rfind_char(char::is_whitespace), Some(9));
+    /// assert_eq!(r.rfind_char(|ch| !ch.is_ascii()), Some(6));
+    /// assert_eq!(r.rfind_char(|ch| ch == 'ƒ'), Some(0));
+    /// assert_eq!(r.rfind_char(char::is_numeric), None);
+    /// ```
+    #[inline]
+    pub fn rfind_char<F>(&self, predicate: F) -> Option<usize>
+    where
+        F: FnMut(char) -> bool,
+    {
+        rfind_char(self.chunks(), self.byte_len(), predicate)
+    }
+
+    /// Sets how eagerly the `Rope` merges its chunks back together after text
+    /// is deleted.
+    ///
+    /// With [`Compaction::Lazy`], the default, chunks are only merged with
+    /// their neighbours when they become underfilled, which keeps the cost of
+    /// each edit to a minimum. After many scattered deletions this can leave
+    /// the `Rope` with lots of sparsely filled chunks, which makes iterating
+    /// over it slower.
+    ///
+    /// With [`Compaction::Eager`], every edit that removes some text also
+    /// merges the chunk where the removal happened with its neighbours if
+    /// their combined text fits in fewer chunks. This copies up to a few
+    /// chunks' worth of text on each deletion, in exchange for a tighter
+    /// `Rope` that's faster to read. This is a good trade-off for `Rope`s
+    /// that are read much more often than they're edited.
+    ///
+    /// Changing the mode doesn't affect the existing chunks, only how future
+    /// deletions are handled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Compaction, Rope};
+    /// #
+    /// let mut r = Rope::from("Hello, world!");
+    ///
+    /// r.set_compaction(Compaction::Eager);
+    ///
+    /// r.delete(5..12);
+    /// assert_eq!(r, "Hello!");
+    /// ```
+    #[inline]
+    pub fn set_compaction(&mut self, compaction: Compaction) {
+        self.compaction = compaction;
+    }
+
+    /// Sets the maximum byte length of the `Rope`, or removes it if `None`.
+    ///
+    /// Once a limit is set, [`try_insert()`](Self::try_insert()) and
+    /// [`try_replace()`](Self::try_replace()) return an error instead of
+    /// applying an edit that would make the `Rope` longer than the limit,
+    /// while all the other edits panic. Edits that don't make the `Rope`
+    /// longer are always allowed, even if it's already over the limit
+    /// because it was set after the text was inserted.
+    ///
+    /// The limit is kept by clones of the `Rope`, but not by `Rope`s created
+    /// from one of its slices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{EditError, Rope};
+    /// #
+    /// let mut r = Rope::from("Hello");
+    ///
+    /// r.set_max_len(Some(8));
+    ///
+    /// assert_eq!(r.try_insert(5, "!!!"), Ok(()));
+    /// assert_eq!(
+    ///     r.try_insert(8, "!"),
+    ///     Err(EditError::MaxLenExceeded { limit: 8, would_be: 9 })
+    /// );
+    /// assert_eq!(r, "Hello!!!");
+    /// ```
+    #[inline]
+    pub fn set_max_len(&mut self, max_len: Option<usize>) {
+        self.max_len = max_len;
+    }
+
+    /// Sets the byte ranges of the `Rope` that are protected from edits,
+    /// replacing the ones set previously.
+    ///
+    /// [`try_insert()`](Self::try_insert()),
+    /// [`try_delete()`](Self::try_delete()) and
+    /// [`try_replace()`](Self::try_replace()) return an error instead of
+    /// applying an edit that would remove text from a protected range or
+    /// insert text strictly inside one. Inserting right before or right after
+    /// a protected range is allowed, and the new text is left outside of it.
+    /// All the other edits are applied without checking the protected ranges.
+    ///
+    /// The ranges are shifted by every edit to keep covering the same text,
+    /// so they can be read back with
+    /// [`readonly_ranges()`](Self::readonly_ranges()). Like the
+    /// [maximum length](Self::set_max_len()) they're kept by clones of the
+    /// `Rope`, but not by `Rope`s created from one of its slices.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of a range is greater than its end, or if its end
+    /// is out of bounds (i.e. greater than [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{EditError, Rope};
+    /// #
+    /// let mut r = Rope::from(">>> ");
+    ///
+    /// r.set_readonly_ranges(vec![0..4]);
+    ///
+    /// assert_eq!(r.try_insert(4, "print(1)"), Ok(()));
+    /// assert_eq!(r.try_delete(3..5), Err(EditError::ReadOnly { at: 3 }));
+    /// assert_eq!(r, ">>> print(1)");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn set_readonly_ranges(&mut self, ranges: Vec<Range<usize>>) {
+        for range in &ranges {
+            if range.start > range.end {
+                panic::byte_start_after_end(range.start, range.end);
+            }
+
+            if range.end > self.byte_len() {
+                panic::byte_offset_out_of_bounds(range.end, self.byte_len());
+            }
+        }
+
+        self.readonly_ranges = ranges;
+    }
+
+    /// Returns the SHA-256 digest of the contents of this `Rope`, formatted
+    /// as a lowercase hexadecimal string.
+    ///
+    /// Like [`content_hash()`](Self::content_hash()) the digest is computed by
+    /// streaming the bytes of each chunk and doesn't depend on how the text
+    /// is split into chunks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("abc");
+    ///
+    /// assert_eq!(
+    ///     r.sha256_hex(),
+    ///     "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    /// );
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "sha256")))]
+    #[cfg(feature = "sha256")]
+    #[inline]
+    pub fn sha256_hex(&self) -> String {
+        sha256_hex_chunks(self.chunks())
+    }
+
+    /// Returns an immutable slice of the `Rope` in the specified byte range.
+    ///
+    /// This is a shorter name for [`byte_slice()`](Self::byte_slice()), which
+    /// it simply forwards to, so see its documentation for more.
+    ///
+    /// `Rope` doesn't implement [`Index`](core::ops::Index) for ranges
+    /// because `Index::index()` has to return a reference to something stored
+    /// in the `Rope`, while a [`RopeSlice`] is a new value pointing into the
+    /// tree that's created on the fly, so there's nothing to borrow it from.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start or the end of the byte range don't lie on a code
+    /// point boundary, if the start is greater than the end or if the end is
+    /// out of bounds (i.e. greater than [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello Earth 🌎!");
+    ///
+    /// assert_eq!(r.slice(6..11), "Earth");
+    /// assert_eq!(r.slice(6..11), r.byte_slice(6..11));
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn slice<R>(&self, byte_range: R) -> RopeSlice<'_>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.byte_slice(byte_range)
+    }
+
+    /// Divides the `Rope` into two slices at the given char offset.
+    ///
+    /// The first slice contains the chars in `[0, char_offset)` and the second
+    /// one the chars in `[char_offset, len)`. `Rope`s don't keep track of
+    /// their number of chars, so unless the text is ASCII this counts the
+    /// chars of every chunk before the one containing the offset, like
+    /// [`char_len()`](Self::char_len()) does for the whole text.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the char offset is out of bounds (i.e. greater than
+    /// [`char_len()`](Self::char_len())). See
+    /// [`split_at_char_checked()`](Self::split_at_char_checked()) for a
+    /// non-panicking version.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("ƒoo 🐸 bär");
+    ///
+    /// let (left, right) = r.split_at_char(4);
+    /// assert_eq!(left, "ƒoo ");
+    /// assert_eq!(right, "🐸 bär");
+    ///
+    /// let (left, right) = r.split_at_char(9);
+    /// assert_eq!(left, "ƒoo 🐸 bär");
+    /// assert_eq!(right, "");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn split_at_char(
+        &self,
+        char_offset: usize,
+    ) -> (RopeSlice<'_>, RopeSlice<'_>) {
+        match self.split_at_char_checked(char_offset) {
+            Some(split) => split,
+            None => {
+                panic::char_index_out_of_bounds(char_offset, self.char_len())
+            },
+        }
+    }
+
+    /// Divides the `Rope` into two slices at the given char offset like
+    /// [`split_at_char()`](Self::split_at_char()), returning `None` if the
+    /// offset is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("ƒoo 🐸 bär");
+    ///
+    /// assert!(r.split_at_char_checked(9).is_some());
+    /// assert!(r.split_at_char_checked(10).is_none());
+    /// ```
+    #[inline]
+    pub fn split_at_char_checked(
+        &self,
+        char_offset: usize,
+    ) -> Option<(RopeSlice<'_>, RopeSlice<'_>)> {
+        let byte_offset = if self.is_ascii() {
+            (char_offset <= self.byte_len()).then_some(char_offset)?
+        } else {
+            byte_of_char(self.chunks(), char_offset)?
+        };
+
+        Some((self.byte_slice(..byte_offset), self.byte_slice(byte_offset..)))
+    }
+
+    /// Splits the `Rope` at the start of the given line, truncating it to its
+    /// first `line_index` lines and returning the rest as a new `Rope`.
+    ///
+    /// The two `Rope`s share the chunks that aren't cut by the split, so this
+    /// is an O(log n) operation. If `line_index` is zero the whole text is
+    /// moved to the returned `Rope`, leaving this one empty, while if it's
+    /// greater than or equal to [`line_len()`](Self::line_len()) the returned
+    /// `Rope` is empty and this one is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("foo\nbar\r\nbaz\n");
+    ///
+    /// let rest = r.split_off_line(1);
+    /// assert_eq!(r, "foo\n");
+    /// assert_eq!(rest, "bar\r\nbaz\n");
+    ///
+    /// let rest = r.split_off_line(1);
+    /// assert_eq!(r, "foo\n");
+    /// assert_eq!(rest, "");
+    /// ```
+    #[inline]
+    pub fn split_off_line(&mut self, line_index: usize) -> Rope {
+        if line_index >= self.line_len() {
+            return Rope::new();
+        }
+
+        let byte_offset = self.byte_of_line(line_index);
+
+        let rest = Rope::from(self.byte_slice(byte_offset..));
+
+        self.delete(byte_offset..);
+
+        rest
+    }
+
+    /// Returns an iterator over the sub-slices of this `Rope` separated by
+    /// any amount of whitespace, where whitespace is defined as in
+    /// [`char::is_whitespace()`].
+    ///
+    /// Like [`str::split_whitespace()`], this never yields empty slices, even
+    /// if the `Rope` starts or ends with whitespace.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("  foo\tbar\r\n baz  ");
+    ///
+    /// let mut words = r.split_whitespace();
+    ///
+    /// assert_eq!("foo", words.next().unwrap());
+    /// assert_eq!("bar", words.next().unwrap());
+    /// assert_eq!("baz", words.next().unwrap());
+    /// assert_eq!(None, words.next());
+    /// ```
+    #[inline]
+    pub fn split_whitespace(&self) -> SplitWhitespace<'_> {
+        SplitWhitespace::new(self.byte_slice(..))
+    }
+
+    /// Removes the UTF-8 byte order mark (`U+FEFF`) at the start of the
+    /// `Rope`, if there's one, returning whether it was removed.
+    ///
+    /// Only the first byte order mark is removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("\u{FEFF}\u{FEFF}foo");
+    ///
+    /// assert!(r.strip_bom());
+    /// assert_eq!(r, "\u{FEFF}foo");
+    ///
+    /// assert!(r.strip_bom());
+    /// assert_eq!(r, "foo");
+    ///
+    /// assert!(!r.strip_bom());
+    /// assert_eq!(r, "foo");
+    /// ```
+    #[inline]
+    pub fn strip_bom(&mut self) -> bool {
+        let has_bom = self.has_bom();
+
+        if has_bom {
+            self.delete(..BOM.len());
+        }
+
+        has_bom
+    }
+
+    /// Exchanges the contents of two non-overlapping byte ranges.
+    ///
+    /// The ranges can have different lengths, in which case the text between
+    /// them is shifted accordingly. Empty ranges are allowed, so this can
+    /// also be used to move some text to another position.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the start of a range is greater than its end, if a
+    /// range is out of bounds, if the start or the end of a range doesn't lie
+    /// on a code point boundary or if the two ranges overlap. The `Rope` is
+    /// left unchanged if an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Rope, SwapRangesError};
+    /// #
+    /// let mut r = Rope::from("foo, barbaz");
+    ///
+    /// r.swap_ranges(0..3, 5..11).unwrap();
+    /// assert_eq!(r, "barbaz, foo");
+    ///
+    /// assert_eq!(r.swap_ranges(0..3, 2..5), Err(SwapRangesError::Overlapping));
+    /// ```
+    #[inline]
+    pub fn swap_ranges(
+        &mut self,
+        a: Range<usize>,
+        b: Range<usize>,
+    ) -> Result<(), SwapRangesError> {
+        for range in [&a, &b] {
+            if range.start > range.end {
+                return Err(SwapRangesError::StartAfterEnd {
+                    start: range.start,
+                    end: range.end,
+                });
+            }
+
+            if range.end > self.byte_len() {
+                return Err(SwapRangesError::OutOfBounds {
+                    byte_offset: range.end,
+                    byte_len: self.byte_len(),
+                });
+            }
+
+            for byte_offset in [range.start, range.end] {
+                if !self.is_char_boundary(byte_offset) {
+                    return Err(SwapRangesError::NotCharBoundary {
+                        byte_offset,
+                    });
+                }
+            }
+        }
+
+        if a.start < b.end && b.start < a.end {
+            return Err(SwapRangesError::Overlapping);
+        }
+
+        let (first, second) = if a.start <= b.start { (a, b) } else { (b, a) };
+
+        let first_text = self.byte_slice(first.clone()).to_string();
+        let second_text = self.byte_slice(second.clone()).to_string();
+
+        // The later range is replaced first so that the offsets of the
+        // earlier one are still valid.
+        self.replace(second, first_text);
+        self.replace(first, second_text);
+
+        Ok(())
+    }
+
+    /// Returns a new empty [`Rope`].
+    ///
+    /// An empty `Rope` can be sliced (e.g. with `byte_slice(..)` or
+    /// `byte_slice(0..0)`) and iterated over like any other, which results in
+    /// empty slices and iterators yielding nothing.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the contents of the `Rope` within the specified byte range
+    /// with the given string, where the start and end of the range are
+    /// interpreted as byte offsets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start or the end of the byte range don't lie on a code
+    /// point boundary, if the start is greater than the end, if the end is
+    /// out of bounds (i.e. greater than [`byte_len()`](Self::byte_len())) or
+    /// if the replacement would make the `Rope` longer than its
+    /// [maximum length](Self::set_max_len()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello Earth 🌎!");
     ///
-    /// r.insert(0, "hey");
-    /// assert!(!r.is_empty());
+    /// r.replace(6..16, "Saturn 🪐");
+    /// assert_eq!(r, "Hello Saturn 🪐!");
     /// ```
+    #[track_caller]
     #[inline]
-    pub fn is_empty(&self) -> bool {
-        self.byte_len() == 0
+    pub fn replace<R, T>(&mut self, byte_range: R, text: T)
+    where
+        R: RangeBounds<usize>,
+        T: AsRef<str>,
+    {
+        self.replace_inner(byte_range, text.as_ref(), None);
     }
 
-    /// Returns `true` if the given byte offset lies on a grapheme cluster
-    /// boundary.
+    /// The implementation of [`replace()`](Self::replace()).
+    ///
+    /// The part of the range that actually changes is copied onto `removed`
+    /// if given, before the tree is edited, and the returned range is the one
+    /// the new text of that part ends up occupying, so that replacing the
+    /// returned range with the removed text undoes the edit.
+    #[track_caller]
+    #[inline]
+    pub(super) fn replace_inner<R>(
+        &mut self,
+        byte_range: R,
+        text: &str,
+        removed: Option<&mut String>,
+    ) -> Range<usize>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.byte_len());
+
+        if start > end {
+            panic::byte_start_after_end(start, end);
+        }
+
+        if end > self.byte_len() {
+            panic::byte_offset_out_of_bounds(end, self.byte_len());
+        }
+
+        if text.len() > Self::MAX_LEN - (self.byte_len() - (end - start)) {
+            panic::rope_too_long(self.byte_len() - (end - start), text.len());
+        }
+
+        if let Err(EditError::MaxLenExceeded { limit, would_be }) =
+            self.check_max_len(start, end, text.len())
+        {
+            panic::max_len_exceeded(limit, would_be);
+        }
+
+        self.generation = next_generation();
+
+        // Only replace the part of the range that actually changes, which
+        // avoids touching the chunks around it when the new text shares a
+        // prefix or a suffix with the old one.
+        let (start, end, text) = if start < end && !text.is_empty() {
+            let old = self.byte_slice(start..end);
+
+            let prefix_len = common_prefix_len(old.chunks(), text);
+
+            let suffix_len = common_suffix_len(
+                old.byte_slice(prefix_len..).chunks(),
+                &text[prefix_len..],
+            );
+
+            if prefix_len + suffix_len == end - start
+                && prefix_len + suffix_len == text.len()
+            {
+                return start..start;
+            }
+
+            (
+                start + prefix_len,
+                end - suffix_len,
+                &text[prefix_len..text.len() - suffix_len],
+            )
+        } else {
+            (start, end, text)
+        };
+
+        self.shift_readonly_ranges(start..end, text.len());
+
+        if let Some(removed) = removed {
+            removed.extend(self.byte_slice(start..end).chunks());
+        }
+
+        let mut update_trailing = false;
+
+        if end == self.byte_len() {
+            if !text.is_empty() {
+                self.has_trailing_newline = text.ends_with('\n');
+            } else if start == 0 {
+                self.has_trailing_newline = false;
+            } else {
+                update_trailing = true;
+            }
+        }
+
+        let replacement =
+            Replacement { text, pool: Some(&mut self.chunk_pool) };
+
+        self.tree.replace(ByteMetric(start)..ByteMetric(end), replacement);
+
+        if update_trailing {
+            self.has_trailing_newline =
+                self.chunks().next_back().unwrap().ends_with('\n');
+        }
+
+        if self.compaction == Compaction::Eager && end > start {
+            self.compact_around(start);
+        }
+
+        start..start + text.len()
+    }
+
+    /// Replaces the text in the specified byte range with the contents of
+    /// the given reader.
+    ///
+    /// Unlike reading the whole contents into a `String` and then calling
+    /// [`replace()`](Self::replace()), the contents are validated and inserted
+    /// one chunk at a time, so the memory used doesn't depend on how much
+    /// text the reader yields.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading from the reader fails or if its contents
+    /// are not valid UTF-8, in which case the error kind is
+    /// [`InvalidData`](std::io::ErrorKind::InvalidData). If the range
+    /// overlaps one of the [read-only ranges](Self::set_readonly_ranges()) of
+    /// the `Rope`, or if the contents would make it longer than its
+    /// [maximum length](Self::set_max_len()), the error kind is
+    /// [`Other`](std::io::ErrorKind::Other) and it wraps an [`EditError`].
+    /// The `Rope` is left unchanged if an error is returned.
     ///
     /// # Panics
     ///
-    /// Panics if the byte offset is out of bounds (i.e. greater than
-    /// [`byte_len()`](Self::byte_len())).
+    /// Panics if the start or the end of the range don't lie on a code point
+    /// boundary, if the start is greater than the end, or if the end is out
+    /// of bounds (i.e. greater than [`byte_len()`](Self::byte_len())).
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("aargh!\r\n🐻‍❄️");
+    /// let mut r = Rope::from("Hello Earth 🌎!");
     ///
-    /// assert!(r.is_grapheme_boundary(0));
-    /// assert!(r.is_grapheme_boundary(r.byte_len()));
-    /// assert!(!r.is_grapheme_boundary(7)); // between '\r' and '\n'
-    /// assert!(r.is_grapheme_boundary(8)); // between '\n' and '🐻‍❄️'
-    /// assert!(!r.is_grapheme_boundary(12)); // between the 1st and 2nd code point of '🐻‍❄️'
+    /// r.replace_from_reader(6..16, "Saturn 🪐".as_bytes()).unwrap();
+    /// assert_eq!(r, "Hello Saturn 🪐!");
+    ///
+    /// assert!(r.replace_from_reader(.., &[0xF0, 0x9F][..]).is_err());
+    /// assert_eq!(r, "Hello Saturn 🪐!");
     /// ```
-    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
-    #[cfg(feature = "graphemes")]
     #[track_caller]
     #[inline]
-    pub fn is_grapheme_boundary(&self, byte_offset: usize) -> bool {
-        if byte_offset > self.byte_len() {
-            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+    pub fn replace_from_reader<R, Rd>(
+        &mut self,
+        byte_range: R,
+        mut reader: Rd,
+    ) -> std::io::Result<()>
+    where
+        R: RangeBounds<usize>,
+        Rd: std::io::Read,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.byte_len());
+
+        if start > end {
+            panic::byte_start_after_end(start, end);
         }
 
-        is_grapheme_boundary(self.chunks(), self.byte_len(), byte_offset)
+        if end > self.byte_len() {
+            panic::byte_offset_out_of_bounds(end, self.byte_len());
+        }
+
+        // The range is deleted with `delete()` before the reader is read from,
+        // so it has to be checked up front like `try_delete()` would.
+        if let Err(err) = self.check_readonly(start, end) {
+            return Err(std::io::Error::new(std::io::ErrorKind::Other, err));
+        }
+
+        // Keep the fields changed by the edits below to restore the original
+        // text if we hit an error after having already inserted some of the
+        // new one. The tree is reference counted, so this doesn't copy any of
+        // its chunks.
+        let original = (
+            self.tree.clone(),
+            self.has_trailing_newline,
+            self.readonly_ranges.clone(),
+        );
+
+        let restore = move |rope: &mut Self| {
+            (rope.tree, rope.has_trailing_newline, rope.readonly_ranges) =
+                original;
+        };
+
+        let invalid_utf8 = || {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            )
+        };
+
+        self.delete(start..end);
+
+        let mut byte_offset = start;
+
+        // The buffer is at least 4 bytes long, so there's always room to read
+        // more bytes after the (at most 3) bytes of an incomplete character
+        // left over from the previous read.
+        let mut buffer = [0u8; CHUNK_MAX_BYTES];
+
+        let mut len = 0;
+
+        loop {
+            match reader.read(&mut buffer[len..]) {
+                Ok(0) => break,
+
+                Ok(read) => len += read,
+
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {
+                    continue
+                },
+
+                Err(err) => {
+                    restore(self);
+                    return Err(err);
+                },
+            }
+
+            let valid_up_to = match core::str::from_utf8(&buffer[..len]) {
+                Ok(_) => len,
+
+                // The buffer ends in the middle of a character whose remaining
+                // bytes will come with the next read.
+                Err(err) if err.error_len().is_none() => err.valid_up_to(),
+
+                Err(_) => {
+                    restore(self);
+                    return Err(invalid_utf8());
+                },
+            };
+
+            // SAFETY: we've just checked that the first `valid_up_to` bytes
+            // are valid UTF-8.
+            let text = unsafe {
+                core::str::from_utf8_unchecked(&buffer[..valid_up_to])
+            };
+
+            if let Err(err) = self.try_insert(byte_offset, text) {
+                restore(self);
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    err,
+                ));
+            }
+
+            byte_offset += valid_up_to;
+
+            buffer.copy_within(valid_up_to..len, 0);
+
+            len -= valid_up_to;
+        }
+
+        // The reader ended in the middle of a character.
+        if len > 0 {
+            restore(self);
+            return Err(invalid_utf8());
+        }
+
+        Ok(())
     }
 
-    /// Returns the line at `line_index`, without its line terminator.
+    /// Replaces the text within the specified byte range with `text` only if
+    /// it's different from it, returning whether the `Rope` was edited.
     ///
-    /// If you want to include the line break consider taking a
-    /// [`line_slice()`](Self::line_slice()) in the
-    /// `line_index..line_index + 1` range.
+    /// The current text is compared against `text` chunk by chunk, so when
+    /// they're equal the tree isn't touched at all and the
+    /// [`generation()`](Self::generation()) of the `Rope` stays the same,
+    /// which makes this a good fit for dirty-tracking.
     ///
     /// # Panics
     ///
-    /// Panics if the line index is out of bounds (i.e. greater than or equal
-    /// to [`line_len()`](Self::byte_len())).
+    /// Panics if the start or the end of the byte range don't lie on a code
+    /// point boundary, if the start is greater than the end, if the end is
+    /// out of bounds (i.e. greater than [`byte_len()`](Self::byte_len())) or
+    /// if the replacement would make the `Rope` longer than its
+    /// [maximum length](Self::set_max_len()).
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("foo\nbar\r\nbaz");
+    /// let mut r = Rope::from("foo bar baz");
+    /// let generation = r.generation();
     ///
-    /// assert_eq!(r.line(0), "foo");
-    /// assert_eq!(r.line(1), "bar");
-    /// assert_eq!(r.line(2), "baz");
+    /// assert!(!r.replace_if_changed(4..7, "bar"));
+    /// assert_eq!(r.generation(), generation);
+    ///
+    /// assert!(r.replace_if_changed(4..7, "qux"));
+    /// assert_eq!(r, "foo qux baz");
+    /// assert_ne!(r.generation(), generation);
     /// ```
     #[track_caller]
     #[inline]
-    pub fn line(&self, line_index: usize) -> RopeSlice<'_> {
-        if line_index >= self.line_len() {
-            panic::line_index_out_of_bounds(line_index, self.line_len());
-        }
-
-        let tree_slice = self
-            .tree
-            .slice(RawLineMetric(line_index)..RawLineMetric(line_index + 1));
+    pub fn replace_if_changed<R, T>(&mut self, byte_range: R, text: T) -> bool
+    where
+        R: RangeBounds<usize>,
+        T: AsRef<str>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.byte_len());
 
-        let mut line = RopeSlice { tree_slice, has_trailing_newline: false };
+        let text = text.as_ref();
 
-        if line.tree_slice.summary().line_breaks() == 1 {
-            line.truncate_trailing_line_break();
+        if self.byte_slice(start..end) == text {
+            return false;
         }
 
-        line
+        self.replace(start..end, text);
+
+        true
     }
 
-    /// Returns the number of lines in the `Rope`.
+    /// Replaces the text in the specified byte range like
+    /// [`replace()`](Self::replace()), updating the given byte offsets so that
+    /// they keep pointing to the same text after the edit.
     ///
-    /// The final line break is optional and doesn't count as a separate empty
-    /// line.
+    /// Offsets before or at the start of the range are left unchanged, offsets
+    /// at or after the end of the range are shifted by the difference between
+    /// the length of the new text and the length of the range, and offsets
+    /// inside the range are moved to its start.
+    ///
+    /// Note that when inserting (i.e. when the range is empty) the offsets
+    /// equal to the insertion point are left unchanged, so they end up before
+    /// the new text.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end, or if the end
+    /// is out of bounds (i.e. greater than [`byte_len()`](Self::byte_len())).
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let mut r = Rope::new();
+    /// let mut r = Rope::from("Hello Earth 🌎!");
     ///
-    /// assert_eq!(r.line_len(), 0);
+    /// let mut offsets = [0, 8, 16, 17];
     ///
-    /// r.insert(0, "a");
-    /// assert_eq!(r.line_len(), 1);
+    /// r.replace_tracking(6..16, "Saturn 🪐", &mut offsets);
     ///
-    /// r.insert(1, "\n");
-    /// assert_eq!(r.line_len(), 1);
+    /// assert_eq!(r, "Hello Saturn 🪐!");
+    /// assert_eq!(offsets, [0, 6, 17, 18]);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn replace_tracking<R, T>(
+        &mut self,
+        byte_range: R,
+        text: T,
+        byte_offsets: &mut [usize],
+    ) where
+        R: RangeBounds<usize>,
+        T: AsRef<str>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.byte_len());
+
+        let text = text.as_ref();
+
+        self.replace(start..end, text);
+
+        for offset in byte_offsets {
+            *offset = track_offset(*offset, start..end, text.len());
+        }
+    }
+
+    /// Retains only the characters for which `f` returns `true`, removing all
+    /// the others.
     ///
-    /// r.insert(2, "b");
-    /// assert_eq!(r.line_len(), 2);
+    /// The `Rope` is rebuilt in a single pass over its chunks, which is much
+    /// faster than finding and deleting the removed characters one by one.
+    /// Its [`Compaction`] mode is kept, and its
+    /// [generation](Self::generation()) is incremented like for any other
+    /// edit.
+    ///
+    /// # Examples
     ///
-    /// r.insert(3, "\r\n");
-    /// assert_eq!(r.line_len(), 2);
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("foo\r\nbar\r\nbaz");
+    ///
+    /// r.retain_chars(|ch| ch != '\r');
+    /// assert_eq!(r, "foo\nbar\nbaz");
     /// ```
     #[inline]
-    pub fn line_len(&self) -> usize {
-        self.tree.summary().line_breaks() + 1
-            - (self.has_trailing_newline as usize)
-            - (self.is_empty() as usize)
+    pub fn retain_chars<F>(&mut self, mut f: F)
+    where
+        F: FnMut(char) -> bool,
+    {
+        let mut builder = RopeBuilder::new();
+
+        let mut readonly_ranges = core::mem::take(&mut self.readonly_ranges);
+
+        // The endpoints of the read-only ranges are shifted back by the number
+        // of bytes removed before them, which can be done in the same pass if
+        // they're visited in order.
+        let mut endpoints = readonly_ranges
+            .iter_mut()
+            .flat_map(|range| [&mut range.start, &mut range.end])
+            .collect::<Vec<_>>();
+
+        endpoints.sort_unstable_by_key(|offset| **offset);
+
+        let mut endpoints = endpoints.into_iter().peekable();
+
+        let mut removed = 0;
+
+        let mut chunk_offset = 0;
+
+        for chunk in self.chunks() {
+            // The retained characters are appended in runs to avoid pushing
+            // them one at a time.
+            let mut run_start = 0;
+
+            for (idx, ch) in chunk.char_indices() {
+                if !f(ch) {
+                    builder.append(&chunk[run_start..idx]);
+                    run_start = idx + ch.len_utf8();
+
+                    let ch_start = chunk_offset + idx;
+
+                    while let Some(offset) = endpoints
+                        .next_if(|offset| **offset < ch_start + ch.len_utf8())
+                    {
+                        *offset = (*offset).min(ch_start) - removed;
+                    }
+
+                    removed += ch.len_utf8();
+                }
+            }
+
+            builder.append(&chunk[run_start..]);
+
+            chunk_offset += chunk.len();
+        }
+
+        for offset in endpoints {
+            *offset -= removed;
+        }
+
+        let mut rope = builder.build();
+        rope.compaction = self.compaction;
+        rope.generation = next_generation();
+        rope.max_len = self.max_len;
+        rope.readonly_ranges = readonly_ranges;
+        *self = rope;
+    }
+
+    /// Checks that replacing the `start..end` byte range with `text_len`
+    /// bytes doesn't make the `Rope` longer than its maximum length.
+    #[inline]
+    fn check_max_len(
+        &self,
+        start: usize,
+        end: usize,
+        text_len: usize,
+    ) -> Result<(), EditError> {
+        let Some(limit) = self.max_len else { return Ok(()) };
+
+        let would_be = self.byte_len() - (end - start) + text_len;
+
+        if would_be > limit && would_be > self.byte_len() {
+            Err(EditError::MaxLenExceeded { limit, would_be })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks that replacing the `start..end` byte range doesn't touch any
+    /// of the read-only ranges.
+    #[inline]
+    fn check_readonly(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> Result<(), EditError> {
+        let at = self
+            .readonly_ranges
+            .iter()
+            .filter(|range| {
+                if start == end {
+                    range.start < start && start < range.end
+                } else {
+                    start < range.end && range.start < end
+                }
+            })
+            .map(|range| start.max(range.start))
+            .min();
+
+        match at {
+            Some(at) => Err(EditError::ReadOnly { at }),
+            None => Ok(()),
+        }
+    }
+
+    /// Shifts the read-only ranges to keep covering the same text after
+    /// replacing the `range` with `text_len` bytes of new text.
+    #[inline]
+    fn shift_readonly_ranges(&mut self, range: Range<usize>, text_len: usize) {
+        for readonly in &mut self.readonly_ranges {
+            // Unlike with `track_offset()`, text inserted at the start of a
+            // read-only range ends up before it.
+            let start = if readonly.start >= range.end {
+                readonly.start - range.end + range.start + text_len
+            } else {
+                track_offset(readonly.start, range.clone(), text_len)
+            };
+
+            let end = track_offset(readonly.end, range.clone(), text_len);
+
+            *readonly = start..end.max(start);
+        }
     }
 
-    /// Returns the line offset of the given byte.
-    ///
-    /// # Panics
+    /// Returns a new `Rope` with the text of this one converted to the
+    /// Unicode Normalization Form C (canonical composition).
     ///
-    /// Panics if the byte offset is out of bounds (i.e. greater than
-    /// [`byte_len()`](Self::byte_len())).
+    /// The chars are streamed through the normalizer across chunk boundaries,
+    /// so a base character and the combining marks following it are composed
+    /// even if they're stored in different chunks. If the text is already in
+    /// NFC the returned `Rope` shares its chunks with this one.
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("foo\nbar\r\nbaz");
+    /// let r = Rope::from("Cafe\u{301} cre\u{300}me");
     ///
-    /// assert_eq!(r.line_of_byte(0), 0);
-    /// assert_eq!(r.line_of_byte(3), 0);
-    /// assert_eq!(r.line_of_byte(4), 1);
-    /// assert_eq!(r.line_of_byte(8), 1); // between the '\r' and the '\n'
-    /// assert_eq!(r.line_of_byte(r.byte_len()), 2);
+    /// assert_eq!(r.to_nfc(), "Caf\u{e9} cr\u{e8}me");
     /// ```
-    #[track_caller]
+    #[cfg_attr(docsrs, doc(cfg(feature = "unicode-normalization")))]
+    #[cfg(feature = "unicode-normalization")]
     #[inline]
-    pub fn line_of_byte(&self, byte_offset: usize) -> usize {
-        if byte_offset > self.byte_len() {
-            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
-        }
+    pub fn to_nfc(&self) -> Rope {
+        use unicode_normalization::{
+            is_nfc_quick,
+            IsNormalized,
+            UnicodeNormalization,
+        };
 
-        let RawLineMetric(line_offset) =
-            self.tree.convert_measure(ByteMetric(byte_offset));
+        if is_nfc_quick(self.chars()) == IsNormalized::Yes {
+            return Rope::from(self.byte_slice(..));
+        }
 
-        line_offset
+        rope_from_chars(self.chars().nfc())
     }
 
-    /// Returns an immutable slice of the `Rope` in the specified line range,
-    /// where the start and end of the range are interpreted as offsets.
-    ///
-    /// # Panics
+    /// Returns a new `Rope` with the text of this one converted to the
+    /// Unicode Normalization Form D (canonical decomposition).
     ///
-    /// Panics if the start is greater than the end or if the end is out of
-    /// bounds (i.e. greater than [`line_len()`](Self::line_len())).
+    /// Like with [`to_nfc()`](Self::to_nfc()), the combining marks are
+    /// reordered across chunk boundaries, and the returned `Rope` shares its
+    /// chunks with this one if the text is already in NFD.
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("foo\nbar\r\nbaz\nfoobar\n");
+    /// let r = Rope::from("Caf\u{e9} cr\u{e8}me");
     ///
-    /// assert_eq!(r.line_slice(..1), "foo\n");
-    /// assert_eq!(r.line_slice(1..3), "bar\r\nbaz\n");
-    /// assert_eq!(r.line_slice(3..), "foobar\n");
+    /// assert_eq!(r.to_nfd(), "Cafe\u{301} cre\u{300}me");
     /// ```
-    #[track_caller]
+    #[cfg_attr(docsrs, doc(cfg(feature = "unicode-normalization")))]
+    #[cfg(feature = "unicode-normalization")]
     #[inline]
-    pub fn line_slice<R>(&self, line_range: R) -> RopeSlice<'_>
-    where
-        R: RangeBounds<usize>,
-    {
-        let (start, end) =
-            range_bounds_to_start_end(line_range, 0, self.line_len());
-
-        if start > end {
-            panic::line_start_after_end(start, end);
-        }
+    pub fn to_nfd(&self) -> Rope {
+        use unicode_normalization::{
+            is_nfd_quick,
+            IsNormalized,
+            UnicodeNormalization,
+        };
 
-        if end > self.line_len() {
-            panic::line_offset_out_of_bounds(end, self.line_len());
+        if is_nfd_quick(self.chars()) == IsNormalized::Yes {
+            return Rope::from(self.byte_slice(..));
         }
 
-        self.tree.slice(RawLineMetric(start)..RawLineMetric(end)).into()
+        rope_from_chars(self.chars().nfd())
     }
 
-    /// Returns an iterator over the lines of this `Rope`, not including the
-    /// line terminators.
+    /// Deletes the text in the specified byte range like
+    /// [`delete()`](Self::delete()), unless it touches one of the
+    /// [read-only ranges](Self::set_readonly_ranges()).
     ///
-    /// The final line break is optional and doesn't cause the iterator to
-    /// return a final empty line.
+    /// # Errors
     ///
-    /// If you want to include the line breaks consider using the
-    /// [`raw_lines()`](Self::raw_lines()) method instead.
+    /// Returns [`EditError::ReadOnly`] if the range intersects a read-only
+    /// range, in which case the `Rope` is left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start or the end of the byte range don't lie on a code
+    /// point boundary, if the start is greater than the end or if the end is
+    /// out of bounds (i.e. greater than [`byte_len()`](Self::byte_len())).
     ///
     /// # Examples
     ///
     /// ```
-    /// # use crop::Rope;
+    /// # use crop::{EditError, Rope};
     /// #
-    /// let r = Rope::from("foo\nbar\r\nbaz\n");
-    ///
-    /// let mut lines = r.lines();
+    /// let mut r = Rope::from("Hello world");
+    /// r.set_readonly_ranges(vec![0..5]);
     ///
-    /// assert_eq!("foo", lines.next().unwrap());
-    /// assert_eq!("bar", lines.next().unwrap());
-    /// assert_eq!("baz", lines.next().unwrap());
-    /// assert_eq!(None, lines.next());
+    /// assert_eq!(r.try_delete(4..6), Err(EditError::ReadOnly { at: 4 }));
+    /// assert_eq!(r.try_delete(5..), Ok(()));
+    /// assert_eq!(r, "Hello");
     /// ```
+    #[track_caller]
     #[inline]
-    pub fn lines(&self) -> Lines<'_> {
-        Lines::from(self)
+    pub fn try_delete<R>(&mut self, byte_range: R) -> Result<(), EditError>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.try_replace(byte_range, "")
     }
 
-    /// Returns an iterator over the lines of this `Rope`, including the
-    /// line terminators.
+    /// Inserts `text` at `byte_offset` like [`insert()`](Self::insert()),
+    /// unless it would make the `Rope` longer than its
+    /// [maximum length](Self::set_max_len()) or the offset is inside one of
+    /// its [read-only ranges](Self::set_readonly_ranges()).
     ///
-    /// The final line break is optional and doesn't cause the iterator to
-    /// return a final empty line.
+    /// # Errors
     ///
-    /// If you don't want to include the line breaks consider using the
-    /// [`lines()`](Self::lines()) method instead.
+    /// Returns [`EditError::MaxLenExceeded`] if the `Rope` would be longer
+    /// than its maximum length after the insertion, or
+    /// [`EditError::ReadOnly`] if the offset is strictly inside a read-only
+    /// range. In both cases the `Rope` is left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset doesn't lie on a code point boundary or if
+    /// it's out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
     ///
     /// # Examples
     ///
     /// ```
-    /// # use crop::Rope;
+    /// # use crop::{EditError, Rope};
     /// #
-    /// let mut r = Rope::from("foo\nbar\r\nbaz");
-    ///
-    /// let mut raw_lines = r.raw_lines();
-    ///
-    /// assert_eq!("foo\n", raw_lines.next().unwrap());
-    /// assert_eq!("bar\r\n", raw_lines.next().unwrap());
-    /// assert_eq!("baz", raw_lines.next().unwrap());
-    /// assert_eq!(None, raw_lines.next());
-    ///
-    /// r.insert(r.byte_len(), "\n");
+    /// let mut r = Rope::from("Hello");
+    /// r.set_max_len(Some(10));
     ///
-    /// let mut raw_lines = r.raw_lines();
-    ///
-    /// assert_eq!("foo\n", raw_lines.next().unwrap());
-    /// assert_eq!("bar\r\n", raw_lines.next().unwrap());
-    /// assert_eq!("baz\n", raw_lines.next().unwrap());
-    /// assert_eq!(None, raw_lines.next());
+    /// assert!(r.try_insert(5, " world").is_err());
+    /// assert!(r.try_insert(5, " you").is_ok());
+    /// assert_eq!(r, "Hello you");
     /// ```
+    #[track_caller]
     #[inline]
-    pub fn raw_lines(&self) -> RawLines<'_> {
-        RawLines::from(self)
-    }
-
-    /// Returns a new empty [`Rope`].
-    #[inline]
-    pub fn new() -> Self {
-        Self::default()
+    pub fn try_insert<T>(
+        &mut self,
+        byte_offset: usize,
+        text: T,
+    ) -> Result<(), EditError>
+    where
+        T: AsRef<str>,
+    {
+        self.try_replace(byte_offset..byte_offset, text)
     }
 
-    /// Replaces the contents of the `Rope` within the specified byte range
-    /// with the given string, where the start and end of the range are
-    /// interpreted as byte offsets.
+    /// Replaces the text in the specified byte range like
+    /// [`replace()`](Self::replace()), unless it would make the `Rope` longer
+    /// than its [maximum length](Self::set_max_len()) or the range touches one
+    /// of its [read-only ranges](Self::set_readonly_ranges()).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EditError::MaxLenExceeded`] if the `Rope` would be longer
+    /// than its maximum length after the replacement, or
+    /// [`EditError::ReadOnly`] if the range intersects a read-only range. In
+    /// both cases the `Rope` is left unchanged.
     ///
     /// # Panics
     ///
@@ -667,16 +4734,25 @@ impl Rope {
     /// # Examples
     ///
     /// ```
-    /// # use crop::Rope;
+    /// # use crop::{EditError, Rope};
     /// #
-    /// let mut r = Rope::from("Hello Earth 🌎!");
+    /// let mut r = Rope::from("Hello Earth!");
+    /// r.set_max_len(Some(12));
     ///
-    /// r.replace(6..16, "Saturn 🪐");
-    /// assert_eq!(r, "Hello Saturn 🪐!");
+    /// assert_eq!(
+    ///     r.try_replace(6..11, "Saturn"),
+    ///     Err(EditError::MaxLenExceeded { limit: 12, would_be: 13 })
+    /// );
+    /// assert_eq!(r.try_replace(6..11, "Mars"), Ok(()));
+    /// assert_eq!(r, "Hello Mars!");
     /// ```
     #[track_caller]
     #[inline]
-    pub fn replace<R, T>(&mut self, byte_range: R, text: T)
+    pub fn try_replace<R, T>(
+        &mut self,
+        byte_range: R,
+        text: T,
+    ) -> Result<(), EditError>
     where
         R: RangeBounds<usize>,
         T: AsRef<str>,
@@ -689,29 +4765,18 @@ impl Rope {
         }
 
         if end > self.byte_len() {
-            panic::byte_offset_out_of_bounds(end, self.line_len());
+            panic::byte_offset_out_of_bounds(end, self.byte_len());
         }
 
         let text = text.as_ref();
 
-        let mut update_trailing = false;
+        self.check_readonly(start, end)?;
 
-        if end == self.byte_len() {
-            if !text.is_empty() {
-                self.has_trailing_newline = text.ends_with('\n');
-            } else if start == 0 {
-                self.has_trailing_newline = false;
-            } else {
-                update_trailing = true;
-            }
-        }
+        self.check_max_len(start, end, text.len())?;
 
-        self.tree.replace(ByteMetric(start)..ByteMetric(end), text);
+        self.replace(start..end, text);
 
-        if update_trailing {
-            self.has_trailing_newline =
-                self.chunks().next_back().unwrap().ends_with('\n');
-        }
+        Ok(())
     }
 
     /// Returns the number of UTF-16 code units the `Rope` would have if it
@@ -808,7 +4873,27 @@ impl Rope {
             panic::utf16_offset_out_of_bounds(end, self.utf16_len());
         }
 
-        self.tree.slice(Utf16Metric(start)..Utf16Metric(end)).into()
+        let (tree_slice, ByteMetric(byte_offset)) =
+            self.tree.slice_with_offset(Utf16Metric(start)..Utf16Metric(end));
+
+        RopeSlice::new(tree_slice, byte_offset, self.generation)
+    }
+}
+
+impl Default for Rope {
+    #[inline]
+    fn default() -> Self {
+        Rope {
+            tree: Tree::default(),
+            has_trailing_newline: false,
+            compaction: Compaction::default(),
+            generation: next_generation(),
+            max_len: None,
+            readonly_ranges: Vec::new(),
+            chunk_pool: ChunkPool::default(),
+            #[cfg(feature = "graphemes")]
+            grapheme_len_cache: GraphemeLenCache::default(),
+        }
     }
 }
 
@@ -818,6 +4903,13 @@ impl From<RopeSlice<'_>> for Rope {
         Self {
             has_trailing_newline: rope_slice.has_trailing_newline,
             tree: Tree::from(rope_slice.tree_slice),
+            compaction: Compaction::default(),
+            generation: next_generation(),
+            max_len: None,
+            readonly_ranges: Vec::new(),
+            chunk_pool: ChunkPool::default(),
+            #[cfg(feature = "graphemes")]
+            grapheme_len_cache: GraphemeLenCache::default(),
         }
     }
 }
@@ -849,6 +4941,13 @@ impl From<&str> for Rope {
             tree: Tree::from_leaves(
                 RopeChunk::segmenter(s).map(RopeChunk::from),
             ),
+            compaction: Compaction::default(),
+            generation: next_generation(),
+            max_len: None,
+            readonly_ranges: Vec::new(),
+            chunk_pool: ChunkPool::default(),
+            #[cfg(feature = "graphemes")]
+            grapheme_len_cache: GraphemeLenCache::default(),
         }
     }
 }
@@ -870,6 +4969,28 @@ impl From<alloc::borrow::Cow<'_, str>> for Rope {
     }
 }
 
+impl From<&Rope> for String {
+    /// Copies the text of the `Rope` into a new `String`.
+    ///
+    /// The `String` is allocated once with the byte length of the `Rope`, and
+    /// the chunks are then copied into it one after the other.
+    ///
+    /// This is much faster than collecting the `char`s of the text into a
+    /// `String`. `to_string()` also copies whole chunks, but through the
+    /// `Display` implementation, so it can't allocate the `String` upfront.
+    #[inline]
+    fn from(rope: &Rope) -> Self {
+        chunks_to_string(rope.chunks(), rope.byte_len())
+    }
+}
+
+impl From<Rope> for String {
+    #[inline]
+    fn from(rope: Rope) -> Self {
+        Self::from(&rope)
+    }
+}
+
 impl core::str::FromStr for Rope {
     type Err = core::convert::Infallible;
 
@@ -879,6 +5000,72 @@ impl core::str::FromStr for Rope {
     }
 }
 
+impl core::ops::AddAssign<&str> for Rope {
+    /// Appends the string to the end of the `Rope`, like
+    /// [`insert()`](Rope::insert())ing it at
+    /// [`byte_len()`](Rope::byte_len()).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the edit would make the `Rope` longer than its
+    /// [`max_len()`](Rope::max_len()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("foo");
+    ///
+    /// r += "\nbar";
+    /// r += "\r\n";
+    ///
+    /// assert_eq!(r, "foo\nbar\r\n");
+    /// assert_eq!(r.line_len(), 2);
+    /// ```
+    #[track_caller]
+    #[inline]
+    fn add_assign(&mut self, rhs: &str) {
+        self.insert(self.byte_len(), rhs);
+    }
+}
+
+impl core::ops::Add<&str> for Rope {
+    type Output = Rope;
+
+    /// Appends the string to the end of the `Rope`, returning it.
+    ///
+    /// This reuses the `Rope`, so it's the same as using `+=` on it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the edit would make the `Rope` longer than its
+    /// [`max_len()`](Rope::max_len()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo") + " bar" + " baz";
+    ///
+    /// assert_eq!(r, "foo bar baz");
+    /// ```
+    #[track_caller]
+    #[inline]
+    fn add(mut self, rhs: &str) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+impl core::hash::Hash for Rope {
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        hash_chunks(self.chunks(), state);
+    }
+}
+
 impl core::cmp::PartialEq<Rope> for Rope {
     #[inline]
     fn eq(&self, rhs: &Rope) -> bool {
@@ -953,4 +5140,152 @@ impl core::cmp::PartialEq<Rope> for alloc::borrow::Cow<'_, str> {
     }
 }
 
+impl core::cmp::PartialEq<[u8]> for Rope {
+    #[inline]
+    fn eq(&self, rhs: &[u8]) -> bool {
+        (self.byte_len() == rhs.len()) && chunks_eq_bytes(self.chunks(), rhs)
+    }
+}
+
+impl core::cmp::PartialEq<Rope> for [u8] {
+    #[inline]
+    fn eq(&self, rhs: &Rope) -> bool {
+        rhs == self
+    }
+}
+
+impl core::cmp::PartialEq<&[u8]> for Rope {
+    #[inline]
+    fn eq(&self, rhs: &&[u8]) -> bool {
+        self == *rhs
+    }
+}
+
+impl core::cmp::PartialEq<Rope> for &[u8] {
+    #[inline]
+    fn eq(&self, rhs: &Rope) -> bool {
+        rhs == self
+    }
+}
+
 impl core::cmp::Eq for Rope {}
+
+/// What an edit removed from a [`Rope`], returned by
+/// [`Rope::delete_counting()`](Rope::delete_counting()).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EditOutcome {
+    bytes_removed: usize,
+    line_breaks_removed: usize,
+}
+
+impl EditOutcome {
+    /// Returns the number of bytes removed by the edit.
+    #[inline]
+    pub fn bytes_removed(&self) -> usize {
+        self.bytes_removed
+    }
+
+    /// Returns the number of line feeds removed by the edit.
+    #[inline]
+    pub fn line_breaks_removed(&self) -> usize {
+        self.line_breaks_removed
+    }
+}
+
+/// A single edit applied by [`Rope::apply_edits()`](Rope::apply_edits()),
+/// which replaces the text in a byte range with new text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edit {
+    /// The byte range of the text to replace.
+    pub range: Range<usize>,
+
+    /// The text to replace it with.
+    pub text: String,
+}
+
+impl Edit {
+    /// Creates a new `Edit` replacing the text in `range` with `text`.
+    #[inline]
+    pub fn new<T: Into<String>>(range: Range<usize>, text: T) -> Self {
+        Self { range, text: text.into() }
+    }
+}
+
+/// The kind of an edit made by [`Rope::edit()`](Rope::edit()), determined by
+/// whether its byte range and its text were empty.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EditKind {
+    /// The byte range was empty and the text wasn't.
+    Insert,
+
+    /// The text was empty and the byte range wasn't.
+    Delete,
+
+    /// Neither the byte range nor the text were empty.
+    Replace,
+
+    /// Both the byte range and the text were empty, so the `Rope` was left
+    /// unchanged.
+    Noop,
+}
+
+/// What a batch of edits removed from and inserted in a [`Rope`], returned by
+/// [`Rope::apply_edits()`](Rope::apply_edits()).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct EditSummary {
+    bytes_removed: usize,
+    bytes_inserted: usize,
+    line_breaks_removed: usize,
+    line_breaks_inserted: usize,
+}
+
+impl EditSummary {
+    /// Returns the number of bytes inserted by the edits.
+    #[inline]
+    pub fn bytes_inserted(&self) -> usize {
+        self.bytes_inserted
+    }
+
+    /// Returns the number of bytes removed by the edits.
+    #[inline]
+    pub fn bytes_removed(&self) -> usize {
+        self.bytes_removed
+    }
+
+    /// Returns the number of line feeds inserted by the edits.
+    #[inline]
+    pub fn line_breaks_inserted(&self) -> usize {
+        self.line_breaks_inserted
+    }
+
+    /// Returns the number of line feeds removed by the edits.
+    #[inline]
+    pub fn line_breaks_removed(&self) -> usize {
+        self.line_breaks_removed
+    }
+}
+
+/// Returns where the given byte offset ends up after replacing the `range`
+/// with `text_len` bytes of new text.
+///
+/// Offsets before or at the start of the range are left unchanged, offsets
+/// at or after the end of the range are shifted by the difference between the
+/// length of the new text and the length of the range, and offsets inside the
+/// range are moved to its start.
+#[inline]
+fn track_offset(offset: usize, range: Range<usize>, text_len: usize) -> usize {
+    if offset <= range.start {
+        offset
+    } else if offset >= range.end {
+        offset - range.end + range.start + text_len
+    } else {
+        range.start
+    }
+}
+
+/// Returns whether the given line, including its line break, is a blank line
+/// delimiting paragraphs, i.e. if it has no content other than its line break.
+#[inline]
+fn is_blank_line(raw_line: RopeSlice<'_>) -> bool {
+    raw_line == "\n" || raw_line == "\r\n"
+}
@@ -1,17 +1,39 @@
 use super::gap_buffer::GapBuffer;
 use super::metrics::ChunkSummary;
 use super::rope::RopeChunk;
-use super::utils::split_adjusted;
-use super::Rope;
+#[cfg(feature = "graphemes")]
+use super::utils::last_grapheme_boundary;
+use super::utils::{
+    next_generation,
+    panic_messages as panic,
+    split_adjusted,
+    str_from_utf8_unchecked,
+};
+use super::{Compaction, Rope};
 use crate::tree::TreeBuilder;
 
 /// An incremental [`Rope`](crate::Rope) builder.
+///
+/// The appended text is accumulated in an internal buffer which is only turned
+/// into a chunk of the `Rope` once it's full (or when calling
+/// [`build()`](Self::build())), so appending many small fragments doesn't
+/// result in many small chunks.
 #[derive(Clone, Default)]
 pub struct RopeBuilder {
     tree_builder: TreeBuilder<{ Rope::arity() }, RopeChunk>,
     buffer: RopeChunk,
     buffer_len_left: usize,
     rope_has_trailing_newline: bool,
+    max_len: Option<usize>,
+    #[cfg(feature = "graphemes")]
+    grapheme_aware_splits: bool,
+
+    /// The bytes of a code point left incomplete at the end of the last call
+    /// to [`append_bytes()`](Self::append_bytes()).
+    incomplete_char: [u8; 3],
+
+    /// The number of bytes in `incomplete_char`.
+    incomplete_char_len: usize,
 }
 
 /// Pushes as mush of the slice as possible onto the left chunk of the gap
@@ -48,20 +70,164 @@ fn gap_buffer_push_with_remainder<'a, const MAX_BYTES: usize>(
     }
 }
 
+/// Returns the byte length of a code point given its first byte, which must
+/// be the first byte of a multi-byte code point.
+#[inline]
+fn utf8_char_width(first_byte: u8) -> usize {
+    debug_assert!(first_byte >= 0xC0);
+
+    if first_byte >= 0xF0 {
+        4
+    } else if first_byte >= 0xE0 {
+        3
+    } else {
+        2
+    }
+}
+
 impl RopeBuilder {
     /// Appends `text` to the end of the `Rope` being built.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the last call to [`append_bytes()`](Self::append_bytes())
+    /// ended in the middle of a code point.
+    #[track_caller]
     #[inline]
     pub fn append<T>(&mut self, text: T) -> &mut Self
     where
         T: AsRef<str>,
     {
-        let mut text = text.as_ref();
+        if self.incomplete_char_len > 0 {
+            panic::incomplete_utf8(self.incomplete_char());
+        }
+
+        self.append_str(text.as_ref())
+    }
+
+    /// Appends the given bytes to the end of the `Rope` being built after
+    /// checking that they're valid UTF-8.
+    ///
+    /// The bytes don't have to end on a code point boundary: the bytes of an
+    /// incomplete code point at the end are held back until the next call
+    /// completes it, so a stream of UTF-8 text can be appended one buffer at
+    /// a time regardless of where the buffers are split.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes, together with the incomplete code point
+    /// held back by the previous call, aren't valid UTF-8. Nothing is
+    /// appended if an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::RopeBuilder;
+    /// #
+    /// let frog = "🐸".as_bytes();
+    ///
+    /// let mut builder = RopeBuilder::new();
+    ///
+    /// builder.append_bytes(b"foo ").unwrap();
+    /// builder.append_bytes(&frog[..1]).unwrap();
+    /// builder.append_bytes(&frog[1..3]).unwrap();
+    /// builder.append_bytes(&[frog[3], b'!']).unwrap();
+    ///
+    /// assert!(builder.append_bytes(&[0xFF]).is_err());
+    ///
+    /// assert_eq!(builder.build(), "foo 🐸!");
+    /// ```
+    #[inline]
+    pub fn append_bytes(
+        &mut self,
+        bytes: &[u8],
+    ) -> Result<&mut Self, core::str::Utf8Error> {
+        let mut completed = [0; 4];
+        let mut completed_len = 0;
+        let mut rest = bytes;
+
+        if self.incomplete_char_len > 0 {
+            let char_len = utf8_char_width(self.incomplete_char[0]);
+
+            completed_len =
+                (self.incomplete_char_len + bytes.len()).min(char_len);
+
+            let (taken, after) =
+                bytes.split_at(completed_len - self.incomplete_char_len);
+
+            completed[..self.incomplete_char_len]
+                .copy_from_slice(self.incomplete_char());
+
+            completed[self.incomplete_char_len..completed_len]
+                .copy_from_slice(taken);
+
+            match core::str::from_utf8(&completed[..completed_len]) {
+                Ok(_) => {},
+
+                // The bytes are a valid prefix of a code point which is still
+                // incomplete.
+                Err(err) if err.error_len().is_none() => {
+                    self.incomplete_char[..completed_len]
+                        .copy_from_slice(&completed[..completed_len]);
+                    self.incomplete_char_len = completed_len;
+                    return Ok(self);
+                },
+
+                Err(err) => return Err(err),
+            }
+
+            rest = after;
+        }
+
+        let (valid, incomplete) = match core::str::from_utf8(rest) {
+            Ok(valid) => (valid, &[][..]),
+
+            Err(err) if err.error_len().is_none() => {
+                let (valid, incomplete) = rest.split_at(err.valid_up_to());
+
+                // SAFETY: the bytes up to `valid_up_to()` are valid UTF-8.
+                (unsafe { str_from_utf8_unchecked(valid) }, incomplete)
+            },
+
+            Err(err) => return Err(err),
+        };
+
+        if completed_len > 0 {
+            // SAFETY: we've just checked that the completed code point is
+            // valid UTF-8.
+            let completed = unsafe {
+                str_from_utf8_unchecked(&completed[..completed_len])
+            };
+
+            self.append_str(completed);
+        }
+
+        self.append_str(valid);
+
+        self.incomplete_char[..incomplete.len()].copy_from_slice(incomplete);
+        self.incomplete_char_len = incomplete.len();
+
+        Ok(self)
+    }
+
+    #[inline]
+    fn append_str(&mut self, mut text: &str) -> &mut Self {
+        if !text.is_empty() {
+            self.rope_has_trailing_newline = text.ends_with('\n');
+        }
 
         while let Some(rest) = gap_buffer_push_with_remainder(
             &mut self.buffer,
             &mut self.buffer_len_left,
             text,
         ) {
+            #[cfg(feature = "graphemes")]
+            if self.grapheme_aware_splits {
+                self.flush_at_grapheme_boundary(rest);
+                text = rest;
+                continue;
+            }
+
             self.buffer.left_summary =
                 ChunkSummary::from(self.buffer_left_chunk());
 
@@ -72,25 +238,68 @@ impl RopeBuilder {
             text = rest;
         }
 
-        self.rope_has_trailing_newline = self.buffer.has_trailing_newline();
-
         self
     }
 
+    /// Appends the buffer to the tree builder like [`append()`] does, except
+    /// that the chunk is cut at the last grapheme boundary before the end of
+    /// the buffer (using `rest` as lookahead) if that doesn't leave it
+    /// underfilled. The bytes after the cut are moved to the new buffer.
+    ///
+    /// [`append()`]: Self::append()
+    #[cfg(feature = "graphemes")]
+    #[inline]
+    fn flush_at_grapheme_boundary(&mut self, rest: &str) {
+        let boundary = last_grapheme_boundary(self.buffer_left_chunk(), rest);
+
+        let split_at = if boundary >= RopeChunk::min_bytes() {
+            boundary
+        } else {
+            self.buffer_len_left
+        };
+
+        let mut next = RopeChunk::default();
+
+        let carried = self.buffer_len_left - split_at;
+
+        next.bytes[..carried].copy_from_slice(
+            &self.buffer.bytes[split_at..self.buffer_len_left],
+        );
+
+        self.buffer_len_left = split_at;
+
+        self.buffer.left_summary =
+            ChunkSummary::from(self.buffer_left_chunk());
+
+        self.tree_builder.append(core::mem::replace(&mut self.buffer, next));
+
+        self.buffer_len_left = carried;
+    }
+
+    #[inline]
+    pub(super) fn incomplete_char(&self) -> &[u8] {
+        &self.incomplete_char[..self.incomplete_char_len]
+    }
+
     #[inline]
     fn buffer_left_chunk(&self) -> &str {
         // SAFETY: we only append string slices to the left chunk of the gap
         // buffer so it's guaranteed to be valid UTF-8.
         unsafe {
-            core::str::from_utf8_unchecked(
-                &self.buffer.bytes[..self.buffer_len_left],
-            )
+            str_from_utf8_unchecked(&self.buffer.bytes[..self.buffer_len_left])
         }
     }
 
     /// Completes the build, consuming the `RopeBuilder` and returning the
     /// `Rope`.
     ///
+    /// # Panics
+    ///
+    /// Panics if the last call to [`append_bytes()`](Self::append_bytes())
+    /// ended in the middle of a code point. Use
+    /// [`build_lossy()`](Self::build_lossy()) to replace it with
+    /// `U+FFFD REPLACEMENT CHARACTER` instead.
+    ///
     /// # Examples
     ///
     /// ```
@@ -104,12 +313,14 @@ impl RopeBuilder {
     ///
     /// assert_eq!(rope, "ƒoo\nbär\r\nbaz");
     /// ```
+    #[track_caller]
     #[inline]
     pub fn build(mut self) -> Rope {
-        if self.buffer_len_left > 0 {
-            self.rope_has_trailing_newline =
-                self.buffer.has_trailing_newline();
+        if self.incomplete_char_len > 0 {
+            panic::incomplete_utf8(self.incomplete_char());
+        }
 
+        if self.buffer_len_left > 0 {
             self.buffer.left_summary =
                 ChunkSummary::from(self.buffer_left_chunk());
 
@@ -119,12 +330,210 @@ impl RopeBuilder {
         Rope {
             tree: self.tree_builder.build(),
             has_trailing_newline: self.rope_has_trailing_newline,
+            compaction: Compaction::default(),
+            generation: next_generation(),
+            max_len: self.max_len,
+            readonly_ranges: Vec::new(),
+            chunk_pool: Default::default(),
+            #[cfg(feature = "graphemes")]
+            grapheme_len_cache: Default::default(),
         }
     }
 
+    /// Completes the build like [`build()`](Self::build()), except that the
+    /// incomplete code point left by the last call to
+    /// [`append_bytes()`](Self::append_bytes()), if any, is replaced with
+    /// `U+FFFD REPLACEMENT CHARACTER`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::RopeBuilder;
+    /// #
+    /// let mut builder = RopeBuilder::new();
+    ///
+    /// builder.append_bytes(b"foo").unwrap();
+    /// builder.append_bytes(&"🐸".as_bytes()[..2]).unwrap();
+    ///
+    /// assert_eq!(builder.build_lossy(), "foo\u{FFFD}");
+    /// ```
+    #[inline]
+    pub fn build_lossy(mut self) -> Rope {
+        if self.incomplete_char_len > 0 {
+            self.incomplete_char_len = 0;
+            self.append_str("\u{FFFD}");
+        }
+
+        self.build()
+    }
+
+    /// Completes the build like [`build()`](Self::build()), also returning
+    /// some statistics about how the text was split into chunks.
+    ///
+    /// The statistics are read from the tree of the `Rope`, so this doesn't
+    /// walk it again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Rope, RopeBuilder};
+    /// #
+    /// let mut builder = RopeBuilder::new();
+    ///
+    /// builder.append("ƒoo\n").append("bär\r\n").append("baz");
+    ///
+    /// let (rope, stats) = builder.build_with_stats();
+    ///
+    /// assert_eq!(rope, "ƒoo\nbär\r\nbaz");
+    /// assert!(stats.leaf_count() >= 1);
+    /// assert!(stats.fill_ratio() > 0.0 && stats.fill_ratio() <= 1.0);
+    /// ```
+    #[inline]
+    pub fn build_with_stats(self) -> (Rope, BuildStats) {
+        let rope = self.build();
+
+        let stats = BuildStats {
+            byte_len: rope.byte_len(),
+            depth: rope.tree.depth(),
+            leaf_count: rope.tree.leaf_count(),
+        };
+
+        (rope, stats)
+    }
+
+    /// Makes the builder prefer grapheme boundaries when choosing where to
+    /// split the text into chunks, so that grapheme clusters are less likely
+    /// to straddle two chunks.
+    ///
+    /// This is a best-effort locality hint, not a guarantee: a chunk is only
+    /// cut short if that doesn't leave it underfilled, clusters longer than
+    /// that are still split, and a cluster continuing in a later call to
+    /// [`append()`](Self::append()) isn't detected. It also only affects the
+    /// chunks created by the builder, not those created by later edits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::RopeBuilder;
+    /// #
+    /// let mut builder = RopeBuilder::new();
+    ///
+    /// builder.grapheme_aware_splits(true).append("e\u{301}".repeat(1000));
+    ///
+    /// let r = builder.build();
+    ///
+    /// let mut offset = 0;
+    ///
+    /// for chunk in r.chunks() {
+    ///     offset += chunk.len();
+    ///     assert!(r.is_grapheme_boundary(offset));
+    /// }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
+    #[cfg(feature = "graphemes")]
+    #[inline]
+    pub fn grapheme_aware_splits(&mut self, enabled: bool) -> &mut Self {
+        self.grapheme_aware_splits = enabled;
+        self
+    }
+
+    /// Sets the maximum byte length of the `Rope` being built, which is then
+    /// enforced by its edits as described in
+    /// [`Rope::set_max_len()`](crate::Rope::set_max_len()).
+    ///
+    /// The limit isn't checked against the text appended to the builder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{EditError, RopeBuilder};
+    /// #
+    /// let mut builder = RopeBuilder::new();
+    ///
+    /// builder.max_len(8).append("Hello");
+    ///
+    /// let mut r = builder.build();
+    ///
+    /// assert_eq!(r.max_len(), Some(8));
+    /// assert_eq!(
+    ///     r.try_insert(5, " world"),
+    ///     Err(EditError::MaxLenExceeded { limit: 8, would_be: 11 })
+    /// );
+    /// ```
+    #[inline]
+    pub fn max_len(&mut self, bytes: usize) -> &mut Self {
+        self.max_len = Some(bytes);
+        self
+    }
+
     /// Creates a new `RopeBuilder`.
     #[inline]
     pub fn new() -> Self {
         Self::default()
     }
 }
+
+/// Concatenates the given strings into a new [`Rope`](crate::Rope).
+///
+/// This is the `Rope` analog of `[&str].concat()`. The parts are appended to
+/// a [`RopeBuilder`], so they're packed into full chunks regardless of their
+/// length instead of getting a chunk each.
+///
+/// # Examples
+///
+/// ```
+/// let r = crop::concat(["foo", "\n", "bar", "\r\n", "baz"]);
+///
+/// assert_eq!(r, "foo\nbar\r\nbaz");
+/// assert_eq!(r.line_len(), 3);
+/// ```
+#[inline]
+pub fn concat<I, S>(parts: I) -> Rope
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut builder = RopeBuilder::new();
+
+    for part in parts {
+        builder.append(part);
+    }
+
+    builder.build()
+}
+
+/// Statistics about the chunks of a [`Rope`](crate::Rope) built by a
+/// [`RopeBuilder`], returned by
+/// [`RopeBuilder::build_with_stats()`](RopeBuilder::build_with_stats()).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BuildStats {
+    byte_len: usize,
+    depth: usize,
+    leaf_count: usize,
+}
+
+impl BuildStats {
+    /// Returns the depth of the `Rope`'s tree, which is zero if the whole text
+    /// fits in a single chunk.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the average number of bytes in a chunk divided by the maximum
+    /// number of bytes a chunk can hold, a number between 0 and 1 where
+    /// higher is better.
+    ///
+    /// The fill ratio of an empty `Rope` is 0.
+    #[inline]
+    pub fn fill_ratio(&self) -> f64 {
+        self.byte_len as f64
+            / (self.leaf_count * RopeChunk::max_bytes()) as f64
+    }
+
+    /// Returns the number of chunks of the `Rope`.
+    #[inline]
+    pub fn leaf_count(&self) -> usize {
+        self.leaf_count
+    }
+}
@@ -1,10 +1,30 @@
-use core::ops::RangeBounds;
-
-use super::iterators::{Bytes, Chars, Chunks, Lines, RawLines};
+use core::ops::{Range, RangeBounds};
+
+#[cfg(feature = "unicode-lines")]
+use super::iterators::LinesWith;
+use super::iterators::{
+    ByteChunks,
+    Bytes,
+    Chars,
+    ChunkBoundaries,
+    ChunkRanges,
+    Chunks,
+    Lines,
+    LinesCow,
+    Matches,
+    RawLines,
+    SplitWhitespace,
+    WrappedLines,
+};
+#[cfg(feature = "unicode-lines")]
+use super::metrics::is_unicode_line_break;
 use super::metrics::{ByteMetric, RawLineMetric};
 use super::rope::RopeChunk;
+use super::text_metric::{self, Metric};
 use super::utils::{panic_messages as panic, *};
-use super::Rope;
+#[cfg(feature = "unicode-lines")]
+use super::LineBreakKind;
+use super::{LineEnding, Reader, Rope};
 use crate::range_bounds_to_start_end;
 use crate::tree::TreeSlice;
 
@@ -13,6 +33,14 @@ use crate::tree::TreeSlice;
 pub struct RopeSlice<'a> {
     pub(super) tree_slice: TreeSlice<'a, { Rope::arity() }, RopeChunk>,
     pub(super) has_trailing_newline: bool,
+
+    /// The byte offset of the start of this slice in the `Rope` it was
+    /// sliced from.
+    pub(super) byte_offset: usize,
+
+    /// The generation of the `Rope` this slice was sliced from at the time
+    /// it was created.
+    pub(super) generation: usize,
 }
 
 impl<'a> RopeSlice<'a> {
@@ -29,8 +57,124 @@ impl<'a> RopeSlice<'a> {
         assert_eq!(self.has_trailing_newline, last.has_trailing_newline())
     }
 
+    /// Creates a new `RopeSlice` from a slice of the tree of a `Rope`, where
+    /// `byte_offset` is the byte offset of the start of the slice in that
+    /// `Rope` and `generation` is its current generation.
+    #[inline]
+    pub(super) fn new(
+        tree_slice: TreeSlice<'a, { Rope::arity() }, RopeChunk>,
+        byte_offset: usize,
+        generation: usize,
+    ) -> Self {
+        Self {
+            has_trailing_newline: tree_slice
+                .end_slice()
+                .has_trailing_newline(),
+            tree_slice,
+            byte_offset,
+            generation,
+        }
+    }
+
+    /// Converts a byte offset relative to the start of this `RopeSlice` into
+    /// the corresponding byte offset in the `Rope` it was sliced from.
+    ///
+    /// This makes it possible to look for a position in a slice and then
+    /// edit the `Rope` at that position. See
+    /// [`parent_range()`](Self::parent_range()) for the range of the whole
+    /// slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("foo\nbar baz\n");
+    ///
+    /// let line = r.line(1);
+    /// let space = line.find_char(|ch| ch == ' ').unwrap();
+    /// let offset = line.absolute_offset(space);
+    ///
+    /// assert_eq!(offset, 7);
+    ///
+    /// r.insert(offset, ",");
+    /// assert_eq!(r, "foo\nbar, baz\n");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn absolute_offset(&self, byte_offset: usize) -> usize {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        self.byte_offset + byte_offset
+    }
+
+    /// Returns the contents of the `RopeSlice` as a byte slice if they're
+    /// stored in a single contiguous chunk, or `None` otherwise.
+    ///
+    /// This is the byte counterpart of [`as_str()`](Self::as_str()), and it
+    /// returns `Some` exactly when `as_str()` does. It can be used to pass
+    /// small texts through FFI as a pointer and a length, falling back to
+    /// [`byte_chunks()`](Self::byte_chunks()) when it returns `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("abc");
+    /// let s = r.byte_slice(1..);
+    ///
+    /// assert_eq!(s.as_contiguous_bytes(), Some(&b"bc"[..]));
+    /// ```
+    #[inline]
+    pub fn as_contiguous_bytes(&self) -> Option<&'a [u8]> {
+        self.as_str().map(str::as_bytes)
+    }
+
+    /// Returns the contents of the `RopeSlice` as a string slice if they're
+    /// stored in a single contiguous chunk, or `None` otherwise.
+    ///
+    /// This allows to avoid allocating a new `String` when passing small
+    /// texts to APIs that expect a `&str`. Note that the text stored in a
+    /// single leaf of the `Rope` can still be split in two by the gap of its
+    /// gap buffer, in which case this returns `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello, world!\n");
+    /// let s = r.line(0);
+    ///
+    /// let text = match s.as_str() {
+    ///     Some(text) => Cow::Borrowed(text),
+    ///     None => Cow::Owned(s.to_string()),
+    /// };
+    ///
+    /// assert_eq!(text, "Hello, world!");
+    ///
+    /// assert_eq!(Rope::new().byte_slice(..).as_str(), Some(""));
+    /// ```
+    #[inline]
+    pub fn as_str(&self) -> Option<&'a str> {
+        chunks_as_str(self.chunks())
+    }
+
     /// Returns the byte at `byte_index`.
     ///
+    /// See [`get_byte()`](Self::get_byte()) for a non-panicking version and
+    /// [`byte_unchecked()`](Self::byte_unchecked()) for a version without
+    /// bounds checking.
+    ///
     /// # Panics
     ///
     /// Panics if the byte index is out of bounds (i.e. greater than or equal
@@ -61,6 +205,29 @@ impl<'a> RopeSlice<'a> {
         chunk.byte(byte_index - chunk_byte_offset)
     }
 
+    /// Returns an iterator over the chunks of this `RopeSlice` as byte slices.
+    ///
+    /// This yields the same chunks as [`chunks()`](Self::chunks()), which is
+    /// handy to run byte-oriented algorithms over the text without going
+    /// through `str`.
+    /// The first and last chunks are trimmed to the bounds of the slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo bar baz");
+    /// let s = r.byte_slice(4..);
+    ///
+    /// let bytes = s.byte_chunks().flatten().copied().collect::<Vec<u8>>();
+    /// assert_eq!(bytes, b"bar baz");
+    /// ```
+    #[inline]
+    pub fn byte_chunks(&self) -> ByteChunks<'a> {
+        ByteChunks::from(self)
+    }
+
     /// Returns the length of the `RopeSlice` in bytes.
     ///
     /// # Examples
@@ -81,6 +248,44 @@ impl<'a> RopeSlice<'a> {
         self.tree_slice.summary().bytes()
     }
 
+    /// Returns the line containing the given byte offset together with the
+    /// byte offset from the start of that line, i.e. its column.
+    ///
+    /// The line is the one returned by
+    /// [`line_of_byte()`](Self::line_of_byte()), and the column is the number
+    /// of bytes between the start of the line and the byte offset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("ƒoo\nbär\r\nbaz");
+    /// let s = r.byte_slice(2..);
+    ///
+    /// assert_eq!(s.byte_line_col(0), (0, 0));
+    /// assert_eq!(s.byte_line_col(4), (1, 1));
+    /// assert_eq!(s.byte_line_col(s.byte_len()), (2, 3));
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn byte_line_col(&self, byte_offset: usize) -> (usize, usize) {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        let (RawLineMetric(line_offset), ByteMetric(line_start)) = self
+            .tree_slice
+            .convert_measure_with_start(ByteMetric(byte_offset));
+
+        (line_offset, byte_offset - line_start)
+    }
+
     /// Returns the byte offset of the start of the given line.
     ///
     /// # Panics
@@ -159,6 +364,11 @@ impl<'a> RopeSlice<'a> {
     /// Returns a sub-slice of this `RopeSlice` in the specified byte range,
     /// where the start and end of the range are interpreted as offsets.
     ///
+    /// Slicing doesn't copy any text. If the range falls within a single
+    /// chunk and isn't split by its gap, the slice is backed by a contiguous
+    /// `str` which can be borrowed with
+    /// [`RopeSlice::as_str()`](crate::RopeSlice::as_str()).
+    ///
     /// # Panics
     ///
     /// Panics if the start or the end of the byte range don't lie on a code
@@ -193,7 +403,57 @@ impl<'a> RopeSlice<'a> {
             panic::byte_offset_out_of_bounds(end, self.byte_len());
         }
 
-        self.tree_slice.slice(ByteMetric(start)..ByteMetric(end)).into()
+        RopeSlice::new(
+            self.tree_slice.slice(ByteMetric(start)..ByteMetric(end)),
+            self.byte_offset + start,
+            self.generation,
+        )
+    }
+
+    /// Returns the byte at `byte_index` like [`byte()`](Self::byte()),
+    /// without checking that the byte index is in bounds.
+    ///
+    /// This is the equivalent of [`slice::get_unchecked()`] for code that has
+    /// already validated its byte indices, e.g. a scanner iterating over
+    /// `0..byte_len()`. The index is still checked in debug builds.
+    ///
+    /// See [`get_byte()`](Self::get_byte()) for a safe version returning an
+    /// `Option`.
+    ///
+    /// # Safety
+    ///
+    /// The byte index must be less than [`byte_len()`](Self::byte_len()).
+    /// Calling this method with an out of bounds index is undefined
+    /// behavior, even if the returned byte is not used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foobar");
+    /// let s = r.byte_slice(3..);
+    ///
+    /// let mut a_count = 0;
+    ///
+    /// for byte_index in 0..s.byte_len() {
+    ///     // SAFETY: the byte index is less than the byte length.
+    ///     if unsafe { s.byte_unchecked(byte_index) } == b'a' {
+    ///         a_count += 1;
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(a_count, 1);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub unsafe fn byte_unchecked(&self, byte_index: usize) -> u8 {
+        debug_assert!(byte_index < self.byte_len());
+
+        let (chunk, ByteMetric(chunk_byte_offset)) =
+            self.tree_slice.leaf_at_measure(ByteMetric(byte_index + 1));
+
+        chunk.byte_unchecked(byte_index - chunk_byte_offset)
     }
 
     /// Returns an iterator over the bytes of this `RopeSlice`.
@@ -219,6 +479,32 @@ impl<'a> RopeSlice<'a> {
         Bytes::from(self)
     }
 
+    /// Returns the number of [`char`]s in this `RopeSlice`.
+    ///
+    /// If the `RopeSlice` is [all ASCII](Self::is_ascii()) this is an O(1)
+    /// operation returning the same value as
+    /// [`byte_len()`](Self::byte_len()). Otherwise the chars are counted one
+    /// chunk at a time, which takes linear time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello, 🌎!");
+    ///
+    /// assert_eq!(r.byte_slice(..7).char_len(), 7);
+    /// assert_eq!(r.byte_slice(5..).char_len(), 4);
+    /// ```
+    #[inline]
+    pub fn char_len(&self) -> usize {
+        if self.is_ascii() {
+            self.byte_len()
+        } else {
+            count_chars(self.chunks())
+        }
+    }
+
     /// Returns an iterator over the [`char`]s of this `RopeSlice`.
     ///
     /// # Examples
@@ -241,336 +527,1546 @@ impl<'a> RopeSlice<'a> {
         Chars::from(self)
     }
 
+    /// Returns an iterator over the byte offsets of the start of every chunk
+    /// yielded by [`chunks()`](Self::chunks()), in strictly increasing order.
+    ///
+    /// The byte length of the `RopeSlice` is not yielded at the end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    /// let s = r.byte_slice(2..10);
+    ///
+    /// let mut offset = 0;
+    ///
+    /// for (start, chunk) in s.chunk_boundaries().zip(s.chunks()) {
+    ///     assert_eq!(start, offset);
+    ///     offset += chunk.len();
+    /// }
+    ///
+    /// assert_eq!(offset, s.byte_len());
+    /// ```
+    #[inline]
+    pub fn chunk_boundaries(&self) -> ChunkBoundaries<'a> {
+        ChunkBoundaries::from(self)
+    }
+
+    /// Returns an iterator over the chunks yielded by
+    /// [`chunks()`](Self::chunks()), each paired with the byte range it spans.
+    ///
+    /// The ranges are relative to the start of the `Rope` the slice was taken
+    /// from, not to the start of the slice, and they tile the range spanned by
+    /// the slice without gaps or overlaps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    /// let s = r.byte_slice(2..10);
+    ///
+    /// let mut expected_start = 2;
+    ///
+    /// for (range, chunk) in s.chunk_ranges() {
+    ///     assert_eq!(range.start, expected_start);
+    ///     assert_eq!(r.byte_slice(range.clone()), chunk);
+    ///     expected_start = range.end;
+    /// }
+    ///
+    /// assert_eq!(expected_start, 10);
+    /// ```
+    #[inline]
+    pub fn chunk_ranges(&self) -> ChunkRanges<'a> {
+        ChunkRanges::from(self)
+    }
+
     /// Returns an iterator over the chunks of this `RopeSlice`.
+    ///
+    /// The chunks are never empty, which also means that the chunks of an
+    /// empty `RopeSlice` are an empty iterator.
     #[inline]
     pub fn chunks(&self) -> Chunks<'a> {
         Chunks::from(self)
     }
 
-    /// Returns an iterator over the extended grapheme clusters of this
-    /// `RopeSlice`.
+    /// Returns an iterator over the chunks of the line at `line_index`,
+    /// without its line terminator.
+    ///
+    /// The first and last chunks are trimmed to the start and end of the
+    /// line, so a line contained in a single chunk yields a single `&str`.
+    /// Reaching the line takes logarithmic time, and it's equivalent to
+    /// `line(line_index).chunks()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line index is out of bounds (i.e. greater than or equal
+    /// to [`line_len()`](Self::line_len())).
     ///
     /// # Examples
     ///
-    /// ```no_run
+    /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("arg!\r\n🐻‍❄️");
-    /// let s = r.byte_slice(3..);
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    /// let s = r.byte_slice(1..);
     ///
-    /// let mut graphemes = s.graphemes();
+    /// assert_eq!(s.chunks_of_line(0).collect::<String>(), "oo");
+    /// assert_eq!(s.chunks_of_line(1).collect::<String>(), "bar");
+    /// assert_eq!(s.chunks_of_line(2).collect::<String>(), "baz");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn chunks_of_line(&self, line_index: usize) -> Chunks<'a> {
+        self.line(line_index).chunks()
+    }
+
+    /// Returns the 64-bit [FNV-1a] hash of the contents of this `RopeSlice`.
+    ///
+    /// The hash is computed by streaming the bytes of each chunk, without
+    /// collecting them into a `String`. Unlike the [`Hash`](core::hash::Hash)
+    /// impl, whose output depends on the `Hasher` it's fed to, this always
+    /// returns the same value for the same text regardless of how it's split
+    /// into chunks, so it can be compared against the FNV-1a hash of a file
+    /// on disk to check whether it has changed.
+    ///
+    /// FNV-1a isn't a cryptographic hash and it's easy to craft collisions,
+    /// so don't use it to detect malicious changes. Enable the `sha256`
+    /// feature and use `sha256_hex()` for that.
+    ///
+    /// [FNV-1a]: https://en.wikipedia.org/wiki/Fowler%E2%80%93Noll%E2%80%93Vo_hash_function
+    ///
+    /// # Examples
     ///
-    /// assert_eq!(Some("!"), graphemes.next().as_deref());
-    /// assert_eq!(Some("\r\n"), graphemes.next().as_deref());
-    /// assert_eq!(Some("🐻‍❄️"), graphemes.next().as_deref());
-    /// assert_eq!(None, graphemes.next());
     /// ```
-    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
-    #[cfg(feature = "graphemes")]
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello, world!");
+    /// let s = r.byte_slice(7..);
+    ///
+    /// assert_eq!(s.content_hash(), Rope::from("world!").content_hash());
+    /// ```
     #[inline]
-    pub fn graphemes(&self) -> crate::iter::Graphemes<'a> {
-        crate::iter::Graphemes::from(self)
+    pub fn content_hash(&self) -> u64 {
+        fnv1a_chunks(self.chunks())
     }
 
-    /// Returns `true` if the given byte offset lies on a [`char`] boundary.
+    /// Converts an offset in the `M1` metric to the corresponding offset in
+    /// the `M2` metric.
+    ///
+    /// See [`Rope::convert()`] for more.
     ///
     /// # Panics
     ///
-    /// Panics if the byte offset is out of bounds (i.e. greater than
-    /// [`byte_len()`](Self::byte_len())).
+    /// Panics if the offset is out of bounds (i.e. greater than
+    /// [`measure::<M1>()`](Self::measure())).
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
-    /// #
-    /// let r = Rope::from("Löwe 老虎 Léopard");
-    /// let s = r.byte_slice(3..);
+    /// use crop::metrics::{Byte, Line};
     ///
-    /// assert!(s.is_char_boundary(0));
-    /// assert!(s.is_char_boundary(s.byte_len()));
-    /// assert!(s.is_char_boundary(6)); // between '老' and '虎'
-    /// assert!(!s.is_char_boundary(12)); // between the 1st and 2nd byte of 'é'
+    /// let r = Rope::from("ƒoo\nbär\r\nbaz");
+    /// let s = r.byte_slice(5..);
+    ///
+    /// assert_eq!(s.convert::<Line, Byte>(1), s.byte_of_line(1));
+    /// assert_eq!(s.convert::<Byte, Line>(4), 0);
     /// ```
     #[track_caller]
     #[inline]
-    pub fn is_char_boundary(&self, byte_offset: usize) -> bool {
-        if byte_offset > self.byte_len() {
-            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
-        }
-
-        let (chunk, ByteMetric(chunk_byte_offset)) =
-            self.tree_slice.leaf_at_measure(ByteMetric(byte_offset));
-
-        chunk.is_char_boundary(byte_offset - chunk_byte_offset)
+    pub fn convert<M1, M2>(&self, offset: usize) -> usize
+    where
+        M1: Metric,
+        M2: Metric,
+    {
+        text_metric::convert::<M1, M2>(*self, offset)
+    }
+
+    /// Copies the bytes of the `RopeSlice` into the given buffer, returning the
+    /// number of bytes that were copied.
+    ///
+    /// If the buffer is shorter than the `RopeSlice` only the first `dst.len()`
+    /// bytes are copied, which means the buffer could end in the middle of a
+    /// multi-byte character.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello, world!");
+    /// let s = r.byte_slice(7..);
+    ///
+    /// let mut buf = [0u8; 32];
+    /// assert_eq!(s.copy_into(&mut buf), 6);
+    /// assert_eq!(&buf[..6], b"world!");
+    /// ```
+    #[inline]
+    pub fn copy_into(&self, dst: &mut [u8]) -> usize {
+        let mut copied = 0;
+
+        for chunk in self.chunks() {
+            let len = chunk.len().min(dst.len() - copied);
+
+            dst[copied..copied + len]
+                .copy_from_slice(&chunk.as_bytes()[..len]);
+
+            copied += len;
+
+            if copied == dst.len() {
+                break;
+            }
+        }
+
+        copied
+    }
+
+    /// Returns an empty slice of the `RopeSlice` at the given byte offset.
+    ///
+    /// This is equivalent to `byte_slice(byte_offset..byte_offset)`. The
+    /// slice compares equal to `""` and all its iterators are empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())) or if it doesn't lie on a code point
+    /// boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("🗻∈🌏");
+    /// let s = r.byte_slice(4..);
+    ///
+    /// let e = s.empty_slice_at(3);
+    /// assert!(e.is_empty());
+    /// assert_eq!(e, "");
+    /// assert_eq!(e.chars().next(), None);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn empty_slice_at(self, byte_offset: usize) -> RopeSlice<'a> {
+        self.byte_slice(byte_offset..byte_offset)
+    }
+
+    /// Returns `true` if the last line of the `RopeSlice` is terminated by a
+    /// line break, i.e. if its last byte is a `'\n'`.
+    ///
+    /// See [`Rope::final_line_terminated()`] for more.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\n");
+    ///
+    /// assert!(r.byte_slice(..4).final_line_terminated());
+    /// assert!(!r.byte_slice(..6).final_line_terminated());
+    /// assert!(!r.byte_slice(..0).final_line_terminated());
+    /// ```
+    #[inline]
+    pub fn final_line_terminated(&self) -> bool {
+        self.has_trailing_newline
+    }
+
+    /// Returns the byte offset of the first char of the `RopeSlice`
+    /// satisfying the given predicate, or `None` if no char does.
+    ///
+    /// See [`Rope::find_char()`] for more.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("ƒoo bär\tbaz");
+    /// let s = r.byte_slice(5..);
+    ///
+    /// assert_eq!(s.find_char(char::is_whitespace), Some(4));
+    /// assert_eq!(s.find_char(|ch| ch == 'ƒ'), None);
+    /// ```
+    #[inline]
+    pub fn find_char<F>(&self, predicate: F) -> Option<usize>
+    where
+        F: FnMut(char) -> bool,
+    {
+        find_char(self.chunks(), predicate)
+    }
+
+    /// Returns the first char of the `RopeSlice`, or `None` if it's empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("🐸 foo\n");
+    /// let s = r.byte_slice(..5);
+    ///
+    /// assert_eq!(s.first_char(), Some('🐸'));
+    /// assert_eq!(r.byte_slice(0..0).first_char(), None);
+    /// ```
+    #[inline]
+    pub fn first_char(&self) -> Option<char> {
+        self.chars().next()
+    }
+
+    /// Returns the byte at `byte_index` like [`byte()`](Self::byte()), or
+    /// `None` if the byte index is out of bounds (i.e. greater than or equal
+    /// to [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foobar");
+    /// let s = r.byte_slice(3..);
+    ///
+    /// assert_eq!(s.get_byte(1), Some(b'a'));
+    /// assert_eq!(s.get_byte(3), None);
+    /// ```
+    #[inline]
+    pub fn get_byte(&self, byte_index: usize) -> Option<u8> {
+        // SAFETY: the byte index was just checked to be in bounds.
+        (byte_index < self.byte_len())
+            .then(|| unsafe { self.byte_unchecked(byte_index) })
+    }
+
+    /// Returns the line at `line_index` like [`line()`](Self::line()), or
+    /// `None` if the line index is out of bounds (i.e. greater than or equal
+    /// to [`line_len()`](Self::line_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    /// let s = r.byte_slice(4..);
+    ///
+    /// assert_eq!(s.get_line(1).unwrap(), "baz");
+    /// assert_eq!(s.get_line(2), None);
+    /// ```
+    #[inline]
+    pub fn get_line(self, line_index: usize) -> Option<RopeSlice<'a>> {
+        (line_index < self.line_len()).then(|| self.line(line_index))
+    }
+
+    /// Returns the byte offset of the start of the extended grapheme cluster
+    /// containing the given byte offset.
+    ///
+    /// If the byte offset already lies on a grapheme boundary it's returned
+    /// unchanged. Otherwise the `RopeSlice` is walked backward from it until
+    /// the start of the grapheme is found, looking at the text before it as
+    /// needed to classify the grapheme correctly (e.g. to pair up regional
+    /// indicators into flags).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())) or if it doesn't lie on a code point
+    /// boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo arg!\r\n🇫🇷🇩🇪");
+    /// let s = r.byte_slice(4..);
+    ///
+    /// assert_eq!(s.grapheme_start(3), 3);
+    /// assert_eq!(s.grapheme_start(5), 4); // between '\r' and '\n'
+    /// assert_eq!(s.grapheme_start(10), 6); // inside '🇫🇷'
+    /// assert_eq!(s.grapheme_start(18), 14); // inside '🇩🇪'
+    /// assert_eq!(s.grapheme_start(s.byte_len()), s.byte_len());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
+    #[cfg(feature = "graphemes")]
+    #[track_caller]
+    #[inline]
+    pub fn grapheme_start(&self, byte_offset: usize) -> usize {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        grapheme_start(*self, byte_offset)
+    }
+
+    /// Returns an iterator over the extended grapheme clusters of this
+    /// `RopeSlice`.
+    ///
+    /// The text before and after the slice is not taken into account, just
+    /// like when iterating over the graphemes of a `&str` sub-slice. For
+    /// example, a slice starting between the `'\r'` and the `'\n'` of a CRLF
+    /// yields a lone `"\n"` grapheme.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("arg!\r\n🐻‍❄️");
+    /// let s = r.byte_slice(3..);
+    ///
+    /// let mut graphemes = s.graphemes();
+    ///
+    /// assert_eq!(Some("!"), graphemes.next().as_deref());
+    /// assert_eq!(Some("\r\n"), graphemes.next().as_deref());
+    /// assert_eq!(Some("🐻‍❄️"), graphemes.next().as_deref());
+    /// assert_eq!(None, graphemes.next());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
+    #[cfg(feature = "graphemes")]
+    #[inline]
+    pub fn graphemes(&self) -> crate::iter::Graphemes<'a> {
+        crate::iter::Graphemes::from(self)
+    }
+
+    /// Returns an iterator over the extended grapheme clusters of this
+    /// `RopeSlice` like [`graphemes()`](Self::graphemes()), starting from the
+    /// grapheme containing the given byte offset.
+    ///
+    /// The byte offset is snapped back to the start of its grapheme with
+    /// [`grapheme_start()`](Self::grapheme_start()), which can be called
+    /// separately to know where the iteration starts. This only looks at the
+    /// text around the byte offset, so resuming the iteration in the middle
+    /// of a `RopeSlice` doesn't have to go through all the graphemes before it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())) or if it doesn't lie on a code point
+    /// boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo arg!\r\n🇫🇷🇩🇪");
+    /// let s = r.byte_slice(4..);
+    ///
+    /// let mut graphemes = s.graphemes_at(18);
+    /// assert_eq!(Some("🇩🇪"), graphemes.next().as_deref());
+    /// assert_eq!(None, graphemes.next());
+    ///
+    /// let mut graphemes = s.graphemes_at(5);
+    /// assert_eq!(Some("\r\n"), graphemes.next().as_deref());
+    /// assert_eq!(Some("🇫🇷"), graphemes.next().as_deref());
+    /// assert_eq!(Some("🇩🇪"), graphemes.next_back().as_deref());
+    /// assert_eq!(None, graphemes.next());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
+    #[cfg(feature = "graphemes")]
+    #[track_caller]
+    #[inline]
+    pub fn graphemes_at(
+        &self,
+        byte_offset: usize,
+    ) -> crate::iter::Graphemes<'a> {
+        let start = self.grapheme_start(byte_offset);
+        self.byte_slice(start..).graphemes()
+    }
+
+    /// Returns `true` if all the bytes of the `RopeSlice` are ASCII.
+    ///
+    /// This is an O(1) operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello, 🌎!");
+    ///
+    /// assert!(r.byte_slice(..7).is_ascii());
+    /// assert!(!r.byte_slice(5..).is_ascii());
+    /// ```
+    #[inline]
+    pub fn is_ascii(&self) -> bool {
+        self.tree_slice.summary().is_ascii()
+    }
+
+    /// Returns `true` if the given byte offset lies on a [`char`] boundary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Löwe 老虎 Léopard");
+    /// let s = r.byte_slice(3..);
+    ///
+    /// assert!(s.is_char_boundary(0));
+    /// assert!(s.is_char_boundary(s.byte_len()));
+    /// assert!(s.is_char_boundary(6)); // between '老' and '虎'
+    /// assert!(!s.is_char_boundary(12)); // between the 1st and 2nd byte of 'é'
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn is_char_boundary(&self, byte_offset: usize) -> bool {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        if self.is_ascii() {
+            return true;
+        }
+
+        let (chunk, ByteMetric(chunk_byte_offset)) =
+            self.tree_slice.leaf_at_measure(ByteMetric(byte_offset));
+
+        chunk.is_char_boundary(byte_offset - chunk_byte_offset)
+    }
+
+    /// Returns `true` if the `RopeSlice` was sliced from a `Rope` with the
+    /// same [generation](Rope::generation()) as `rope`.
+    ///
+    /// This is meant to check that a slice of a snapshot of a `Rope` still
+    /// reflects the text of the `Rope` it was cloned from, i.e. that the
+    /// latter hasn't been edited since. Generations are never shared by
+    /// unrelated `Rope`s, so this returns `false` for any `Rope` other than
+    /// the one the slice was taken from or a clone of it, as long as neither
+    /// has been edited.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("foo bar");
+    ///
+    /// let snapshot = r.clone();
+    /// let bar = snapshot.byte_slice(4..);
+    /// assert!(bar.is_current(&r));
+    ///
+    /// r.insert(0, "baz ");
+    /// assert!(!bar.is_current(&r));
+    /// ```
+    #[inline]
+    pub fn is_current(&self, rope: &Rope) -> bool {
+        self.generation == rope.generation
+    }
+
+    /// Returns `true` if the `RopeSlice`'s byte length is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("");
+    /// assert!(r.byte_slice(..).is_empty());
+    ///
+    /// let r = Rope::from("foo");
+    /// assert!(!r.line_slice(..).is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.byte_len() == 0
+    }
+
+    /// Returns `true` if the given byte offset lies on a grapheme cluster
+    /// boundary.
+    ///
+    /// Grapheme cluster boundaries are always code point boundaries, so this
+    /// returns `false` if the byte offset is in the middle of a code point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("aargh!\r\n🐻‍❄️");
+    /// let s = r.line_slice(1..);
+    ///
+    /// assert!(s.is_grapheme_boundary(0));
+    /// assert!(s.is_grapheme_boundary(s.byte_len()));
+    /// assert!(!s.is_grapheme_boundary(4)); // between the 1st and 2nd code point of '🐻‍❄️'
+    /// assert!(!s.is_grapheme_boundary(1)); // inside the 1st code point of '🐻‍❄️'
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
+    #[cfg(feature = "graphemes")]
+    #[track_caller]
+    #[inline]
+    pub fn is_grapheme_boundary(&self, byte_offset: usize) -> bool {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        is_grapheme_boundary(self.chunks(), self.byte_len(), byte_offset)
+    }
+
+    /// Returns the last char of the `RopeSlice`, or `None` if it's empty.
+    ///
+    /// Only the last chunk is decoded, and it's reached in `O(log n)` time
+    /// instead of iterating over all the chars.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo 🐸\n");
+    /// let s = r.byte_slice(..8);
+    ///
+    /// assert_eq!(s.last_char(), Some('🐸'));
+    /// assert_eq!(r.byte_slice(0..0).last_char(), None);
+    /// ```
+    #[inline]
+    pub fn last_char(&self) -> Option<char> {
+        self.chars().next_back()
+    }
+
+    /// Returns the line at `line_index`, without its line terminator.
+    ///
+    /// Line indices are relative to the `RopeSlice`, so if the slice starts
+    /// in the middle of a line its line 0 is the rest of that line.
+    ///
+    /// If you want to include the line break consider taking a
+    /// [`line_slice()`](Self::line_slice()) in the
+    /// `line_index..line_index + 1` range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line index is out of bounds (i.e. greater than or equal
+    /// to [`line_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    /// let s = r.line_slice(..2);
+    ///
+    /// assert_eq!(s.line(0), "foo");
+    /// assert_eq!(s.line(1), "bar");
+    ///
+    /// let s = r.byte_slice(1..6);
+    ///
+    /// assert_eq!(s.line(0), "oo");
+    /// assert_eq!(s.line(1), "ba");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn line(self, line_index: usize) -> RopeSlice<'a> {
+        if line_index >= self.line_len() {
+            panic::line_offset_out_of_bounds(line_index, self.line_len());
+        }
+
+        let (tree_slice, ByteMetric(byte_offset)) =
+            self.tree_slice.slice_with_offset(
+                RawLineMetric(line_index)..RawLineMetric(line_index + 1),
+            );
+
+        let mut line = Self {
+            tree_slice,
+            has_trailing_newline: false,
+            byte_offset: self.byte_offset + byte_offset,
+            generation: self.generation,
+        };
+
+        if line.tree_slice.summary().line_breaks() == 1 {
+            line.truncate_trailing_line_break();
+        }
+
+        line
+    }
+
+    /// Returns the number of lines in the `RopeSlice` as an editor would
+    /// display them, i.e. the number of line breaks plus one.
+    ///
+    /// Unlike [`line_len()`](Self::line_len()), an empty `RopeSlice` counts as
+    /// one empty line and a trailing line break starts a new, empty line.
+    /// Note that `"\r\n"` counts as a single line break, and that a lone
+    /// `'\r'` is not a line break.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// for (text, line_count) in
+    ///     [("", 1), ("a", 1), ("a\n", 2), ("a\nb", 2), ("\n", 2), ("a\r\n", 2)]
+    /// {
+    ///     let r = Rope::from(text);
+    ///     let s = r.byte_slice(..);
+    ///     assert_eq!(s.line_count(), line_count);
+    /// }
+    /// ```
+    #[inline]
+    pub fn line_count(&self) -> usize {
+        self.newline_count() + 1
+    }
+
+    /// Returns the number of lines in the `RopeSlice`.
+    ///
+    /// The final line break is optional and doesn't count as a separate empty
+    /// line, and an empty `RopeSlice` has no lines. This is the number of items
+    /// yielded by [`lines()`](Self::lines()). See
+    /// [`line_count()`](Self::line_count()) and
+    /// [`newline_count()`](Self::newline_count()) for the other ways to count
+    /// lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("a\nb\r\n");
+    ///
+    /// let s = r.byte_slice(..);
+    /// assert_eq!(s.line_len(), 2);
+    ///
+    /// let s = r.byte_slice(..3);
+    /// assert_eq!(s.line_len(), 2);
+    ///
+    /// let s = r.byte_slice(..2);
+    /// assert_eq!(s.line_len(), 1);
+    ///
+    /// let s = r.byte_slice(..1);
+    /// assert_eq!(s.line_len(), 1);
+    ///
+    /// let s = r.byte_slice(..0);
+    /// assert_eq!(s.line_len(), 0);
+    /// ```
+    #[inline]
+    pub fn line_len(&self) -> usize {
+        self.tree_slice.summary().line_breaks() + 1
+            - (self.has_trailing_newline as usize)
+            - (self.is_empty() as usize)
+    }
+
+    /// Returns the line offset of the given byte.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    ///
+    /// let s = r.byte_slice(..);
+    /// assert_eq!(s.line_of_byte(0), 0);
+    /// assert_eq!(s.line_of_byte(4), 1);
+    ///
+    /// let s = r.line_slice(1..);
+    /// assert_eq!(s.line_of_byte(0), 0);
+    /// assert_eq!(s.line_of_byte(4), 0); // between the '\r' and the '\n'
+    /// assert_eq!(s.line_of_byte(s.byte_len()), 1);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn line_of_byte(&self, byte_offset: usize) -> usize {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        let RawLineMetric(line_offset) =
+            self.tree_slice.convert_measure(ByteMetric(byte_offset));
+
+        line_offset
+    }
+
+    /// Returns a sub-slice of this `RopeSlice` in the specified line range,
+    /// where the start and end of the range are interpreted as offsets.
+    ///
+    /// Every line in the range is included together with its line break, so
+    /// `line_slice(a..=b)` goes from the start of line `a` to the start of
+    /// line `b + 1`. If the range includes the last line and the `RopeSlice`
+    /// doesn't end with a line break, the slice extends to the end of the
+    /// `RopeSlice`.
+    ///
+    /// See [`line_slice_content()`](Self::line_slice_content()) to leave out
+    /// the line break of the last line in the range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start is greater than the end or if the end is out of
+    /// bounds (i.e. greater than [`line_len()`](Self::line_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\nfoobar\n");
+    /// let s = r.byte_slice(2..17);
+    ///
+    /// assert_eq!(s.line_slice(..1), "o\n");
+    /// assert_eq!(s.line_slice(1..3), "bar\r\nbaz\n");
+    /// assert_eq!(s.line_slice(3..), "foob");
+    /// assert_eq!(s.line_slice(2..=3), "baz\nfoob");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn line_slice<R>(self, line_range: R) -> RopeSlice<'a>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(line_range, 0, self.line_len());
+
+        if start > end {
+            panic::line_start_after_end(start, end);
+        }
+
+        if end > self.line_len() {
+            panic::line_offset_out_of_bounds(end, self.line_len());
+        }
+
+        // The last line isn't counted by the `RawLineMetric` if it doesn't
+        // end with a line break, so an empty range past the last line break
+        // can't be sliced from the tree.
+        if start == end {
+            return self.empty_slice_at(self.byte_of_line(start));
+        }
+
+        let (tree_slice, ByteMetric(byte_offset)) = self
+            .tree_slice
+            .slice_with_offset(RawLineMetric(start)..RawLineMetric(end));
+
+        RopeSlice::new(
+            tree_slice,
+            self.byte_offset + byte_offset,
+            self.generation,
+        )
+    }
+
+    /// Returns a sub-slice of this `RopeSlice` in the specified line range,
+    /// without the line break ending the last line of the range.
+    ///
+    /// Only the line break of the last line is left out: the lines before it
+    /// keep theirs, so `line_slice_content(a..b)` goes from the start of line
+    /// `a` to the end of the content of line `b - 1`. This is the text a
+    /// user sees when selecting the lines, and it's equal to
+    /// [`line(a)`](Self::line()) for a single line. An empty range returns
+    /// an empty slice at the start of line `a`.
+    ///
+    /// See [`line_slice_raw()`](Self::line_slice_raw()) to also include the
+    /// last line break.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start is greater than the end or if the end is out of
+    /// bounds (i.e. greater than [`line_len()`](Self::line_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\nfoobar\n");
+    /// let s = r.byte_slice(2..17);
+    ///
+    /// assert_eq!(s.line_slice_content(..1), "o");
+    /// assert_eq!(s.line_slice_content(1..3), "bar\r\nbaz");
+    /// assert_eq!(s.line_slice_content(3..), "foob");
+    /// assert_eq!(s.line_slice_content(1..1), "");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn line_slice_content<R>(self, line_range: R) -> RopeSlice<'a>
+    where
+        R: RangeBounds<usize>,
+    {
+        let slice = self.line_slice(line_range);
+
+        if !slice.has_trailing_newline {
+            return slice;
+        }
+
+        let mut end = slice.byte_len() - 1;
+
+        if end > 0 && slice.byte(end - 1) == b'\r' {
+            end -= 1;
+        }
+
+        slice.byte_slice(..end)
+    }
+
+    /// Returns a sub-slice of this `RopeSlice` in the specified line range,
+    /// including the line break ending the last line of the range.
+    ///
+    /// This is the same as [`line_slice()`](Self::line_slice()), under a
+    /// name that makes it explicit that every line of the range comes with
+    /// its line break. Only the last line of the `RopeSlice` can be missing
+    /// one, if the `RopeSlice` doesn't end with a line break.
+    ///
+    /// See [`line_slice_content()`](Self::line_slice_content()) to leave out
+    /// the last line break.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start is greater than the end or if the end is out of
+    /// bounds (i.e. greater than [`line_len()`](Self::line_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\nfoobar\n");
+    /// let s = r.byte_slice(2..17);
+    ///
+    /// assert_eq!(s.line_slice_raw(..1), "o\n");
+    /// assert_eq!(s.line_slice_raw(1..3), "bar\r\nbaz\n");
+    /// assert_eq!(s.line_slice_raw(3..), "foob");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn line_slice_raw<R>(self, line_range: R) -> RopeSlice<'a>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.line_slice(line_range)
+    }
+
+    /// Returns the line at `line_index` like [`line()`](Self::line()),
+    /// together with the line terminator that follows it.
+    ///
+    /// The terminator is `None` for the last line if the `RopeSlice` doesn't
+    /// end with a line break. There's no variant for a lone `'\r'` since it's
+    /// not a line break, and a `"\r\n"` whose bytes are stored in different
+    /// chunks is still detected as a single [`LineEnding::CrLf`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line index is out of bounds (i.e. greater than or equal
+    /// to [`line_len()`](Self::line_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{LineEnding, Rope};
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    /// let s = r.byte_slice(..);
+    ///
+    /// let (line, ending) = s.line_with_ending(0);
+    /// assert_eq!(line, "foo");
+    /// assert_eq!(ending, Some(LineEnding::Lf));
+    ///
+    /// assert_eq!(s.line_with_ending(1).1, Some(LineEnding::CrLf));
+    /// assert_eq!(s.line_with_ending(2).1, None);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn line_with_ending(
+        self,
+        line_index: usize,
+    ) -> (RopeSlice<'a>, Option<LineEnding>) {
+        let line = self.line(line_index);
+
+        let line_end = line.byte_offset - self.byte_offset + line.byte_len();
+
+        let ending = match self.byte_of_line(line_index + 1) - line_end {
+            0 => None,
+            1 => Some(LineEnding::Lf),
+            _ => Some(LineEnding::CrLf),
+        };
+
+        (line, ending)
+    }
+
+    /// Returns an iterator over the lines of this `RopeSlice`, not including
+    /// the line terminators.
+    ///
+    /// The final line break is optional and doesn't cause the iterator to
+    /// return a final empty line.
+    ///
+    /// The iterator is lazy: creating it doesn't do any work proportional to
+    /// the size of the text, and each line is located when it's yielded, so
+    /// e.g. `lines().take(n)` only visits the chunks of the first `n` lines.
+    ///
+    /// If you want to include the line breaks consider using the
+    /// [`raw_lines()`](Self::raw_lines()) method instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\n");
+    /// let s = r.line_slice(1..);
+    ///
+    /// let mut lines = s.lines();
+    ///
+    /// assert_eq!("bar", lines.next().unwrap());
+    /// assert_eq!("baz", lines.next().unwrap());
+    /// assert_eq!(None, lines.next());
+    /// ```
+    #[inline]
+    pub fn lines(&self) -> Lines<'a> {
+        Lines::from(self)
+    }
+
+    /// Returns an iterator over the lines of this `RopeSlice` like
+    /// [`lines()`](Self::lines()), starting from the line containing the
+    /// given byte offset.
+    ///
+    /// The first line is always yielded from its start, even if the byte
+    /// offset is in the middle of it. If the byte offset is equal to
+    /// [`byte_len()`](Self::byte_len()) and the `RopeSlice` ends with a line
+    /// break the iterator is empty, consistently with `lines()` not yielding
+    /// a final empty line.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    /// let s = r.byte_slice(2..);
+    ///
+    /// let mut lines = s.lines_at_byte(1);
+    ///
+    /// assert_eq!("o", lines.next().unwrap());
+    /// assert_eq!("bar", lines.next().unwrap());
+    /// assert_eq!("baz", lines.next().unwrap());
+    /// assert_eq!(None, lines.next());
+    ///
+    /// assert_eq!("baz", s.lines_at_byte(s.byte_len()).next().unwrap());
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn lines_at_byte(&self, byte_offset: usize) -> Lines<'a> {
+        let line_offset = self.line_of_byte(byte_offset);
+        self.line_slice(line_offset..).lines()
+    }
+
+    /// Returns an iterator over the lines of this `RopeSlice` like
+    /// [`lines()`](Self::lines()), but yielding them as `Cow<str>`s.
+    ///
+    /// Lines contained in a single chunk are borrowed without allocating,
+    /// while lines spanning multiple chunks are copied into an owned
+    /// `String`. This makes it cheap to process the lines of a text made of
+    /// mostly short lines as `&str`s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\n");
+    ///
+    /// let lines = r.byte_slice(..).lines_cow().collect::<Vec<_>>();
+    ///
+    /// assert_eq!(lines, ["foo", "bar", "baz"]);
+    /// ```
+    #[inline]
+    pub fn lines_cow(&self) -> LinesCow<'a> {
+        LinesCow::from(self.lines())
+    }
+
+    /// Returns an iterator over the lines of this `RopeSlice` like
+    /// [`lines()`](Self::lines()), splitting the lines longer than `width`
+    /// bytes into several visual lines, which is what's needed to render the
+    /// text in a viewport that soft-wraps.
+    ///
+    /// Lines that fit within `width` are yielded whole. Longer lines are
+    /// broken every `width` bytes, moving each break back to the previous
+    /// char boundary if it doesn't lie on one, so every visual line is at
+    /// most `width` bytes long unless a single char is wider than `width`,
+    /// in which case it gets a visual line of its own.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nLorem ipsum\n\n🐸🐸");
+    ///
+    /// let lines = r.byte_slice(..).wrapped_lines(5).collect::<Vec<_>>();
+    ///
+    /// assert_eq!(lines, ["foo", "Lorem", " ipsu", "m", "", "🐸", "🐸"]);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn wrapped_lines(&self, width: usize) -> WrappedLines<'a> {
+        WrappedLines::new(self.lines(), width)
+    }
+
+    /// Returns the number of lines in this `RopeSlice` when splitting on the
+    /// given kind of line breaks.
+    ///
+    /// With [`LineBreakKind::Lf`](crate::LineBreakKind::Lf) this is the same
+    /// as [`line_len()`](Self::line_len()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{LineBreakKind, Rope};
+    /// #
+    /// let r = Rope::from("foo\u{2028}bar\r\nbaz\u{0085}");
+    /// let s = r.byte_slice(..);
+    ///
+    /// assert_eq!(s.line_len_with(LineBreakKind::Lf), 2);
+    /// assert_eq!(s.line_len_with(LineBreakKind::UnicodeFull), 3);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "unicode-lines")))]
+    #[cfg(feature = "unicode-lines")]
+    #[inline]
+    pub fn line_len_with(&self, kind: LineBreakKind) -> usize {
+        match kind {
+            LineBreakKind::Lf => self.line_len(),
+
+            LineBreakKind::UnicodeFull => {
+                let has_trailing_line_break = self
+                    .chars()
+                    .next_back()
+                    .map_or(false, is_unicode_line_break);
+
+                self.tree_slice.summary().unicode_line_breaks() + 1
+                    - (has_trailing_line_break as usize)
+                    - (self.is_empty() as usize)
+            },
+        }
+    }
+
+    /// Returns an iterator over the lines of this `RopeSlice` split on the
+    /// given kind of line breaks, not including the line terminators.
+    ///
+    /// With [`LineBreakKind::Lf`](crate::LineBreakKind::Lf) this yields the
+    /// same lines as [`lines()`](Self::lines()). With
+    /// [`LineBreakKind::UnicodeFull`](crate::LineBreakKind::UnicodeFull) the
+    /// lines are also split on next lines (NEL), line separators (LS) and
+    /// paragraph separators (PS). In both cases the final line break is
+    /// optional and doesn't cause the iterator to return a final empty line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{LineBreakKind, Rope};
+    /// #
+    /// let r = Rope::from("foo\u{2028}bar\r\nbaz\u{0085}");
+    /// let s = r.byte_slice(..);
+    ///
+    /// let mut lines = s.lines_with(LineBreakKind::UnicodeFull);
+    ///
+    /// assert_eq!("foo", lines.next().unwrap());
+    /// assert_eq!("bar", lines.next().unwrap());
+    /// assert_eq!("baz", lines.next().unwrap());
+    /// assert_eq!(None, lines.next());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "unicode-lines")))]
+    #[cfg(feature = "unicode-lines")]
+    #[inline]
+    pub fn lines_with(&self, kind: LineBreakKind) -> LinesWith<'a> {
+        LinesWith::new(*self, kind)
+    }
+
+    /// Returns an iterator over the non-overlapping matches of `needle` in
+    /// this `RopeSlice`, yielded as `RopeSlice`s from left to right.
+    ///
+    /// Matches spanning multiple chunks are found as well. If `needle` is
+    /// empty the iterator doesn't yield anything.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo bar foo baz foo");
+    /// let s = r.byte_slice(1..);
+    ///
+    /// let mut matches = s.matches("foo");
+    ///
+    /// assert_eq!(matches.next().unwrap(), s.byte_slice(7..10));
+    /// assert_eq!(matches.next().unwrap(), s.byte_slice(15..18));
+    /// assert_eq!(None, matches.next());
+    /// ```
+    #[inline]
+    pub fn matches(&self, needle: &'a str) -> Matches<'a> {
+        Matches::new(*self, needle)
+    }
+
+    /// Returns the length of the `RopeSlice` in the `M` metric.
+    ///
+    /// See [`Rope::measure()`] for more.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// use crop::metrics::{Byte, Line};
+    ///
+    /// let r = Rope::from("ƒoo\nbär\r\nbaz");
+    /// let s = r.byte_slice(5..);
+    ///
+    /// assert_eq!(s.measure::<Byte>(), s.byte_len());
+    /// assert_eq!(s.measure::<Line>(), 2);
+    /// ```
+    #[inline]
+    pub fn measure<M: Metric>(&self) -> usize {
+        text_metric::measure::<M>(*self)
+    }
+
+    /// Returns the number of line breaks in the `RopeSlice`.
+    ///
+    /// Note that `"\r\n"` counts as a single line break, and that a lone
+    /// `'\r'` is not a line break.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// for (text, newline_count) in
+    ///     [("", 0), ("a", 0), ("a\n", 1), ("a\nb", 1), ("\n", 1), ("a\r\n", 1)]
+    /// {
+    ///     let r = Rope::from(text);
+    ///     let s = r.byte_slice(..);
+    ///     assert_eq!(s.newline_count(), newline_count);
+    /// }
+    /// ```
+    #[inline]
+    pub fn newline_count(&self) -> usize {
+        self.tree_slice.summary().line_breaks()
+    }
+
+    /// Returns the byte range spanned by this `RopeSlice` in the `Rope` it
+    /// was sliced from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    ///
+    /// assert_eq!(r.line(1).parent_range(), 4..7);
+    /// assert_eq!(r.line(1).byte_slice(1..).parent_range(), 5..7);
+    /// assert_eq!(r.byte_slice(..).parent_range(), 0..r.byte_len());
+    /// ```
+    #[inline]
+    pub fn parent_range(&self) -> Range<usize> {
+        self.byte_offset..self.byte_offset + self.byte_len()
+    }
+
+    /// Returns an iterator over the lines of this `RopeSlice`, including the
+    /// line terminators.
+    ///
+    /// The final line break is optional and doesn't cause the iterator to
+    /// return a final empty line.
+    ///
+    /// If you don't  want to include the line breaks consider using the
+    /// [`raw_lines()`](Self::raw_lines()) method instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\n");
+    /// let s = r.byte_slice(..r.byte_len() - 1);
+    ///
+    /// let mut raw_lines = s.raw_lines();
+    ///
+    /// assert_eq!("foo\n", raw_lines.next().unwrap());
+    /// assert_eq!("bar\r\n", raw_lines.next().unwrap());
+    /// assert_eq!("baz", raw_lines.next().unwrap());
+    /// assert_eq!(None, raw_lines.next());
+    ///
+    /// let s = r.byte_slice(..);
+    ///
+    /// let mut raw_lines = s.raw_lines();
+    ///
+    /// assert_eq!("foo\n", raw_lines.next().unwrap());
+    /// assert_eq!("bar\r\n", raw_lines.next().unwrap());
+    /// assert_eq!("baz\n", raw_lines.next().unwrap());
+    /// assert_eq!(None, raw_lines.next());
+    /// ```
+    #[inline]
+    pub fn raw_lines(&self) -> RawLines<'a> {
+        RawLines::from(self)
+    }
+
+    /// Returns a [`Reader`] over the bytes of the `RopeSlice`, implementing
+    /// [`Read`](std::io::Read) and [`BufRead`](std::io::BufRead).
+    ///
+    /// See [`Rope::reader()`] for more.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io::BufRead;
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    ///
+    /// let mut line = String::new();
+    /// r.byte_slice(4..).reader().read_line(&mut line).unwrap();
+    ///
+    /// assert_eq!(line, "bar\r\n");
+    /// ```
+    #[inline]
+    pub fn reader(&self) -> Reader<'a> {
+        Reader::new(self.chunks())
     }
 
-    /// Returns `true` if the `RopeSlice`'s byte length is zero.
+    /// Returns the byte offset of the last char of the `RopeSlice` satisfying
+    /// the given predicate, or `None` if no char does.
+    ///
+    /// See [`Rope::rfind_char()`] for more.
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("");
-    /// assert!(r.byte_slice(..).is_empty());
+    /// let r = Rope::from("ƒoo bär\tbaz");
+    /// let s = r.byte_slice(..8);
     ///
-    /// let r = Rope::from("foo");
-    /// assert!(!r.line_slice(..).is_empty());
+    /// assert_eq!(s.rfind_char(char::is_whitespace), Some(4));
+    /// assert_eq!(s.rfind_char(|ch| ch == 'z'), None);
     /// ```
     #[inline]
-    pub fn is_empty(&self) -> bool {
-        self.byte_len() == 0
+    pub fn rfind_char<F>(&self, predicate: F) -> Option<usize>
+    where
+        F: FnMut(char) -> bool,
+    {
+        rfind_char(self.chunks(), self.byte_len(), predicate)
     }
 
-    /// Returns `true` if the given byte offset lies on a grapheme cluster
-    /// boundary.
-    ///
-    /// # Panics
+    /// Returns the SHA-256 digest of the contents of this `RopeSlice`,
+    /// formatted as a lowercase hexadecimal string.
     ///
-    /// Panics if the byte offset is out of bounds (i.e. greater than
-    /// [`byte_len()`](Self::byte_len())).
+    /// Like [`content_hash()`](Self::content_hash()) the digest is computed by
+    /// streaming the bytes of each chunk and doesn't depend on how the text
+    /// is split into chunks.
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("aargh!\r\n🐻‍❄️");
-    /// let s = r.line_slice(1..);
+    /// let r = Rope::from("abc");
     ///
-    /// assert!(s.is_grapheme_boundary(0));
-    /// assert!(s.is_grapheme_boundary(s.byte_len()));
-    /// assert!(!s.is_grapheme_boundary(4)); // between the 1st and 2nd code point of '🐻‍❄️'
+    /// assert_eq!(
+    ///     r.byte_slice(..).sha256_hex(),
+    ///     "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    /// );
     /// ```
-    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
-    #[cfg(feature = "graphemes")]
-    #[track_caller]
+    #[cfg_attr(docsrs, doc(cfg(feature = "sha256")))]
+    #[cfg(feature = "sha256")]
     #[inline]
-    pub fn is_grapheme_boundary(&self, byte_offset: usize) -> bool {
-        if byte_offset > self.byte_len() {
-            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
-        }
-
-        is_grapheme_boundary(self.chunks(), self.byte_len(), byte_offset)
+    pub fn sha256_hex(&self) -> String {
+        sha256_hex_chunks(self.chunks())
     }
 
-    /// Returns the line at `line_index`, without its line terminator.
+    /// Returns an immutable sub-slice of the `RopeSlice` in the specified
+    /// byte range.
     ///
-    /// If you want to include the line break consider taking a
-    /// [`line_slice()`](Self::line_slice()) in the
-    /// `line_index..line_index + 1` range.
+    /// This is a shorter name for [`byte_slice()`](Self::byte_slice()), see
+    /// [`Rope::slice()`] for more.
     ///
     /// # Panics
     ///
-    /// Panics if the line index is out of bounds (i.e. greater than or equal
-    /// to [`line_len()`](Self::byte_len())).
+    /// Panics if the start or the end of the byte range don't lie on a code
+    /// point boundary, if the start is greater than the end or if the end is
+    /// out of bounds (i.e. greater than [`byte_len()`](Self::byte_len())).
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("foo\nbar\r\nbaz");
-    /// let s = r.line_slice(..2);
+    /// let r = Rope::from("Hello Earth 🌎!");
+    /// let s = r.slice(6..);
     ///
-    /// assert_eq!(s.line(0), "foo");
-    /// assert_eq!(s.line(1), "bar");
+    /// assert_eq!(s.slice(..5), "Earth");
     /// ```
     #[track_caller]
     #[inline]
-    pub fn line(self, line_index: usize) -> RopeSlice<'a> {
-        if line_index >= self.line_len() {
-            panic::line_offset_out_of_bounds(line_index, self.line_len());
-        }
-
-        let tree_slice = self
-            .tree_slice
-            .slice(RawLineMetric(line_index)..RawLineMetric(line_index + 1));
-
-        let mut line = Self { tree_slice, has_trailing_newline: false };
-
-        if line.tree_slice.summary().line_breaks() == 1 {
-            line.truncate_trailing_line_break();
-        }
-
-        line
+    pub fn slice<R>(self, byte_range: R) -> RopeSlice<'a>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.byte_slice(byte_range)
     }
 
-    /// Returns the number of lines in the `RopeSlice`.
+    /// Returns the sub-slice of this `RopeSlice` after `prefix` if it starts
+    /// with it, or `None` otherwise.
     ///
-    /// The final line break is optional and doesn't count as a separate empty
-    /// line.
+    /// Like [`str::strip_prefix()`], the prefix is only removed once. The
+    /// returned slice shares the same `Rope`, so no text is copied.
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("a\nb\r\n");
-    ///
+    /// let r = Rope::from("foo:bar");
     /// let s = r.byte_slice(..);
-    /// assert_eq!(s.line_len(), 2);
-    ///
-    /// let s = r.byte_slice(..3);
-    /// assert_eq!(s.line_len(), 2);
-    ///
-    /// let s = r.byte_slice(..2);
-    /// assert_eq!(s.line_len(), 1);
-    ///
-    /// let s = r.byte_slice(..1);
-    /// assert_eq!(s.line_len(), 1);
     ///
-    /// let s = r.byte_slice(..0);
-    /// assert_eq!(s.line_len(), 0);
+    /// assert_eq!(s.strip_prefix("foo:").unwrap(), "bar");
+    /// assert_eq!(s.strip_prefix("bar"), None);
     /// ```
     #[inline]
-    pub fn line_len(&self) -> usize {
-        self.tree_slice.summary().line_breaks() + 1
-            - (self.has_trailing_newline as usize)
-            - (self.is_empty() as usize)
+    pub fn strip_prefix(self, prefix: &str) -> Option<RopeSlice<'a>> {
+        (prefix.len() <= self.byte_len()
+            && chunks_start_with(self.chunks(), prefix.as_bytes()))
+        .then(|| self.byte_slice(prefix.len()..))
     }
 
-    /// Returns the line offset of the given byte.
-    ///
-    /// # Panics
+    /// Returns the sub-slice of this `RopeSlice` before `suffix` if it ends
+    /// with it, or `None` otherwise.
     ///
-    /// Panics if the byte offset is out of bounds (i.e. greater than
-    /// [`byte_len()`](Self::byte_len())).
+    /// Like [`str::strip_suffix()`], the suffix is only removed once. The
+    /// returned slice shares the same `Rope`, so no text is copied.
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("foo\nbar\r\nbaz");
-    ///
+    /// let r = Rope::from("foo:bar");
     /// let s = r.byte_slice(..);
-    /// assert_eq!(s.line_of_byte(0), 0);
-    /// assert_eq!(s.line_of_byte(4), 1);
     ///
-    /// let s = r.line_slice(1..);
-    /// assert_eq!(s.line_of_byte(0), 0);
-    /// assert_eq!(s.line_of_byte(4), 0); // between the '\r' and the '\n'
-    /// assert_eq!(s.line_of_byte(s.byte_len()), 1);
+    /// assert_eq!(s.strip_suffix(":bar").unwrap(), "foo");
+    /// assert_eq!(s.strip_suffix("foo"), None);
     /// ```
-    #[track_caller]
     #[inline]
-    pub fn line_of_byte(&self, byte_offset: usize) -> usize {
-        if byte_offset > self.byte_len() {
-            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
-        }
-
-        let RawLineMetric(line_offset) =
-            self.tree_slice.convert_measure(ByteMetric(byte_offset));
-
-        line_offset
+    pub fn strip_suffix(self, suffix: &str) -> Option<RopeSlice<'a>> {
+        (suffix.len() <= self.byte_len()
+            && chunks_end_with(self.chunks(), suffix.as_bytes()))
+        .then(|| self.byte_slice(..self.byte_len() - suffix.len()))
     }
 
-    /// Returns a sub-slice of this `RopeSlice` in the specified line range,
-    /// where the start and end of the range are interpreted as offsets.
+    /// Divides the `RopeSlice` into two slices at the given char offset.
+    ///
+    /// The first slice contains the chars in `[0, char_offset)` and the second
+    /// one the chars in `[char_offset, len)`. `RopeSlice`s don't keep track of
+    /// their number of chars, so unless the text is ASCII this counts the
+    /// chars of every chunk before the one containing the offset, like
+    /// [`char_len()`](Self::char_len()) does for the whole text.
     ///
     /// # Panics
     ///
-    /// Panics if the start is greater than the end or if the end is out of
-    /// bounds (i.e. greater than [`line_len()`](Self::line_len())).
+    /// Panics if the char offset is out of bounds (i.e. greater than
+    /// [`char_len()`](Self::char_len())). See
+    /// [`split_at_char_checked()`](Self::split_at_char_checked()) for a
+    /// non-panicking version.
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("foo\nbar\r\nbaz\nfoobar\n");
-    /// let s = r.byte_slice(2..17);
+    /// let r = Rope::from("ƒoo 🐸 bär");
+    /// let s = r.byte_slice(..);
     ///
-    /// assert_eq!(s.line_slice(..1), "o\n");
-    /// assert_eq!(s.line_slice(1..3), "bar\r\nbaz\n");
-    /// assert_eq!(s.line_slice(3..), "foob");
+    /// let (left, right) = s.split_at_char(4);
+    /// assert_eq!(left, "ƒoo ");
+    /// assert_eq!(right, "🐸 bär");
+    ///
+    /// let (left, right) = s.split_at_char(9);
+    /// assert_eq!(left, "ƒoo 🐸 bär");
+    /// assert_eq!(right, "");
     /// ```
     #[track_caller]
     #[inline]
-    pub fn line_slice<R>(self, line_range: R) -> RopeSlice<'a>
-    where
-        R: RangeBounds<usize>,
-    {
-        let (start, end) =
-            range_bounds_to_start_end(line_range, 0, self.line_len());
-
-        if start > end {
-            panic::line_start_after_end(start, end);
-        }
-
-        if end > self.line_len() {
-            panic::line_offset_out_of_bounds(end, self.line_len());
+    pub fn split_at_char(
+        self,
+        char_offset: usize,
+    ) -> (RopeSlice<'a>, RopeSlice<'a>) {
+        match self.split_at_char_checked(char_offset) {
+            Some(split) => split,
+            None => {
+                panic::char_index_out_of_bounds(char_offset, self.char_len())
+            },
         }
-
-        self.tree_slice.slice(RawLineMetric(start)..RawLineMetric(end)).into()
     }
 
-    /// Returns an iterator over the lines of this `RopeSlice`, not including
-    /// the line terminators.
-    ///
-    /// The final line break is optional and doesn't cause the iterator to
-    /// return a final empty line.
-    ///
-    /// If you want to include the line breaks consider using the
-    /// [`raw_lines()`](Self::raw_lines()) method instead.
+    /// Divides the `RopeSlice` into two slices at the given char offset like
+    /// [`split_at_char()`](Self::split_at_char()), returning `None` if the
+    /// offset is out of bounds.
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("foo\nbar\r\nbaz\n");
-    /// let s = r.line_slice(1..);
-    ///
-    /// let mut lines = s.lines();
+    /// let r = Rope::from("ƒoo 🐸 bär");
+    /// let s = r.byte_slice(..);
     ///
-    /// assert_eq!("bar", lines.next().unwrap());
-    /// assert_eq!("baz", lines.next().unwrap());
-    /// assert_eq!(None, lines.next());
+    /// assert!(s.split_at_char_checked(9).is_some());
+    /// assert!(s.split_at_char_checked(10).is_none());
     /// ```
     #[inline]
-    pub fn lines(&self) -> Lines<'a> {
-        Lines::from(self)
+    pub fn split_at_char_checked(
+        self,
+        char_offset: usize,
+    ) -> Option<(RopeSlice<'a>, RopeSlice<'a>)> {
+        let byte_offset = if self.is_ascii() {
+            (char_offset <= self.byte_len()).then_some(char_offset)?
+        } else {
+            byte_of_char(self.chunks(), char_offset)?
+        };
+
+        Some((self.byte_slice(..byte_offset), self.byte_slice(byte_offset..)))
     }
 
-    /// Returns an iterator over the lines of this `RopeSlice`, including the
-    /// line terminators.
-    ///
-    /// The final line break is optional and doesn't cause the iterator to
-    /// return a final empty line.
+    /// Returns an iterator over the sub-slices of this `RopeSlice` separated by
+    /// any amount of whitespace, where whitespace is defined as in
+    /// [`char::is_whitespace()`].
     ///
-    /// If you don't  want to include the line breaks consider using the
-    /// [`raw_lines()`](Self::raw_lines()) method instead.
+    /// Like [`str::split_whitespace()`], this never yields empty slices, even
+    /// if the `RopeSlice` starts or ends with whitespace.
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("foo\nbar\r\nbaz\n");
-    /// let s = r.byte_slice(..r.byte_len() - 1);
-    ///
-    /// let mut raw_lines = s.raw_lines();
-    ///
-    /// assert_eq!("foo\n", raw_lines.next().unwrap());
-    /// assert_eq!("bar\r\n", raw_lines.next().unwrap());
-    /// assert_eq!("baz", raw_lines.next().unwrap());
-    /// assert_eq!(None, raw_lines.next());
-    ///
-    /// let s = r.byte_slice(..);
+    /// let r = Rope::from("  foo\tbar\r\n baz  ");
+    /// let s = r.byte_slice(4..);
     ///
-    /// let mut raw_lines = s.raw_lines();
+    /// let mut words = s.split_whitespace();
     ///
-    /// assert_eq!("foo\n", raw_lines.next().unwrap());
-    /// assert_eq!("bar\r\n", raw_lines.next().unwrap());
-    /// assert_eq!("baz\n", raw_lines.next().unwrap());
-    /// assert_eq!(None, raw_lines.next());
+    /// assert_eq!("o", words.next().unwrap());
+    /// assert_eq!("bar", words.next().unwrap());
+    /// assert_eq!("baz", words.next().unwrap());
+    /// assert_eq!(None, words.next());
     /// ```
     #[inline]
-    pub fn raw_lines(&self) -> RawLines<'a> {
-        RawLines::from(self)
+    pub fn split_whitespace(&self) -> SplitWhitespace<'a> {
+        SplitWhitespace::new(*self)
     }
 
     /// Removes the last char from the range spanned by this slice.
@@ -630,6 +2126,64 @@ impl<'a> RopeSlice<'a> {
         }
     }
 
+    /// Returns the sub-slice of this `RopeSlice` with all the suffixes
+    /// matching `pattern` repeatedly removed.
+    ///
+    /// If `pattern` is empty the slice is returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("--foo--bar----");
+    /// let s = r.byte_slice(..);
+    ///
+    /// assert_eq!(s.trim_end_matches("--"), "--foo--bar");
+    /// assert_eq!(s.trim_end_matches("---"), "--foo--bar-");
+    /// ```
+    #[inline]
+    pub fn trim_end_matches(mut self, pattern: &str) -> RopeSlice<'a> {
+        if pattern.is_empty() {
+            return self;
+        }
+
+        while let Some(stripped) = self.strip_suffix(pattern) {
+            self = stripped;
+        }
+
+        self
+    }
+
+    /// Returns the sub-slice of this `RopeSlice` with all the prefixes
+    /// matching `pattern` repeatedly removed.
+    ///
+    /// If `pattern` is empty the slice is returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("----foo--bar--");
+    /// let s = r.byte_slice(..);
+    ///
+    /// assert_eq!(s.trim_start_matches("--"), "foo--bar--");
+    /// assert_eq!(s.trim_start_matches("---"), "-foo--bar--");
+    /// ```
+    #[inline]
+    pub fn trim_start_matches(mut self, pattern: &str) -> RopeSlice<'a> {
+        if pattern.is_empty() {
+            return self;
+        }
+
+        while let Some(stripped) = self.strip_prefix(pattern) {
+            self = stripped;
+        }
+
+        self
+    }
+
     /// Returns the number of UTF-16 code units this `RopeSlice` would span if
     /// it stores its contents as UTF-16 instead of UTF-8.
     ///
@@ -727,29 +2281,25 @@ impl<'a> RopeSlice<'a> {
             panic::utf16_offset_out_of_bounds(end, self.utf16_len());
         }
 
-        self.tree_slice.slice(Utf16Metric(start)..Utf16Metric(end)).into()
-    }
-}
-
-impl<'a> From<TreeSlice<'a, { Rope::arity() }, RopeChunk>> for RopeSlice<'a> {
-    #[inline]
-    fn from(tree_slice: TreeSlice<'a, { Rope::arity() }, RopeChunk>) -> Self {
-        Self {
-            has_trailing_newline: tree_slice
-                .end_slice()
-                .has_trailing_newline(),
+        let (tree_slice, ByteMetric(byte_offset)) = self
+            .tree_slice
+            .slice_with_offset(Utf16Metric(start)..Utf16Metric(end));
 
+        RopeSlice::new(
             tree_slice,
-        }
+            self.byte_offset + byte_offset,
+            self.generation,
+        )
     }
 }
 
 impl core::fmt::Debug for RopeSlice<'_> {
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-        f.write_str("RopeSlice(\"")?;
-        debug_chunks(self.chunks(), f)?;
-        f.write_str("\")")
+        f.debug_struct("RopeSlice")
+            .field("bytes", &self.parent_range())
+            .field("text", &DebugPreview(self.chunks()))
+            .finish()
     }
 }
 
@@ -763,6 +2313,28 @@ impl core::fmt::Display for RopeSlice<'_> {
     }
 }
 
+impl From<RopeSlice<'_>> for String {
+    /// Copies the text of the `RopeSlice` into a new `String`.
+    ///
+    /// The `String` is allocated once with the byte length of the slice, and
+    /// the chunks are then copied into it one after the other.
+    ///
+    /// This is much faster than collecting the `char`s of the text into a
+    /// `String`. `to_string()` also copies whole chunks, but through the
+    /// `Display` implementation, so it can't allocate the `String` upfront.
+    #[inline]
+    fn from(slice: RopeSlice<'_>) -> Self {
+        chunks_to_string(slice.chunks(), slice.byte_len())
+    }
+}
+
+impl core::hash::Hash for RopeSlice<'_> {
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        hash_chunks(self.chunks(), state);
+    }
+}
+
 impl core::cmp::PartialEq<RopeSlice<'_>> for RopeSlice<'_> {
     #[inline]
     fn eq(&self, rhs: &RopeSlice<'_>) -> bool {
@@ -835,4 +2407,32 @@ impl core::cmp::PartialEq<RopeSlice<'_>> for alloc::borrow::Cow<'_, str> {
     }
 }
 
+impl core::cmp::PartialEq<[u8]> for RopeSlice<'_> {
+    #[inline]
+    fn eq(&self, rhs: &[u8]) -> bool {
+        (self.byte_len() == rhs.len()) && chunks_eq_bytes(self.chunks(), rhs)
+    }
+}
+
+impl core::cmp::PartialEq<RopeSlice<'_>> for [u8] {
+    #[inline]
+    fn eq(&self, rhs: &RopeSlice<'_>) -> bool {
+        rhs == self
+    }
+}
+
+impl core::cmp::PartialEq<&[u8]> for RopeSlice<'_> {
+    #[inline]
+    fn eq(&self, rhs: &&[u8]) -> bool {
+        self == *rhs
+    }
+}
+
+impl core::cmp::PartialEq<RopeSlice<'_>> for &[u8] {
+    #[inline]
+    fn eq(&self, rhs: &RopeSlice<'_>) -> bool {
+        rhs == self
+    }
+}
+
 impl core::cmp::Eq for RopeSlice<'_> {}
@@ -0,0 +1,139 @@
+//! This module contains the metrics exported as `crop::metrics`, which can
+//! be used to measure and convert offsets generically over their unit.
+
+use super::RopeSlice;
+
+mod sealed {
+    use super::RopeSlice;
+
+    pub trait Sealed {
+        /// Returns the length of the slice in this metric.
+        fn len(slice: RopeSlice<'_>) -> usize;
+
+        /// Returns the byte offset corresponding to the given offset in this
+        /// metric, panicking if it's out of bounds.
+        fn to_byte(slice: RopeSlice<'_>, offset: usize) -> usize;
+
+        /// Returns the offset in this metric corresponding to the given byte
+        /// offset, panicking if it's out of bounds.
+        fn from_byte(slice: RopeSlice<'_>, byte_offset: usize) -> usize;
+    }
+}
+
+/// A unit of measure of the text of [`Rope`](crate::Rope)s and
+/// [`RopeSlice`](crate::RopeSlice)s.
+///
+/// This trait is sealed and can't be implemented outside of this crate. Every
+/// metric is measured and converted in `O(log n)` time, which is why there's
+/// no metric for chars: the number of chars in a chunk isn't kept track of.
+pub trait Metric: sealed::Sealed {}
+
+/// Measures text in bytes, like
+/// [`Rope::byte_len()`](crate::Rope::byte_len()).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Byte;
+
+/// Measures text in lines, like
+/// [`Rope::line_len()`](crate::Rope::line_len()).
+///
+/// Converting a line offset to another metric gives the offset of the start of
+/// that line, while converting an offset to a line offset gives the line it
+/// falls in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Line;
+
+/// Measures text in UTF-16 code units, like
+/// [`Rope::utf16_len()`](crate::Rope::utf16_len()).
+#[cfg_attr(docsrs, doc(cfg(feature = "utf16-metric")))]
+#[cfg(feature = "utf16-metric")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Utf16CodeUnit;
+
+impl Metric for Byte {}
+
+impl sealed::Sealed for Byte {
+    #[inline]
+    fn len(slice: RopeSlice<'_>) -> usize {
+        slice.byte_len()
+    }
+
+    #[track_caller]
+    #[inline]
+    fn to_byte(slice: RopeSlice<'_>, offset: usize) -> usize {
+        if offset > slice.byte_len() {
+            super::utils::panic_messages::byte_offset_out_of_bounds(
+                offset,
+                slice.byte_len(),
+            );
+        }
+
+        offset
+    }
+
+    #[track_caller]
+    #[inline]
+    fn from_byte(slice: RopeSlice<'_>, byte_offset: usize) -> usize {
+        Self::to_byte(slice, byte_offset)
+    }
+}
+
+impl Metric for Line {}
+
+impl sealed::Sealed for Line {
+    #[inline]
+    fn len(slice: RopeSlice<'_>) -> usize {
+        slice.line_len()
+    }
+
+    #[track_caller]
+    #[inline]
+    fn to_byte(slice: RopeSlice<'_>, offset: usize) -> usize {
+        slice.byte_of_line(offset)
+    }
+
+    #[track_caller]
+    #[inline]
+    fn from_byte(slice: RopeSlice<'_>, byte_offset: usize) -> usize {
+        slice.line_of_byte(byte_offset)
+    }
+}
+
+#[cfg(feature = "utf16-metric")]
+impl Metric for Utf16CodeUnit {}
+
+#[cfg(feature = "utf16-metric")]
+impl sealed::Sealed for Utf16CodeUnit {
+    #[inline]
+    fn len(slice: RopeSlice<'_>) -> usize {
+        slice.utf16_len()
+    }
+
+    #[track_caller]
+    #[inline]
+    fn to_byte(slice: RopeSlice<'_>, offset: usize) -> usize {
+        slice.byte_of_utf16_code_unit(offset)
+    }
+
+    #[track_caller]
+    #[inline]
+    fn from_byte(slice: RopeSlice<'_>, byte_offset: usize) -> usize {
+        slice.utf16_code_unit_of_byte(byte_offset)
+    }
+}
+
+/// Returns the length of the slice in the `M` metric.
+#[inline]
+pub(super) fn measure<M: Metric>(slice: RopeSlice<'_>) -> usize {
+    M::len(slice)
+}
+
+/// Converts an offset in the `M1` metric to the corresponding offset in the
+/// `M2` metric, going through the byte offset.
+#[track_caller]
+#[inline]
+pub(super) fn convert<M1: Metric, M2: Metric>(
+    slice: RopeSlice<'_>,
+    offset: usize,
+) -> usize {
+    M2::from_byte(slice, M1::to_byte(slice, offset))
+}
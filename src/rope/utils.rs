@@ -1,8 +1,22 @@
 //! This module contains utility functions on strings and code to be shared
 //! between `Rope`s and `RopeSlice`s, `RopeChunk`s and `ChunkSlice`s.
 
+use core::sync::atomic::{AtomicUsize, Ordering};
+
 use super::iterators::Chunks;
 
+/// Returns a [generation](super::Rope::generation()) that no `Rope` has had
+/// yet.
+///
+/// Generations are taken from a counter shared by all the `Rope`s, so that two
+/// unrelated `Rope`s, or two clones of a `Rope` edited in different ways,
+/// never end up with the same generation.
+#[inline]
+pub(super) fn next_generation() -> usize {
+    static NEXT_GENERATION: AtomicUsize = AtomicUsize::new(0);
+    NEXT_GENERATION.fetch_add(1, Ordering::Relaxed)
+}
+
 /// Adjusts the candidate byte offset to make sure it's a char boundary for
 /// `s`. Offsets past the end of the string will be clipped to the length of
 /// the string.
@@ -37,6 +51,17 @@ pub(super) fn adjust_split_point<const WITH_RIGHT_BIAS: bool>(
     offset
 }
 
+/// Returns the only chunk yielded by the given [`Chunks`], or `None` if it
+/// yields more than one. An empty iterator corresponds to the empty string.
+#[inline]
+pub(super) fn chunks_as_str(mut chunks: Chunks<'_>) -> Option<&str> {
+    match (chunks.next(), chunks.next()) {
+        (None, _) => Some(""),
+        (Some(chunk), None) => Some(chunk),
+        _ => None,
+    }
+}
+
 /// Checks equality between the chunks yielded by iterating over two
 /// [`Chunks`].
 ///
@@ -53,13 +78,13 @@ pub(super) fn chunks_eq_chunks(
 
     loop {
         if left_chunk.len() < right_chunk.len() {
-            if left_chunk != &right_chunk[..left_chunk.len()] {
+            if !bytes_eq(left_chunk, &right_chunk[..left_chunk.len()]) {
                 return false;
             } else {
                 right_chunk = &right_chunk[left_chunk.len()..];
                 left_chunk = &[];
             }
-        } else if &left_chunk[..right_chunk.len()] != right_chunk {
+        } else if !bytes_eq(&left_chunk[..right_chunk.len()], right_chunk) {
             return false;
         } else {
             left_chunk = &left_chunk[right_chunk.len()..];
@@ -88,6 +113,15 @@ pub(super) fn chunks_eq_chunks(
     }
 }
 
+/// Checks equality between two byte slices of the same length, skipping the
+/// comparison if they point to the same memory (which is the case for the
+/// chunks shared by a `Rope` and its clones).
+#[inline]
+fn bytes_eq(lhs: &[u8], rhs: &[u8]) -> bool {
+    debug_assert_eq!(lhs.len(), rhs.len());
+    lhs.as_ptr() == rhs.as_ptr() || lhs == rhs
+}
+
 /// Checks equality between the chunks yielded by iterating over a [`Chunks`]
 /// and a string slice.
 ///
@@ -96,10 +130,15 @@ pub(super) fn chunks_eq_chunks(
 /// same number of bytes.
 #[inline]
 pub(super) fn chunks_eq_str(chunks: Chunks<'_>, s: &str) -> bool {
-    let s = s.as_bytes();
+    chunks_eq_bytes(chunks, s.as_bytes())
+}
+
+/// Same as [`chunks_eq_str()`], but comparing against a byte slice.
+#[inline]
+pub(super) fn chunks_eq_bytes(chunks: Chunks<'_>, bytes: &[u8]) -> bool {
     let mut checked = 0;
     for chunk in chunks {
-        if chunk.as_bytes() != &s[checked..(checked + chunk.len())] {
+        if chunk.as_bytes() != &bytes[checked..(checked + chunk.len())] {
             return false;
         }
         checked += chunk.len();
@@ -107,6 +146,237 @@ pub(super) fn chunks_eq_str(chunks: Chunks<'_>, s: &str) -> bool {
     true
 }
 
+/// Returns whether the bytes yielded by iterating over the chunks start with
+/// the given bytes.
+#[inline]
+pub(super) fn chunks_start_with(
+    chunks: Chunks<'_>,
+    mut prefix: &[u8],
+) -> bool {
+    for chunk in chunks {
+        if prefix.is_empty() {
+            break;
+        }
+
+        let chunk = chunk.as_bytes();
+        let len = chunk.len().min(prefix.len());
+
+        if chunk[..len] != prefix[..len] {
+            return false;
+        }
+
+        prefix = &prefix[len..];
+    }
+
+    prefix.is_empty()
+}
+
+/// Returns whether the bytes yielded by iterating over the chunks end with the
+/// given bytes.
+#[inline]
+pub(super) fn chunks_end_with(chunks: Chunks<'_>, mut suffix: &[u8]) -> bool {
+    for chunk in chunks.rev() {
+        if suffix.is_empty() {
+            break;
+        }
+
+        let chunk = chunk.as_bytes();
+        let len = chunk.len().min(suffix.len());
+
+        if chunk[chunk.len() - len..] != suffix[suffix.len() - len..] {
+            return false;
+        }
+
+        suffix = &suffix[..suffix.len() - len];
+    }
+
+    suffix.is_empty()
+}
+
+/// Returns the length in bytes of the longest common prefix of the text
+/// yielded by the chunks and `s`, adjusted down to a char boundary.
+#[inline]
+pub(super) fn common_prefix_len(chunks: Chunks<'_>, s: &str) -> usize {
+    let bytes = s.as_bytes();
+
+    let mut len = 0;
+
+    'outer: for chunk in chunks {
+        for &byte in chunk.as_bytes() {
+            if len == bytes.len() || bytes[len] != byte {
+                break 'outer;
+            }
+            len += 1;
+        }
+    }
+
+    // Both texts are equal up to `len`, so it's enough to check one of them.
+    while !s.is_char_boundary(len) {
+        len -= 1;
+    }
+
+    len
+}
+
+/// Returns the length in bytes of the longest common suffix of the text
+/// yielded by the chunks and `s`, adjusted down to a char boundary.
+#[inline]
+pub(super) fn common_suffix_len(chunks: Chunks<'_>, s: &str) -> usize {
+    let bytes = s.as_bytes();
+
+    let mut len = 0;
+
+    'outer: for chunk in chunks.rev() {
+        for &byte in chunk.as_bytes().iter().rev() {
+            if len == bytes.len() || bytes[bytes.len() - len - 1] != byte {
+                break 'outer;
+            }
+            len += 1;
+        }
+    }
+
+    while !s.is_char_boundary(s.len() - len) {
+        len -= 1;
+    }
+
+    len
+}
+
+/// Concatenates the chunks into a new `String` of `byte_len` bytes, with a
+/// single allocation.
+#[inline]
+pub(super) fn chunks_to_string(chunks: Chunks<'_>, byte_len: usize) -> String {
+    let mut string = String::with_capacity(byte_len);
+
+    for chunk in chunks {
+        string.push_str(chunk);
+    }
+
+    debug_assert_eq!(string.len(), byte_len);
+
+    string
+}
+
+/// Returns the number of chars in the string.
+#[inline]
+pub(super) fn char_count(s: &str) -> usize {
+    #[cfg(not(miri))]
+    {
+        str_indices::chars::count(s)
+    }
+    #[cfg(miri)]
+    {
+        s.chars().count()
+    }
+}
+
+/// Returns the number of chars yielded by `chunks`.
+#[inline]
+pub(super) fn count_chars(chunks: Chunks<'_>) -> usize {
+    chunks.map(char_count).sum()
+}
+
+/// Returns the byte offset of the char at `char_offset` in the text yielded
+/// by `chunks`, or `None` if there are fewer than `char_offset` chars.
+///
+/// Chunks always start and end on char boundaries, so only the chunk
+/// containing the char has to be decoded, the others are just counted.
+#[inline]
+pub(super) fn byte_of_char(
+    chunks: Chunks<'_>,
+    char_offset: usize,
+) -> Option<usize> {
+    let mut byte_offset = 0;
+    let mut chars_left = char_offset;
+
+    for chunk in chunks {
+        let chunk_chars = char_count(chunk);
+
+        if chars_left <= chunk_chars {
+            #[cfg(not(miri))]
+            let byte_idx = str_indices::chars::to_byte_idx(chunk, chars_left);
+
+            #[cfg(miri)]
+            let byte_idx = chunk
+                .char_indices()
+                .nth(chars_left)
+                .map_or(chunk.len(), |(idx, _)| idx);
+
+            return Some(byte_offset + byte_idx);
+        }
+
+        chars_left -= chunk_chars;
+        byte_offset += chunk.len();
+    }
+
+    (chars_left == 0).then_some(byte_offset)
+}
+
+/// Returns the byte offset of the first char yielded by `chunks` satisfying
+/// the predicate.
+///
+/// Chunks always start and end on char boundaries, so we can search each one
+/// on its own.
+#[inline]
+pub(super) fn find_char<F>(
+    chunks: Chunks<'_>,
+    mut predicate: F,
+) -> Option<usize>
+where
+    F: FnMut(char) -> bool,
+{
+    let mut offset = 0;
+
+    for chunk in chunks {
+        if let Some(idx) = chunk.find(&mut predicate) {
+            return Some(offset + idx);
+        }
+        offset += chunk.len();
+    }
+
+    None
+}
+
+/// Returns the byte offset of the last char yielded by `chunks` satisfying
+/// the predicate, where `byte_len` is the total length of the chunks.
+#[inline]
+pub(super) fn rfind_char<F>(
+    chunks: Chunks<'_>,
+    byte_len: usize,
+    mut predicate: F,
+) -> Option<usize>
+where
+    F: FnMut(char) -> bool,
+{
+    let mut end = byte_len;
+
+    for chunk in chunks.rev() {
+        end -= chunk.len();
+        if let Some(idx) = chunk.rfind(&mut predicate) {
+            return Some(end + idx);
+        }
+    }
+
+    None
+}
+
+/// Returns the length in bytes of the whitespace at the start of the text
+/// yielded by the chunks, where whitespace is defined as in
+/// [`char::is_whitespace()`].
+#[inline]
+pub(super) fn leading_whitespace_len(chunks: Chunks<'_>) -> usize {
+    let mut len = 0;
+
+    for chunk in chunks {
+        match chunk.find(|ch: char| !ch.is_whitespace()) {
+            Some(idx) => return len + idx,
+            None => len += chunk.len(),
+        }
+    }
+
+    len
+}
+
 /// Iterates over the string slices yielded by [`Chunks`], writing the debug
 /// output of each chunk to a formatter.
 #[inline]
@@ -121,6 +391,39 @@ pub(super) fn debug_chunks(
     Ok(())
 }
 
+/// The maximum number of `char`s shown by [`DebugPreview`].
+const DEBUG_PREVIEW_MAX_CHARS: usize = 64;
+
+/// Formats the text yielded by a [`Chunks`] as a quoted string like the
+/// `Debug` impl of `str` does, but only showing its first
+/// [`DEBUG_PREVIEW_MAX_CHARS`] `char`s followed by an ellipsis if the text is
+/// longer than that.
+pub(super) struct DebugPreview<'a>(pub(super) Chunks<'a>);
+
+impl core::fmt::Debug for DebugPreview<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("\"")?;
+
+        let mut chars_left = DEBUG_PREVIEW_MAX_CHARS;
+
+        for chunk in self.0.clone() {
+            match chunk.char_indices().nth(chars_left) {
+                Some((byte_idx, _)) => {
+                    debug_no_quotes(&chunk[..byte_idx], f)?;
+                    return f.write_str("…\"");
+                },
+
+                None => {
+                    debug_no_quotes(chunk, f)?;
+                    chars_left -= chunk.chars().count();
+                },
+            }
+        }
+
+        f.write_str("\"")
+    }
+}
+
 /// Writes the `Debug` output of the given string to the formatter without
 /// enclosing it in double quotes.
 pub(super) fn debug_no_quotes(
@@ -145,6 +448,118 @@ pub(super) fn debug_no_quotes(
     f.write_str(&s[written..])
 }
 
+/// Computes the 64-bit FNV-1a hash of the bytes yielded by iterating over a
+/// [`Chunks`].
+///
+/// FNV-1a processes one byte at a time, so the result doesn't depend on where
+/// the chunk boundaries fall.
+#[inline]
+pub(super) fn fnv1a_chunks(chunks: Chunks<'_>) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut hash = OFFSET_BASIS;
+
+    for chunk in chunks {
+        for &byte in chunk.as_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(PRIME);
+        }
+    }
+
+    hash
+}
+
+/// Computes the SHA-256 digest of the bytes yielded by iterating over a
+/// [`Chunks`], formatted as a lowercase hexadecimal string.
+#[cfg(feature = "sha256")]
+#[inline]
+pub(super) fn sha256_hex_chunks(chunks: Chunks<'_>) -> String {
+    use core::fmt::Write;
+
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+
+    for chunk in chunks {
+        hasher.update(chunk.as_bytes());
+    }
+
+    let mut hex = String::with_capacity(64);
+
+    for byte in hasher.finalize() {
+        // Writing to a `String` never fails.
+        let _ = write!(hex, "{byte:02x}");
+    }
+
+    hex
+}
+
+/// Builds a new [`Rope`](super::Rope) from the chars yielded by an iterator,
+/// appending them to the builder a chunk at a time.
+#[cfg(feature = "unicode-normalization")]
+#[inline]
+pub(super) fn rope_from_chars<I>(chars: I) -> super::Rope
+where
+    I: Iterator<Item = char>,
+{
+    let max_bytes = super::rope::RopeChunk::max_bytes();
+
+    let mut builder = super::RopeBuilder::new();
+
+    let mut buffer = String::with_capacity(max_bytes);
+
+    for ch in chars {
+        if buffer.len() + ch.len_utf8() > max_bytes {
+            builder.append(&buffer);
+            buffer.clear();
+        }
+
+        buffer.push(ch);
+    }
+
+    builder.append(&buffer);
+
+    builder.build()
+}
+
+/// Feeds the bytes yielded by iterating over a [`Chunks`] to a hasher.
+///
+/// The `Hasher` trait doesn't guarantee that writing the same bytes split in
+/// different ways produces the same hash, so we buffer the chunks and always
+/// write fixed-size blocks to make the result independent of where the chunk
+/// boundaries fall. The trailing `0xff` mirrors the `Hash` impl of `str`.
+#[inline]
+pub(super) fn hash_chunks<H: core::hash::Hasher>(
+    chunks: Chunks<'_>,
+    state: &mut H,
+) {
+    const BLOCK_SIZE: usize = 256;
+
+    let mut buffer = [0u8; BLOCK_SIZE];
+    let mut buffered = 0;
+
+    for chunk in chunks {
+        let mut bytes = chunk.as_bytes();
+
+        while !bytes.is_empty() {
+            let to_copy = bytes.len().min(BLOCK_SIZE - buffered);
+            buffer[buffered..buffered + to_copy]
+                .copy_from_slice(&bytes[..to_copy]);
+            buffered += to_copy;
+            bytes = &bytes[to_copy..];
+
+            if buffered == BLOCK_SIZE {
+                state.write(&buffer);
+                buffered = 0;
+            }
+        }
+    }
+
+    state.write(&buffer[..buffered]);
+    state.write_u8(0xff);
+}
+
 /// Returns whether `byte_offset` is a grapheme boundary in the string
 /// constructed by concatenating the chunks yielded by `chunks`.
 #[cfg(feature = "graphemes")]
@@ -158,6 +573,12 @@ pub(super) fn is_grapheme_boundary(
 
     debug_assert!(byte_offset <= byte_len);
 
+    // The start and the end of the text are always boundaries. This also
+    // covers empty texts, which don't have any chunks to look at.
+    if byte_offset == 0 || byte_offset == byte_len {
+        return true;
+    }
+
     let mut cursor = GraphemeCursor::new(0, byte_len, true);
     cursor.set_cursor(byte_offset);
 
@@ -198,6 +619,115 @@ pub(super) fn is_grapheme_boundary(
     }
 }
 
+/// Returns the byte offset of the start of the grapheme cluster containing
+/// `byte_offset` in the given slice, which is `byte_offset` itself if it's
+/// already a grapheme boundary.
+///
+/// Only the chunks around `byte_offset` are looked at, plus as many chunks
+/// before it as needed to provide context to the cursor.
+#[cfg(feature = "graphemes")]
+#[track_caller]
+#[inline]
+pub(super) fn grapheme_start(
+    slice: super::RopeSlice<'_>,
+    byte_offset: usize,
+) -> usize {
+    use unicode_segmentation::{GraphemeCursor, GraphemeIncomplete};
+
+    let byte_len = slice.byte_len();
+
+    // This also panics if the byte offset is not a char boundary.
+    let Some(chunk) = slice.byte_slice(byte_offset..).chunks().next() else {
+        return byte_offset;
+    };
+
+    if byte_offset == 0 {
+        return 0;
+    }
+
+    let mut cursor = GraphemeCursor::new(byte_offset, byte_len, true);
+
+    let provide_context = |cursor: &mut GraphemeCursor, byte_idx: usize| {
+        let chunk = slice.byte_slice(..byte_idx).chunks().next_back().unwrap();
+        cursor.provide_context(chunk, byte_idx - chunk.len());
+    };
+
+    loop {
+        match cursor.is_boundary(chunk, byte_offset) {
+            Ok(true) => return byte_offset,
+
+            Ok(false) => break,
+
+            Err(GraphemeIncomplete::PreContext(byte_idx)) => {
+                provide_context(&mut cursor, byte_idx);
+            },
+
+            _ => unreachable!(),
+        }
+    }
+
+    let mut chunks = slice.byte_slice(..byte_offset).chunks();
+    let mut chunk = chunks.next_back().unwrap();
+    let mut chunk_start = byte_offset - chunk.len();
+
+    loop {
+        match cursor.prev_boundary(chunk, chunk_start) {
+            Ok(Some(start)) => return start,
+
+            Ok(None) => return 0,
+
+            Err(GraphemeIncomplete::PrevChunk) => {
+                chunk = chunks.next_back().unwrap();
+                chunk_start -= chunk.len();
+            },
+
+            Err(GraphemeIncomplete::PreContext(byte_idx)) => {
+                provide_context(&mut cursor, byte_idx);
+            },
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Returns the last grapheme boundary at or before the end of `left` in the
+/// string obtained by concatenating `left` and `right`.
+///
+/// Only `right` is used as lookahead, so a grapheme cluster that keeps going
+/// past its end may still end up being split.
+#[cfg(feature = "graphemes")]
+#[inline]
+pub(super) fn last_grapheme_boundary(left: &str, right: &str) -> usize {
+    use unicode_segmentation::{GraphemeCursor, GraphemeIncomplete};
+
+    if right.is_empty() {
+        return left.len();
+    }
+
+    let mut cursor =
+        GraphemeCursor::new(left.len(), left.len() + right.len(), true);
+
+    loop {
+        match cursor.is_boundary(right, left.len()) {
+            Ok(true) => return left.len(),
+
+            Ok(false) => break,
+
+            Err(GraphemeIncomplete::PreContext(_)) => {
+                cursor.provide_context(left, 0);
+            },
+
+            _ => return left.len(),
+        }
+    }
+
+    match cursor.prev_boundary(left, 0) {
+        Ok(Some(offset)) => offset,
+        Ok(None) => 0,
+        _ => left.len(),
+    }
+}
+
 #[inline]
 pub(super) fn split_adjusted<const WITH_RIGHT_BIAS: bool>(
     s: &str,
@@ -207,7 +737,42 @@ pub(super) fn split_adjusted<const WITH_RIGHT_BIAS: bool>(
     (&s[..split_point], &s[split_point..])
 }
 
+/// Converts a slice of bytes to a string slice without checking that it's
+/// valid UTF-8, unless the `strict-utf8-checks` feature is enabled, in which
+/// case it panics if it isn't.
+///
+/// # Safety
+///
+/// Same as [`core::str::from_utf8_unchecked()`].
+#[track_caller]
+#[inline]
+pub(super) unsafe fn str_from_utf8_unchecked(bytes: &[u8]) -> &str {
+    #[cfg(feature = "strict-utf8-checks")]
+    {
+        match core::str::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(err) => panic_messages::invalid_utf8_chunk(bytes, err),
+        }
+    }
+
+    #[cfg(not(feature = "strict-utf8-checks"))]
+    {
+        core::str::from_utf8_unchecked(bytes)
+    }
+}
+
 pub mod panic_messages {
+    #[cfg(feature = "strict-utf8-checks")]
+    #[track_caller]
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn invalid_utf8_chunk(
+        bytes: &[u8],
+        err: core::str::Utf8Error,
+    ) -> ! {
+        panic!("chunk is not valid UTF-8: {err} in {bytes:?}")
+    }
+
     #[track_caller]
     #[cold]
     #[inline(never)]
@@ -284,6 +849,31 @@ pub mod panic_messages {
         );
     }
 
+    #[track_caller]
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn char_index_out_of_bounds(
+        char_index: usize,
+        char_len: usize,
+    ) -> ! {
+        debug_assert!(char_index > char_len);
+
+        panic!(
+            "char index out of bounds: the index is {char_index} but the \
+             length is {char_len}"
+        );
+    }
+
+    #[track_caller]
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn incomplete_utf8(bytes: &[u8]) -> ! {
+        panic!(
+            "incomplete UTF-8: the bytes {bytes:?} appended to the \
+             RopeBuilder end in the middle of a code point"
+        );
+    }
+
     #[track_caller]
     #[cold]
     #[inline(never)]
@@ -329,6 +919,43 @@ pub mod panic_messages {
         );
     }
 
+    #[track_caller]
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn max_len_exceeded(limit: usize, would_be: usize) -> ! {
+        debug_assert!(would_be > limit);
+
+        panic!(
+            "Rope too long: the edit would make the Rope {would_be} bytes \
+             long but its maximum length is {limit} bytes"
+        );
+    }
+
+    #[track_caller]
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn placements_overlap(previous_end: usize, start: usize) -> ! {
+        debug_assert!(start < previous_end);
+
+        panic!(
+            "placements overlap or are not sorted: a placement starts at \
+             {start} but the previous one ends at {previous_end}"
+        );
+    }
+
+    #[track_caller]
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn rope_too_long(byte_len: usize, text_len: usize) -> ! {
+        debug_assert!(text_len > crate::Rope::MAX_LEN - byte_len);
+
+        panic!(
+            "Rope too long: inserting {text_len} bytes in a Rope of \
+             {byte_len} bytes would exceed the maximum length of {} bytes",
+            crate::Rope::MAX_LEN
+        );
+    }
+
     #[cfg(feature = "utf16-metric")]
     #[track_caller]
     #[cold]
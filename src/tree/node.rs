@@ -125,6 +125,87 @@ impl<const N: usize, L: Leaf> Node<N, L> {
         }
     }
 
+    /// Returns the `M2`-measure up to `up_to` like
+    /// [`convert_measure()`](Self::convert_measure()), together with the
+    /// `M1`-measure of the start of the `M2` unit containing `up_to`.
+    ///
+    /// The start is found while descending the tree to `up_to`: if the leaf
+    /// at `up_to` doesn't contain it, it's in the last child skipped on the
+    /// way down with a non-zero `M2`-measure, so only that subtree needs to
+    /// be searched instead of starting over from the root.
+    #[inline]
+    pub(super) fn convert_measure_with_start<M1, M2>(
+        &self,
+        up_to: M1,
+    ) -> (M2, M1)
+    where
+        M1: SlicingMetric<L>,
+        M2: SlicingMetric<L>,
+    {
+        debug_assert!(up_to <= self.measure::<M1>());
+
+        let mut m1 = M1::zero();
+        let mut m2 = M2::zero();
+
+        // The last node skipped with a non-zero `M2`-measure, together with
+        // the `M1`-measure of all the nodes before it.
+        let mut last_skipped: Option<(&Self, M1)> = None;
+
+        let mut node = self;
+
+        'outer: loop {
+            match node {
+                Node::Internal(inode) => {
+                    for child in inode.children() {
+                        let child_m1 = child.measure::<M1>();
+
+                        if m1 + child_m1 >= up_to {
+                            node = &**child;
+                            continue 'outer;
+                        }
+
+                        let child_m2 = child.measure::<M2>();
+
+                        if child_m2 > M2::zero() {
+                            last_skipped = Some((&**child, m1));
+                        }
+
+                        m1 += child_m1;
+                        m2 += child_m2;
+                    }
+
+                    unreachable!();
+                },
+
+                Node::Leaf(leaf) => {
+                    let (_, left_summary) = M1::slice_up_to(
+                        leaf.as_slice(),
+                        up_to - m1,
+                        leaf.summary(),
+                    );
+
+                    let leaf_m2 = M2::measure(&left_summary);
+
+                    let start = if leaf_m2 > M2::zero() {
+                        let (_, left_summary) = M2::slice_up_to(
+                            leaf.as_slice(),
+                            leaf_m2,
+                            leaf.summary(),
+                        );
+                        m1 + M1::measure(&left_summary)
+                    } else if let Some((skipped, m1)) = last_skipped {
+                        m1 + skipped
+                            .convert_measure::<M2, M1>(skipped.measure::<M2>())
+                    } else {
+                        M1::zero()
+                    };
+
+                    return (m2 + leaf_m2, start);
+                },
+            }
+        }
+    }
+
     #[inline]
     pub(super) fn depth(&self) -> usize {
         match self {
@@ -116,6 +116,19 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
             actual_leaves
         );
 
+        let actual_summary = self
+            .children()
+            .iter()
+            .fold(L::Summary::default(), |sum, c| sum + c.summary());
+
+        assert_eq!(
+            self.summary,
+            actual_summary,
+            "An internal node of depth {} has a summary which doesn't match \
+             the combined summaries of its children",
+            self.depth(),
+        );
+
         for child in self.children() {
             assert_eq!(
                 self.depth(),
@@ -415,6 +428,11 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
         &self.children
     }
 
+    #[inline]
+    pub(super) fn into_children(self) -> Vec<Arc<Node<N, L>>> {
+        self.children
+    }
+
     /// Returns the index of the child at the given measure together
     /// with the combined `M`-offset of the other children up to but not
     /// including that child.
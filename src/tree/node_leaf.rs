@@ -62,6 +62,11 @@ impl<L: Leaf> Lnode<L> {
         self.measure::<L::BaseMetric>()
     }
 
+    #[inline]
+    pub(super) fn into_value(self) -> L {
+        self.value
+    }
+
     #[inline]
     pub(super) fn is_underfilled(&self) -> bool
     where
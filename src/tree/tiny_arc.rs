@@ -48,6 +48,27 @@ impl<T> Arc<T> {
         unsafe { self.ptr.as_ref() }
     }
 
+    /// Returns the inner value if this is the only `Arc` pointing to it,
+    /// otherwise drops this `Arc` and returns `None`.
+    #[inline]
+    pub(super) fn into_inner(this: Self) -> Option<T> {
+        if !this.is_unique() {
+            return None;
+        }
+
+        // Synchronize with the `Release` decrements of the other `Arc`s that
+        // used to point to the value, like in `Drop`.
+        atomic::fence(atomic::Ordering::Acquire);
+
+        let this = core::mem::ManuallyDrop::new(this);
+
+        // SAFETY: this is the only `Arc` pointing to the inner value and it
+        // won't be dropped, so the memory can be reclaimed here.
+        let inner = unsafe { Box::from_raw(this.ptr.as_ptr()) };
+
+        Some(inner.data)
+    }
+
     #[inline]
     fn is_unique(&self) -> bool {
         self.inner().counter.load(atomic::Ordering::Relaxed) == 1
@@ -68,6 +68,73 @@ impl<const ARITY: usize, L: BalancedLeaf + Clone> From<TreeSlice<'_, ARITY, L>>
 }
 
 impl<const ARITY: usize, L: Leaf> Tree<ARITY, L> {
+    /// Appends `other` at the end of this `Tree`.
+    ///
+    /// The nodes of `other` are shared instead of being copied, and only the
+    /// nodes along the seam between the two trees are rebalanced, so this
+    /// runs in O(log n).
+    #[inline]
+    pub fn append(&mut self, mut other: Self)
+    where
+        L: BalancedLeaf + Clone,
+    {
+        if other.base_measure() == L::BaseMetric::zero() {
+            return;
+        }
+
+        if self.base_measure() == L::BaseMetric::zero() {
+            *self = other;
+            return;
+        }
+
+        match self.depth().cmp(&other.depth()) {
+            core::cmp::Ordering::Greater => {
+                let extra = Arc::make_mut(&mut self.root)
+                    .get_internal_mut()
+                    .append_at_depth(other.root);
+
+                if let Some(extra) = extra {
+                    let extra = Arc::new(Node::Internal(extra));
+                    self.root =
+                        Arc::new(Node::Internal(Inode::from_children([
+                            Arc::clone(&self.root),
+                            extra,
+                        ])));
+                }
+            },
+
+            core::cmp::Ordering::Less => {
+                let extra = Arc::make_mut(&mut other.root)
+                    .get_internal_mut()
+                    .prepend_at_depth(Arc::clone(&self.root));
+
+                self.root = match extra {
+                    Some(extra) => {
+                        let extra = Arc::new(Node::Internal(extra));
+                        Arc::new(Node::Internal(Inode::from_children([
+                            extra, other.root,
+                        ])))
+                    },
+
+                    None => other.root,
+                };
+            },
+
+            core::cmp::Ordering::Equal => {
+                Arc::make_mut(&mut self.root)
+                    .balance(Arc::make_mut(&mut other.root));
+
+                if !other.root.is_empty() {
+                    self.root =
+                        Arc::new(Node::Internal(Inode::from_children([
+                            Arc::clone(&self.root),
+                            other.root,
+                        ])));
+                }
+            },
+        }
+    }
+
     #[doc(hidden)]
     pub fn assert_invariants(&self) {
         match &*self.root {
@@ -76,6 +143,15 @@ impl<const ARITY: usize, L: Leaf> Tree<ARITY, L> {
                 // children.
                 assert!(root.len() >= 2 && root.len() <= ARITY);
 
+                assert_eq!(
+                    root.summary(),
+                    &root
+                        .children()
+                        .iter()
+                        .fold(L::Summary::default(), |sum, c| sum
+                            + c.summary())
+                );
+
                 for child in root.children() {
                     child.assert_invariants()
                 }
@@ -103,6 +179,26 @@ impl<const ARITY: usize, L: Leaf> Tree<ARITY, L> {
         self.root.convert_measure(up_to)
     }
 
+    /// Returns the `M2`-measure up to `up_to` like
+    /// [`convert_measure()`](Self::convert_measure()), together with the
+    /// `M1`-measure of the start of the `M2` unit containing `up_to`, in a
+    /// single descent of the tree.
+    #[inline]
+    pub fn convert_measure_with_start<M1, M2>(&self, up_to: M1) -> (M2, M1)
+    where
+        M1: SlicingMetric<L>,
+        M2: SlicingMetric<L>,
+    {
+        debug_assert!(up_to <= self.measure::<M1>());
+        self.root.convert_measure_with_start(up_to)
+    }
+
+    /// Returns the depth of this `Tree`, which is zero if the root is a leaf.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.root.depth()
+    }
+
     /// Creates a new `Tree` from a sequence of leaves.
     ///
     /// If the iterator doesn't yield any items the `Tree` will contain a
@@ -134,6 +230,29 @@ impl<const ARITY: usize, L: Leaf> Tree<ARITY, L> {
         Self { root: Arc::new(Node::Internal(root)) }
     }
 
+    /// Consumes the `Tree`, calling `f` on each of its leaves that's not
+    /// shared with another `Tree`.
+    ///
+    /// The leaves shared with other `Tree`s (e.g. because the `Tree` was
+    /// cloned) are left to them.
+    #[inline]
+    pub fn into_unique_leaves<F>(self, mut f: F)
+    where
+        F: FnMut(L),
+    {
+        let mut nodes = vec![self.root];
+
+        while let Some(node) = nodes.pop() {
+            match Arc::into_inner(node) {
+                Some(Node::Internal(inode)) => {
+                    nodes.extend(inode.into_children())
+                },
+                Some(Node::Leaf(leaf)) => f(leaf.into_value()),
+                None => {},
+            }
+        }
+    }
+
     /// Returns the leaf containing the `measure`-th unit of the `M`-metric,
     /// plus the `M`-measure of all the leaves before it.
     #[inline]
@@ -200,6 +319,22 @@ impl<const ARITY: usize, L: Leaf> Tree<ARITY, L> {
     #[track_caller]
     #[inline]
     pub fn slice<M>(&self, range: Range<M>) -> TreeSlice<'_, ARITY, L>
+    where
+        M: SlicingMetric<L>,
+        L::BaseMetric: SlicingMetric<L>,
+        for<'d> L::Slice<'d>: Default,
+    {
+        self.slice_with_offset(range).0
+    }
+
+    /// Same as [`slice()`](Self::slice()), but also returns the base measure
+    /// of the `Tree` before the start of the slice.
+    #[track_caller]
+    #[inline]
+    pub fn slice_with_offset<M>(
+        &self,
+        range: Range<M>,
+    ) -> (TreeSlice<'_, ARITY, L>, L::BaseMetric)
     where
         M: SlicingMetric<L>,
         L::BaseMetric: SlicingMetric<L>,
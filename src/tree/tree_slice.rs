@@ -140,6 +140,32 @@ impl<'a, const ARITY: usize, L: Leaf> TreeSlice<'a, ARITY, L> {
         }
     }
 
+    /// Returns the `M2`-measure up to `up_to` like
+    /// [`convert_measure()`](Self::convert_measure()), together with the
+    /// `M1`-measure of the start of the `M2` unit containing `up_to`, or zero
+    /// if that unit starts before this slice.
+    #[inline]
+    pub fn convert_measure_with_start<M1, M2>(&self, up_to: M1) -> (M2, M1)
+    where
+        M1: SlicingMetric<L>,
+        M2: SlicingMetric<L>,
+    {
+        debug_assert!(up_to <= self.measure::<M1>());
+
+        if up_to == M1::zero() {
+            return (M2::zero(), M1::zero());
+        }
+
+        let offset = M1::measure(&self.offset);
+
+        let (m2, start) =
+            self.root.convert_measure_with_start::<M1, M2>(offset + up_to);
+
+        let start = if start > offset { start - offset } else { M1::zero() };
+
+        (m2 - M2::measure(&self.offset), start)
+    }
+
     #[inline]
     pub fn end_slice(&self) -> L::Slice<'a> {
         self.end_slice
@@ -220,12 +246,14 @@ impl<'a, const ARITY: usize, L: Leaf> TreeSlice<'a, ARITY, L>
 where
     for<'d> L::Slice<'d>: Default,
 {
+    /// Slices `root` in the given range, also returning the base measure of
+    /// `root`'s subtree before the start of the slice.
     #[track_caller]
     #[inline]
     pub(super) fn from_range_in_root<M>(
         root: &'a Arc<Node<ARITY, L>>,
         range: Range<M>,
-    ) -> Self
+    ) -> (Self, L::BaseMetric)
     where
         M: SlicingMetric<L>,
         L::BaseMetric: SlicingMetric<L>,
@@ -245,7 +273,22 @@ where
 
     #[track_caller]
     #[inline]
-    pub fn slice<M>(self, mut range: Range<M>) -> Self
+    pub fn slice<M>(self, range: Range<M>) -> Self
+    where
+        M: SlicingMetric<L>,
+        L::BaseMetric: SlicingMetric<L>,
+    {
+        self.slice_with_offset(range).0
+    }
+
+    /// Same as [`slice()`](Self::slice()), but also returns the base measure
+    /// of this slice before the start of the returned one.
+    #[track_caller]
+    #[inline]
+    pub fn slice_with_offset<M>(
+        self,
+        mut range: Range<M>,
+    ) -> (Self, L::BaseMetric)
     where
         M: SlicingMetric<L>,
         L::BaseMetric: SlicingMetric<L>,
@@ -254,7 +297,7 @@ where
         debug_assert!(range.start <= range.end);
         debug_assert!(range.end <= self.measure::<M>() + M::one());
 
-        match (
+        let (slice, offset) = match (
             range.start > M::zero(),
             range.end < self.measure::<M>() + M::one(),
         ) {
@@ -282,8 +325,10 @@ where
                 Self::slice_impl(self.root, start, start)
             },
 
-            (false, false) => self,
-        }
+            (false, false) => return (self, L::BaseMetric::zero()),
+        };
+
+        (slice, offset - L::BaseMetric::measure(&self.offset))
     }
 
     /// Returns the `TreeSlice` obtained by slicing `root` between `start` and
@@ -296,13 +341,16 @@ where
     /// It's the caller's responsibility to guarantee this, and this function
     /// can panic or return an incorrect or invalid `TreeSlice` if this
     /// condition is not met.
+    ///
+    /// Also returns the base measure of `root`'s subtree before the start of
+    /// the slice.
     #[track_caller]
     #[inline]
     fn slice_impl<S, E>(
         root: &'a Arc<Node<ARITY, L>>,
         start: S,
         end: E,
-    ) -> Self
+    ) -> (Self, L::BaseMetric)
     where
         S: SlicingMetric<L>,
         E: SlicingMetric<L>,
@@ -310,7 +358,7 @@ where
         debug_assert!(S::zero() <= start);
         debug_assert!(end <= root.measure::<E>());
 
-        let (root, start, end) =
+        let (root, start, end, root_offset) =
             deepest_node_containing_range(root, start, end);
 
         let mut slice = Self {
@@ -336,6 +384,8 @@ where
             &mut false,
         );
 
+        let offset = root_offset + L::BaseMetric::measure(&slice.offset);
+
         if recompute_root {
             let start = L::BaseMetric::measure(&slice.offset);
 
@@ -349,7 +399,7 @@ where
             slice.offset -= &offset;
         }
 
-        slice
+        (slice, offset)
     }
 
     #[inline]
@@ -363,18 +413,21 @@ where
 
 /// Returns the deepest node under `nodes`'s subtree that fully contains the
 /// range between `start` and `end`, together with the `S` and `E` offsets with
-/// respect to that node.
+/// respect to that node and the base measure of the input `node`'s subtree
+/// before it.
 #[inline]
 fn deepest_node_containing_range<const N: usize, L, S, E>(
     mut node: &Arc<Node<N, L>>,
     mut start: S,
     mut end: E,
-) -> (&Arc<Node<N, L>>, S, E)
+) -> (&Arc<Node<N, L>>, S, E, L::BaseMetric)
 where
     L: Leaf,
     S: Metric<L::Summary>,
     E: Metric<L::Summary>,
 {
+    let mut offset = L::BaseMetric::zero();
+
     'outer: loop {
         match &**node {
             Node::Internal(inode) => {
@@ -396,9 +449,10 @@ where
                             node = child;
                             start -= S::measure(&measured);
                             end -= E::measure(&measured);
+                            offset += L::BaseMetric::measure(&measured);
                             continue 'outer;
                         } else {
-                            return (node, start, end);
+                            return (node, start, end, offset);
                         }
                     } else {
                         measured += child_summary;
@@ -408,7 +462,7 @@ where
                 unreachable!();
             },
 
-            Node::Leaf(_) => return (node, start, end),
+            Node::Leaf(_) => return (node, start, end, offset),
         }
     }
 }
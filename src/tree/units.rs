@@ -224,6 +224,12 @@ struct UnitsForward<'a, const N: usize, L: Leaf, M: Metric<L::Summary>> {
     /// we're iterating over a `TreeSlice`.
     last_slice: Option<(L::Slice<'a>, L::Summary)>,
 
+    /// The summary of the leaf containing the whole yielding range up to the
+    /// start of that range. It's only set if we're iterating over a
+    /// `TreeSlice` spanning a single leaf, in which case the first slice
+    /// doesn't extend to the end of its leaf.
+    offset_in_single_leaf: Option<L::Summary>,
+
     /// The start of the yielding range as an offset into the root.
     base_start: L::BaseMetric,
 
@@ -250,6 +256,7 @@ impl<const N: usize, L: Leaf, M: Metric<L::Summary>> Clone
             yielded_in_leaf: self.yielded_in_leaf.clone(),
             first_slice: self.first_slice.clone(),
             last_slice: self.last_slice.clone(),
+            offset_in_single_leaf: self.offset_in_single_leaf.clone(),
             start_summary: self.start_summary.clone(),
             ..*self
         }
@@ -272,6 +279,7 @@ where
             start_summary: L::Summary::default(),
             first_slice: None,
             last_slice: None,
+            offset_in_single_leaf: None,
             base_start: L::BaseMetric::zero(),
             base_yielded: L::BaseMetric::zero(),
             base_total: tree.base_measure(),
@@ -305,6 +313,8 @@ where
                 tree_slice.end_slice,
                 tree_slice.end_summary.clone(),
             )),
+            offset_in_single_leaf: (tree_slice.leaf_count() == 1)
+                .then(|| tree_slice.offset.clone()),
             base_start: L::BaseMetric::measure(&tree_slice.offset),
             base_yielded: L::BaseMetric::zero(),
             base_total: tree_slice.base_measure(),
@@ -354,7 +364,13 @@ impl<'a, const N: usize, L: Leaf, M: UnitMetric<L>> UnitsForward<'a, N, L, M> {
                     match self.first_slice.take() {
                         Some((slice, summary)) => {
                             self.yielded_in_leaf =
-                                leaf.summary().clone() - &summary;
+                                match self.offset_in_single_leaf.take() {
+                                    Some(offset) => {
+                                        debug_assert!(self.path.is_empty());
+                                        offset
+                                    },
+                                    None => leaf.summary().clone() - &summary,
+                                };
 
                             self.start_slice = slice;
                             self.start_summary = summary;
@@ -959,6 +975,12 @@ struct UnitsBackward<'a, const N: usize, L: Leaf, M: Metric<L::Summary>> {
     /// we're iterating over a `TreeSlice`.
     last_slice: Option<(L::Slice<'a>, L::Summary)>,
 
+    /// The summary of the leaf containing the whole yielding range up to the
+    /// start of that range. It's only set if we're iterating over a
+    /// `TreeSlice` spanning a single leaf, in which case the last slice
+    /// doesn't extend to the start of its leaf.
+    offset_in_single_leaf: Option<L::Summary>,
+
     /// The start of the yielding range as an offset into the root.
     base_start: L::BaseMetric,
 
@@ -979,6 +1001,7 @@ impl<const N: usize, L: Leaf, M: Metric<L::Summary>> Clone
             yielded_in_leaf: self.yielded_in_leaf.clone(),
             first_slice: self.first_slice.clone(),
             last_slice: self.last_slice.clone(),
+            offset_in_single_leaf: self.offset_in_single_leaf.clone(),
             end_summary: self.end_summary.clone(),
             ..*self
         }
@@ -1001,6 +1024,7 @@ where
             end_summary: L::Summary::default(),
             first_slice: None,
             last_slice: None,
+            offset_in_single_leaf: None,
             base_start: L::BaseMetric::zero(),
             base_remaining: tree.base_measure(),
             units_remaining: tree.root().measure::<M>(),
@@ -1032,6 +1056,8 @@ where
                 tree_slice.end_slice,
                 tree_slice.end_summary.clone(),
             )),
+            offset_in_single_leaf: (tree_slice.leaf_count() == 1)
+                .then(|| tree_slice.offset.clone()),
             base_start: L::BaseMetric::measure(&tree_slice.offset),
             base_remaining: tree_slice.base_measure(),
             units_remaining: tree_slice.measure::<M>(),
@@ -1083,8 +1109,16 @@ impl<'a, const N: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
 
                     match self.last_slice.take() {
                         Some((slice, summary)) => {
-                            self.yielded_in_leaf =
-                                leaf.summary().clone() - &summary;
+                            self.yielded_in_leaf = match self
+                                .offset_in_single_leaf
+                                .take()
+                            {
+                                Some(offset) => {
+                                    debug_assert!(self.path.is_empty());
+                                    leaf.summary().clone() - &offset - &summary
+                                },
+                                None => leaf.summary().clone() - &summary,
+                            };
 
                             self.end_slice = slice;
                             self.end_summary = summary;
@@ -1297,7 +1331,7 @@ impl<'a, const N: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
             return (
                 TreeSlice {
                     root: self.leaf_node,
-                    offset: L::Summary::default(),
+                    offset: self.end_slice_offset(),
                     summary: end_summary.clone(),
                     start_slice: end_slice,
                     start_summary: end_summary.clone(),
@@ -1380,6 +1414,18 @@ impl<'a, const N: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
         )
     }
 
+    /// Returns the summary of `self.leaf_node` up to the start of
+    /// `self.end_slice`.
+    ///
+    /// This is only non-zero if `self.end_slice` is the first slice of the
+    /// yielding range and that slice doesn't start at the start of its leaf.
+    #[inline]
+    fn end_slice_offset(&self) -> L::Summary {
+        self.leaf_node.summary().clone()
+            - &self.yielded_in_leaf
+            - &self.end_summary
+    }
+
     /// Yields the previous unit in the current `self.leaf_node`. To do this
     /// correctly `self.end_slice` cannot have any `M`-remainder and it needs
     /// to contain at least 2 `M`-units.
@@ -1395,7 +1441,7 @@ impl<'a, const N: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
             L::BaseMetric::measure(&rest_summary) > L::BaseMetric::zero()
         );
 
-        let offset = rest_summary.clone();
+        let offset = self.end_slice_offset() + &rest_summary;
 
         self.yielded_in_leaf += &advance;
         self.end_slice = rest;
@@ -1749,7 +1795,7 @@ impl<'a, const N: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
                 M::remainder(self.end_slice, &self.end_summary);
 
             if L::BaseMetric::measure(&summary) > L::BaseMetric::zero() {
-                let offset = rest_summary.clone();
+                let offset = self.end_slice_offset() + &rest_summary;
 
                 self.yielded_in_leaf += &summary;
                 self.end_slice = rest;
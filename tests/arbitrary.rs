@@ -0,0 +1,25 @@
+#![cfg(feature = "arbitrary")]
+
+use arbitrary::{Arbitrary, Unstructured};
+use crop::Rope;
+use rand::Rng;
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn arbitrary_ropes_are_valid() {
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..100 {
+        let len = rng.gen_range(0..=1 << 12);
+        let data = (0..len).map(|_| rng.gen()).collect::<Vec<u8>>();
+        let mut u = Unstructured::new(&data);
+
+        let rope = Rope::arbitrary(&mut u).unwrap();
+
+        rope.assert_invariants();
+
+        let string = rope.to_string();
+        assert_eq!(rope, string);
+        assert_eq!(rope.line_len(), crop::Rope::from(&*string).line_len());
+    }
+}
@@ -138,3 +138,231 @@ fn graphemes_is_boundary_out_of_bounds() {
     let r = Rope::from("🇷🇸🇮🇴");
     assert!(r.is_grapheme_boundary(17));
 }
+
+/// The graphemes of a `RopeSlice` don't get any context from the text outside
+/// of the slice, just like the graphemes of a `&str` sub-slice.
+#[cfg(feature = "graphemes")]
+#[test]
+fn graphemes_slice_edges() {
+    let r = Rope::from("aaa\r\nbbb🐻‍❄️");
+
+    // Starting in the middle of a CRLF.
+    let s = r.byte_slice(4..);
+    let graphemes = s.graphemes().collect::<Vec<_>>();
+    assert_eq!(["\n", "b", "b", "b", "🐻‍❄️"], &*graphemes);
+
+    // Ending in the middle of a CRLF.
+    let s = r.byte_slice(..4);
+    let graphemes = s.graphemes().rev().collect::<Vec<_>>();
+    assert_eq!(["\r", "a", "a", "a"], &*graphemes);
+
+    // Ending in the middle of a ZWJ sequence.
+    let s = r.byte_slice(5..15);
+    let graphemes = s.graphemes().collect::<Vec<_>>();
+    assert_eq!(["b", "b", "b", "🐻\u{200d}"], &*graphemes);
+
+    // Starting in the middle of a ZWJ sequence.
+    let s = r.byte_slice(15..);
+    let graphemes = s.graphemes().rev().collect::<Vec<_>>();
+    assert_eq!(["❄️"], &*graphemes);
+}
+
+#[cfg(feature = "graphemes")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn graphemes_random_slices() {
+    use rand::Rng;
+    use unicode_segmentation::UnicodeSegmentation;
+
+    let s = "aaa\r\nbbb🐻‍❄️\r\nc\u{301}\u{308}d🐕‍🦺\ne\u{301}\r\r\n🐻‍❄️🐕‍🦺";
+
+    let r = Rope::from(s);
+
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..1000 {
+        let mut start = rng.gen_range(0..=s.len());
+        while !s.is_char_boundary(start) {
+            start -= 1;
+        }
+
+        let mut end = rng.gen_range(start..=s.len());
+        while !s.is_char_boundary(end) {
+            end += 1;
+        }
+
+        let slice = r.byte_slice(start..end);
+
+        let expected = s[start..end].graphemes(true).collect::<Vec<_>>();
+
+        assert_eq!(expected, slice.graphemes().collect::<Vec<_>>());
+
+        assert_eq!(
+            expected.iter().rev().copied().collect::<Vec<_>>(),
+            slice.graphemes().rev().collect::<Vec<_>>()
+        );
+    }
+}
+
+/// Tests `is_grapheme_boundary()` against every byte offset of a `Rope` and of
+/// its slices, including the ones in the middle of a code point.
+#[cfg(feature = "graphemes")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn graphemes_is_boundary_every_offset() {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    let s = "aaa\r\nbbb🐻‍❄️\r\nc\u{301}\u{308}d🐕‍🦺\ne\u{301}\r\r\n🐻‍❄️🐕‍🦺";
+
+    let r = Rope::from(s);
+
+    for start in (0..=s.len()).filter(|&i| s.is_char_boundary(i)) {
+        let slice = r.byte_slice(start..);
+
+        let boundaries = s[start..]
+            .grapheme_indices(true)
+            .map(|(offset, _)| offset)
+            .chain(core::iter::once(s.len() - start))
+            .collect::<Vec<_>>();
+
+        for offset in 0..=slice.byte_len() {
+            let expected = boundaries.contains(&offset);
+            assert_eq!(expected, slice.is_grapheme_boundary(offset));
+
+            if start == 0 {
+                assert_eq!(expected, r.is_grapheme_boundary(offset));
+            }
+        }
+    }
+}
+
+/// Tests `grapheme_start()` and `graphemes_at()` against every code point
+/// boundary of a `Rope` and of its slices.
+#[cfg(feature = "graphemes")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn graphemes_at_every_offset() {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    let s = "aaa\r\nbbb🐻‍❄️\r\nc\u{301}\u{308}d🐕‍🦺\ne\u{301}\r\r\n🐻‍❄️🐕‍🦺";
+
+    let r = Rope::from(s);
+
+    for start in (0..=s.len()).filter(|&i| s.is_char_boundary(i)) {
+        let slice = r.byte_slice(start..);
+
+        let boundaries = s[start..]
+            .grapheme_indices(true)
+            .map(|(offset, _)| offset)
+            .chain(core::iter::once(s.len() - start))
+            .collect::<Vec<_>>();
+
+        for offset in
+            (0..=slice.byte_len()).filter(|&i| s[start..].is_char_boundary(i))
+        {
+            let expected_start = boundaries
+                .iter()
+                .copied()
+                .rfind(|&boundary| boundary <= offset)
+                .unwrap();
+
+            let expected = s[start + expected_start..]
+                .graphemes(true)
+                .collect::<Vec<_>>();
+
+            assert_eq!(expected_start, slice.grapheme_start(offset));
+
+            assert_eq!(
+                expected,
+                slice.graphemes_at(offset).collect::<Vec<_>>()
+            );
+
+            assert_eq!(
+                expected.iter().rev().copied().collect::<Vec<_>>(),
+                slice.graphemes_at(offset).rev().collect::<Vec<_>>()
+            );
+
+            if start == 0 {
+                assert_eq!(expected_start, r.grapheme_start(offset));
+                assert_eq!(
+                    expected,
+                    r.graphemes_at(offset).collect::<Vec<_>>()
+                );
+            }
+        }
+    }
+}
+
+/// The start of a flag depends on how many regional indicators come before
+/// it, which `grapheme_start()` has to look back for.
+#[cfg(feature = "graphemes")]
+#[test]
+fn grapheme_start_flags() {
+    let r = Rope::from("a🇷🇸🇮🇴🇫");
+
+    // (byte_offset, grapheme_start)
+    let cases = [(0, 0), (1, 1), (5, 1), (9, 9), (13, 9), (17, 17), (21, 21)];
+
+    for (byte_offset, expected) in cases {
+        assert_eq!(expected, r.grapheme_start(byte_offset), "{byte_offset}");
+        assert_eq!(expected, r.byte_slice(..).grapheme_start(byte_offset));
+    }
+}
+
+#[cfg(feature = "graphemes")]
+#[should_panic]
+#[test]
+fn graphemes_at_out_of_bounds() {
+    let r = Rope::from("foo");
+    let _ = r.graphemes_at(4);
+}
+
+/// Tests that the cached `grapheme_len()` stays in sync with the text across
+/// edits, including the ones made to clones of the `Rope`.
+#[cfg(feature = "graphemes")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn grapheme_len_after_edits() {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    let mut s = String::from("aaa\r\nbbb🐻‍❄️\r\nc\u{301}\u{308}d🐕‍🦺\n");
+
+    let mut r = Rope::from(s.as_str());
+
+    assert_eq!(s.graphemes(true).count(), r.grapheme_len());
+    assert_eq!(s.graphemes(true).count(), r.grapheme_len());
+
+    for (offset, text) in
+        [(0, "e\u{301}"), (3, "\n"), (4, "🐻‍❄️"), (10, ""), (1, "\r")]
+    {
+        let clone = r.clone();
+
+        r.insert(offset, text);
+        s.insert_str(offset, text);
+
+        assert_eq!(s.graphemes(true).count(), r.grapheme_len());
+        assert_eq!(clone.graphemes().count(), clone.grapheme_len());
+
+        let first_char_len = s.chars().next().unwrap().len_utf8();
+        r.delete(..first_char_len);
+        s.replace_range(..first_char_len, "");
+
+        assert_eq!(s.graphemes(true).count(), r.grapheme_len());
+    }
+}
+
+#[cfg(feature = "graphemes")]
+#[test]
+fn iter_graphemes_fused() {
+    for s in ["", "a", "🐻‍❄️ foo\r\n", "e\u{301}\r\n🐕‍🦺"] {
+        let r = Rope::from(s);
+
+        let mut graphemes = r.graphemes();
+        while graphemes.next().is_some() {}
+
+        for _ in 0..3 {
+            assert_eq!(graphemes.next(), None);
+            assert_eq!(graphemes.next_back(), None);
+        }
+    }
+}
@@ -1,3 +1,6 @@
+use std::borrow::Cow;
+
+use crop::iter::Chunks;
 use crop::Rope;
 use rand::{thread_rng, Rng};
 
@@ -86,6 +89,7 @@ fn iter_bytes_both_ways() {
     assert_eq!(None, rope_bytes.next_back());
 }
 
+#[allow(clippy::needless_as_bytes)]
 #[test]
 fn iter_bytes_cursed() {
     let s = CURSED_LIPSUM;
@@ -271,6 +275,358 @@ fn iter_chars_cursed() {
     }
 }
 
+#[test]
+fn iter_byte_chunks_empty() {
+    let r = Rope::new();
+    assert_eq!(None, r.byte_chunks().next());
+    assert_eq!(None, r.byte_slice(..).byte_chunks().next_back());
+
+    let r = Rope::from("foo");
+    assert_eq!(None, r.byte_slice(1..1).byte_chunks().next());
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_byte_chunks_over_random_slices() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let rope = Rope::from(s);
+
+        let bytes = rope.byte_chunks().flatten().copied().collect::<Vec<_>>();
+        assert_eq!(bytes, s.as_bytes());
+
+        for _ in 0..10 {
+            let mut start = rng.gen_range(0..=rope.byte_len());
+            while !s.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end = rng.gen_range(start..=rope.byte_len());
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+
+            let slice = rope.byte_slice(start..end);
+
+            let mut offset = start;
+
+            for (bytes, chunk) in slice.byte_chunks().zip(slice.chunks()) {
+                assert!(!bytes.is_empty());
+                assert_eq!(bytes, chunk.as_bytes());
+                assert_eq!(bytes, &s.as_bytes()[offset..offset + bytes.len()]);
+                offset += bytes.len();
+            }
+
+            assert_eq!(offset, end);
+            assert_eq!(slice.byte_chunks().count(), slice.chunks().count());
+
+            let mut offset = end;
+
+            for bytes in slice.byte_chunks().rev() {
+                offset -= bytes.len();
+                assert_eq!(bytes, &s.as_bytes()[offset..offset + bytes.len()]);
+            }
+
+            assert_eq!(offset, start);
+        }
+    }
+}
+
+#[test]
+fn iter_chunk_boundaries_empty() {
+    let r = Rope::new();
+    assert_eq!(None, r.chunk_boundaries().next());
+    assert_eq!(None, r.byte_slice(..).chunk_boundaries().next());
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_chunk_boundaries_over_random_slices() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE] {
+        let rope = Rope::from(s);
+
+        for _ in 0..10 {
+            let start = rng.gen_range(0..=rope.byte_len());
+            let end = rng.gen_range(start..=rope.byte_len());
+
+            let slice = rope.byte_slice(start..end);
+
+            let mut offset = 0;
+
+            for (boundary, chunk) in
+                slice.chunk_boundaries().zip(slice.chunks())
+            {
+                assert_eq!(boundary, offset);
+                offset += chunk.len();
+            }
+
+            assert_eq!(offset, slice.byte_len());
+            assert_eq!(
+                slice.chunk_boundaries().count(),
+                slice.chunks().count()
+            );
+        }
+    }
+}
+
+#[test]
+fn iter_chunks_empty() {
+    let r = Rope::new();
+    assert_eq!(None, r.chunks().next());
+    assert_eq!(None, r.chunks().next_back());
+    assert_eq!(None, r.byte_slice(..).chunks().next());
+
+    let r = Rope::from("foo");
+    assert_eq!(None, r.byte_slice(1..1).chunks().next());
+    assert_eq!(None, r.byte_slice(3..).chunks().next_back());
+}
+
+/// Tests that slices whose endpoints coincide with chunk boundaries never
+/// yield empty chunks.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_chunks_never_empty_at_chunk_boundaries() {
+    let mut rng = thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+        let mut r = Rope::from(s);
+
+        // Edits move the gaps of the chunks around, which splits them into
+        // two `&str`s.
+        for _ in 0..20 {
+            let mut offset = rng.gen_range(0..=r.byte_len());
+            while !r.is_char_boundary(offset) {
+                offset -= 1;
+            }
+            r.insert(offset, "ab");
+            r.delete(offset..offset + 1);
+        }
+
+        let boundaries = r
+            .chunk_ranges()
+            .map(|(range, _)| range.start)
+            .chain(Some(r.byte_len()))
+            .collect::<Vec<_>>();
+
+        for _ in 0..100 {
+            let start = boundaries[rng.gen_range(0..boundaries.len())];
+            let end = boundaries[rng.gen_range(0..boundaries.len())];
+            let (start, end) = (start.min(end), start.max(end));
+
+            let slice = r.byte_slice(start..end);
+
+            assert!(slice.chunks().all(|chunk| !chunk.is_empty()));
+            assert!(slice.chunks().rev().all(|chunk| !chunk.is_empty()));
+            assert_eq!(slice.chunks().collect::<String>(), slice);
+        }
+
+        assert!(r.chunks().all(|chunk| !chunk.is_empty()));
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_chunk_ranges_over_random_slices() {
+    let mut rng = thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        for _ in 0..10 {
+            let mut start = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(start) {
+                start += 1;
+            }
+            let mut end = rng.gen_range(start..=s.len());
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+
+            let slice = r.byte_slice(start..end);
+
+            let mut offset = start;
+
+            for ((range, chunk), expected) in
+                slice.chunk_ranges().zip(slice.chunks())
+            {
+                assert_eq!(range.start, offset);
+                assert_eq!(chunk, expected);
+                assert_eq!(&s[range.clone()], chunk);
+                offset = range.end;
+            }
+
+            assert_eq!(offset, end);
+
+            let mut offset = end;
+
+            for (range, chunk) in slice.chunk_ranges().rev() {
+                assert_eq!(range.end, offset);
+                assert_eq!(&s[range.clone()], chunk);
+                offset = range.start;
+            }
+
+            assert_eq!(offset, start);
+        }
+
+        assert!(r.chunk_ranges().eq(r.byte_slice(..).chunk_ranges()));
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn reader_random() {
+    use std::io::{BufRead, Read};
+
+    let mut rng = thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        let mut read = String::new();
+        r.reader().read_to_string(&mut read).unwrap();
+        assert_eq!(read, s);
+
+        for _ in 0..10 {
+            let mut start = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end = rng.gen_range(start..=s.len());
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+
+            let slice = r.byte_slice(start..end);
+
+            let lines = slice.reader().lines().map(Result::unwrap);
+            assert!(lines.eq(s[start..end].lines()));
+
+            // Reading with a tiny buffer has to span chunks.
+            let mut reader = slice.reader();
+            let mut bytes = Vec::new();
+            let mut buf = [0; 3];
+            loop {
+                match reader.read(&mut buf).unwrap() {
+                    0 => break,
+                    n => bytes.extend_from_slice(&buf[..n]),
+                }
+            }
+            assert_eq!(bytes, &s.as_bytes()[start..end]);
+
+            // The buffer is the rest of the current chunk.
+            let mut reader = slice.reader();
+            if let Some(chunk) = slice.chunks().find(|c| !c.is_empty()) {
+                assert_eq!(reader.fill_buf().unwrap(), chunk.as_bytes());
+                reader.consume(chunk.len() - 1);
+                assert_eq!(
+                    reader.fill_buf().unwrap(),
+                    &chunk.as_bytes()[chunk.len() - 1..]
+                );
+            }
+        }
+    }
+
+    let r = Rope::new();
+    assert!(r.reader().fill_buf().unwrap().is_empty());
+}
+
+#[test]
+fn iter_chunks_resume_at_end() {
+    let r = Rope::from("foo");
+
+    let mut chunks = r.chunks();
+    while chunks.next().is_some() {}
+
+    assert_eq!(chunks.position().byte_offset(), r.byte_len());
+    assert_eq!(None, Chunks::resume_at(&r, chunks.position()).next());
+}
+
+/// Tests that resuming from a saved position yields exactly the chunks that
+/// were left to iterate over, including when the position falls between the
+/// two halves of a gap buffer.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_chunks_resume_at_random() {
+    let mut rng = thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let mut r = Rope::from(s);
+
+        for _ in 0..10 {
+            let mut offset = rng.gen_range(0..=r.byte_len());
+            while !r.is_char_boundary(offset) {
+                offset += 1;
+            }
+            r.insert(offset, "ab");
+        }
+
+        let chunks = r.chunks().collect::<Vec<_>>();
+
+        for _ in 0..20 {
+            let skip = rng.gen_range(0..=chunks.len());
+
+            let mut iter = r.chunks();
+            for _ in 0..skip {
+                let _ = iter.next();
+            }
+
+            let pos = iter.position();
+
+            assert_eq!(
+                pos.byte_offset(),
+                chunks[..skip].iter().map(|c| c.len()).sum::<usize>()
+            );
+
+            let resumed = Chunks::resume_at(&r, pos);
+            assert_eq!(resumed.position(), pos);
+            assert_eq!(resumed.collect::<Vec<_>>(), &chunks[skip..]);
+        }
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_chunks_resume_at_slice_random() {
+    let mut rng = thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        for _ in 0..20 {
+            let mut start = rng.gen_range(0..=r.byte_len());
+            while !r.is_char_boundary(start) {
+                start += 1;
+            }
+
+            let mut end = rng.gen_range(start..=r.byte_len());
+            while !r.is_char_boundary(end) {
+                end += 1;
+            }
+
+            let slice = r.byte_slice(start..end);
+
+            let chunks = slice.chunks().collect::<Vec<_>>();
+
+            let skip = rng.gen_range(0..=chunks.len());
+
+            let mut iter = slice.chunks();
+            for _ in 0..skip {
+                let _ = iter.next();
+            }
+
+            let pos = iter.position();
+
+            let resumed = Chunks::resume_at_slice(slice, pos);
+            assert_eq!(resumed.position(), pos);
+            assert_eq!(resumed.collect::<Vec<_>>(), &chunks[skip..]);
+        }
+    }
+}
+
 #[test]
 fn iter_lines_empty() {
     let r = Rope::new();
@@ -475,6 +831,369 @@ fn iter_lines_over_random_slices() {
     }
 }
 
+#[test]
+fn iter_lines_at_byte_0() {
+    let r = Rope::from("foo\nbar\r\nbaz\n");
+
+    for (byte_offset, first_line) in
+        [(0, "foo"), (3, "foo"), (4, "bar"), (8, "bar"), (9, "baz")]
+    {
+        assert_eq!(first_line, r.lines_at_byte(byte_offset).next().unwrap());
+    }
+
+    assert_eq!(0, r.lines_at_byte(r.byte_len()).count());
+
+    let r = Rope::from("foo\nbar");
+    assert_eq!(1, r.lines_at_byte(r.byte_len()).count());
+
+    assert_eq!(0, Rope::new().lines_at_byte(0).count());
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_lines_at_byte_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE] {
+        let rope = Rope::from(s);
+        let slice = rope.byte_slice(..);
+
+        for _ in 0..10 {
+            let byte_offset = rng.gen_range(0..=rope.byte_len());
+
+            let line_start = s[..byte_offset].rfind('\n').map_or(0, |i| i + 1);
+
+            let str_lines = s[line_start..].lines();
+
+            assert_eq!(
+                str_lines.clone().count(),
+                rope.lines_at_byte(byte_offset).count()
+            );
+
+            for ((rope_line, slice_line), str_line) in rope
+                .lines_at_byte(byte_offset)
+                .zip(slice.lines_at_byte(byte_offset))
+                .zip(str_lines)
+            {
+                assert_eq!(rope_line, str_line);
+                assert_eq!(slice_line, str_line);
+            }
+        }
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_lines_cow_over_random_slices() {
+    let mut rng = thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE] {
+        let r = Rope::from(s);
+
+        for _ in 0..10 {
+            let start = rng.gen_range(0..=r.byte_len());
+            let end = rng.gen_range(start..=r.byte_len());
+
+            let slice = r.byte_slice(start..end);
+
+            assert_eq!(slice.lines_cow().len(), slice.line_len());
+
+            for (cow, line) in slice.lines_cow().zip(slice.lines()) {
+                assert_eq!(line, cow);
+                assert_eq!(
+                    matches!(cow, Cow::Borrowed(_)),
+                    line.as_str().is_some()
+                );
+            }
+
+            for (cow, line) in slice.lines_cow().rev().zip(slice.lines().rev())
+            {
+                assert_eq!(line, cow);
+            }
+        }
+    }
+}
+
+#[test]
+fn iter_lines_ending_in_r() {
+    let r = Rope::from("bar\nbaz\r\nqux\r");
+
+    let lines = r.lines().collect::<Vec<_>>();
+    assert_eq!(lines, ["bar", "baz", "qux\r"]);
+
+    let lines = r.lines().rev().collect::<Vec<_>>();
+    assert_eq!(lines, ["qux\r", "baz", "bar"]);
+}
+
+/// Splits the string on the line breaks recognized by
+/// `LineBreakKind::UnicodeFull`, without yielding a final empty line.
+#[cfg(feature = "unicode-lines")]
+fn unicode_lines(s: &str) -> Vec<&str> {
+    let mut lines = Vec::new();
+    let mut line_start = 0;
+
+    for (idx, ch) in s.char_indices() {
+        if matches!(ch, '\n' | '\u{0085}' | '\u{2028}' | '\u{2029}') {
+            let mut line = &s[line_start..idx];
+            if ch == '\n' {
+                line = line.strip_suffix('\r').unwrap_or(line);
+            }
+            lines.push(line);
+            line_start = idx + ch.len_utf8();
+        }
+    }
+
+    if line_start < s.len() {
+        lines.push(&s[line_start..]);
+    }
+
+    lines
+}
+
+#[cfg(feature = "unicode-lines")]
+#[test]
+fn iter_lines_with_unicode_full_0() {
+    use crop::LineBreakKind;
+
+    for s in [
+        "",
+        "abc",
+        "a\u{0085}b\u{2028}c\u{2029}d",
+        "\u{2028}\u{2028}",
+        "a\r\u{2029}b\r\n",
+        "\u{0085}\r\n\n\u{2029}a",
+        "bar\u{2028}baz\nqux\u{0085}",
+    ] {
+        let r = Rope::from(s);
+        let expected = unicode_lines(s);
+
+        for slice in [r.byte_slice(..), r.line_slice(..)] {
+            assert_eq!(
+                slice.line_len_with(LineBreakKind::UnicodeFull),
+                expected.len()
+            );
+
+            let lines = slice.lines_with(LineBreakKind::UnicodeFull);
+            assert_eq!(lines.len(), expected.len());
+            assert_eq!(lines.collect::<Vec<_>>(), expected);
+
+            let mut lines = slice
+                .lines_with(LineBreakKind::UnicodeFull)
+                .rev()
+                .collect::<Vec<_>>();
+            lines.reverse();
+            assert_eq!(lines, expected);
+        }
+
+        assert_eq!(
+            r.lines_with(LineBreakKind::UnicodeFull).collect::<Vec<_>>(),
+            expected
+        );
+
+        assert!(r.lines_with(LineBreakKind::Lf).eq(r.lines()));
+        assert_eq!(r.line_len_with(LineBreakKind::Lf), r.line_len());
+    }
+}
+
+#[cfg(feature = "unicode-lines")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_lines_with_unicode_full_random() {
+    use crop::LineBreakKind;
+
+    let mut rng = thread_rng();
+
+    let pieces = [
+        "a", "bc", "\u{e9}", "\n", "\r", "\r\n", "\u{0085}", "\u{2028}",
+        "\u{2029}",
+    ];
+
+    for _ in 0..20 {
+        let s = (0..rng.gen_range(0..2000))
+            .map(|_| pieces[rng.gen_range(0..pieces.len())])
+            .collect::<String>();
+
+        let mut r = Rope::from(s.as_str());
+
+        let boundaries = s
+            .char_indices()
+            .map(|(idx, _)| idx)
+            .chain(Some(s.len()))
+            .collect::<Vec<_>>();
+
+        for _ in 0..10 {
+            let start = boundaries[rng.gen_range(0..boundaries.len())];
+            let end = boundaries[rng.gen_range(0..boundaries.len())];
+            let (start, end) = (start.min(end), start.max(end));
+
+            let slice = r.byte_slice(start..end);
+            let expected = unicode_lines(&s[start..end]);
+
+            assert_eq!(
+                slice.line_len_with(LineBreakKind::UnicodeFull),
+                expected.len()
+            );
+
+            let lines = slice.lines_with(LineBreakKind::UnicodeFull);
+            assert_eq!(lines.collect::<Vec<_>>(), expected);
+
+            let mut lines = slice
+                .lines_with(LineBreakKind::UnicodeFull)
+                .rev()
+                .collect::<Vec<_>>();
+            lines.reverse();
+            assert_eq!(lines, expected);
+        }
+
+        // Editing the rope has to keep the line breaks tracked in the chunk
+        // summaries up to date.
+        let mut s = s;
+
+        for _ in 0..10 {
+            let boundaries = s
+                .char_indices()
+                .map(|(idx, _)| idx)
+                .chain(Some(s.len()))
+                .collect::<Vec<_>>();
+
+            let start = boundaries[rng.gen_range(0..boundaries.len())];
+            let end = boundaries[rng.gen_range(0..boundaries.len())];
+            let (start, end) = (start.min(end), start.max(end));
+
+            let text = pieces[rng.gen_range(0..pieces.len())];
+
+            r.replace(start..end, text);
+            s.replace_range(start..end, text);
+
+            let expected = unicode_lines(&s);
+
+            assert_eq!(
+                r.line_len_with(LineBreakKind::UnicodeFull),
+                expected.len()
+            );
+            assert_eq!(
+                r.lines_with(LineBreakKind::UnicodeFull).collect::<Vec<_>>(),
+                expected
+            );
+        }
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_line_ranges() {
+    for s in
+        ["", "\n", "a\r\n\r\nb\r", TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM]
+    {
+        let r = Rope::from(s);
+
+        let ranges = r.line_ranges().collect::<Vec<_>>();
+        let raw_ranges = r.raw_line_ranges().collect::<Vec<_>>();
+
+        assert_eq!(ranges.len(), r.line_len());
+        assert_eq!(raw_ranges.len(), r.line_len());
+
+        let mut offset = 0;
+
+        for ((range, raw_range), line) in
+            ranges.iter().zip(&raw_ranges).zip(s.lines())
+        {
+            assert_eq!(&s[range.clone()], line);
+            assert_eq!(raw_range.start, offset);
+            assert_eq!(range.start, offset);
+            offset = raw_range.end;
+        }
+
+        assert_eq!(offset, s.len());
+
+        let mut rev = r.line_ranges().rev().collect::<Vec<_>>();
+        rev.reverse();
+        assert_eq!(rev, ranges);
+
+        let mut rev = r.raw_line_ranges().rev().collect::<Vec<_>>();
+        rev.reverse();
+        assert_eq!(rev, raw_ranges);
+    }
+}
+
+#[test]
+fn iter_matches_empty() {
+    let r = Rope::new();
+    assert_eq!(0, r.matches("a").count());
+    assert_eq!(0, r.byte_slice(..).matches("a").count());
+
+    let r = Rope::from("aaa");
+    assert_eq!(0, r.matches("").count());
+}
+
+#[test]
+fn iter_matches_0() {
+    let r = Rope::from("aaaaa");
+
+    let mut matches = r.matches("aa");
+
+    assert_eq!("aa", matches.next().unwrap());
+    assert_eq!("aa", matches.next().unwrap());
+    assert_eq!(None, matches.next());
+    assert_eq!(None, matches.next());
+}
+
+#[test]
+fn iter_matches_1() {
+    let r = Rope::from(CURSED_LIPSUM);
+
+    assert_eq!(
+        r.matches("\r\n").count(),
+        CURSED_LIPSUM.matches("\r\n").count()
+    );
+    assert_eq!(r.matches("ṁ").count(), CURSED_LIPSUM.matches("ṁ").count());
+    assert_eq!(r.matches(CURSED_LIPSUM).count(), 1);
+    assert_eq!(r.matches(&CURSED_LIPSUM[3..]).count(), 1);
+    assert_eq!(r.matches("ḼḼ").count(), 0);
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_matches_over_random_slices() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE] {
+        let rope = Rope::from(s);
+
+        for _ in 0..100 {
+            let start = rng.gen_range(0..=rope.byte_len());
+            let end = rng.gen_range(start..=rope.byte_len());
+
+            let range = start..end;
+
+            let rope_slice = rope.byte_slice(range.clone());
+            let str_slice = &s[range.clone()];
+
+            if str_slice.is_empty() {
+                continue;
+            }
+
+            let needle_start = rng.gen_range(0..str_slice.len());
+            let needle_end =
+                (needle_start + rng.gen_range(1..16)).min(str_slice.len());
+            let needle = &str_slice[needle_start..needle_end];
+
+            let mut count = 0;
+
+            for rope_match in rope_slice.matches(needle) {
+                rope_match.assert_invariants();
+                assert_eq!(rope_match, needle);
+                count += 1;
+            }
+
+            if count != str_slice.matches(needle).count() {
+                println!("Failed on {needle:?} in byte range: {range:?}");
+                assert_eq!(count, str_slice.matches(needle).count());
+            }
+        }
+    }
+}
+
 #[test]
 fn iter_raw_lines_0() {
     let r = Rope::from(
@@ -618,3 +1337,316 @@ fn iter_raw_lines_over_random_slices() {
         }
     }
 }
+
+#[test]
+fn iter_split_whitespace_empty() {
+    let r = Rope::new();
+    assert_eq!(0, r.split_whitespace().count());
+
+    let r = Rope::from(" \t\r\n\u{3000} \n");
+    assert_eq!(0, r.split_whitespace().count());
+    assert_eq!(0, r.byte_slice(2..).split_whitespace().count());
+}
+
+#[test]
+fn iter_split_whitespace_0() {
+    let r = Rope::from(" foo\u{3000}bar\u{a0}\u{a0}こんにちは\r\n🐕‍🦺 ");
+
+    let words = r.split_whitespace().collect::<Vec<_>>();
+
+    assert_eq!(["foo", "bar", "こんにちは", "🐕‍🦺"], &*words);
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_split_whitespace_over_random_slices() {
+    let mut rng = thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        for _ in 0..10 {
+            let start = rng.gen_range(0..=r.byte_len());
+            let end = rng.gen_range(start..=r.byte_len());
+
+            if !s.is_char_boundary(start) || !s.is_char_boundary(end) {
+                continue;
+            }
+
+            let rope_words = r
+                .byte_slice(start..end)
+                .split_whitespace()
+                .collect::<Vec<_>>();
+
+            let str_words =
+                s[start..end].split_whitespace().collect::<Vec<_>>();
+
+            assert_eq!(rope_words, str_words);
+        }
+    }
+}
+
+#[test]
+fn iter_wrapped_lines_0() {
+    let r = Rope::from("foo\nLorem ipsum\n\n🐸🐸\r\nbar\n");
+
+    let lines = r.wrapped_lines(5).collect::<Vec<_>>();
+    assert_eq!(lines, ["foo", "Lorem", " ipsu", "m", "", "🐸", "🐸", "bar"]);
+
+    let lines = r.wrapped_lines(2).collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        [
+            "fo", "o", "Lo", "re", "m ", "ip", "su", "m", "", "🐸", "🐸",
+            "ba", "r"
+        ]
+    );
+
+    assert_eq!(Rope::new().wrapped_lines(1).count(), 0);
+}
+
+#[test]
+#[should_panic]
+fn iter_wrapped_lines_zero_width() {
+    let _ = Rope::from("foo").wrapped_lines(0);
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_wrapped_lines_random() {
+    let mut rng = thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        for _ in 0..5 {
+            let width = rng.gen_range(1..=100);
+
+            let mut expected = Vec::new();
+
+            for mut line in s.lines() {
+                if line.is_empty() {
+                    expected.push(line);
+                }
+
+                while !line.is_empty() {
+                    let mut split_at = width.min(line.len());
+
+                    while !line.is_char_boundary(split_at) {
+                        split_at -= 1;
+                    }
+
+                    if split_at == 0 {
+                        split_at = line.chars().next().unwrap().len_utf8();
+                    }
+
+                    expected.push(&line[..split_at]);
+                    line = &line[split_at..];
+                }
+            }
+
+            let lines = r.wrapped_lines(width).collect::<Vec<_>>();
+
+            assert_eq!(lines, expected);
+        }
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_bytes_position_rposition_find() {
+    let mut rng = thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        for _ in 0..20 {
+            let mut bytes = r.bytes();
+            let mut expected = s.as_bytes().iter().copied();
+
+            // Consume part of the iterators from both ends.
+            for _ in 0..rng.gen_range(0..=10) {
+                assert_eq!(bytes.next(), expected.next());
+            }
+            for _ in 0..rng.gen_range(0..=10) {
+                assert_eq!(bytes.next_back(), expected.next_back());
+            }
+
+            let target = s.as_bytes().get(rng.gen_range(0..s.len())).copied();
+            let target = target.unwrap_or(b'\n');
+
+            match rng.gen_range(0..4) {
+                0 => assert_eq!(
+                    bytes.position(|b| b == target),
+                    expected.position(|b| b == target)
+                ),
+                1 => assert_eq!(
+                    bytes.rposition(|b| b == target),
+                    expected.rposition(|b| b == target)
+                ),
+                2 => assert_eq!(
+                    bytes.find(|&b| b > target),
+                    expected.find(|&b| b > target)
+                ),
+                _ => assert_eq!(
+                    bytes.position(|_| false),
+                    expected.position(|_| false)
+                ),
+            }
+
+            assert_eq!(bytes.len(), expected.len());
+
+            // The iterators must also agree on what's left.
+            for _ in 0..rng.gen_range(0..10) {
+                assert_eq!(bytes.next_back(), expected.next_back());
+            }
+            assert_eq!(
+                bytes.collect::<Vec<_>>(),
+                expected.collect::<Vec<_>>()
+            );
+        }
+    }
+}
+
+#[test]
+fn iter_bytes_position_is_absolute_offset() {
+    let r = Rope::from("foo\nbar\nbaz");
+
+    assert_eq!(r.bytes().position(|b| b == b'\n'), Some(3));
+    assert_eq!(r.bytes().rposition(|b| b == b'\n'), Some(7));
+    assert_eq!(r.byte_slice(4..).bytes().position(|b| b == b'\n'), Some(3));
+
+    let mut bytes = r.bytes();
+    assert_eq!(bytes.position(|b| b == b'\n'), Some(3));
+    // Like for any other iterator, the next index is relative to where the
+    // iterator resumes.
+    assert_eq!(bytes.position(|b| b == b'\n'), Some(3));
+    assert_eq!(bytes.position(|b| b == b'\n'), None);
+    assert_eq!(bytes.next(), None);
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_chars_count() {
+    let mut rng = thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        assert_eq!(r.chars().count(), s.chars().count());
+
+        for _ in 0..20 {
+            let mut chars = r.chars();
+            let mut expected = s.chars();
+
+            for _ in 0..rng.gen_range(0..=10) {
+                assert_eq!(chars.next(), expected.next());
+            }
+            for _ in 0..rng.gen_range(0..=10) {
+                assert_eq!(chars.next_back(), expected.next_back());
+            }
+
+            assert_eq!(chars.count(), expected.count());
+        }
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_chars_and_bytes_last() {
+    let mut rng = thread_rng();
+
+    for s in ["", "a", "🐸", TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        assert_eq!(r.chars().last(), s.chars().last());
+        assert_eq!(r.bytes().last(), s.bytes().last());
+        assert_eq!(r.first_char(), s.chars().next());
+        assert_eq!(r.last_char(), s.chars().next_back());
+
+        for _ in 0..20 {
+            let mut end = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+
+            let slice = r.byte_slice(..end);
+            let s = &s[..end];
+
+            assert_eq!(slice.first_char(), s.chars().next());
+            assert_eq!(slice.last_char(), s.chars().next_back());
+
+            let mut chars = slice.chars();
+            let mut expected = s.chars();
+
+            for _ in 0..rng.gen_range(0..=3) {
+                assert_eq!(chars.next(), expected.next());
+            }
+
+            assert_eq!(chars.last(), expected.last());
+
+            let mut bytes = slice.bytes();
+            let mut expected = s.bytes();
+
+            for _ in 0..rng.gen_range(0..=3) {
+                assert_eq!(bytes.next_back(), expected.next_back());
+            }
+
+            assert_eq!(bytes.last(), expected.last());
+        }
+    }
+}
+
+/// Exhausts the iterator and checks that it keeps returning `None`.
+fn assert_fused<I: core::iter::FusedIterator>(mut iter: I) {
+    while iter.next().is_some() {}
+
+    for _ in 0..3 {
+        assert!(iter.next().is_none());
+    }
+}
+
+/// Like `assert_fused()`, but exhausts the iterator from both ends.
+fn assert_fused_both_ways<I>(mut iter: I)
+where
+    I: core::iter::FusedIterator + DoubleEndedIterator,
+{
+    loop {
+        if iter.next().is_none() || iter.next_back().is_none() {
+            break;
+        }
+    }
+
+    for _ in 0..3 {
+        assert!(iter.next().is_none());
+        assert!(iter.next_back().is_none());
+    }
+}
+
+#[test]
+fn iter_fused() {
+    for s in ["", "a", "foo\nbar\r\nbaz\n", TINY, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        let mut mid = s.len() / 2;
+        while !s.is_char_boundary(mid) {
+            mid += 1;
+        }
+
+        for slice in [r.byte_slice(..), r.byte_slice(mid..)] {
+            assert_fused_both_ways(slice.byte_chunks());
+            assert_fused_both_ways(slice.bytes());
+            assert_fused_both_ways(slice.chars());
+            assert_fused_both_ways(slice.chunks());
+            assert_fused_both_ways(slice.lines());
+            assert_fused_both_ways(slice.raw_lines());
+        }
+
+        assert_fused(r.byte_chunks());
+        assert_fused(r.bytes());
+        assert_fused(r.chars());
+        assert_fused(r.chunks());
+        assert_fused(r.lines());
+        assert_fused(r.raw_lines());
+    }
+}
@@ -0,0 +1,70 @@
+mod common;
+
+#[cfg(feature = "unicode-normalization")]
+mod tests {
+    use crop::Rope;
+    use rand::Rng;
+    use unicode_normalization::UnicodeNormalization;
+
+    use crate::common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TINY};
+
+    #[test]
+    fn nfc_nfd_0() {
+        let r = Rope::from("Cafe\u{301} cre\u{300}me bru\u{302}le\u{301}e");
+
+        assert_eq!(r.to_nfc(), "Caf\u{e9} cr\u{e8}me br\u{fb}l\u{e9}e");
+        assert_eq!(r.to_nfd(), r);
+        assert_eq!(r.to_nfc().to_nfd(), r);
+
+        assert_eq!(Rope::new().to_nfc(), "");
+        assert_eq!(Rope::new().to_nfd(), "");
+    }
+
+    /// Tests that combining marks are composed with the base character
+    /// preceding them even when the two are stored in different chunks.
+    #[test]
+    fn nfc_across_chunks() {
+        let s = "e\u{301}\u{323}".repeat(2000);
+
+        let r = Rope::from(s.as_str());
+
+        assert!(r.chunks().any(|chunk| !chunk.starts_with('e')));
+
+        let nfc = r.to_nfc();
+
+        assert_eq!(nfc, s.nfc().collect::<String>());
+        nfc.assert_invariants();
+
+        assert_eq!(nfc.to_nfd(), s.nfd().collect::<String>());
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn nfc_nfd_random() {
+        let mut rng = rand::thread_rng();
+
+        for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+            let mut r = Rope::from(s);
+            let mut s = s.to_owned();
+
+            for _ in 0..10 {
+                let mut offset = rng.gen_range(0..=r.byte_len());
+
+                while !s.is_char_boundary(offset) {
+                    offset -= 1;
+                }
+
+                r.insert(offset, "o\u{308}\u{301}");
+                s.insert_str(offset, "o\u{308}\u{301}");
+            }
+
+            let nfc = r.to_nfc();
+            nfc.assert_invariants();
+            assert_eq!(nfc, s.nfc().collect::<String>());
+
+            let nfd = r.to_nfd();
+            nfd.assert_invariants();
+            assert_eq!(nfd, s.nfd().collect::<String>());
+        }
+    }
+}
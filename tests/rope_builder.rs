@@ -1,7 +1,8 @@
 mod common;
 
-use common::LARGE;
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TINY};
 use crop::{Rope, RopeBuilder};
+use rand::Rng;
 
 #[test]
 fn builder_empty() {
@@ -51,3 +52,309 @@ fn builder_crlf_1() {
     r.assert_invariants();
     assert_eq!(r, "aaa\r\nbbb");
 }
+
+#[test]
+fn builder_trailing_newline() {
+    let mut b = RopeBuilder::new();
+    b.append("aaa\n").append("");
+    let r = b.build();
+    r.assert_invariants();
+    assert_eq!(r.line_len(), 1);
+
+    let mut b = RopeBuilder::new();
+    b.append("aaa\r\n").append("bbb\n").append("ccc");
+    let r = b.build();
+    r.assert_invariants();
+    assert_eq!(r.line_len(), 3);
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn builder_coalesces_small_appends() {
+    let mut b = RopeBuilder::new();
+    let mut s = String::new();
+
+    for ch in ["a", "é", "🦀", "\n", "\r"].into_iter().cycle().take(10000) {
+        b.append(ch);
+        s.push_str(ch);
+    }
+
+    let r = b.build();
+    r.assert_invariants();
+    assert_eq!(r, s);
+
+    // The chunks of the builder are filled up to their maximum capacity, so
+    // there should be at most as many as when creating the `Rope` directly.
+    assert!(r.chunks().count() <= Rope::from(&*s).chunks().count());
+    assert!(r.chunks().count() < s.len() / 2);
+}
+
+#[test]
+fn builder_stats() {
+    let (r, stats) = RopeBuilder::new().build_with_stats();
+    assert!(r.is_empty());
+    assert_eq!(stats.depth(), 0);
+    assert_eq!(stats.fill_ratio(), 0.0);
+
+    let mut b = RopeBuilder::new();
+
+    for line in LARGE.lines() {
+        b.append(line).append("\n");
+    }
+
+    let (r, stats) = b.build_with_stats();
+    r.assert_invariants();
+
+    assert_eq!(stats.leaf_count(), r.chunks().count());
+    assert!(stats.depth() >= 1);
+    assert!(stats.fill_ratio() > 0.9 && stats.fill_ratio() <= 1.0);
+}
+
+#[cfg(feature = "graphemes")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn builder_grapheme_aware_splits() {
+    // Clusters of 2 to 4 bytes, short enough to always fit in the slack
+    // between the minimum and maximum size of a chunk.
+    let clusters = ["e\u{301}", "\r\n", "a", "ö", "g\u{308}"];
+
+    let s = (0..5000)
+        .map(|i| clusters[(i * 7 + i / 3) % clusters.len()])
+        .collect::<String>();
+
+    let mut b = RopeBuilder::new();
+    b.grapheme_aware_splits(true).append(&s);
+    let r = b.build();
+
+    r.assert_invariants();
+    assert_eq!(r, s);
+
+    let mut offset = 0;
+
+    for chunk in r.chunks() {
+        offset += chunk.len();
+        assert!(r.is_grapheme_boundary(offset));
+    }
+
+    // The hint is off by default.
+    let mut b = RopeBuilder::new();
+    b.append(&s);
+    let r = b.build();
+
+    let mut offset = 0;
+
+    assert!(r.chunks().any(|chunk| {
+        offset += chunk.len();
+        !r.is_grapheme_boundary(offset)
+    }));
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn map_chunks() {
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        let mapped = r.clone().map_chunks(|chunk| chunk.replace('a', "aaa"));
+        mapped.assert_invariants();
+        assert_eq!(mapped, s.replace('a', "aaa"));
+
+        let mapped = r.clone().map_chunks(|chunk| chunk.replace('\n', ""));
+        mapped.assert_invariants();
+        assert_eq!(mapped, s.replace('\n', ""));
+
+        let mapped = r.map_chunks(|_| String::new());
+        mapped.assert_invariants();
+        assert!(mapped.is_empty());
+    }
+}
+
+#[test]
+fn from_placements_0() {
+    let r = Rope::from_placements(0, ' ', &[]);
+    assert!(r.is_empty());
+
+    let r = Rope::from_placements(3, 'a', &[(0, ""), (3, "")]);
+    assert_eq!(r, "aaa");
+
+    let r =
+        Rope::from_placements(10, '.', &[(0, "foo"), (3, "\n"), (8, "ba")]);
+    r.assert_invariants();
+    assert_eq!(r, "foo\n....ba");
+
+    let r = Rope::from_placements(10, '·', &[(2, "\r\n"), (6, "ö")]);
+    r.assert_invariants();
+    assert_eq!(r, "·\r\n·ö·");
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn from_placements_large() {
+    let lines = LARGE.lines().collect::<Vec<_>>();
+
+    let mut placements = Vec::new();
+    let mut expected = String::new();
+
+    for line in lines.iter().step_by(3) {
+        expected.push_str(&" ".repeat(line.len() % 7));
+        placements.push((expected.len(), *line));
+        expected.push_str(line);
+    }
+
+    expected.push_str("    ");
+
+    let r = Rope::from_placements(expected.len(), ' ', &placements);
+    r.assert_invariants();
+    assert_eq!(r, expected);
+}
+
+#[should_panic]
+#[test]
+fn from_placements_overlapping() {
+    let _ = Rope::from_placements(10, ' ', &[(0, "foo"), (2, "bar")]);
+}
+
+#[should_panic]
+#[test]
+fn from_placements_out_of_bounds() {
+    let _ = Rope::from_placements(10, ' ', &[(8, "foo")]);
+}
+
+#[should_panic]
+#[test]
+fn from_placements_splitting_fill() {
+    let _ = Rope::from_placements(6, 'ö', &[(1, "a")]);
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn concat_0() {
+    assert_eq!(crop::concat(Vec::<String>::new()), Rope::new());
+    assert_eq!(crop::concat(["", "", ""]), Rope::new());
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let words = s.split_inclusive(' ').collect::<Vec<_>>();
+
+        let r = crop::concat(&words);
+        r.assert_invariants();
+        assert_eq!(r, s);
+
+        let owned = crop::concat(words.iter().map(|word| word.to_owned()));
+        assert_eq!(owned, s);
+
+        // The words are packed into full chunks instead of getting one each.
+        assert!(r.chunks().count() <= Rope::from(s).chunks().count());
+    }
+}
+
+#[test]
+fn from_utf8_lossy_0() {
+    let r = Rope::from_utf8_lossy(b"");
+    assert_eq!(r, "");
+
+    let r = Rope::from_utf8_lossy(b"\xFF\xFE\n");
+    r.assert_invariants();
+    assert_eq!(r, "\u{FFFD}\u{FFFD}\n");
+
+    // A truncated code point at the end is replaced by a single replacement
+    // character.
+    let r = Rope::from_utf8_lossy(b"foo\xF0\x9F\x90");
+    r.assert_invariants();
+    assert_eq!(r, "foo\u{FFFD}");
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn from_utf8_lossy_random() {
+    let mut rng = rand::thread_rng();
+
+    let invalid: [&[u8]; 5] =
+        [b"\xFF", b"\xC3", b"\xE2\x82", b"\xF0\x9F\x90", b"\xED\xA0\x80"];
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let mut bytes = s.as_bytes().to_vec();
+
+        // Put invalid sequences around the chunk boundaries of the builder
+        // as well as at random positions.
+        let mut offsets = (1..=bytes.len() / 2048)
+            .map(|i| i * 2048 - 1)
+            .chain((0..10).map(|_| rng.gen_range(0..=bytes.len())))
+            .collect::<Vec<_>>();
+
+        offsets.sort_unstable();
+
+        for (i, &offset) in offsets.iter().enumerate().rev() {
+            bytes.splice(offset..offset, invalid[i % invalid.len()].to_vec());
+        }
+
+        let r = Rope::from_utf8_lossy(&bytes);
+        r.assert_invariants();
+        assert_eq!(r, String::from_utf8_lossy(&bytes));
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn builder_append_bytes_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, CURSED_LIPSUM, "🐸🐕‍🦺ƒ"] {
+        let mut builder = RopeBuilder::new();
+
+        let mut bytes = s.as_bytes();
+
+        while !bytes.is_empty() {
+            let frame_len = rng.gen_range(0..=bytes.len().min(5));
+            let (frame, rest) = bytes.split_at(frame_len);
+            builder.append_bytes(frame).unwrap();
+            bytes = rest;
+        }
+
+        let r = builder.build();
+        r.assert_invariants();
+        assert_eq!(r, s);
+    }
+}
+
+#[test]
+fn builder_append_bytes_invalid() {
+    let frog = "🐸".as_bytes();
+
+    let mut builder = RopeBuilder::new();
+
+    builder.append_bytes(b"foo").unwrap();
+    assert!(builder.append_bytes(b"\xF0\x28").is_err());
+    builder.append_bytes(&frog[..2]).unwrap();
+
+    builder.append_bytes(&frog[2..3]).unwrap();
+
+    // The last byte of the frog is missing.
+    assert!(builder.append_bytes(b"a").is_err());
+
+    builder.append_bytes(&[frog[3], frog[0]]).unwrap();
+    assert!(builder.append_bytes(&[frog[0]]).is_err());
+
+    let r = builder.build_lossy();
+    assert_eq!(r, "foo🐸\u{FFFD}");
+
+    let mut builder = RopeBuilder::new();
+    builder.append_bytes(&frog[..3]).unwrap();
+    builder.append_bytes(&frog[3..]).unwrap();
+    assert_eq!(builder.build_lossy(), "🐸");
+}
+
+#[test]
+#[should_panic]
+fn builder_build_incomplete_utf8() {
+    let mut builder = RopeBuilder::new();
+    builder.append_bytes(&"🐸".as_bytes()[..1]).unwrap();
+    let _ = builder.build();
+}
+
+#[test]
+#[should_panic]
+fn builder_append_after_incomplete_utf8() {
+    let mut builder = RopeBuilder::new();
+    builder.append_bytes(&"🐸".as_bytes()[..1]).unwrap();
+    builder.append("foo");
+}
@@ -1,4 +1,5 @@
-use crop::Rope;
+use crop::{LineEnding, Rope};
+use rand::Rng;
 
 mod common;
 
@@ -19,6 +20,40 @@ fn rope_byte_0() {
     }
 }
 
+#[cfg_attr(miri, ignore)]
+#[test]
+fn rope_get_byte_and_byte_unchecked() {
+    for s in ["", "Hi", "🐕‍🦺", TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        let mut mid = s.len() / 2;
+        while !s.is_char_boundary(mid) {
+            mid += 1;
+        }
+
+        let slice = r.byte_slice(mid..);
+
+        for (byte_index, &byte) in s.as_bytes().iter().enumerate() {
+            assert_eq!(r.get_byte(byte_index), Some(byte));
+
+            // SAFETY: the byte index is less than the byte length.
+            assert_eq!(unsafe { r.byte_unchecked(byte_index) }, byte);
+
+            if byte_index >= mid {
+                let byte_index = byte_index - mid;
+                assert_eq!(slice.get_byte(byte_index), Some(byte));
+
+                // SAFETY: the byte index is less than the byte length.
+                assert_eq!(unsafe { slice.byte_unchecked(byte_index) }, byte);
+            }
+        }
+
+        assert_eq!(r.get_byte(s.len()), None);
+        assert_eq!(r.get_byte(usize::MAX), None);
+        assert_eq!(slice.get_byte(slice.byte_len()), None);
+    }
+}
+
 #[cfg_attr(miri, ignore)]
 #[test]
 fn rope_is_char_boundary() {
@@ -35,6 +70,190 @@ fn rope_is_char_boundary() {
     }
 }
 
+#[cfg_attr(miri, ignore)]
+#[test]
+fn rope_is_ascii() {
+    for s in ["", "Hi", "🐕‍🦺", TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM]
+    {
+        let rope = Rope::from(s);
+        assert_eq!(s.is_ascii(), rope.is_ascii());
+        assert_eq!(s.is_ascii(), rope.byte_slice(..).is_ascii());
+    }
+
+    let rope = Rope::from(CURSED_LIPSUM);
+
+    for start in (0..=CURSED_LIPSUM.len())
+        .filter(|&idx| CURSED_LIPSUM.is_char_boundary(idx))
+    {
+        for end in (start..=CURSED_LIPSUM.len())
+            .filter(|&idx| CURSED_LIPSUM.is_char_boundary(idx))
+        {
+            assert_eq!(
+                CURSED_LIPSUM[start..end].is_ascii(),
+                rope.byte_slice(start..end).is_ascii()
+            );
+        }
+    }
+
+    let mut rope = Rope::from(LARGE);
+    rope.insert(LARGE.len() / 2, "🐕‍🦺");
+    assert!(!rope.is_ascii());
+    rope.delete(LARGE.len() / 2..LARGE.len() / 2 + "🐕‍🦺".len());
+    assert!(rope.is_ascii());
+}
+
+/// Tests the three ways of counting the lines of a `Rope`, which only differ
+/// on empty `Rope`s and on `Rope`s ending with a line break.
+#[test]
+fn rope_line_counts() {
+    // (text, line_len, line_count, newline_count)
+    let cases = [
+        ("", 0, 1, 0),
+        ("a", 1, 1, 0),
+        ("a\n", 1, 2, 1),
+        ("a\nb", 2, 2, 1),
+        ("\n", 1, 2, 1),
+        ("\n\n", 2, 3, 2),
+        ("a\r\nb\r\n", 2, 3, 2),
+        ("a\rb", 1, 1, 0),
+    ];
+
+    for (text, line_len, line_count, newline_count) in cases {
+        let r = Rope::from(text);
+        assert_eq!(r.line_len(), line_len, "{text:?}");
+        assert_eq!(r.line_count(), line_count, "{text:?}");
+        assert_eq!(r.newline_count(), newline_count, "{text:?}");
+        assert_eq!(r.lines().count(), line_len, "{text:?}");
+
+        let s = r.byte_slice(..);
+        assert_eq!(s.line_len(), line_len, "{text:?}");
+        assert_eq!(s.line_count(), line_count, "{text:?}");
+        assert_eq!(s.newline_count(), newline_count, "{text:?}");
+    }
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+        assert_eq!(r.newline_count(), s.matches('\n').count());
+        assert_eq!(r.line_count(), s.split('\n').count());
+    }
+}
+
+/// Tests that `final_line_terminated()` reflects the last byte of the text,
+/// and that writing a `Rope` back never adds a trailing line break.
+#[test]
+fn rope_final_line_terminated() {
+    use std::fmt::Write as _;
+    use std::io::Read;
+
+    for (text, terminated) in [
+        ("", false),
+        ("a", false),
+        ("a\n", true),
+        ("a\r\n", true),
+        ("a\r", false),
+        ("\n", true),
+        ("a\nb", false),
+    ] {
+        let r = Rope::from(text);
+        assert_eq!(r.final_line_terminated(), terminated, "{text:?}");
+        assert_eq!(r.byte_slice(..).final_line_terminated(), terminated);
+
+        assert_eq!(r.to_string(), text);
+        assert_eq!(r.byte_slice(..).to_string(), text);
+
+        let mut displayed = String::new();
+        write!(displayed, "{r}").unwrap();
+        assert_eq!(displayed, text);
+
+        let mut read = String::new();
+        r.reader().read_to_string(&mut read).unwrap();
+        assert_eq!(read, text);
+
+        assert_eq!(r.chunks().collect::<String>(), text);
+    }
+
+    // Edits keep it up to date.
+    let mut r = Rope::from("a");
+    r.insert(1, "\n");
+    assert!(r.final_line_terminated());
+    r.delete(1..);
+    assert!(!r.final_line_terminated());
+    r.delete(..);
+    assert!(!r.final_line_terminated());
+
+    for s in [TINY, SMALL, MEDIUM, LARGE] {
+        let r = Rope::from(s);
+        assert_eq!(r.final_line_terminated(), s.ends_with('\n'));
+        assert_eq!(r.to_string(), s);
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn rope_byte_line_col_every_offset() {
+    let long_lines = format!("{}\n\n{}\r\n{}", "a".repeat(5000), "b", "c");
+
+    for s in
+        ["", "\n", "foo\nbar\r\n", &long_lines, TINY, SMALL, CURSED_LIPSUM]
+    {
+        let r = Rope::from(s);
+
+        for byte_offset in (0..=s.len()).filter(|&i| s.is_char_boundary(i)) {
+            let line = s[..byte_offset].matches('\n').count();
+
+            let line_start = s[..byte_offset].rfind('\n').map_or(0, |i| i + 1);
+
+            assert_eq!(
+                r.byte_line_col(byte_offset),
+                (line, byte_offset - line_start)
+            );
+        }
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn rope_line_breaks_in_range_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        for _ in 0..100 {
+            let mut start = rng.gen_range(0..=r.byte_len());
+            let mut end = rng.gen_range(0..=r.byte_len());
+
+            if start > end {
+                std::mem::swap(&mut start, &mut end);
+            }
+
+            while !s.is_char_boundary(start) {
+                start += 1;
+            }
+
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+
+            assert_eq!(
+                r.line_breaks_in_range(start..end),
+                s.as_bytes()[start..end]
+                    .iter()
+                    .filter(|&&b| b == b'\n')
+                    .count(),
+                "{start}..{end}"
+            );
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn rope_line_breaks_in_range_out_of_bounds() {
+    let r = Rope::from("foo\nbar");
+    let _ = r.line_breaks_in_range(..8);
+}
+
 /// ```
 /// Root
 /// ├───┐
@@ -99,3 +318,290 @@ fn rope_line_3() {
     let l = r.line(2);
     assert_eq!("", l);
 }
+
+/// Checks the terminators returned by `line_with_ending()` against the ones
+/// found by splitting the string. With the `small_chunks` feature many of the
+/// `"\r\n"`s end up split between two chunks.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn rope_line_with_ending() {
+    let mut rng = rand::thread_rng();
+
+    for s in ["a\r\nb", "\r\n\r\n", "\n\r\r\n\r", TINY, SMALL, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        let (start, end) = if s.len() < 10 {
+            (0, s.len())
+        } else {
+            let mut start = rng.gen_range(0..s.len() / 2);
+            let mut end = s.len() - 3;
+            while !s.is_char_boundary(start) {
+                start += 1;
+            }
+            while !s.is_char_boundary(end) {
+                end -= 1;
+            }
+            (start, end)
+        };
+
+        let slice = r.byte_slice(start..end);
+
+        for (idx, raw) in s[start..end].split_inclusive('\n').enumerate() {
+            let (line, ending) = slice.line_with_ending(idx);
+
+            let expected = if let Some(line) = raw.strip_suffix("\r\n") {
+                (line, Some(LineEnding::CrLf))
+            } else if let Some(line) = raw.strip_suffix('\n') {
+                (line, Some(LineEnding::Lf))
+            } else {
+                (raw, None)
+            };
+
+            assert_eq!(line, expected.0);
+            assert_eq!(ending, expected.1);
+
+            // The last line of the slice can be cut short by its end.
+            if start == 0 && (end == s.len() || expected.1.is_some()) {
+                let (line, ending) = r.line_with_ending(idx);
+                assert_eq!(line, expected.0);
+                assert_eq!(ending, expected.1);
+            }
+        }
+    }
+}
+
+#[test]
+fn rope_line_indent_0() {
+    let r = Rope::from("  \t\n\u{a0}  a b \r\n\r\nfoo");
+
+    assert_eq!(r.line_indent(0), "  \t");
+    assert_eq!(r.line_indent_end(0), 3);
+
+    assert_eq!(r.line_indent(1), "\u{a0}  ");
+    assert_eq!(r.line_indent_end(1), 8);
+
+    assert_eq!(r.line_indent(2), "");
+    assert_eq!(r.line_indent_end(2), 14);
+
+    assert_eq!(r.line_indent(3), "");
+    assert_eq!(r.line_indent_end(3), 16);
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn rope_line_indent_random() {
+    let mut s = String::new();
+
+    for (idx, line) in MEDIUM.lines().enumerate() {
+        s.extend(core::iter::repeat(' ').take(idx % 7));
+        s.push_str(if idx % 5 == 0 { " \t " } else { line });
+        s.push('\n');
+    }
+
+    let r = Rope::from(s.as_str());
+
+    let mut line_start = 0;
+
+    for (idx, line) in s.lines().enumerate() {
+        let indent_len = line.len() - line.trim_start().len();
+
+        assert_eq!(r.line_indent(idx), &line[..indent_len]);
+        assert_eq!(r.line_indent_end(idx), line_start + indent_len);
+
+        line_start += line.len() + 1;
+    }
+}
+
+#[should_panic]
+#[test]
+fn rope_line_indent_out_of_bounds() {
+    let r = Rope::from("foo\nbar\n");
+    let _ = r.line_indent_end(2);
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn rope_get_line() {
+    for s in ["", "\n", "a\r\nb", TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        for line_index in 0..r.line_len() {
+            assert_eq!(r.get_line(line_index).unwrap(), r.line(line_index));
+        }
+
+        assert_eq!(r.get_line(r.line_len()), None);
+        assert_eq!(r.get_line(usize::MAX), None);
+
+        let mut mid = s.len() / 2;
+        while !s.is_char_boundary(mid) {
+            mid += 1;
+        }
+
+        let slice = r.byte_slice(mid..);
+
+        for line_index in 0..slice.line_len() {
+            assert_eq!(
+                slice.get_line(line_index).unwrap(),
+                slice.line(line_index)
+            );
+        }
+
+        assert_eq!(slice.get_line(slice.line_len()), None);
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn rope_chunks_of_line() {
+    for s in ["", "\n", "a\r\nb", TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        for (line_index, line) in s.lines().enumerate() {
+            let chunks = r.chunks_of_line(line_index).collect::<Vec<_>>();
+            assert_eq!(chunks.concat(), line);
+            assert!(chunks.iter().all(|chunk| !chunk.is_empty()));
+        }
+
+        let mut mid = s.len() / 2;
+        while !s.is_char_boundary(mid) {
+            mid += 1;
+        }
+
+        let slice = r.byte_slice(mid..);
+
+        for (line_index, line) in s[mid..].lines().enumerate() {
+            assert_eq!(
+                slice.chunks_of_line(line_index).collect::<String>(),
+                line
+            );
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn rope_chunks_of_line_out_of_bounds() {
+    let r = Rope::from("foo\nbar\n");
+    let _ = r.chunks_of_line(2);
+}
+
+#[test]
+fn rope_paragraphs_0() {
+    let r = Rope::new();
+    assert_eq!(r.paragraph_start(0), 0);
+    assert_eq!(r.paragraph_end(0), 0);
+
+    let r = Rope::from("\r\n\r\nfoo\r\nbar\r\n\r\n\r\nbaz");
+
+    let paragraphs = [(0, 2), (2, 4), (4, 14), (14, 16), (16, 18), (18, 21)];
+
+    for (start, end) in paragraphs {
+        for byte_offset in start..end {
+            assert_eq!(r.paragraph_start(byte_offset), start);
+            assert_eq!(r.paragraph_end(byte_offset), end);
+        }
+    }
+
+    assert_eq!(r.paragraph_start(21), 18);
+    assert_eq!(r.paragraph_end(21), 21);
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn rope_paragraphs_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+        // Turn some lines into blank lines.
+        let s = s
+            .split_inclusive('\n')
+            .map(|line| if rng.gen_ratio(1, 4) { "\n" } else { line })
+            .collect::<String>();
+
+        let r = Rope::from(s.as_str());
+
+        // The (start, end) byte ranges of the paragraphs.
+        let mut paragraphs = Vec::<(usize, usize)>::new();
+        let mut offset = 0;
+        let mut previous_blank = true;
+
+        for line in s.split_inclusive('\n') {
+            let blank = line == "\n" || line == "\r\n";
+            let end = offset + line.len();
+
+            match paragraphs.last_mut() {
+                Some(last) if !blank && !previous_blank => last.1 = end,
+                _ => paragraphs.push((offset, end)),
+            }
+
+            previous_blank = blank;
+            offset = end;
+        }
+
+        for (start, end) in paragraphs {
+            for _ in 0..5 {
+                let mut byte_offset = rng.gen_range(start..end);
+                while !s.is_char_boundary(byte_offset) {
+                    byte_offset -= 1;
+                }
+
+                assert_eq!(r.paragraph_start(byte_offset), start);
+                assert_eq!(r.paragraph_end(byte_offset), end);
+            }
+        }
+    }
+}
+
+#[test]
+fn byte_offsets_of_lines_0() {
+    for s in ["", "a", "\n", "a\n", "a\n\n", "\r\n\r\n", "a\r\nb\nc"] {
+        let r = Rope::from(s);
+
+        for start in 0..=r.line_len() {
+            for end in start..=r.line_len() {
+                let mut offsets = Vec::new();
+                r.byte_offsets_of_lines(start..end, &mut offsets);
+
+                let expected = (start..end)
+                    .map(|l| r.byte_of_line(l))
+                    .collect::<Vec<_>>();
+
+                assert_eq!(offsets, expected, "{s:?} {start}..{end}");
+            }
+        }
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn byte_offsets_of_lines_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        let mut offsets = vec![42];
+
+        for _ in 0..20 {
+            let start = rng.gen_range(0..=r.line_len());
+            let end = rng.gen_range(start..=r.line_len());
+
+            offsets.truncate(1);
+            r.byte_offsets_of_lines(start..end, &mut offsets);
+
+            assert_eq!(offsets[0], 42);
+
+            let expected =
+                (start..end).map(|l| r.byte_of_line(l)).collect::<Vec<_>>();
+
+            assert_eq!(offsets[1..], expected);
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn byte_offsets_of_lines_out_of_bounds() {
+    let r = Rope::from("foo\nbar");
+    r.byte_offsets_of_lines(1..3, &mut Vec::new());
+}
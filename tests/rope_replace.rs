@@ -1,9 +1,20 @@
-use crop::Rope;
+use crop::{
+    ApplyEditsError,
+    Compaction,
+    Edit,
+    EditError,
+    EditKind,
+    InverseEdit,
+    LineEnding,
+    Rope,
+    RopeBuilder,
+    SwapRangesError,
+};
 use rand::Rng;
 
 mod common;
 
-use common::{LARGE, MEDIUM, SMALL, TEXT, TINY};
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TEXT, TINY};
 
 #[test]
 fn insert_1() {
@@ -119,6 +130,55 @@ fn insert_8() {
     assert_eq!(r, "Hello Earth 🌎!");
 }
 
+#[test]
+fn insert_at_0() {
+    let mut r = Rope::new();
+
+    let mut cursor = 0;
+
+    for text in ["こ", "ん", "に", "ち", "は"] {
+        cursor = r.insert_at(cursor, text);
+        assert_eq!(cursor, r.byte_len());
+    }
+
+    r.assert_invariants();
+    assert_eq!(r, "こんにちは");
+
+    let cursor = r.insert_at(3, "\r\n");
+    assert_eq!(r.byte_slice(cursor..), "んにちは");
+}
+
+#[test]
+fn insert_expanded_0() {
+    let mut r = Rope::from("a\tb\r\nこん");
+
+    // The column of the insertion point accounts for the tab already in the
+    // line.
+    r.insert_expanded(3, "\t|", 4);
+    assert_eq!(r, "a\tb   |\r\nこん");
+
+    // Columns are counted in chars, not bytes.
+    r.insert_expanded(r.byte_len(), "\t|", 4);
+    assert_eq!(r, "a\tb   |\r\nこん  |");
+
+    // Columns are reset after each line break in the inserted text.
+    r.insert_expanded(0, "ab\tc\n\t", 3);
+    assert_eq!(r, "ab c\n   a\tb   |\r\nこん  |");
+
+    // Text without tabs is inserted as is.
+    r.insert_expanded(0, "xyz", 3);
+    assert_eq!(r, "xyzab c\n   a\tb   |\r\nこん  |");
+
+    r.assert_invariants();
+}
+
+#[test]
+#[should_panic]
+fn insert_expanded_zero_tab_width() {
+    let mut r = Rope::from("foo");
+    r.insert_expanded(0, "\t", 0);
+}
+
 #[cfg_attr(miri, ignore)]
 #[test]
 fn insert_small_random() {
@@ -269,6 +329,125 @@ fn delete_9() {
     r.delete(128..129); // Removing past the end
 }
 
+#[test]
+#[should_panic(expected = "the offset is 128 but the length is 127")]
+fn delete_10() {
+    let mut r = Rope::from(TEXT);
+    r.delete(..128); // Removing past the end
+}
+
+#[test]
+#[should_panic(expected = "byte offset out of bounds")]
+fn delete_11() {
+    let mut r = Rope::from(TEXT);
+    r.delete(..=usize::MAX); // The end of the range saturates
+}
+
+#[test]
+fn delete_at_0() {
+    let mut r = Rope::from("こんにちは");
+
+    assert_eq!(r.delete_at(3..9), 3);
+    assert_eq!(r, "こちは");
+
+    assert_eq!(r.delete_at(..), 0);
+    assert!(r.is_empty());
+}
+
+#[test]
+fn drain_0() {
+    let mut r = Rope::from("こんにちは");
+
+    let mut drain = r.drain(3..12);
+    assert_eq!(r, "こは");
+    assert_eq!(drain.size_hint(), (3, Some(9)));
+    assert_eq!(drain.next(), Some('ん'));
+    assert_eq!(drain.next_back(), Some('ち'));
+    assert_eq!(drain.as_str(), "に");
+    assert_eq!(drain.next(), Some('に'));
+    assert_eq!(drain.next(), None);
+    assert_eq!(drain.next_back(), None);
+
+    // Dropping the `Drain` without consuming it still removes the range.
+    r.drain(..3);
+    assert_eq!(r, "は");
+
+    assert_eq!(r.drain(3..3).count(), 0);
+    assert_eq!(r, "は");
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn drain_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let mut r = Rope::from(s);
+        let mut s = s.to_owned();
+
+        for _ in 0..20 {
+            let mut start = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end = rng.gen_range(start..=(start + 500).min(s.len()));
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+
+            let drained = r.drain(start..end).rev().collect::<String>();
+            let expected = s.drain(start..end).rev().collect::<String>();
+
+            assert_eq!(drained, expected);
+            assert_eq!(r, s);
+            r.assert_invariants();
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn drain_not_char_boundary() {
+    let mut r = Rope::from("こんにちは");
+    r.drain(1..);
+}
+
+#[test]
+fn strip_bom_0() {
+    let mut r = Rope::from("\u{FEFF}foo\r\nbar\n");
+
+    assert!(r.has_bom());
+    assert_eq!(r.lines().next().unwrap(), "\u{FEFF}foo");
+
+    assert!(r.strip_bom());
+    r.assert_invariants();
+    assert!(!r.has_bom());
+    assert_eq!(r, "foo\r\nbar\n");
+    assert_eq!(r.lines().next().unwrap(), "foo");
+
+    assert!(!r.strip_bom());
+    assert_eq!(r, "foo\r\nbar\n");
+
+    // A byte order mark which isn't at the start is left alone.
+    r.insert(3, "\u{FEFF}");
+    assert!(!r.has_bom());
+    assert!(!r.strip_bom());
+
+    // A `Rope` made only of a byte order mark.
+    let mut r = Rope::from("\u{FEFF}");
+    assert!(r.strip_bom());
+    r.assert_invariants();
+    assert!(r.is_empty());
+
+    // A byte order mark at the start of a large `Rope`.
+    let mut r = Rope::from(LARGE);
+    r.insert(0, "\u{FEFF}");
+    assert!(r.strip_bom());
+    r.assert_invariants();
+    assert_eq!(r, LARGE);
+}
+
 #[cfg_attr(miri, ignore)]
 #[test]
 fn delete_random() {
@@ -352,54 +531,1604 @@ fn replace_random() {
     }
 }
 
-/// ```
-/// Root
-/// ├── "aaa\r"
-/// ├── "bbbb"
-/// └── "\nccc"
-/// ```
+/// The maximum number of bytes in a chunk, which is much smaller with the
+/// `small_chunks` feature.
+const MAX_BYTES: usize = if cfg!(feature = "small_chunks") { 4 } else { 2048 };
+
+/// Lengths clustered around the size limits of the chunks, i.e. multiples of
+/// the minimum and maximum number of bytes in a chunk, plus or minus 2
+/// bytes.
+fn lengths_around_chunk_sizes() -> Vec<usize> {
+    let min_bytes = MAX_BYTES / 4;
+
+    let mut lengths = [0, min_bytes, 2 * min_bytes, MAX_BYTES, 2 * MAX_BYTES]
+        .into_iter()
+        .flat_map(|len| len.saturating_sub(2)..=len + 2)
+        .collect::<Vec<_>>();
+
+    lengths.sort_unstable();
+    lengths.dedup();
+    lengths
+}
+
+/// Returns a string of exactly `len` bytes, made of ASCII bytes if `ascii`
+/// and of mostly 4 byte code points otherwise.
+fn string_of_len(len: usize, ascii: bool) -> String {
+    if ascii {
+        return "abcdefghi\n".chars().cycle().take(len).collect();
+    }
+
+    let mut s = "🐕".repeat(len / 4);
+    s.extend(core::iter::repeat('a').take(len % 4));
+    s
+}
+
 #[test]
-fn fix_crlf_0() {
-    let mut r = Rope::from("aaa\rbbbb\nccc");
-    r.delete(4..8);
-    r.assert_invariants();
-    assert_eq!(r, "aaa\r\nccc");
+fn replace_empty_range_at_end() {
+    for len in lengths_around_chunk_sizes() {
+        for ascii in [true, false] {
+            let s = string_of_len(len, ascii);
+            let mut r = Rope::from(s.as_str());
+
+            r.replace(len..len, "");
+            r.assert_invariants();
+            assert_eq!(r, s);
+
+            r.insert(r.byte_len(), "");
+            r.delete(len..);
+            r.assert_invariants();
+            assert_eq!(r, s);
+
+            for insert_len in lengths_around_chunk_sizes() {
+                let text = string_of_len(insert_len, !ascii);
+
+                let mut r = r.clone();
+                r.replace(len..len, &text);
+                r.assert_invariants();
+                assert_eq!(r, s.clone() + &text);
+            }
+        }
+    }
 }
 
-/// ```
-/// Root
-/// └── "aaa\r"
-/// ```
+/// Sweeps replacements whose lengths land on or right next to the chunk size
+/// limits, at offsets on or right next to the boundaries between chunks.
+#[cfg_attr(miri, ignore)]
 #[test]
-fn fix_crlf_1() {
-    let mut r = Rope::from("aaa\r");
-    r.insert(4, "\nbbb");
+fn replace_around_chunk_sizes() {
+    let lengths = lengths_around_chunk_sizes();
+
+    for &len in &lengths {
+        for ascii in [true, false] {
+            let s = string_of_len(len, ascii);
+            let r = Rope::from(s.as_str());
+
+            let mut offsets = r
+                .chunks()
+                .scan(0, |offset, chunk| {
+                    *offset += chunk.len();
+                    Some(*offset)
+                })
+                .flat_map(|offset| offset.saturating_sub(1)..=offset + 1)
+                .chain([0, len / 2])
+                .filter(|&offset| offset <= len)
+                .collect::<Vec<_>>();
+
+            offsets.sort_unstable();
+            offsets.dedup();
+
+            for &start in &offsets {
+                for removed in [0, 1, 4, MAX_BYTES] {
+                    let (start, end) =
+                        char_boundaries(&s, start, (start + removed).min(len));
+
+                    for &inserted in &lengths {
+                        let text = string_of_len(inserted, !ascii);
+
+                        let mut r = r.clone();
+                        let mut s = s.clone();
+
+                        r.replace(start..end, &text);
+                        s.replace_range(start..end, &text);
+
+                        r.assert_invariants();
+                        assert_eq!(r, s, "{len} {start}..{end} {inserted}");
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn replace_tracking_0() {
+    let mut r = Rope::from("aaabbbccc");
+
+    let mut offsets = [0, 3, 4, 6, 9];
+    r.replace_tracking(3..6, "dd", &mut offsets);
+    assert_eq!(r, "aaaddccc");
+    assert_eq!(offsets, [0, 3, 3, 5, 8]);
+
+    // Offsets at the insertion point stay before the inserted text.
+    let mut offsets = [3, 4];
+    r.replace_tracking(3..3, "ee", &mut offsets);
+    assert_eq!(r, "aaaeeddccc");
+    assert_eq!(offsets, [3, 6]);
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn replace_tracking_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE] {
+        let mut r = Rope::from(s);
+        let mut s = s.to_owned();
+
+        for _ in 0..20 {
+            let replace_range = {
+                let start = rng.gen_range(0..=r.byte_len());
+                let end = rng.gen_range(start..=r.byte_len());
+                start..end
+            };
+
+            let replace_with = {
+                let start = rng.gen_range(0..=r.byte_len());
+                let end = rng.gen_range(start..=r.byte_len());
+                s[start..end].to_owned()
+            };
+
+            let old_offsets = (0..10)
+                .map(|_| rng.gen_range(0..=r.byte_len()))
+                .collect::<Vec<_>>();
+
+            let mut offsets = old_offsets.clone();
+
+            let old_s = s.clone();
+
+            r.replace_tracking(
+                replace_range.clone(),
+                &replace_with,
+                &mut offsets,
+            );
+            s.replace_range(replace_range.clone(), &replace_with);
+
+            r.assert_invariants();
+            assert_eq!(s, r);
+
+            for (old, new) in old_offsets.into_iter().zip(offsets) {
+                if old <= replace_range.start {
+                    assert_eq!(old, new);
+                } else if old >= replace_range.end {
+                    assert_eq!(old_s[old..], s[new..]);
+                } else {
+                    assert_eq!(replace_range.start, new);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn dedup_consecutive_lines_0() {
+    let mut r = Rope::new();
+    assert_eq!(r.dedup_consecutive_lines(), 0);
+
+    let mut r = Rope::from("\n\n\r\n\n");
+    assert_eq!(r.dedup_consecutive_lines(), 3);
+    assert_eq!(r, "\n");
+
+    let mut r = Rope::from("aaaa\naaaa\naaa\naaaa");
+    assert_eq!(r.dedup_consecutive_lines(), 1);
     r.assert_invariants();
-    assert_eq!(r, "aaa\r\nbbb");
+    assert_eq!(r, "aaaa\naaa\naaaa");
 }
 
-/// ```
-/// Root
-/// ├── "aaaa"
-/// ├── "bbbb"
-/// └── "\nccc"
-/// ```
 #[test]
-fn fix_crlf_2() {
-    let mut r = Rope::from("aaaabbbb\nccc");
-    r.replace(4..8, "ddd\r");
+fn dedup_consecutive_lines_unterminated_last_line() {
+    let mut r = Rope::from("foo\nfoo");
+    assert_eq!(r.dedup_consecutive_lines(), 1);
     r.assert_invariants();
-    assert_eq!(r, "aaaaddd\r\nccc");
+    assert_eq!(r, "foo");
+
+    let mut r = Rope::from("foo\r\nfoo\nfoo");
+    assert_eq!(r.dedup_consecutive_lines(), 2);
+    r.assert_invariants();
+    assert_eq!(r, "foo");
+
+    let mut r = Rope::from("bar\nfoo\r\nfoo\r\nfoo");
+    assert_eq!(r.dedup_consecutive_lines(), 2);
+    r.assert_invariants();
+    assert_eq!(r, "bar\nfoo");
+
+    // A terminated last line keeps the line break of the one it duplicates.
+    let mut r = Rope::from("foo\nfoo\n");
+    assert_eq!(r.dedup_consecutive_lines(), 1);
+    assert_eq!(r, "foo\n");
 }
 
+#[cfg_attr(miri, ignore)]
 #[test]
-fn fix_crlf_4() {
-    let mut r =
-        Rope::from("\r\n\r\n\r\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n");
+fn dedup_consecutive_lines_random() {
+    let mut rng = rand::thread_rng();
 
-    r.delete(3..6);
+    let words = ["", "foo", "こんにちは", "a longer line which spans chunks"];
+
+    for _ in 0..20 {
+        let mut s = String::new();
+
+        for _ in 0..rng.gen_range(0..100) {
+            s.push_str(words[rng.gen_range(0..words.len())]);
+            s.push_str(if rng.gen() { "\n" } else { "\r\n" });
+        }
+
+        if rng.gen() {
+            s.push_str(words[rng.gen_range(1..words.len())]);
+        }
+
+        let mut expected = String::new();
+        let mut expected_removed = 0;
+        let mut previous = None;
+        let mut last_removed = false;
+
+        for raw_line in s.split_inclusive('\n') {
+            let line = raw_line.trim_end_matches(['\r', '\n']);
+            last_removed = previous == Some(line);
+            if last_removed {
+                expected_removed += 1;
+            } else {
+                expected.push_str(raw_line);
+            }
+            previous = Some(line);
+        }
+
+        if last_removed && !s.ends_with('\n') {
+            expected.pop();
+            if expected.ends_with('\r') {
+                expected.pop();
+            }
+        }
+
+        let mut r = Rope::from(s);
+        assert_eq!(r.dedup_consecutive_lines(), expected_removed);
+        r.assert_invariants();
+        assert_eq!(r, expected);
+    }
+}
 
+/// A reader which yields the bytes of a slice a few at a time, to make sure
+/// characters get split across reads.
+struct Trickle<'a> {
+    bytes: &'a [u8],
+    step: usize,
+}
+
+impl std::io::Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.step.min(buf.len()).min(self.bytes.len());
+        buf[..n].copy_from_slice(&self.bytes[..n]);
+        self.bytes = &self.bytes[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn replace_from_reader_0() {
+    let mut r = Rope::from("aaa\rbbb");
+
+    for step in 1..5 {
+        let mut r = r.clone();
+        let text = "\nこんにちは\r\n🐕‍🦺";
+        let reader = Trickle { bytes: text.as_bytes(), step };
+        r.replace_from_reader(4..6, reader).unwrap();
+        r.assert_invariants();
+        assert_eq!(r, "aaa\r\nこんにちは\r\n🐕‍🦺b");
+    }
+
+    // Invalid UTF-8 in the middle of the stream.
+    let reader = Trickle { bytes: b"ccc\xFFddd", step: 2 };
+    let err = r.replace_from_reader(.., reader).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
     r.assert_invariants();
+    assert_eq!(r, "aaa\rbbb");
 
-    assert_eq!(r, "\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n");
+    // Incomplete character at the end of the stream.
+    let reader = Trickle { bytes: &"こ".as_bytes()[..2], step: 1 };
+    assert!(r.replace_from_reader(3..3, reader).is_err());
+    r.assert_invariants();
+    assert_eq!(r, "aaa\rbbb");
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn replace_from_reader_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE] {
+        let mut r = Rope::from(s);
+        let mut s = s.to_owned();
+
+        for _ in 0..20 {
+            let replace_range = {
+                let start = rng.gen_range(0..=r.byte_len());
+                let end = rng.gen_range(start..=r.byte_len());
+                start..end
+            };
+
+            let replace_with = {
+                let start = rng.gen_range(0..=r.byte_len());
+                let end = rng.gen_range(start..=r.byte_len());
+                s[start..end].to_owned()
+            };
+
+            let reader = Trickle {
+                bytes: replace_with.as_bytes(),
+                step: rng.gen_range(1..64),
+            };
+
+            r.replace_from_reader(replace_range.clone(), reader).unwrap();
+            s.replace_range(replace_range, &replace_with);
+
+            r.assert_invariants();
+            assert_eq!(s, r);
+        }
+    }
+}
+
+#[test]
+fn from_reader_0() {
+    for step in 1..5 {
+        let text = "aaa\r\nこんにちは\r\n🐕‍🦺";
+        let reader = Trickle { bytes: text.as_bytes(), step };
+        let r = Rope::from_reader(reader).unwrap();
+        r.assert_invariants();
+        assert_eq!(r, text);
+    }
+
+    let r = Rope::from_reader(Trickle { bytes: b"", step: 1 }).unwrap();
+    assert!(r.is_empty());
+
+    // Invalid UTF-8 in the middle of the stream.
+    let reader = Trickle { bytes: b"ccc\xFFddd", step: 2 };
+    let err = Rope::from_reader(reader).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    // Incomplete character at the end of the stream.
+    let reader = Trickle { bytes: &"aこ".as_bytes()[..3], step: 1 };
+    let err = Rope::from_reader(reader).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn from_reader_with_progress_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let step = rng.gen_range(1..1024);
+        let reader = Trickle { bytes: s.as_bytes(), step };
+
+        let mut progress = Vec::new();
+
+        let r = Rope::from_reader_with_progress(reader, |read| {
+            progress.push(read)
+        })
+        .unwrap();
+
+        r.assert_invariants();
+        assert_eq!(r, s);
+
+        // The reader yields `step` bytes per read, and the callback is called
+        // once per read with the running total.
+        assert_eq!(progress.len(), (s.len() + step - 1) / step);
+
+        for (idx, &read) in progress.iter().enumerate() {
+            assert_eq!(read, ((idx + 1) * step).min(s.len()));
+        }
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn retain_chars() {
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let mut r = Rope::from(s);
+
+        let generation = r.generation();
+
+        r.retain_chars(|ch| ch != 'a' && ch != '\n');
+        r.assert_invariants();
+
+        let mut s = s.to_owned();
+        s.retain(|ch| ch != 'a' && ch != '\n');
+
+        assert_eq!(r, s);
+        assert!(r.generation() > generation);
+
+        r.retain_chars(char::is_whitespace);
+        r.assert_invariants();
+
+        s.retain(char::is_whitespace);
+        assert_eq!(r, s);
+
+        r.retain_chars(|_| false);
+        r.assert_invariants();
+        assert!(r.is_empty());
+    }
+}
+
+#[test]
+fn swap_ranges_0() {
+    let mut r = Rope::from("foo\nbär\nbaz");
+
+    r.swap_ranges(9..12, 0..3).unwrap();
+    r.assert_invariants();
+    assert_eq!(r, "baz\nbär\nfoo");
+
+    r.swap_ranges(0..4, 4..9).unwrap();
+    r.assert_invariants();
+    assert_eq!(r, "bär\nbaz\nfoo");
+
+    r.swap_ranges(9..12, 0..0).unwrap();
+    r.assert_invariants();
+    assert_eq!(r, "foobär\nbaz\n");
+
+    r.swap_ranges(0..0, 12..12).unwrap();
+    assert_eq!(r, "foobär\nbaz\n");
+
+    assert_eq!(r.swap_ranges(0..4, 3..6), Err(SwapRangesError::Overlapping));
+
+    assert_eq!(r.swap_ranges(1..1, 0..4), Err(SwapRangesError::Overlapping));
+
+    assert_eq!(
+        r.swap_ranges(0..3, 4..5),
+        Err(SwapRangesError::NotCharBoundary { byte_offset: 5 })
+    );
+
+    assert_eq!(
+        r.swap_ranges(0..3, 8..20),
+        Err(SwapRangesError::OutOfBounds { byte_offset: 20, byte_len: 12 })
+    );
+
+    #[allow(clippy::reversed_empty_ranges)]
+    let err = r.swap_ranges(3..0, 4..5);
+    assert_eq!(err, Err(SwapRangesError::StartAfterEnd { start: 3, end: 0 }));
+
+    assert_eq!(r, "foobär\nbaz\n");
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn swap_ranges_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE] {
+        let mut r = Rope::from(s);
+        let mut s = s.to_owned();
+
+        for _ in 0..20 {
+            let mut offsets = [0; 4].map(|_| rng.gen_range(0..=s.len()));
+
+            offsets.sort_unstable();
+
+            let (a_start, a_end) = char_boundaries(&s, offsets[0], offsets[1]);
+
+            let (b_start, b_end) =
+                char_boundaries(&s, offsets[2].max(a_end), offsets[3]);
+
+            let a = a_start..a_end;
+            let b = b_start..b_end;
+
+            if rng.gen() {
+                r.swap_ranges(a.clone(), b.clone()).unwrap();
+            } else {
+                r.swap_ranges(b.clone(), a.clone()).unwrap();
+            }
+
+            s = [&s[..a.start], &s[b.clone()], &s[a.end..b.start], &s[a]]
+                .concat()
+                + &s[b.end..];
+
+            r.assert_invariants();
+            assert_eq!(s, r);
+        }
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn eager_compaction_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE] {
+        let mut lazy = Rope::from(s);
+        let mut eager = Rope::from(s);
+        eager.set_compaction(Compaction::Eager);
+
+        let mut s = s.to_owned();
+
+        for _ in 0..(s.len() / 8).min(2000) {
+            let (start, end) = {
+                let start = rng.gen_range(0..=s.len());
+                let end = rng.gen_range(start..=(start + 8).min(s.len()));
+                char_boundaries(&s, start, end)
+            };
+
+            let text = if rng.gen_ratio(1, 4) { "ab\n" } else { "" };
+
+            lazy.replace(start..end, text);
+            eager.replace(start..end, text);
+            s.replace_range(start..end, text);
+        }
+
+        eager.assert_invariants();
+        assert_eq!(eager, s);
+        assert_eq!(eager, lazy);
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn eager_compaction_scattered_deletions() {
+    let mut lazy = Rope::from(LARGE);
+    let mut eager = Rope::from(LARGE);
+    eager.set_compaction(Compaction::Eager);
+
+    let len = LARGE.len();
+
+    for start in (0..len).step_by(8).rev() {
+        let (start, end) = char_boundaries(LARGE, start, (start + 6).min(len));
+        lazy.delete(start..end);
+        eager.delete(start..end);
+    }
+
+    eager.assert_invariants();
+    assert_eq!(eager, lazy);
+    assert!(eager.chunks().count() < lazy.chunks().count());
+}
+
+#[test]
+fn insert_newline_0() {
+    let mut r = Rope::new();
+    assert_eq!(r.detect_line_ending(), LineEnding::Lf);
+
+    r.insert_newline(0);
+    assert_eq!(r, "\n");
+
+    let mut r = Rope::from("\r\n");
+    assert_eq!(r.detect_line_ending(), LineEnding::CrLf);
+
+    r.insert_newline(2);
+    r.insert_newline(0);
+    assert_eq!(r, "\r\n\r\n\r\n");
+
+    let mut r = Rope::from("\rfoo\r\r\n\n");
+    assert_eq!(r.detect_line_ending(), LineEnding::CrLf);
+
+    r.insert_newline(1);
+    assert_eq!(r, "\r\r\nfoo\r\r\n\n");
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn insert_char_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+        let mut r = Rope::from(s);
+        let mut s = s.to_owned();
+
+        for ch in "aé「🐸\n\r".chars().cycle().take(200) {
+            let mut at = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(at) {
+                at -= 1;
+            }
+
+            r.insert_char(at, ch);
+            s.insert(at, ch);
+        }
+
+        r.assert_invariants();
+        assert_eq!(r, s);
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn add_assign_str() {
+    for s in [TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+        let mut r = Rope::new();
+
+        for line in s.split_inclusive('\n') {
+            r += line;
+        }
+
+        r.assert_invariants();
+        assert_eq!(r, s);
+        assert_eq!(r.line_len(), Rope::from(s).line_len());
+    }
+
+    let r = Rope::new() + "foo" + "\r" + "\n";
+    assert_eq!(r, "foo\r\n");
+    assert_eq!(r.line_len(), 1);
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn replace_if_changed_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let mut r = Rope::from(s);
+        let mut s = s.to_owned();
+
+        for _ in 0..50 {
+            let mut start = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end = rng.gen_range(start..=(start + 5000).min(s.len()));
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+
+            // Replacing a range with its own text is a no-op.
+            let generation = r.generation();
+            let same = s[start..end].to_owned();
+            assert!(!r.replace_if_changed(start..end, &same));
+            assert_eq!(r.generation(), generation);
+
+            let mut different = same.clone();
+            if rng.gen() {
+                different.push('!');
+            } else {
+                different.insert(0, '!');
+            }
+            assert!(r.replace_if_changed(start..end, &different));
+            assert_ne!(r.generation(), generation);
+
+            s.replace_range(start..end, &different);
+            assert_eq!(r, s);
+        }
+
+        r.assert_invariants();
+    }
+}
+
+#[test]
+fn insert_pair_0() {
+    let mut r = Rope::new();
+    assert_eq!(r.insert_pair(0, '(', ')'), 1);
+    assert_eq!(r, "()");
+
+    assert_eq!(r.insert_pair(1, '"', '"'), 2);
+    assert_eq!(r, "(\"\")");
+
+    let mut r = Rope::from("ƒoo");
+    assert_eq!(r.insert_pair(2, '「', '」'), 5);
+    assert_eq!(r, "ƒ「」oo");
+    assert_eq!(r.byte_slice(5..), "」oo");
+
+    assert_eq!(r.insert_pair(r.byte_len(), '🐸', 'a'), 14);
+    assert_eq!(r, "ƒ「」oo🐸a");
+}
+
+#[test]
+#[should_panic]
+fn insert_pair_not_char_boundary() {
+    let mut r = Rope::from("ƒoo");
+    r.insert_pair(1, '(', ')');
+}
+
+#[test]
+fn detect_line_ending_across_chunks() {
+    let mut text = "a".repeat(5000);
+    text.push_str("\r\nb\n");
+
+    let r = Rope::from(text.as_str());
+    assert_eq!(r.detect_line_ending(), LineEnding::CrLf);
+
+    // The "\r" and the "\n" are appended separately so that they can end up
+    // in different chunks.
+    let mut builder = RopeBuilder::new();
+    for s in ["a", "aa", "aaa\r", "\nb\n"] {
+        builder.append(s);
+    }
+    let r = builder.build();
+    assert_eq!(r.detect_line_ending(), LineEnding::CrLf);
+
+    let mut r = Rope::from("aaa\rbbb\n");
+    assert_eq!(r.detect_line_ending(), LineEnding::Lf);
+
+    r.delete(4..7);
+    assert_eq!(r.detect_line_ending(), LineEnding::CrLf);
+}
+
+/// ```
+/// Root
+/// ├── "aaa\r"
+/// ├── "bbbb"
+/// └── "\nccc"
+/// ```
+#[test]
+fn fix_crlf_0() {
+    let mut r = Rope::from("aaa\rbbbb\nccc");
+    r.delete(4..8);
+    r.assert_invariants();
+    assert_eq!(r, "aaa\r\nccc");
+}
+
+/// ```
+/// Root
+/// └── "aaa\r"
+/// ```
+#[test]
+fn fix_crlf_1() {
+    let mut r = Rope::from("aaa\r");
+    r.insert(4, "\nbbb");
+    r.assert_invariants();
+    assert_eq!(r, "aaa\r\nbbb");
+}
+
+/// ```
+/// Root
+/// ├── "aaaa"
+/// ├── "bbbb"
+/// └── "\nccc"
+/// ```
+#[test]
+fn fix_crlf_2() {
+    let mut r = Rope::from("aaaabbbb\nccc");
+    r.replace(4..8, "ddd\r");
+    r.assert_invariants();
+    assert_eq!(r, "aaaaddd\r\nccc");
+}
+
+#[test]
+fn fix_crlf_4() {
+    let mut r =
+        Rope::from("\r\n\r\n\r\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n");
+
+    r.delete(3..6);
+
+    r.assert_invariants();
+
+    assert_eq!(r, "\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n");
+}
+
+/// Moves `start` and `end` to the closest char boundaries before and after
+/// them, respectively.
+fn char_boundaries(
+    s: &str,
+    mut start: usize,
+    mut end: usize,
+) -> (usize, usize) {
+    while !s.is_char_boundary(start) {
+        start -= 1;
+    }
+    while !s.is_char_boundary(end) {
+        end += 1;
+    }
+    (start, end)
+}
+
+/// Checks that all the fields of the summaries (not just the byte and line
+/// break counts) are kept up to date when editing, both for the `Rope` and
+/// for its slices.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn summaries_after_random_edits() {
+    let mut rng = rand::thread_rng();
+
+    let texts = ["a", "foo\n", "\r\n", "こんにちは", "🐕‍🦺", "Ḽơᶉëᶆ\r", "\n🦀"];
+
+    let mut r = Rope::new();
+    let mut s = String::new();
+
+    for _ in 0..2000 {
+        let (start, end) = {
+            let start = rng.gen_range(0..=s.len());
+            let end = rng.gen_range(start..=(start + 16).min(s.len()));
+            char_boundaries(&s, start, end)
+        };
+
+        let text = if rng.gen_ratio(1, 3) {
+            ""
+        } else {
+            texts[rng.gen_range(0..texts.len())]
+        };
+
+        r.replace(start..end, text);
+        s.replace_range(start..end, text);
+
+        r.assert_invariants();
+
+        assert_eq!(r.byte_len(), s.len());
+        assert_eq!(r.is_ascii(), s.is_ascii());
+        assert_eq!(r.line_len(), s.lines().count());
+        #[cfg(feature = "utf16-metric")]
+        assert_eq!(r.utf16_len(), s.encode_utf16().count());
+
+        let (start, end) = {
+            let start = rng.gen_range(0..=s.len());
+            let end = rng.gen_range(start..=s.len());
+            char_boundaries(&s, start, end)
+        };
+
+        let slice = r.byte_slice(start..end);
+        let str_slice = &s[start..end];
+
+        slice.assert_invariants();
+
+        assert_eq!(slice.is_ascii(), str_slice.is_ascii());
+        assert_eq!(slice.line_len(), str_slice.lines().count());
+        #[cfg(feature = "utf16-metric")]
+        assert_eq!(slice.utf16_len(), str_slice.encode_utf16().count());
+    }
+}
+
+#[test]
+fn max_len_0() {
+    let mut r = Rope::from("Hello");
+
+    // Without a limit every edit is allowed.
+    assert_eq!(r.try_insert(5, ", world"), Ok(()));
+    assert_eq!(r, "Hello, world");
+
+    r.set_max_len(Some(14));
+
+    assert_eq!(r.try_insert(12, "!!"), Ok(()));
+    assert_eq!(
+        r.try_insert(14, "!"),
+        Err(EditError::MaxLenExceeded { limit: 14, would_be: 15 })
+    );
+    assert_eq!(
+        r.try_replace(0..5, "Goodbye"),
+        Err(EditError::MaxLenExceeded { limit: 14, would_be: 16 })
+    );
+    assert_eq!(r, "Hello, world!!");
+
+    let generation = r.generation();
+    assert!(r.try_insert(0, "a").is_err());
+    assert_eq!(r.generation(), generation);
+
+    // Lowering the limit below the current length still allows edits that
+    // don't grow the `Rope`.
+    r.set_max_len(Some(4));
+    assert_eq!(r.try_replace(0..5, "Bye"), Ok(()));
+    assert_eq!(r.try_replace(0..3, "Hey"), Ok(()));
+    r.delete(3..);
+    assert_eq!(r, "Hey");
+    assert!(r.try_insert(3, "!!").is_err());
+
+    let mut clone = r.clone();
+    assert_eq!(clone.max_len(), Some(4));
+    assert!(clone.try_insert(3, "!!").is_err());
+
+    let mut from_slice = Rope::from(r.byte_slice(..));
+    assert_eq!(from_slice.max_len(), None);
+    assert!(from_slice.try_insert(3, "!!").is_ok());
+
+    r.set_max_len(None);
+    assert_eq!(r.try_insert(3, "!!"), Ok(()));
+}
+
+#[test]
+fn max_len_builder() {
+    let mut builder = RopeBuilder::new();
+    builder.max_len(10).append("0123456789");
+
+    let mut r = builder.build();
+
+    assert_eq!(r.max_len(), Some(10));
+    assert!(r.try_insert(10, "a").is_err());
+    assert_eq!(r.try_replace(0..1, "a"), Ok(()));
+    assert_eq!(r, "a123456789");
+}
+
+#[test]
+fn max_len_replace_from_reader() {
+    let mut r = Rope::from("Hello");
+    r.set_max_len(Some(8));
+
+    let err = r.replace_from_reader(5..5, " world".as_bytes()).unwrap_err();
+
+    assert_eq!(err.kind(), std::io::ErrorKind::Other);
+    assert_eq!(r, "Hello");
+
+    assert!(r.replace_from_reader(0..5, "Howdy!".as_bytes()).is_ok());
+    assert_eq!(r, "Howdy!");
+}
+
+#[should_panic]
+#[test]
+fn max_len_exceeded_by_insert() {
+    let mut r = Rope::from("Hello");
+    r.set_max_len(Some(5));
+    r.insert(5, "!");
+}
+
+#[test]
+fn readonly_ranges_0() {
+    let mut r = Rope::from(">>> foo\n>>> ");
+
+    r.set_readonly_ranges(vec![0..4, 8..12]);
+
+    // Edits touching a protected range are rejected.
+    assert_eq!(r.try_insert(2, "x"), Err(EditError::ReadOnly { at: 2 }));
+    assert_eq!(r.try_delete(3..5), Err(EditError::ReadOnly { at: 3 }));
+    assert_eq!(r.try_replace(6..9, "o"), Err(EditError::ReadOnly { at: 8 }));
+    assert_eq!(r.try_delete(..), Err(EditError::ReadOnly { at: 0 }));
+    assert_eq!(r, ">>> foo\n>>> ");
+
+    // Edits right next to them are allowed and shift them.
+    assert_eq!(r.try_insert(4, "f"), Ok(()));
+    assert_eq!(r.try_insert(0, "\n"), Ok(()));
+    assert_eq!(r.try_delete(6..7), Ok(()));
+    assert_eq!(r, "\n>>> foo\n>>> ");
+    assert_eq!(r.readonly_ranges(), &[1..5, 9..13]);
+
+    assert_eq!(r.try_insert(13, "bar"), Ok(()));
+    assert_eq!(r.readonly_ranges(), &[1..5, 9..13]);
+
+    // The infallible edits aren't checked.
+    r.delete(3..10);
+    assert_eq!(r, "\n>>>> bar");
+    assert_eq!(r.readonly_ranges(), &[1..3, 3..6]);
+
+    assert_eq!(r.clone().readonly_ranges(), &[1..3, 3..6]);
+    assert!(Rope::from(r.byte_slice(..)).readonly_ranges().is_empty());
+
+    r.set_readonly_ranges(Vec::new());
+    assert_eq!(r.try_delete(..), Ok(()));
+}
+
+#[test]
+fn readonly_ranges_retain_chars() {
+    let mut r = Rope::from("a\rb\r\nc\r\nd");
+
+    r.set_readonly_ranges(vec![0..1, 1..4, 5..9, 3..3]);
+
+    r.retain_chars(|ch| ch != '\r');
+
+    assert_eq!(r, "ab\nc\nd");
+    assert_eq!(r.readonly_ranges(), &[0..1, 1..2, 3..6, 2..2]);
+}
+
+#[test]
+fn readonly_ranges_replace_same_text() {
+    let mut r = Rope::from(">>> foo bar baz");
+
+    r.set_readonly_ranges(vec![0..4, 12..15]);
+
+    // Rewriting the same text over a read-only range leaves it untouched.
+    r.replace(.., ">>> foo bar baz");
+    assert_eq!(r.readonly_ranges(), &[0..4, 12..15]);
+
+    // Only the part of the text that actually changes shifts the ranges.
+    r.replace(.., ">>> foo quux baz");
+    assert_eq!(r, ">>> foo quux baz");
+    assert_eq!(r.readonly_ranges(), &[0..4, 13..16]);
+
+    r.replace(2..16, "> foo bar baz");
+    assert_eq!(r, ">>> foo bar baz");
+    assert_eq!(r.readonly_ranges(), &[0..4, 12..15]);
+}
+
+#[test]
+fn readonly_ranges_replace_from_reader() {
+    let mut r = Rope::from(">>> print(1)");
+
+    r.set_readonly_ranges(vec![0..4, 10..12]);
+
+    // The reader is empty, so only the deletion could touch the text.
+    let err = r.replace_from_reader(1..2, &b""[..]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::Other);
+    assert_eq!(r, ">>> print(1)");
+
+    let err = r.replace_from_reader(2..6, "foo".as_bytes()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::Other);
+    assert_eq!(r, ">>> print(1)");
+
+    r.replace_from_reader(4..9, "input".as_bytes()).unwrap();
+    assert_eq!(r, ">>> input(1)");
+    assert_eq!(r.readonly_ranges(), &[0..4, 10..12]);
+}
+
+#[should_panic]
+#[test]
+fn readonly_ranges_out_of_bounds() {
+    let mut r = Rope::from("Hello");
+    r.set_readonly_ranges(vec![0..2, 4..6]);
+}
+
+#[test]
+fn replace_common_prefix_suffix_0() {
+    let mut r = Rope::from("Hello, world!");
+
+    r.replace(0..13, "Hello, there world!");
+    assert_eq!(r, "Hello, there world!");
+
+    r.replace(7..18, "ther world");
+    assert_eq!(r, "Hello, ther world!");
+
+    // Multi-byte characters sharing their first bytes.
+    let mut r = Rope::from("aéa");
+    r.replace(.., "aèa");
+    assert_eq!(r, "aèa");
+
+    let mut r = Rope::from("aaaa");
+    r.replace(1..3, "aaaaaa");
+    assert_eq!(r, "aaaaaaaa");
+    r.replace(0..8, "aa");
+    assert_eq!(r, "aa");
+}
+
+/// Replacing a range with the text it already contains doesn't touch the
+/// chunks.
+#[test]
+fn replace_with_same_text() {
+    let mut r = Rope::from(MEDIUM);
+
+    let chunks = r.chunks().map(String::from).collect::<Vec<_>>();
+
+    let text = String::from(r.byte_slice(100..3000));
+    r.replace(100..3000, &text);
+
+    assert_eq!(r, MEDIUM);
+    assert!(r.chunks().eq(chunks.iter().map(String::as_str)));
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn replace_common_prefix_suffix_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+        let mut r = Rope::from(s);
+        let mut s = String::from(s);
+
+        for _ in 0..50 {
+            let start = rng.gen_range(0..=s.len());
+            let end = rng.gen_range(start..=s.len());
+            let (start, end) = char_boundaries(&s, start, end);
+
+            // Build the new text by changing the middle of the old one, so
+            // that they share a prefix and a suffix.
+            let old = &s[start..end];
+            let (mid_start, mid_end) = char_boundaries(
+                old,
+                rng.gen_range(0..=old.len()),
+                rng.gen_range(0..=old.len()),
+            );
+            let (mid_start, mid_end) =
+                (mid_start.min(mid_end), mid_start.max(mid_end));
+
+            let middle = ["", "a", "é", "\n", "\r\n", "🐕‍🦺"]
+                [rng.gen_range(0..6)]
+            .repeat(rng.gen_range(0..4));
+
+            let text =
+                format!("{}{middle}{}", &old[..mid_start], &old[mid_end..]);
+
+            r.replace(start..end, &text);
+            s.replace_range(start..end, &text);
+
+            r.assert_invariants();
+            assert_eq!(r, s);
+        }
+    }
+}
+
+#[test]
+fn insert_rope_0() {
+    let mut r = Rope::from("Hello !");
+    let world = Rope::from("world");
+
+    r.insert_rope(6, &world);
+    r.assert_invariants();
+    assert_eq!(r, "Hello world!");
+
+    r.insert_rope(r.byte_len(), &Rope::from("\n"));
+    r.assert_invariants();
+    assert_eq!(r, "Hello world!\n");
+
+    r.insert_rope(0, &Rope::new());
+    r.assert_invariants();
+    assert_eq!(r, "Hello world!\n");
+
+    let mut empty = Rope::new();
+    empty.insert_rope(0, &r);
+    empty.assert_invariants();
+    assert_eq!(empty, r);
+}
+
+#[test]
+#[should_panic]
+fn insert_rope_not_char_boundary() {
+    let mut r = Rope::from("🐸");
+    r.insert_rope(1, &Rope::from("a"));
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn insert_slice_random() {
+    let mut rng = rand::thread_rng();
+
+    for source in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let source_rope = Rope::from(source);
+
+        for dest in [TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+            let mut r = Rope::from(dest);
+            let mut s = dest.to_owned();
+
+            for _ in 0..10 {
+                let mut start = rng.gen_range(0..=source.len());
+                while !source.is_char_boundary(start) {
+                    start -= 1;
+                }
+
+                let mut end = rng.gen_range(start..=source.len());
+                while !source.is_char_boundary(end) {
+                    end += 1;
+                }
+
+                let mut at = rng.gen_range(0..=s.len());
+                while !s.is_char_boundary(at) {
+                    at -= 1;
+                }
+
+                r.insert_slice(at, source_rope.byte_slice(start..end));
+                s.insert_str(at, &source[start..end]);
+            }
+
+            r.assert_invariants();
+            assert_eq!(r, s);
+
+            // Editing the rope must not affect the chunks it shares with the
+            // source.
+            let mut mid = s.len() / 2;
+            while !s.is_char_boundary(mid) {
+                mid -= 1;
+            }
+            r.delete(..mid);
+            r.insert(0, "foo");
+            r.assert_invariants();
+            source_rope.assert_invariants();
+            assert_eq!(source_rope, source);
+        }
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn insert_rope_into_itself() {
+    for s in [TINY, SMALL, MEDIUM, LARGE] {
+        let mut r = Rope::from(s);
+        let mut expected = s.to_owned();
+
+        for _ in 0..3 {
+            let copy = r.clone();
+            r.insert_rope(r.byte_len() / 2, &copy);
+
+            let mut at = expected.len() / 2;
+            while !expected.is_char_boundary(at) {
+                at -= 1;
+            }
+            assert_eq!(at, expected.len() / 2);
+            let copy = expected.clone();
+            expected.insert_str(at, &copy);
+
+            r.assert_invariants();
+            assert_eq!(r, expected);
+        }
+    }
+}
+
+#[test]
+fn delete_counting_0() {
+    let mut r = Rope::from("foo\nbar\r\nbaz\n");
+
+    let outcome = r.delete_counting(8..12);
+    assert_eq!(r, "foo\nbar\r\n");
+    assert_eq!(outcome.bytes_removed(), 4);
+    assert_eq!(outcome.line_breaks_removed(), 1);
+
+    // Deleting half of a CRLF doesn't remove a line break.
+    let outcome = r.delete_counting(7..8);
+    assert_eq!(r, "foo\nbar\n");
+    assert_eq!(outcome.line_breaks_removed(), 0);
+
+    let outcome = r.delete_counting(2..2);
+    assert_eq!(r, "foo\nbar\n");
+    assert_eq!(outcome.bytes_removed(), 0);
+    assert_eq!(outcome.line_breaks_removed(), 0);
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn delete_counting_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let mut r = Rope::from(s);
+        let mut s = s.to_owned();
+
+        for _ in 0..20 {
+            let mut start = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end = rng.gen_range(start..=(start + 200).min(s.len()));
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+
+            let line_breaks =
+                s[start..end].bytes().filter(|&b| b == b'\n').count();
+
+            let outcome = r.delete_counting(start..end);
+            s.replace_range(start..end, "");
+
+            assert_eq!(outcome.bytes_removed(), end - start);
+            assert_eq!(outcome.line_breaks_removed(), line_breaks);
+            assert_eq!(r, s);
+        }
+
+        r.assert_invariants();
+    }
+}
+
+#[test]
+fn insert_tracking_0() {
+    let mut r = Rope::from("foo bar");
+
+    let mut ranges = [0..3, 3..3, 3..4, 4..7, 2..5];
+
+    r.insert_tracking(3, "!!", &mut ranges);
+
+    assert_eq!(r, "foo!! bar");
+    assert_eq!(ranges, [0..3, 3..3, 3..6, 6..9, 2..7]);
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn delete_and_insert_tracking_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+        let mut r = Rope::from(s);
+
+        // Every range covers a char and is checked to still end with the
+        // same char after each edit, unless the char was deleted. Text
+        // inserted at the start of a range ends up inside of it.
+        let mut ranges = Vec::new();
+        let mut chars = Vec::new();
+
+        for (offset, ch) in s.char_indices().step_by(97) {
+            ranges.push(offset..offset + ch.len_utf8());
+            chars.push(Some(ch));
+        }
+
+        for _ in 0..20 {
+            let mut start = rng.gen_range(0..=r.byte_len());
+            while !r.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            if rng.gen_bool(0.5) {
+                r.insert_tracking(start, "a🐸\n", &mut ranges);
+            } else {
+                let mut end =
+                    rng.gen_range(start..=r.byte_len().min(start + 20));
+                while !r.is_char_boundary(end) {
+                    end += 1;
+                }
+
+                for (range, ch) in ranges.iter().zip(&mut chars) {
+                    if range.start < end && start < range.end {
+                        *ch = None;
+                    }
+                }
+
+                r.delete_tracking(start..end, &mut ranges);
+            }
+
+            for (range, ch) in ranges.iter().zip(&chars) {
+                assert!(range.start <= range.end && range.end <= r.byte_len());
+
+                if let Some(ch) = ch {
+                    let last = r.byte_slice(range.clone()).chars().next_back();
+                    assert_eq!(last, Some(*ch));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn apply_edits_0() {
+    let mut r = Rope::from("foo\nbar\r\nbaz");
+
+    let mut edits = vec![
+        Edit::new(12..12, "!"),
+        Edit::new(0..0, "b"),
+        Edit::new(0..0, "a"),
+        Edit::new(3..4, ""),
+        Edit::new(4..9, "\n\n"),
+    ];
+
+    let summary = r.apply_edits(&mut edits).unwrap();
+
+    assert_eq!(r, "bafoo\n\nbaz!");
+    assert_eq!(summary.bytes_removed(), 6);
+    assert_eq!(summary.bytes_inserted(), 5);
+    assert_eq!(summary.line_breaks_removed(), 2);
+    assert_eq!(summary.line_breaks_inserted(), 2);
+
+    let ranges = edits.iter().map(|edit| edit.range.clone());
+    assert!(ranges.clone().zip(ranges.skip(1)).all(|(a, b)| a.end <= b.start));
+
+    r.assert_invariants();
+}
+
+#[test]
+fn apply_edits_errors() {
+    let mut r = Rope::from("foo 🐸 bar");
+
+    #[allow(clippy::reversed_empty_ranges)]
+    let cases = [
+        (
+            vec![Edit::new(1..0, "")],
+            ApplyEditsError::Edit(EditError::StartAfterEnd {
+                start: 1,
+                end: 0,
+            }),
+        ),
+        (
+            vec![Edit::new(0..1, ""), Edit::new(3..13, "")],
+            ApplyEditsError::Edit(EditError::OutOfBounds {
+                byte_offset: 13,
+                byte_len: 12,
+            }),
+        ),
+        (
+            vec![Edit::new(5..6, "")],
+            ApplyEditsError::Edit(EditError::NotCharBoundary {
+                byte_offset: 5,
+            }),
+        ),
+        (
+            vec![Edit::new(0..3, ""), Edit::new(2..2, "")],
+            ApplyEditsError::Overlapping { byte_offset: 2 },
+        ),
+    ];
+
+    for (mut edits, err) in cases {
+        assert_eq!(r.apply_edits(&mut edits), Err(err));
+        assert_eq!(r, "foo 🐸 bar");
+    }
+
+    r.set_max_len(Some(13));
+
+    let mut edits = vec![Edit::new(0..0, "a"), Edit::new(1..1, "bc")];
+
+    assert_eq!(
+        r.apply_edits(&mut edits),
+        Err(ApplyEditsError::Edit(EditError::MaxLenExceeded {
+            limit: 13,
+            would_be: 15
+        }))
+    );
+
+    // Only the final length is checked against the maximum length.
+    let mut edits = vec![Edit::new(0..0, "a"), Edit::new(1..3, "")];
+    assert!(r.apply_edits(&mut edits).is_ok());
+    assert_eq!(r, "af 🐸 bar");
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn apply_edits_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let mut r = Rope::from(s);
+        let mut s = s.to_owned();
+
+        for _ in 0..10 {
+            let mut edits = Vec::new();
+
+            let mut offset = 0;
+
+            while offset < s.len() && edits.len() < 50 {
+                let (start, end) = char_boundaries(
+                    &s,
+                    rng.gen_range(offset..=(offset + 100).min(s.len())),
+                    rng.gen_range(offset..=(offset + 200).min(s.len())),
+                );
+
+                if start < offset || start > end {
+                    break;
+                }
+
+                let text = if rng.gen() { "foo\n" } else { "" };
+                edits.push(Edit::new(start..end, text));
+                offset = end;
+            }
+
+            let mut expected = s.clone();
+
+            for edit in edits.iter().rev() {
+                expected.replace_range(edit.range.clone(), &edit.text);
+            }
+
+            let removed = edits.iter().map(|e| e.range.len()).sum::<usize>();
+
+            // Shuffling the edits doesn't change the result.
+            edits.reverse();
+
+            let summary = r.apply_edits(&mut edits).unwrap();
+
+            assert_eq!(r, expected);
+            assert_eq!(summary.bytes_removed(), removed);
+
+            s = expected;
+        }
+
+        r.assert_invariants();
+    }
+}
+
+#[test]
+fn edit_recording_0() {
+    let mut r = Rope::from("foo bar baz");
+
+    let mut recorder = r.edit_recording();
+    let insert = recorder.insert(3, "\r\n");
+    let delete = recorder.delete(..4);
+    let noop = recorder.replace(2..5, "bar");
+    let replace = recorder.replace(1.., "🐸");
+    assert_eq!(recorder.rope(), "\n🐸");
+
+    assert_eq!(insert.byte_range(), 3..5);
+    assert_eq!(insert.text(), "");
+    assert_eq!(delete.byte_range(), 0..0);
+    assert_eq!(delete.text(), "foo\r");
+    assert!(noop.is_empty());
+    assert_eq!(replace.byte_range(), 1..5);
+    assert_eq!(replace.text(), " bar baz");
+
+    for inverse in [replace, noop, delete, insert] {
+        inverse.apply(&mut r);
+        r.assert_invariants();
+    }
+
+    assert_eq!(r, "foo bar baz");
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn edit_recording_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let mut r = Rope::from(s);
+
+        let mut history = vec![r.clone()];
+        let mut undo_stack = Vec::<InverseEdit>::new();
+
+        for _ in 0..20 {
+            let (start, end) = char_boundaries(
+                &r.to_string(),
+                rng.gen_range(0..=r.byte_len()),
+                rng.gen_range(0..=r.byte_len()),
+            );
+
+            let (start, end) = (start.min(end), start.max(end));
+
+            let mut recorder = r.edit_recording();
+
+            let inverse = match rng.gen_range(0..3) {
+                0 => recorder.insert(start, "foo\r\n"),
+                1 => recorder.delete(start..end),
+                _ => recorder.replace(start..end, "🐸\n"),
+            };
+
+            r.assert_invariants();
+            history.push(r.clone());
+            undo_stack.push(inverse);
+        }
+
+        let edited = r.clone();
+
+        let mut redo_stack = Vec::new();
+
+        while let Some(inverse) = undo_stack.pop() {
+            history.pop();
+            redo_stack.push(inverse.apply(&mut r));
+            r.assert_invariants();
+            assert_eq!(r, *history.last().unwrap());
+        }
+
+        assert_eq!(r, s);
+
+        while let Some(inverse) = redo_stack.pop() {
+            inverse.apply(&mut r);
+        }
+
+        r.assert_invariants();
+        assert_eq!(r, edited);
+    }
+}
+
+#[test]
+fn edit_kind() {
+    let mut r = Rope::from("foo\nbar");
+
+    assert_eq!(r.edit(3..3, "\r"), EditKind::Insert);
+    assert_eq!(r, "foo\r\nbar");
+
+    assert_eq!(r.edit(..3, ""), EditKind::Delete);
+    assert_eq!(r, "\r\nbar");
+
+    assert_eq!(r.edit(2.., "bar"), EditKind::Replace);
+    assert_eq!(r, "\r\nbar");
+
+    assert_eq!(r.edit(.., "baz"), EditKind::Replace);
+    assert_eq!(r, "baz");
+
+    assert_eq!(r.edit(3.., ""), EditKind::Noop);
+    assert_eq!(r, "baz");
+
+    let mut r = Rope::new();
+    assert_eq!(r.edit(.., ""), EditKind::Noop);
+    assert_eq!(r.edit(.., "foo"), EditKind::Insert);
+    assert_eq!(r.edit(.., ""), EditKind::Delete);
+    assert!(r.is_empty());
+}
+
+#[test]
+#[should_panic]
+fn edit_out_of_bounds() {
+    let mut r = Rope::from("foo");
+    let _ = r.edit(2..4, "bar");
+}
+
+#[test]
+fn clear_retaining_capacity() {
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let mut r = Rope::from(s);
+        r.set_readonly_ranges(vec![0..1, 2..4]);
+
+        let clone = r.clone();
+        let generation = r.generation();
+
+        r.clear_retaining_capacity();
+        r.assert_invariants();
+        assert!(r.is_empty());
+        assert_eq!(r.line_len(), 0);
+        assert!(r.generation() > generation);
+        assert_eq!(r.readonly_ranges(), &[0..0, 0..0]);
+        assert_eq!(clone, s);
+
+        r.clear_retaining_capacity();
+        assert!(r.is_empty());
+
+        for _ in 0..3 {
+            for line in s.split_inclusive('\n') {
+                r.insert(r.byte_len(), line);
+            }
+
+            r.assert_invariants();
+            assert_eq!(r, s);
+            assert_eq!(r.line_len(), clone.line_len());
+
+            r.clear_retaining_capacity();
+            r.assert_invariants();
+            assert!(r.is_empty());
+        }
+    }
 }
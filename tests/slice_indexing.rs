@@ -105,6 +105,48 @@ fn line_of_byte_random() {
     }
 }
 
+/// Tests `RopeSlice::byte_line_col()` on a bunch of random RopeSlices over
+/// different texts, some of which contain multibyte characters.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn byte_line_col_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        for _ in 0..100 {
+            let start = rng.gen_range(0..=r.byte_len());
+            let end = rng.gen_range(start..=r.byte_len());
+
+            if !s.is_char_boundary(start) || !s.is_char_boundary(end) {
+                continue;
+            }
+
+            let str_slice = &s[start..end];
+            let rope_slice = r.byte_slice(start..end);
+
+            for _ in 0..10 {
+                let byte_offset = rng.gen_range(0..=str_slice.len());
+
+                if !str_slice.is_char_boundary(byte_offset) {
+                    continue;
+                }
+
+                let line = str_slice[..byte_offset].matches('\n').count();
+
+                let line_start =
+                    str_slice[..byte_offset].rfind('\n').map_or(0, |i| i + 1);
+
+                assert_eq!(
+                    (line, byte_offset - line_start),
+                    rope_slice.byte_line_col(byte_offset)
+                );
+            }
+        }
+    }
+}
+
 /// Tests `crop::RopeSlice::byte_of_line()` against Ropey's
 /// `ropey::RopeSlice::line_to_byte()`.
 #[cfg_attr(miri, ignore)]
@@ -138,3 +180,171 @@ fn byte_of_line_random() {
         }
     }
 }
+
+/// Tests that the generic `measure()` and `convert()` agree with the methods
+/// specific to each metric.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn measure_and_convert_random() {
+    use crop::metrics::{Byte, Line};
+
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE] {
+        let r = Rope::from(s);
+
+        assert_eq!(r.measure::<Byte>(), r.byte_len());
+        assert_eq!(r.measure::<Line>(), r.line_len());
+
+        for _ in 0..100 {
+            let start = rng.gen_range(0..r.byte_len());
+            let end = rng.gen_range(start + 1..=r.byte_len());
+            let slice = r.byte_slice(start..end);
+
+            assert_eq!(slice.measure::<Byte>(), slice.byte_len());
+            assert_eq!(slice.measure::<Line>(), slice.line_len());
+
+            for _ in 0..10 {
+                let byte_offset = rng.gen_range(0..=slice.byte_len());
+                let line_offset = rng.gen_range(0..=slice.line_len());
+
+                assert_eq!(
+                    slice.convert::<Byte, Line>(byte_offset),
+                    slice.line_of_byte(byte_offset)
+                );
+                assert_eq!(
+                    slice.convert::<Line, Byte>(line_offset),
+                    slice.byte_of_line(line_offset)
+                );
+                assert_eq!(
+                    slice.convert::<Byte, Byte>(byte_offset),
+                    byte_offset
+                );
+                assert_eq!(slice.convert::<Line, Line>(line_offset), {
+                    slice.line_of_byte(slice.byte_of_line(line_offset))
+                });
+            }
+        }
+    }
+}
+
+#[cfg(feature = "utf16-metric")]
+#[test]
+fn convert_utf16() {
+    use crop::metrics::{Byte, Line, Utf16CodeUnit};
+
+    // Both "𐐀" and "🐸" are encoded using two code units in UTF-16.
+    let r = Rope::from("ab𐐀\nde🐸");
+
+    assert_eq!(r.measure::<Utf16CodeUnit>(), r.utf16_len());
+    assert_eq!(r.convert::<Utf16CodeUnit, Byte>(4), 6);
+    assert_eq!(r.convert::<Byte, Utf16CodeUnit>(6), 4);
+    assert_eq!(r.convert::<Line, Utf16CodeUnit>(1), 5);
+}
+
+#[test]
+#[should_panic(expected = "byte offset out of bounds")]
+fn convert_out_of_bounds() {
+    use crop::metrics::{Byte, Line};
+
+    let r = Rope::from("foo\nbar");
+    let _ = r.convert::<Byte, Line>(8);
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn find_char_random() {
+    let mut rng = rand::thread_rng();
+
+    let predicates: [fn(char) -> bool; 4] = [
+        char::is_whitespace,
+        |ch| !ch.is_ascii(),
+        |ch| ch == 'z',
+        |ch| ch == '\u{0}',
+    ];
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        for predicate in predicates {
+            assert_eq!(r.find_char(predicate), s.find(predicate));
+            assert_eq!(r.rfind_char(predicate), s.rfind(predicate));
+        }
+
+        for _ in 0..20 {
+            let mut start = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end = rng.gen_range(start..=s.len());
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+
+            let slice = r.byte_slice(start..end);
+            let str_slice = &s[start..end];
+
+            for predicate in predicates {
+                assert_eq!(
+                    slice.find_char(predicate),
+                    str_slice.find(predicate)
+                );
+                assert_eq!(
+                    slice.rfind_char(predicate),
+                    str_slice.rfind(predicate)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn char_len_0() {
+    let mut r = Rope::from("fn main() {}\n");
+    assert!(r.is_ascii());
+    assert_eq!(r.char_len(), r.byte_len());
+
+    r.insert(11, "🦀 «»");
+    assert!(!r.is_ascii());
+    assert_eq!(r.char_len(), 17);
+    assert_eq!(r.byte_slice(..11).char_len(), 11);
+    assert_eq!(r.byte_slice(11..).char_len(), 6);
+
+    r.delete(11..20);
+    assert!(r.is_ascii());
+    assert_eq!(r.char_len(), 13);
+
+    assert_eq!(Rope::new().char_len(), 0);
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn char_len_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        assert_eq!(r.char_len(), s.chars().count());
+
+        for _ in 0..20 {
+            let mut start = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end = rng.gen_range(start..=s.len());
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+
+            let slice = r.byte_slice(start..end);
+            let expected = s[start..end].chars().count();
+
+            assert_eq!(slice.is_ascii(), s[start..end].is_ascii());
+            assert_eq!(slice.char_len(), expected);
+            assert_eq!(slice.char_len(), slice.chars().count());
+        }
+    }
+}
@@ -1,4 +1,8 @@
-use crop::Rope;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crop::{HashedSlice, Rope, RopeBuilder, RopeSlice};
 use rand::Rng;
 
 mod common;
@@ -12,6 +16,102 @@ fn byte_slice_empty() {
     assert!(s.is_empty());
 }
 
+/// Tests that the read API of empty `Rope`s, however they were created, and
+/// of empty slices of non-empty `Rope`s returns empty results instead of
+/// panicking.
+#[test]
+fn read_api_on_empty() {
+    let mut deleted = Rope::from(LARGE);
+    deleted.delete(..);
+
+    let mut builder = RopeBuilder::new();
+    builder.append("");
+
+    let ropes = [Rope::new(), Rope::from(""), builder.build(), deleted];
+
+    for r in &ropes {
+        assert_empty_slice(r.byte_slice(..));
+        assert_empty_slice(r.byte_slice(0..0));
+        assert_empty_slice(r.byte_slice(0..));
+        assert_empty_slice(r.byte_slice(..0));
+        assert_empty_slice(r.byte_slice(..).byte_slice(..));
+        assert_empty_slice(r.line_slice(..));
+        assert_empty_slice(r.line_slice(0..0));
+        assert_empty_slice(r.empty_slice_at(0));
+
+        assert_eq!(r.chunks().count(), 0);
+        assert_eq!(r.chunks().rev().count(), 0);
+        assert_eq!(r.bytes().count(), 0);
+        assert_eq!(r.bytes().rev().count(), 0);
+        assert_eq!(r.chars().count(), 0);
+        assert_eq!(r.chars().rev().count(), 0);
+        assert_eq!(r.lines().count(), 0);
+        assert_eq!(r.lines().rev().count(), 0);
+        assert_eq!(r.raw_lines().count(), 0);
+        assert_eq!(r.raw_lines().rev().count(), 0);
+        assert_eq!(r.line_ranges().count(), 0);
+        assert_eq!(r.lines_at_byte(0).count(), 0);
+        assert_eq!(r.chunk_ranges().count(), 0);
+        assert_eq!(r.matches("a").count(), 0);
+        assert_eq!(r.split_whitespace().count(), 0);
+
+        assert_eq!(r.line_len(), 0);
+        assert_eq!(r.line_of_byte(0), 0);
+        assert_eq!(r.byte_of_line(0), 0);
+        assert_eq!(r.get_line(0), None);
+        assert_eq!(r.find_char(|_| true), None);
+        assert_eq!(r.to_string(), "");
+    }
+
+    // Empty slices at the start, in the middle and at the end of a `Rope`.
+    let r = Rope::from("foo\nbar\r\n");
+
+    for byte_offset in [0, 4, 9, r.byte_len()] {
+        assert_empty_slice(r.byte_slice(byte_offset..byte_offset));
+        assert_empty_slice(r.empty_slice_at(byte_offset));
+    }
+
+    assert_empty_slice(r.line_slice(1..1));
+    assert_empty_slice(r.line_slice(r.line_len()..));
+}
+
+#[track_caller]
+fn assert_empty_slice(s: RopeSlice<'_>) {
+    s.assert_invariants();
+
+    assert!(s.is_empty());
+    assert_eq!(s, "");
+    assert_eq!(s.byte_len(), 0);
+    assert_eq!(s.line_len(), 0);
+
+    assert_eq!(s.chunks().count(), 0);
+    assert_eq!(s.chunks().rev().count(), 0);
+    assert_eq!(s.bytes().count(), 0);
+    assert_eq!(s.bytes().rev().count(), 0);
+    assert_eq!(s.chars().count(), 0);
+    assert_eq!(s.chars().rev().count(), 0);
+    assert_eq!(s.lines().count(), 0);
+    assert_eq!(s.lines().rev().count(), 0);
+    assert_eq!(s.raw_lines().count(), 0);
+    assert_eq!(s.raw_lines().rev().count(), 0);
+    assert_eq!(s.chunk_ranges().count(), 0);
+
+    assert_empty_slice_no_recurse(s.byte_slice(..));
+    assert_empty_slice_no_recurse(s.line_slice(..));
+    assert_eq!(s.get_line(0), None);
+    assert_eq!(s.byte_of_line(0), 0);
+    assert_eq!(s.line_of_byte(0), 0);
+    assert_eq!(String::from(s), "");
+}
+
+#[track_caller]
+fn assert_empty_slice_no_recurse(s: RopeSlice<'_>) {
+    s.assert_invariants();
+    assert!(s.is_empty());
+    assert_eq!(s.chunks().count(), 0);
+    assert_eq!(s.lines().count(), 0);
+}
+
 /// Tests that slicing at the start, at a node boundary and at end of a Rope
 /// works correctly.
 /// ```
@@ -28,6 +128,40 @@ fn byte_slice_empty() {
 ///     └── "hhhh"
 /// ```
 #[cfg(all(feature = "small_chunks", feature = "arity_4"))]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn empty_slice_at_0() {
+    for s in ["", "a", "aaa\r\nbbb", TINY, SMALL, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        for offset in (0..=s.len()).filter(|&idx| s.is_char_boundary(idx)) {
+            let empty = r.empty_slice_at(offset);
+            empty.assert_invariants();
+            assert_eq!(empty, "");
+            assert_eq!(empty, r.byte_slice(offset..offset));
+            assert_eq!(0, empty.chunks().count());
+            assert_eq!(0, empty.lines().count());
+
+            let empty = r.byte_slice(..).empty_slice_at(offset);
+            assert_eq!(empty, "");
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "byte offset out of bounds")]
+fn byte_slice_inclusive_usize_max() {
+    let r = Rope::from("🗻∈🌏");
+    let _ = r.byte_slice(..=usize::MAX);
+}
+
+#[test]
+#[should_panic]
+fn empty_slice_at_not_char_boundary() {
+    let r = Rope::from("🗻∈🌏");
+    let _ = r.empty_slice_at(5);
+}
+
 #[test]
 fn byte_slice_0() {
     let r = Rope::from("aaaabbbbccccddddeeeeffffgggghhhh");
@@ -82,6 +216,52 @@ fn byte_slice_random() {
     }
 }
 
+#[test]
+fn slice_is_byte_slice() {
+    let r = Rope::from(TINY);
+
+    assert_eq!(r.slice(..), r.byte_slice(..));
+    assert_eq!(r.slice(10..100), r.byte_slice(10..100));
+    assert_eq!(r.slice(10..100).slice(5..=20), &TINY[15..=30]);
+    assert_eq!(r.slice(10..).slice(..0), "");
+}
+
+#[test]
+#[should_panic(expected = "byte offset out of bounds")]
+fn slice_out_of_bounds() {
+    let r = Rope::from("foo");
+    let _ = r.slice(..4);
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn copy_into_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE] {
+        let r = Rope::from(s);
+
+        let mut buf = vec![0u8; s.len() + 8];
+
+        let copied = r.copy_into(&mut buf);
+        assert_eq!(copied, s.len());
+        assert_eq!(&buf[..copied], s.as_bytes());
+
+        for _ in 0..100 {
+            let start = rng.gen_range(0..=r.byte_len());
+            let end = rng.gen_range(start..=r.byte_len());
+            let buf_len = rng.gen_range(0..=end - start + 8);
+
+            let mut buf = vec![0u8; buf_len];
+
+            let copied = r.byte_slice(start..end).copy_into(&mut buf);
+
+            assert_eq!(copied, buf_len.min(end - start));
+            assert_eq!(&buf[..copied], &s.as_bytes()[start..start + copied]);
+        }
+    }
+}
+
 #[test]
 fn line_slice_empty() {
     let r = Rope::from("");
@@ -124,6 +304,139 @@ fn line_slice_1() {
     assert_eq!("Hello world\nthis is\na test\n", r.line_slice(..));
 }
 
+#[test]
+fn line_slice_inclusive() {
+    let r = Rope::from("aaa\nbbb\r\nccc\nddd");
+
+    assert_eq!("aaa\n", r.line_slice(0..=0));
+    assert_eq!("bbb\r\nccc\n", r.line_slice(1..=2));
+    assert_eq!("ccc\nddd", r.line_slice(2..=3));
+    assert_eq!("ddd", r.line_slice(3..=3));
+
+    for start in 0..r.line_len() {
+        for end in start..r.line_len() {
+            let byte_range = r.byte_of_line(start)..r.byte_of_line(end + 1);
+            assert_eq!(r.byte_slice(byte_range), r.line_slice(start..=end));
+        }
+    }
+}
+
+#[test]
+fn line_slice_content_and_raw_0() {
+    let r = Rope::from("aaa\nbbb\r\nccc\n");
+
+    // Empty range.
+    assert_eq!("", r.line_slice_content(1..1));
+    assert_eq!("", r.line_slice_raw(1..1));
+    assert_eq!(4..4, r.line_slice_content(1..1).parent_range());
+
+    // Single line.
+    assert_eq!("aaa", r.line_slice_content(0..1));
+    assert_eq!("aaa\n", r.line_slice_raw(0..1));
+    assert_eq!("bbb", r.line_slice_content(1..=1));
+    assert_eq!("bbb\r\n", r.line_slice_raw(1..=1));
+
+    // Ranges over CRLF-terminated lines keep the inner line breaks.
+    assert_eq!("aaa\nbbb", r.line_slice_content(..2));
+    assert_eq!("bbb\r\nccc", r.line_slice_content(1..));
+    assert_eq!("bbb\r\nccc\n", r.line_slice_raw(1..));
+
+    // Range ending at the last line, with a trailing line break.
+    assert_eq!("aaa\nbbb\r\nccc", r.line_slice_content(..));
+    assert_eq!("aaa\nbbb\r\nccc\n", r.line_slice_raw(..));
+
+    // Range ending at the last line, without a trailing line break.
+    let r = Rope::from("aaa\r\nbbb");
+    assert_eq!("aaa\r\nbbb", r.line_slice_content(..));
+    assert_eq!("aaa\r\nbbb", r.line_slice_raw(..));
+    assert_eq!("bbb", r.line_slice_content(1..));
+    assert_eq!("bbb", r.line_slice_raw(1..));
+
+    // A lone '\r' is not a line break.
+    let r = Rope::from("aaa\r");
+    assert_eq!("aaa\r", r.line_slice_content(..));
+}
+
+/// Checks `line_slice_content()` and `line_slice_raw()` on every line range
+/// of a bunch of `Rope`s and of their slices against the lines of the
+/// corresponding `&str`s.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn line_slice_content_and_raw_every_range() {
+    let crlf_small = SMALL.replace('\n', "\r\n");
+
+    for s in [
+        "",
+        "a",
+        "a\n",
+        "a\r\n",
+        "\n\n",
+        "\r\n\r\n",
+        "a\nb",
+        "a\r\nb\r\n",
+        "a\rb\r\n\r",
+        TINY,
+        &crlf_small,
+    ] {
+        let r = Rope::from(s);
+
+        for slice_start in [0, 1, 3].into_iter().filter(|&i| i <= s.len()) {
+            let slice = r.byte_slice(slice_start..);
+            let s = &s[slice_start..];
+
+            // The byte offsets of the start of every line, plus the end.
+            let mut line_offsets = s
+                .split_inclusive('\n')
+                .scan(0, |offset, line| {
+                    let start = *offset;
+                    *offset += line.len();
+                    Some(start)
+                })
+                .collect::<Vec<_>>();
+
+            line_offsets.push(s.len());
+
+            assert_eq!(line_offsets.len() - 1, slice.line_len());
+
+            for start in 0..=slice.line_len() {
+                for end in start..=slice.line_len() {
+                    let raw = &s[line_offsets[start]..line_offsets[end]];
+
+                    let content = raw
+                        .strip_suffix("\r\n")
+                        .or_else(|| raw.strip_suffix('\n'))
+                        .unwrap_or(raw);
+
+                    assert_eq!(raw, slice.line_slice_raw(start..end));
+                    assert_eq!(raw, slice.line_slice(start..end));
+                    assert_eq!(content, slice.line_slice_content(start..end));
+
+                    slice.line_slice_content(start..end).assert_invariants();
+
+                    if slice_start == 0 {
+                        assert_eq!(raw, r.line_slice_raw(start..end));
+                        assert_eq!(content, r.line_slice_content(start..end));
+                    }
+
+                    if end == start + 1 {
+                        assert_eq!(
+                            slice.line(start),
+                            slice.line_slice_content(start..end)
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn line_slice_content_out_of_bounds() {
+    let r = Rope::from("foo\nbar\n");
+    let _ = r.line_slice_content(1..3);
+}
+
 #[test]
 fn byte_slice_then_line() {
     let r = Rope::from("foo\nbar\r\nbaz\nfoobar\n");
@@ -220,3 +533,883 @@ fn rope_from_slice() {
         }
     }
 }
+
+fn hash_of<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Tests that equal `Rope`s and `RopeSlice`s hash to the same value no matter
+/// how their contents are split into chunks.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn hash_independent_of_chunks() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        let mut builder = RopeBuilder::new();
+        let mut start = 0;
+        while start < s.len() {
+            let mut end = rng.gen_range(start..=s.len().min(start + 64));
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+            builder.append(&s[start..end]);
+            start = end;
+        }
+        let built = builder.build();
+
+        let mut offset = s.len() / 2;
+        while !s.is_char_boundary(offset) {
+            offset += 1;
+        }
+        let mut edited = Rope::from(&s[..offset]);
+        edited.insert(0, "");
+        edited.insert(offset, &s[offset..]);
+
+        let expected = hash_of(&r);
+        assert_eq!(expected, hash_of(&built));
+        assert_eq!(expected, hash_of(&edited));
+        assert_eq!(expected, hash_of(&r.byte_slice(..)));
+        assert_eq!(expected, hash_of(&built.byte_slice(..)));
+    }
+}
+
+/// Tests using `HashedSlice`s as `HashMap` keys.
+#[cfg_attr(miri, ignore)]
+/// The 64-bit FNV-1a hash of a string.
+fn fnv1a(s: &str) -> u64 {
+    s.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn content_hash_independent_of_chunks() {
+    let mut rng = rand::thread_rng();
+
+    assert_eq!(Rope::new().content_hash(), 0xcbf29ce484222325);
+    assert_eq!(Rope::from("a").content_hash(), 0xaf63dc4c8601ec8c);
+
+    for s in [TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        let mut builder = RopeBuilder::new();
+        let mut start = 0;
+        while start < s.len() {
+            let mut end = rng.gen_range(start..=s.len().min(start + 64));
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+            builder.append(&s[start..end]);
+            start = end;
+        }
+        let built = builder.build();
+
+        assert_eq!(r.content_hash(), fnv1a(s));
+        assert_eq!(built.content_hash(), fnv1a(s));
+
+        for _ in 0..10 {
+            let mut start = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(start) {
+                start += 1;
+            }
+            let mut end = rng.gen_range(start..=s.len());
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+
+            let expected = fnv1a(&s[start..end]);
+            assert_eq!(r.byte_slice(start..end).content_hash(), expected);
+            assert_eq!(built.byte_slice(start..end).content_hash(), expected);
+        }
+
+        #[cfg(feature = "sha256")]
+        assert_eq!(r.sha256_hex(), built.sha256_hex());
+    }
+}
+
+#[cfg(feature = "sha256")]
+#[test]
+fn sha256_hex_0() {
+    assert_eq!(
+        Rope::new().sha256_hex(),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+
+    let r = Rope::from("Hello, abc!");
+    assert_eq!(
+        r.byte_slice(7..10).sha256_hex(),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+}
+
+#[test]
+fn hashed_slice_map_keys() {
+    let r = Rope::from(MEDIUM);
+
+    let mut counts = HashMap::<HashedSlice, usize>::new();
+
+    for line in r.lines() {
+        *counts.entry(HashedSlice::new(line)).or_default() += 1;
+    }
+
+    let mut expected = HashMap::<&str, usize>::new();
+
+    for line in MEDIUM.lines() {
+        *expected.entry(line).or_default() += 1;
+    }
+
+    assert_eq!(counts.len(), expected.len());
+
+    for (line, count) in counts {
+        let line = line.slice().to_string();
+        assert_eq!(expected[line.as_str()], count);
+    }
+}
+
+/// Tests that `as_str()` returns the contents of `Rope`s and `RopeSlice`s iff
+/// they're made of a single chunk.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn as_str_random() {
+    let mut rng = rand::thread_rng();
+
+    let mut r = Rope::new();
+    assert_eq!(r.as_str(), Some(""));
+
+    r.insert(0, "abc");
+    assert_eq!(r.as_str(), Some("abc"));
+    assert_eq!(r.byte_slice(1..).as_str(), Some("bc"));
+
+    for s in [TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        match r.as_str() {
+            Some(str) => assert_eq!(str, s),
+            None => assert!(r.chunks().count() > 1),
+        }
+
+        for _ in 0..100 {
+            let mut start = rng.gen_range(0..=r.byte_len());
+
+            while !r.is_char_boundary(start) {
+                start += 1;
+            }
+
+            let mut end = rng.gen_range(start..=r.byte_len());
+
+            while !r.is_char_boundary(end) {
+                end += 1;
+            }
+
+            let slice = r.byte_slice(start..end);
+
+            match slice.as_str() {
+                Some(str) => assert_eq!(str, &s[start..end]),
+                None => assert!(slice.chunks().count() > 1),
+            }
+        }
+    }
+}
+
+/// Tests that `as_contiguous_bytes()` returns `Some` exactly when `as_str()`
+/// does, and that the non-contiguous case can fall back to `byte_chunks()`.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn as_contiguous_bytes_random() {
+    let mut rng = rand::thread_rng();
+
+    assert_eq!(Rope::new().as_contiguous_bytes(), Some(&b""[..]));
+
+    for s in [TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        assert_eq!(r.as_contiguous_bytes(), r.as_str().map(str::as_bytes));
+
+        for _ in 0..100 {
+            let mut start = rng.gen_range(0..=r.byte_len());
+
+            while !r.is_char_boundary(start) {
+                start += 1;
+            }
+
+            let mut end = rng.gen_range(start..=r.byte_len());
+
+            while !r.is_char_boundary(end) {
+                end += 1;
+            }
+
+            let slice = r.byte_slice(start..end);
+
+            let bytes = match slice.as_contiguous_bytes() {
+                Some(bytes) => {
+                    assert_eq!(slice.byte_chunks().count().max(1), 1);
+                    bytes.to_vec()
+                },
+                None => {
+                    assert!(slice.as_str().is_none());
+                    slice.byte_chunks().flatten().copied().collect()
+                },
+            };
+
+            assert_eq!(bytes, &s.as_bytes()[start..end]);
+        }
+    }
+}
+
+#[test]
+fn slice_debug() {
+    let r = Rope::from("foo\nbar\r\n\"baz\"");
+
+    assert_eq!(
+        format!("{:?}", r.byte_slice(4..)),
+        r#"RopeSlice { bytes: 4..14, text: "bar\r\n\"baz\"" }"#
+    );
+
+    assert_eq!(
+        format!("{:?}", r.byte_slice(4..).byte_slice(5..5)),
+        r#"RopeSlice { bytes: 9..9, text: "" }"#
+    );
+
+    assert_eq!(
+        format!("{:?}", r.line(1)),
+        r#"RopeSlice { bytes: 4..7, text: "bar" }"#
+    );
+
+    let r = Rope::from("ƒ".repeat(100));
+
+    assert_eq!(
+        format!("{:?}", r.byte_slice(2..)),
+        format!(
+            "RopeSlice {{ bytes: 2..200, text: \"{}…\" }}",
+            "ƒ".repeat(64)
+        )
+    );
+
+    assert_eq!(
+        format!("{:?}", r.byte_slice(..128)),
+        format!("RopeSlice {{ bytes: 0..128, text: \"{}\" }}", "ƒ".repeat(64))
+    );
+}
+
+/// Tests that the slices yielded by the line iterators know their byte range
+/// in the `Rope`, whichever end they're yielded from.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn slice_debug_lines_byte_ranges() {
+    fn debug_prefix(start: usize, end: usize) -> String {
+        format!("RopeSlice {{ bytes: {start}..{end}, ")
+    }
+
+    for s in [TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        let mut expected = Vec::new();
+        let mut offset = 0;
+
+        for line in s.split_inclusive('\n') {
+            let content = line
+                .strip_suffix('\n')
+                .map(|l| l.strip_suffix('\r').unwrap_or(l))
+                .unwrap_or(line);
+
+            expected.push((offset, offset + line.len(), content.len()));
+            offset += line.len();
+        }
+
+        let raw_lines = r.raw_lines().collect::<Vec<_>>();
+        let lines = r.lines().collect::<Vec<_>>();
+        let mut raw_lines_back = r.raw_lines().rev().collect::<Vec<_>>();
+        let mut lines_back = r.lines().rev().collect::<Vec<_>>();
+        raw_lines_back.reverse();
+        lines_back.reverse();
+
+        assert_eq!(raw_lines.len(), expected.len());
+        assert_eq!(lines.len(), expected.len());
+
+        for (i, &(start, end, len)) in expected.iter().enumerate() {
+            let raw = debug_prefix(start, end);
+            let line = debug_prefix(start, start + len);
+            assert!(format!("{:?}", raw_lines[i]).starts_with(&raw));
+            assert!(format!("{:?}", raw_lines_back[i]).starts_with(&raw));
+            assert!(format!("{:?}", lines[i]).starts_with(&line));
+            assert!(format!("{:?}", lines_back[i]).starts_with(&line));
+            assert!(format!("{:?}", r.line(i)).starts_with(&line));
+            assert!(format!("{:?}", r.line_slice(i..=i)).starts_with(&raw));
+        }
+    }
+}
+
+#[test]
+fn mid_line_slice_lines() {
+    let r = Rope::from("foo\nbar\r\nbaz");
+
+    let s = r.byte_slice(1..11);
+
+    assert_eq!(s.line_len(), 3);
+    assert_eq!(s.line(0), "oo");
+    assert_eq!(s.line(1), "bar");
+    assert_eq!(s.line(2), "ba");
+    assert_eq!(s.byte_of_line(0), 0);
+    assert_eq!(s.byte_of_line(1), 3);
+    assert_eq!(s.byte_of_line(2), 8);
+    assert_eq!(s.byte_of_line(3), 10);
+
+    let s = r.byte_slice(5..6);
+
+    assert_eq!(s.line_len(), 1);
+    assert_eq!(s.line(0), "a");
+    assert_eq!(s.byte_of_line(1), 1);
+}
+
+/// Tests that line indices of slices starting and ending in the middle of a
+/// line are relative to the slice, and that the lines yielded by the line
+/// iterators can be sliced again.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn mid_line_slices_random() {
+    fn check_reslicing(line: RopeSlice, expected: &str) {
+        assert_eq!(line, expected);
+
+        for k in (0..=expected.len()).filter(|&k| expected.is_char_boundary(k))
+        {
+            assert_eq!(line.byte_slice(k..), &expected[k..]);
+            assert_eq!(line.byte_slice(..k), &expected[..k]);
+        }
+    }
+
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        for _ in 0..50 {
+            let mut start = rng.gen_range(0..=r.byte_len());
+            while !s.is_char_boundary(start) {
+                start += 1;
+            }
+
+            let mut end = rng.gen_range(start..=r.byte_len());
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+
+            let slice = r.byte_slice(start..end);
+            let str_slice = &s[start..end];
+
+            let lines = str_slice.lines().collect::<Vec<_>>();
+            let raw_lines =
+                str_slice.split_inclusive('\n').collect::<Vec<_>>();
+
+            assert_eq!(slice.line_len(), lines.len());
+
+            let mut offset = 0;
+
+            for (i, (line, raw_line)) in
+                lines.iter().zip(&raw_lines).enumerate()
+            {
+                assert_eq!(slice.byte_of_line(i), offset);
+                check_reslicing(slice.line(i), line);
+                offset += raw_line.len();
+            }
+
+            for (line, expected) in slice.lines().zip(&lines) {
+                check_reslicing(line, expected);
+            }
+
+            for (line, expected) in slice.lines().rev().zip(lines.iter().rev())
+            {
+                check_reslicing(line, expected);
+            }
+
+            for (line, expected) in slice.raw_lines().zip(&raw_lines) {
+                check_reslicing(line, expected);
+            }
+
+            for (line, expected) in
+                slice.raw_lines().rev().zip(raw_lines.iter().rev())
+            {
+                check_reslicing(line, expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn slice_is_current() {
+    let mut r = Rope::from("foo\nbar\r\nbaz\n");
+
+    let snapshot = r.clone();
+    let s = snapshot.byte_slice(2..);
+
+    assert_eq!(snapshot.generation(), r.generation());
+    assert!(s.is_current(&r));
+    assert!(s.line_slice(1..).is_current(&r));
+    assert!(s.byte_slice(3..5).line(0).is_current(&r));
+    assert!(s.lines().chain(s.raw_lines()).all(|l| l.is_current(&r)));
+    assert!(r.lines().rev().all(|l| l.is_current(&snapshot)));
+
+    let mut generation = r.generation();
+
+    r.insert(0, "");
+    assert!(r.generation() > generation);
+    assert!(!s.is_current(&r));
+    generation = r.generation();
+
+    r.delete(..3);
+    assert!(r.generation() > generation);
+    generation = r.generation();
+
+    r.replace(0..1, "a");
+    assert!(r.generation() > generation);
+
+    let snapshot = r.clone();
+    assert!(snapshot.byte_slice(..).is_current(&r));
+}
+
+#[test]
+fn slice_is_current_across_ropes() {
+    let r1 = Rope::from("foo");
+    let r2 = Rope::from("foo");
+    let r3 = Rope::new();
+    let r4 = Rope::default();
+
+    assert!(!r1.byte_slice(..).is_current(&r2));
+    assert!(!r2.byte_slice(..).is_current(&r1));
+    assert!(!r3.byte_slice(..).is_current(&r4));
+
+    // Editing two clones doesn't give them the same generation.
+    let mut a = r1.clone();
+    let mut b = r1.clone();
+
+    a.insert(0, "bar");
+    b.insert(0, "baz");
+
+    assert_ne!(a.generation(), b.generation());
+    assert!(!a.byte_slice(..).is_current(&b));
+    assert!(!r1.byte_slice(..).is_current(&a));
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn slice_parent_range_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        for _ in 0..100 {
+            let mut start = rng.gen_range(0..=s.len());
+            let mut end = rng.gen_range(0..=s.len());
+
+            if start > end {
+                std::mem::swap(&mut start, &mut end);
+            }
+
+            while !s.is_char_boundary(start) {
+                start += 1;
+            }
+
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+
+            let slice = r.byte_slice(start..end);
+            assert_eq!(slice.parent_range(), start..end);
+
+            for line in slice.raw_lines() {
+                let range = line.parent_range();
+                assert_eq!(&s[range.clone()], line);
+                assert_eq!(line.absolute_offset(0), range.start);
+                assert_eq!(line.absolute_offset(line.byte_len()), range.end);
+            }
+        }
+    }
+}
+
+/// Checks that the slices returned by `line()`, `line_slice()` and
+/// `utf16_slice()` know where they start in the `Rope`.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn line_slices_parent_range_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        for line_index in 0..r.line_len() {
+            let start = r.byte_of_line(line_index);
+            assert_eq!(r.line(line_index).parent_range().start, start);
+            assert_eq!(r.line_slice(line_index..).parent_range().start, start);
+        }
+
+        for _ in 0..100 {
+            let mut start = rng.gen_range(0..=s.len());
+            let mut end = rng.gen_range(0..=s.len());
+
+            if start > end {
+                std::mem::swap(&mut start, &mut end);
+            }
+
+            while !s.is_char_boundary(start) {
+                start += 1;
+            }
+
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+
+            let slice = r.byte_slice(start..end);
+
+            let line_start = rng.gen_range(0..=slice.line_len());
+            let line_end = rng.gen_range(line_start..=slice.line_len());
+
+            assert_eq!(
+                slice.line_slice(line_start..line_end).parent_range().start,
+                start + slice.byte_of_line(line_start)
+            );
+
+            if line_start < slice.line_len() {
+                assert_eq!(
+                    slice.line(line_start).parent_range().start,
+                    start + slice.byte_of_line(line_start)
+                );
+            }
+
+            #[cfg(feature = "utf16-metric")]
+            {
+                let utf16_start = rng.gen_range(0..=slice.utf16_len());
+                let utf16_end = rng.gen_range(utf16_start..=slice.utf16_len());
+
+                assert_eq!(
+                    slice.utf16_slice(utf16_start..utf16_end).parent_range(),
+                    start + slice.byte_of_utf16_code_unit(utf16_start)
+                        ..start + slice.byte_of_utf16_code_unit(utf16_end)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn slice_absolute_offset_out_of_bounds() {
+    let r = Rope::from("foo\nbar\n");
+    let _ = r.line(1).absolute_offset(4);
+}
+
+#[test]
+fn string_from_rope_and_slices() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        assert_eq!(String::from(&r), s);
+        assert_eq!(String::from(r.clone()), s);
+
+        for _ in 0..10 {
+            let mut start = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end = rng.gen_range(start..=s.len());
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+
+            assert_eq!(String::from(r.byte_slice(start..end)), &s[start..end]);
+        }
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn eq_shared_and_misaligned_chunks() {
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        // A clone shares all its chunks with the original.
+        let mut clone = r.clone();
+        assert_eq!(r, clone);
+
+        let mut builder = RopeBuilder::new();
+        for line in s.split_inclusive('\n') {
+            builder.append(line);
+        }
+        let misaligned = builder.build();
+        assert_eq!(r, misaligned);
+        assert_eq!(r.byte_slice(..), misaligned.byte_slice(..));
+
+        if let Some(last) = s.char_indices().last().map(|(idx, _)| idx) {
+            clone.replace(last.., "\u{0}");
+            assert_ne!(r, clone);
+            assert_ne!(clone, misaligned);
+        }
+    }
+
+    // Slices starting at different offsets of the same chunks.
+    let r = Rope::from("a".repeat(10_000));
+    assert_eq!(r.byte_slice(..9_000), r.byte_slice(1..9_001));
+    assert_ne!(r.byte_slice(..9_000), r.byte_slice(1..9_000));
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn eq_bytes() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        assert_eq!(r, s.as_bytes());
+        assert_eq!(*s.as_bytes(), r);
+        assert_ne!(r, &s.as_bytes()[1..]);
+
+        for _ in 0..10 {
+            let mut start = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end = rng.gen_range(start..=s.len());
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+
+            let slice = r.byte_slice(start..end);
+            let bytes = &s.as_bytes()[start..end];
+
+            assert_eq!(slice, bytes);
+            assert_eq!(bytes, slice);
+            assert_eq!(slice == bytes, slice == s[start..end]);
+
+            if let Some(last) = bytes.len().checked_sub(1) {
+                let mut different = bytes.to_vec();
+                different[last] = 0xFF;
+                assert_ne!(slice, different.as_slice());
+            }
+        }
+    }
+}
+
+#[test]
+fn strip_prefix_suffix_0() {
+    let r = Rope::from("foo:bar:baz");
+    let s = r.byte_slice(..);
+
+    assert_eq!(s.strip_prefix("").unwrap(), "foo:bar:baz");
+    assert_eq!(s.strip_prefix("foo:bar:baz").unwrap(), "");
+    assert_eq!(s.strip_prefix("foo:bar:baz:"), None);
+    assert_eq!(s.strip_suffix("").unwrap(), "foo:bar:baz");
+    assert_eq!(s.strip_suffix("foo:bar:baz").unwrap(), "");
+    assert_eq!(s.strip_suffix(":foo:bar:baz"), None);
+
+    let stripped = s.strip_prefix("foo:").unwrap();
+    assert_eq!(stripped.strip_suffix(":baz").unwrap(), "bar");
+
+    assert_eq!(s.trim_start_matches(""), s);
+    assert_eq!(s.trim_end_matches(""), s);
+
+    let r = Rope::from("abababa");
+    let s = r.byte_slice(..);
+    assert_eq!(s.trim_start_matches("ab"), "a");
+    assert_eq!(s.trim_end_matches("ba"), "a");
+    assert_eq!(s.trim_start_matches("abababa"), "");
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn strip_prefix_suffix_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        for _ in 0..20 {
+            let mut start = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(start) {
+                start += 1;
+            }
+            let mut end = rng.gen_range(start..=s.len());
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+
+            let slice = r.byte_slice(start..end);
+            let str_slice = &s[start..end];
+
+            let mut len = rng.gen_range(0..=str_slice.len().min(100));
+            while !str_slice.is_char_boundary(len) {
+                len -= 1;
+            }
+
+            let prefix = &str_slice[..len];
+
+            let mut suffix_start = str_slice.len() - len;
+            while !str_slice.is_char_boundary(suffix_start) {
+                suffix_start += 1;
+            }
+
+            let suffix = &str_slice[suffix_start..];
+
+            let stripped = slice.strip_prefix(prefix).unwrap();
+            assert_eq!(stripped, &str_slice[len..]);
+
+            let stripped = slice.strip_suffix(suffix).unwrap();
+            assert_eq!(stripped, &str_slice[..suffix_start]);
+
+            let other = "\u{1f408}";
+            assert_eq!(
+                slice.strip_prefix(other).is_some(),
+                str_slice.strip_prefix(other).is_some()
+            );
+            assert_eq!(
+                slice.strip_suffix(other).is_some(),
+                str_slice.strip_suffix(other).is_some()
+            );
+
+            if !prefix.is_empty() {
+                assert_eq!(
+                    slice.trim_start_matches(prefix),
+                    str_slice.trim_start_matches(prefix)
+                );
+                assert_eq!(
+                    slice.trim_end_matches(suffix),
+                    str_slice.trim_end_matches(suffix)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn split_off_line_0() {
+    let mut r = Rope::from("foo\nbar\r\nbaz");
+
+    let rest = r.split_off_line(3);
+    assert_eq!(r, "foo\nbar\r\nbaz");
+    assert_eq!(rest, "");
+
+    let rest = r.split_off_line(2);
+    assert_eq!(r, "foo\nbar\r\n");
+    assert_eq!(rest, "baz");
+
+    let rest = r.split_off_line(0);
+    r.assert_invariants();
+    rest.assert_invariants();
+    assert_eq!(r, "");
+    assert_eq!(rest, "foo\nbar\r\n");
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn split_off_line_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        for _ in 0..10 {
+            let line_index = rng.gen_range(0..=r.line_len() + 1);
+
+            let mut head = r.clone();
+            let rest = head.split_off_line(line_index);
+
+            head.assert_invariants();
+            rest.assert_invariants();
+
+            let (expected_head, expected_rest) = if line_index >= r.line_len()
+            {
+                (s, "")
+            } else {
+                s.split_at(r.byte_of_line(line_index))
+            };
+
+            assert_eq!(head, expected_head);
+            assert_eq!(rest, expected_rest);
+            assert_eq!(head.line_len(), line_index.min(r.line_len()));
+            assert_eq!(r, s);
+        }
+    }
+}
+
+#[test]
+fn split_at_char_0() {
+    let r = Rope::from("aƒ🐸\r\nb");
+
+    let (left, right) = r.split_at_char(0);
+    assert_eq!(left, "");
+    assert_eq!(right, "aƒ🐸\r\nb");
+
+    let (left, right) = r.split_at_char(3);
+    assert_eq!(left, "aƒ🐸");
+    assert_eq!(right, "\r\nb");
+
+    let (left, right) = r.split_at_char(6);
+    assert_eq!(left, "aƒ🐸\r\nb");
+    assert_eq!(right, "");
+
+    assert!(r.split_at_char_checked(7).is_none());
+
+    let s = r.byte_slice(1..9);
+    let (left, right) = s.split_at_char(2);
+    assert_eq!(left, "ƒ🐸");
+    assert_eq!(right, "\r\n");
+    assert!(s.split_at_char_checked(4).is_some());
+    assert!(s.split_at_char_checked(5).is_none());
+}
+
+#[test]
+#[should_panic]
+fn split_at_char_out_of_bounds() {
+    let r = Rope::from("ƒoo");
+    let _ = r.split_at_char(4);
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn split_at_char_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        for _ in 0..10 {
+            let mut start = rng.gen_range(0..=r.byte_len());
+            while !s.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end = rng.gen_range(start..=r.byte_len());
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+
+            let str_slice = &s[start..end];
+            let rope_slice = r.byte_slice(start..end);
+
+            let char_len = str_slice.chars().count();
+            let char_offset = rng.gen_range(0..=char_len);
+
+            let byte_offset = str_slice
+                .char_indices()
+                .nth(char_offset)
+                .map_or(str_slice.len(), |(idx, _)| idx);
+
+            let (left, right) = rope_slice.split_at_char(char_offset);
+            left.assert_invariants();
+            right.assert_invariants();
+            assert_eq!(left, &str_slice[..byte_offset]);
+            assert_eq!(right, &str_slice[byte_offset..]);
+
+            assert!(rope_slice.split_at_char_checked(char_len + 1).is_none());
+        }
+    }
+}